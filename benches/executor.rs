@@ -0,0 +1,144 @@
+use std::{error::Error, pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use futures::Stream;
+use langchain_rust::{
+    agent::{AgentExecutor, ConversationalAgentBuilder},
+    chain::Chain,
+    language_models::{llm::LLM, GenerateResult, LLMError},
+    output_parsers::{MarkdownParser, OutputParser},
+    prompt_args,
+    schemas::{Message, MessageType, StreamData},
+    tools::Tool,
+};
+use serde_json::{json, Value};
+
+/// LLM returning a tool call until `steps` tool results are in the prompt, then a final answer.
+#[derive(Clone)]
+struct MockLLM {
+    steps: usize,
+}
+
+fn fenced(action: &str, action_input: &str) -> String {
+    format!(
+        "```json\n{}\n```",
+        json!({ "action": action, "action_input": action_input })
+    )
+}
+
+#[async_trait]
+impl LLM for MockLLM {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let taken = messages
+            .iter()
+            .filter(|m| m.message_type == MessageType::AIMessage)
+            .count();
+        let generation = if taken < self.steps {
+            fenced("Echo", "ping")
+        } else {
+            fenced("Final Answer", "done")
+        };
+        Ok(GenerateResult {
+            generation,
+            ..Default::default()
+        })
+    }
+
+    async fn stream(
+        &self,
+        _messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        unimplemented!("the benchmarks only generate")
+    }
+}
+
+struct Echo {
+    observation: String,
+}
+
+#[async_trait]
+impl Tool for Echo {
+    fn name(&self) -> String {
+        "Echo".to_string()
+    }
+    fn description(&self) -> String {
+        "Returns a fixed observation".to_string()
+    }
+    async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+        Ok(self.observation.clone())
+    }
+}
+
+fn executor(steps: usize, observation_len: usize) -> impl Chain {
+    let tool: Arc<dyn Tool> = Arc::new(Echo {
+        observation: "x".repeat(observation_len),
+    });
+    let agent = ConversationalAgentBuilder::new()
+        .tools(&[tool])
+        .build(MockLLM { steps })
+        .unwrap();
+    AgentExecutor::from_agent(agent).with_max_iterations(steps as i32 + 1)
+}
+
+fn executor_iterations(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("executor_iterations");
+    for steps in [1, 5, 10] {
+        let executor = executor(steps, 64);
+        group.throughput(Throughput::Elements(steps as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(steps), &steps, |b, _| {
+            b.to_async(&runtime)
+                .iter(|| executor.invoke(prompt_args! {"input" => "bench"}));
+        });
+    }
+    group.finish();
+}
+
+fn executor_large_observations(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut group = c.benchmark_group("executor_large_observations");
+    for observation_len in [1_024, 64 * 1_024, 1_024 * 1_024] {
+        let executor = executor(3, observation_len);
+        group.throughput(Throughput::Bytes(3 * observation_len as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(observation_len),
+            &observation_len,
+            |b, _| {
+                b.to_async(&runtime)
+                    .iter(|| executor.invoke(prompt_args! {"input" => "bench"}));
+            },
+        );
+    }
+    group.finish();
+}
+
+fn parsing_throughput(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let parser = MarkdownParser::new();
+    let mut group = c.benchmark_group("markdown_parsing");
+    for input_len in [64, 4_096, 65_536] {
+        let output = format!(
+            "Thought: {}\n{}",
+            "y".repeat(input_len),
+            fenced("Echo", "ping")
+        );
+        group.throughput(Throughput::Bytes(output.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(input_len),
+            &output,
+            |b, output| {
+                b.to_async(&runtime).iter(|| parser.parse(output));
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    executor_iterations,
+    executor_large_observations,
+    parsing_throughput
+);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use langchain_rust::{
+    fmt_message, fmt_placeholder, fmt_template, message_formatter,
+    prompt::{FormatPrompter, HumanMessagePromptTemplate, PromptBuffer, PromptFromatter},
+    prompt_args,
+    schemas::Message,
+    template_jinja2,
+};
+
+fn history(len: usize) -> Vec<Message> {
+    (0..len)
+        .map(|i| Message::new_ai_message(format!("message {} {}", i, "z".repeat(256))))
+        .collect()
+}
+
+fn template_rendering(c: &mut Criterion) {
+    let template = template_jinja2!(
+        "You are {{role}}.\nContext:\n{{context}}\nQuestion: {{input}}",
+        "role",
+        "context",
+        "input"
+    );
+    let mut group = c.benchmark_group("template_rendering");
+    for history_len in [0, 10, 100] {
+        let args = prompt_args! {
+            "role" => "a helpful assistant",
+            "context" => "c".repeat(2_048),
+            "input" => "What is the answer?",
+            "chat_history" => history(history_len),
+        };
+        group.bench_with_input(BenchmarkId::new("format", history_len), &args, |b, args| {
+            b.iter(|| template.format(args.clone()).unwrap())
+        });
+        let mut buffer = PromptBuffer::new();
+        group.bench_with_input(
+            BenchmarkId::new("prompt_buffer", history_len),
+            &args,
+            |b, args| b.iter(|| buffer.render(&template, args).unwrap().len()),
+        );
+    }
+    group.finish();
+}
+
+fn chat_prompt_assembly(c: &mut Criterion) {
+    let formatter = message_formatter![
+        fmt_message!(Message::new_system_message("You are a helpful assistant")),
+        fmt_placeholder!("chat_history"),
+        fmt_template!(HumanMessagePromptTemplate::new(template_jinja2!(
+            "{{input}}",
+            "input"
+        ))),
+        fmt_template!(HumanMessagePromptTemplate::new(template_jinja2!(
+            "Remember: {{reminder}}",
+            "reminder"
+        ))),
+    ];
+    let mut group = c.benchmark_group("chat_prompt_assembly");
+    for history_len in [10, 100, 1_000] {
+        let args = prompt_args! {
+            "input" => "What is the answer?",
+            "reminder" => "be concise",
+            "chat_history" => history(history_len),
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(history_len),
+            &args,
+            |b, args| b.iter(|| formatter.format_prompt(args.clone()).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, template_rendering, chat_prompt_assembly);
+criterion_main!(benches);
@@ -0,0 +1,195 @@
+//! UniFFI bindings exposing a simplified agent API to Swift and Kotlin hosts.
+//!
+//! Build the library, then generate the foreign sources from it:
+//!
+//! ```text
+//! cargo build -p langchain-rust-ffi --release
+//! cargo run -p langchain-rust-ffi --features cli --bin uniffi-bindgen -- \
+//!     generate --library target/release/liblangchain_rust_ffi.so --language kotlin --out-dir out
+//! ```
+//!
+//! The agent talks to any OpenAI-compatible endpoint, so on-device models served by llama.cpp,
+//! Ollama or similar are used by pointing [`AgentConfig::api_base`] at the local server.
+use std::sync::{Arc, OnceLock};
+
+use langchain_rust::{
+    agent::{AgentExecutor, ConversationalAgent, ConversationalAgentBuilder},
+    chain::Chain,
+    llm::openai::{OpenAI, OpenAIConfig},
+    memory::SimpleMemory,
+    prompt_args,
+    schemas::BaseMemory,
+    tools::Tool,
+};
+use tokio::{runtime::Runtime, sync::Mutex};
+
+mod tools;
+pub use tools::*;
+
+uniffi::setup_scaffolding!();
+
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+pub enum AgentError {
+    #[error("Invalid agent configuration: {message}")]
+    Config { message: String },
+    #[error("Agent run failed: {message}")]
+    Run { message: String },
+    #[error("Tool failed: {message}")]
+    Tool { message: String },
+}
+
+/// Settings used to create an [`Agent`].
+#[derive(uniffi::Record)]
+pub struct AgentConfig {
+    pub model: String,
+    /// Base URL of an OpenAI-compatible API; defaults to OpenAI.
+    #[uniffi(default = None)]
+    pub api_base: Option<String>,
+    #[uniffi(default = None)]
+    pub api_key: Option<String>,
+    /// Replaces the default agent instructions.
+    #[uniffi(default = None)]
+    pub system_prompt: Option<String>,
+    #[uniffi(default = 10)]
+    pub max_iterations: i32,
+}
+
+/// Progress reported to an [`EventListener`] while an agent runs.
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum AgentEvent {
+    ToolStart { tool: String, input: String },
+    ToolEnd { tool: String, output: String },
+    ToolError { tool: String, message: String },
+    Final { output: String },
+}
+
+#[uniffi::export(with_foreign)]
+pub trait EventListener: Send + Sync {
+    fn on_event(&self, event: AgentEvent);
+}
+
+/// All agents share one runtime; the exported methods block the calling thread, so hosts
+/// should call them off the main thread.
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("failed to start the agent runtime"))
+}
+
+/// A conversational agent with memory of previous turns.
+#[derive(uniffi::Object)]
+pub struct Agent {
+    llm: OpenAI<OpenAIConfig>,
+    tools: Vec<Arc<dyn Tool>>,
+    system_prompt: Option<String>,
+    max_iterations: i32,
+    memory: Arc<Mutex<dyn BaseMemory>>,
+}
+
+impl Agent {
+    fn executor(
+        &self,
+        listener: Option<&Arc<dyn EventListener>>,
+    ) -> Result<AgentExecutor<ConversationalAgent>, AgentError> {
+        let tools = match listener {
+            Some(listener) => self
+                .tools
+                .iter()
+                .map(|tool| {
+                    Arc::new(EventTool::new(tool.clone(), listener.clone())) as Arc<dyn Tool>
+                })
+                .collect(),
+            None => self.tools.clone(),
+        };
+
+        let mut builder = ConversationalAgentBuilder::new().tools(&tools);
+        if let Some(system_prompt) = &self.system_prompt {
+            builder = builder.prefix(system_prompt.clone());
+        }
+        let agent = builder
+            .build(self.llm.clone())
+            .map_err(|err| AgentError::Config {
+                message: err.to_string(),
+            })?;
+
+        Ok(AgentExecutor::from_agent(agent)
+            .with_max_iterations(self.max_iterations)
+            .with_memory(self.memory.clone()))
+    }
+
+    fn run(
+        &self,
+        input: String,
+        listener: Option<Arc<dyn EventListener>>,
+    ) -> Result<String, AgentError> {
+        let executor = self.executor(listener.as_ref())?;
+        let output = runtime()
+            .block_on(executor.invoke(prompt_args! { "input" => input }))
+            .map_err(|err| AgentError::Run {
+                message: err.to_string(),
+            })?;
+        if let Some(listener) = listener {
+            listener.on_event(AgentEvent::Final {
+                output: output.clone(),
+            });
+        }
+        Ok(output)
+    }
+}
+
+#[uniffi::export]
+impl Agent {
+    #[uniffi::constructor]
+    pub fn new(
+        config: AgentConfig,
+        tools: Vec<Arc<dyn HostTool>>,
+    ) -> Result<Arc<Self>, AgentError> {
+        if config.model.trim().is_empty() {
+            return Err(AgentError::Config {
+                message: "model must not be empty".into(),
+            });
+        }
+        if config.max_iterations < 1 {
+            return Err(AgentError::Config {
+                message: "max_iterations must be at least 1".into(),
+            });
+        }
+
+        let mut openai_config = OpenAIConfig::default();
+        if let Some(api_base) = config.api_base {
+            openai_config = openai_config.with_api_base(api_base);
+        }
+        if let Some(api_key) = config.api_key {
+            openai_config = openai_config.with_api_key(api_key);
+        }
+
+        Ok(Arc::new(Self {
+            llm: OpenAI::new(openai_config).with_model(config.model),
+            tools: tools
+                .into_iter()
+                .map(|tool| Arc::new(ForeignTool::new(tool)) as Arc<dyn Tool>)
+                .collect(),
+            system_prompt: config.system_prompt,
+            max_iterations: config.max_iterations,
+            memory: SimpleMemory::new().into(),
+        }))
+    }
+
+    /// Runs the agent and returns its final answer.
+    pub fn invoke(&self, input: String) -> Result<String, AgentError> {
+        self.run(input, None)
+    }
+
+    /// Like [`Agent::invoke`], reporting tool calls and the final answer to `listener`.
+    pub fn stream(
+        &self,
+        input: String,
+        listener: Arc<dyn EventListener>,
+    ) -> Result<String, AgentError> {
+        self.run(input, Some(listener))
+    }
+
+    /// Forgets the conversation so far.
+    pub fn reset(&self) {
+        self.memory.blocking_lock().clear();
+    }
+}
@@ -0,0 +1,158 @@
+use std::{error::Error, sync::Arc};
+
+use async_trait::async_trait;
+use langchain_rust::tools::Tool;
+use serde_json::Value;
+
+use crate::{AgentError, AgentEvent, EventListener};
+
+/// A tool implemented by the host application.
+#[uniffi::export(with_foreign)]
+pub trait HostTool: Send + Sync {
+    fn name(&self) -> String;
+    fn description(&self) -> String;
+    fn call(&self, input: String) -> Result<String, AgentError>;
+}
+
+/// Adapts a [`HostTool`] to [`Tool`]. Host code may block, so it runs on the blocking pool.
+pub(crate) struct ForeignTool {
+    tool: Arc<dyn HostTool>,
+}
+
+impl ForeignTool {
+    pub(crate) fn new(tool: Arc<dyn HostTool>) -> Self {
+        Self { tool }
+    }
+}
+
+#[async_trait]
+impl Tool for ForeignTool {
+    fn name(&self) -> String {
+        self.tool.name()
+    }
+
+    fn description(&self) -> String {
+        self.tool.description()
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let input = match input {
+            Value::String(input) => input,
+            input => input.to_string(),
+        };
+        let tool = self.tool.clone();
+        Ok(tokio::task::spawn_blocking(move || tool.call(input)).await??)
+    }
+}
+
+/// Reports tool events to the listener around every call of the wrapped tool.
+pub(crate) struct EventTool {
+    inner: Arc<dyn Tool>,
+    listener: Arc<dyn EventListener>,
+}
+
+impl EventTool {
+    pub(crate) fn new(inner: Arc<dyn Tool>, listener: Arc<dyn EventListener>) -> Self {
+        Self { inner, listener }
+    }
+}
+
+#[async_trait]
+impl Tool for EventTool {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn description(&self) -> String {
+        self.inner.description()
+    }
+
+    fn parameters(&self) -> Value {
+        self.inner.parameters()
+    }
+
+    async fn call(&self, input: &str) -> Result<String, Box<dyn Error>> {
+        let tool = self.inner.name();
+        self.listener.on_event(AgentEvent::ToolStart {
+            tool: tool.clone(),
+            input: input.to_string(),
+        });
+        let result = self.inner.call(input).await;
+        self.listener.on_event(match &result {
+            Ok(output) => AgentEvent::ToolEnd {
+                tool,
+                output: output.clone(),
+            },
+            Err(err) => AgentEvent::ToolError {
+                tool,
+                message: err.to_string(),
+            },
+        });
+        result
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        self.inner.run(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    struct Reverse;
+
+    impl HostTool for Reverse {
+        fn name(&self) -> String {
+            "reverse".into()
+        }
+
+        fn description(&self) -> String {
+            "Reverses text".into()
+        }
+
+        fn call(&self, input: String) -> Result<String, AgentError> {
+            if input.is_empty() {
+                return Err(AgentError::Tool {
+                    message: "empty input".into(),
+                });
+            }
+            Ok(input.chars().rev().collect())
+        }
+    }
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<AgentEvent>>);
+
+    impl EventListener for Recorder {
+        fn on_event(&self, event: AgentEvent) {
+            self.0.lock().unwrap().push(event);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_host_tool_reports_events() {
+        let recorder = Arc::new(Recorder::default());
+        let tool = EventTool::new(
+            Arc::new(ForeignTool::new(Arc::new(Reverse))),
+            recorder.clone(),
+        );
+
+        assert_eq!(tool.call("abc").await.unwrap(), "cba");
+        assert!(tool.call("").await.is_err());
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(
+            events[1],
+            AgentEvent::ToolEnd {
+                tool: "reverse".into(),
+                output: "cba".into()
+            }
+        );
+        assert!(
+            matches!(&events[3], AgentEvent::ToolError { message, .. } if message.contains("empty input"))
+        );
+    }
+}
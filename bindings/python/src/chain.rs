@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use futures::StreamExt;
+use langchain_rust::{
+    chain::{Chain, LLMChain, LLMChainBuilder},
+    prompt::{PromptArgs, PromptTemplate, TemplateFormat},
+};
+use pyo3::{prelude::*, types::PyDict};
+use serde_json::json;
+
+use crate::{events::emit, py_to_json, to_py_err, PyLLM};
+
+/// An LLM chain over an f-string prompt template. Template variables are passed as keyword
+/// arguments; `stream` and `astream` call `callback` with `token` events and a `final` event.
+#[pyclass(name = "LLMChain", module = "langchain_rust", frozen)]
+pub struct PyLLMChain {
+    chain: Arc<LLMChain>,
+}
+
+fn prompt_args(kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<PromptArgs> {
+    let mut args = PromptArgs::new();
+    if let Some(kwargs) = kwargs {
+        for (key, value) in kwargs.iter() {
+            args.insert(key.extract()?, py_to_json(&value)?);
+        }
+    }
+    Ok(args)
+}
+
+async fn invoke(chain: Arc<LLMChain>, args: PromptArgs) -> PyResult<String> {
+    chain.invoke(args).await.map_err(to_py_err)
+}
+
+async fn stream(chain: Arc<LLMChain>, args: PromptArgs, callback: Py<PyAny>) -> PyResult<String> {
+    let mut stream = chain.stream(args).await.map_err(to_py_err)?;
+    let mut output = String::new();
+    while let Some(data) = stream.next().await {
+        let data = data.map_err(to_py_err)?;
+        emit(&callback, json!({"type": "token", "content": data.content}));
+        output.push_str(&data.content);
+    }
+    emit(&callback, json!({"type": "final", "output": output}));
+    Ok(output)
+}
+
+#[pymethods]
+impl PyLLMChain {
+    #[new]
+    #[pyo3(signature = (llm, template, input_variables=Vec::new()))]
+    fn new(
+        llm: PyRef<'_, PyLLM>,
+        template: String,
+        input_variables: Vec<String>,
+    ) -> PyResult<Self> {
+        let chain = LLMChainBuilder::new()
+            .prompt(PromptTemplate::new(
+                template,
+                input_variables,
+                TemplateFormat::FString,
+            ))
+            .llm(llm.llm())
+            .build()
+            .map_err(to_py_err)?;
+        Ok(Self {
+            chain: Arc::new(chain),
+        })
+    }
+
+    #[pyo3(signature = (**kwargs))]
+    fn invoke(&self, py: Python<'_>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<String> {
+        let (chain, args) = (self.chain.clone(), prompt_args(kwargs)?);
+        py.allow_threads(|| pyo3_async_runtimes::tokio::get_runtime().block_on(invoke(chain, args)))
+    }
+
+    #[pyo3(signature = (**kwargs))]
+    fn ainvoke<'py>(
+        &self,
+        py: Python<'py>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (chain, args) = (self.chain.clone(), prompt_args(kwargs)?);
+        pyo3_async_runtimes::tokio::future_into_py(py, invoke(chain, args))
+    }
+
+    #[pyo3(signature = (callback, **kwargs))]
+    fn stream(
+        &self,
+        py: Python<'_>,
+        callback: Py<PyAny>,
+        kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<String> {
+        let (chain, args) = (self.chain.clone(), prompt_args(kwargs)?);
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(stream(chain, args, callback))
+        })
+    }
+
+    #[pyo3(signature = (callback, **kwargs))]
+    fn astream<'py>(
+        &self,
+        py: Python<'py>,
+        callback: Py<PyAny>,
+        kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (chain, args) = (self.chain.clone(), prompt_args(kwargs)?);
+        pyo3_async_runtimes::tokio::future_into_py(py, stream(chain, args, callback))
+    }
+}
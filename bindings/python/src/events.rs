@@ -0,0 +1,83 @@
+use std::{error::Error, future::Future, sync::Arc};
+
+use async_trait::async_trait;
+use langchain_rust::tools::Tool;
+use pyo3::prelude::*;
+use serde_json::{json, Value};
+
+use crate::json_to_py;
+
+/// Forwards an event to the Python callback. A failing callback must not abort the run, so
+/// its exception is printed the way asyncio reports errors in callbacks.
+pub(crate) fn emit(callback: &Py<PyAny>, event: Value) {
+    Python::with_gil(|py| {
+        if let Err(err) = json_to_py(py, &event).and_then(|event| callback.call1(py, (event,))) {
+            err.print(py);
+        }
+    })
+}
+
+/// Reports `tool_start` and `tool_end` events around every call of the wrapped tool.
+pub(crate) struct EventTool {
+    inner: Arc<dyn Tool>,
+    callback: Arc<Py<PyAny>>,
+}
+
+impl EventTool {
+    pub(crate) fn new(inner: Arc<dyn Tool>, callback: Arc<Py<PyAny>>) -> Self {
+        Self { inner, callback }
+    }
+
+    async fn reported<F>(&self, input: Value, call: F) -> Result<String, Box<dyn Error>>
+    where
+        F: Future<Output = Result<String, Box<dyn Error>>>,
+    {
+        let tool = self.inner.name();
+        emit(
+            &self.callback,
+            json!({"type": "tool_start", "tool": tool, "input": input}),
+        );
+        let result = call.await;
+        let event = match &result {
+            Ok(output) => json!({"type": "tool_end", "tool": tool, "output": output}),
+            Err(err) => json!({"type": "tool_end", "tool": tool, "error": err.to_string()}),
+        };
+        emit(&self.callback, event);
+        result
+    }
+}
+
+#[async_trait]
+impl Tool for EventTool {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn description(&self) -> String {
+        self.inner.description()
+    }
+
+    fn parameters(&self) -> Value {
+        self.inner.parameters()
+    }
+
+    async fn call(&self, input: &str) -> Result<String, Box<dyn Error>> {
+        self.reported(json!(input), self.inner.call(input)).await
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        self.reported(input.clone(), self.inner.run(input)).await
+    }
+
+    fn idempotency_key(&self, input: &Value) -> Option<String> {
+        self.inner.idempotency_key(input)
+    }
+
+    async fn shutdown(&self) -> Result<(), Box<dyn Error>> {
+        self.inner.shutdown().await
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        self.inner.parse_input(input).await
+    }
+}
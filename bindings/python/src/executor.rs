@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use langchain_rust::{
+    agent::{AgentExecutor, ConversationalAgent, ConversationalAgentBuilder},
+    chain::Chain,
+    language_models::llm::LLM,
+    memory::SimpleMemory,
+    prompt_args,
+    schemas::BaseMemory,
+    tools::Tool,
+};
+use pyo3::prelude::*;
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::{
+    events::{emit, EventTool},
+    to_py_err, PyLLM, PyTool,
+};
+
+/// A conversational agent running Python tools.
+///
+/// The agent is always a [`ConversationalAgent`], which asks for actions as JSON in the reply
+/// text, so it works with any [`PyLLM`] subclass but never uses native tool calling.
+///
+/// `stream` and `astream` call `callback` with `tool_start`, `tool_end` and `final` events.
+#[pyclass(name = "AgentExecutor", module = "langchain_rust", frozen)]
+pub struct PyAgentExecutor {
+    llm: Box<dyn LLM>,
+    tools: Vec<Arc<dyn Tool>>,
+    prefix: Option<String>,
+    max_iterations: i32,
+    memory: Option<Arc<Mutex<dyn BaseMemory>>>,
+}
+
+impl PyAgentExecutor {
+    /// Agents are cheap to build, so each run gets its own; this keeps the event callback of
+    /// one `stream` call from leaking into concurrent runs.
+    fn executor(
+        &self,
+        callback: Option<&Arc<Py<PyAny>>>,
+    ) -> PyResult<AgentExecutor<ConversationalAgent>> {
+        let tools = match callback {
+            Some(callback) => self
+                .tools
+                .iter()
+                .map(|tool| {
+                    Arc::new(EventTool::new(tool.clone(), callback.clone())) as Arc<dyn Tool>
+                })
+                .collect(),
+            None => self.tools.clone(),
+        };
+
+        let mut builder = ConversationalAgentBuilder::new().tools(&tools);
+        if let Some(prefix) = &self.prefix {
+            builder = builder.prefix(prefix.clone());
+        }
+        let agent = builder.build(self.llm.clone_box()).map_err(to_py_err)?;
+
+        let mut executor =
+            AgentExecutor::from_agent(agent).with_max_iterations(self.max_iterations);
+        if let Some(memory) = &self.memory {
+            executor = executor.with_memory(memory.clone());
+        }
+        Ok(executor)
+    }
+}
+
+async fn run(
+    executor: AgentExecutor<ConversationalAgent>,
+    input: String,
+    callback: Option<Arc<Py<PyAny>>>,
+) -> PyResult<String> {
+    let output = executor
+        .invoke(prompt_args! { "input" => input })
+        .await
+        .map_err(to_py_err)?;
+    if let Some(callback) = callback {
+        emit(&callback, json!({"type": "final", "output": output}));
+    }
+    Ok(output)
+}
+
+#[pymethods]
+impl PyAgentExecutor {
+    #[new]
+    #[pyo3(signature = (llm, tools=Vec::new(), prefix=None, max_iterations=10, memory=true))]
+    fn new(
+        llm: PyRef<'_, PyLLM>,
+        tools: Vec<PyRef<'_, PyTool>>,
+        prefix: Option<String>,
+        max_iterations: i32,
+        memory: bool,
+    ) -> Self {
+        Self {
+            llm: llm.llm(),
+            tools: tools.iter().map(|tool| tool.tool()).collect(),
+            prefix,
+            max_iterations,
+            memory: memory.then(|| SimpleMemory::new().into()),
+        }
+    }
+
+    fn invoke(&self, py: Python<'_>, input: String) -> PyResult<String> {
+        let executor = self.executor(None)?;
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(run(executor, input, None))
+        })
+    }
+
+    fn ainvoke<'py>(&self, py: Python<'py>, input: String) -> PyResult<Bound<'py, PyAny>> {
+        let executor = self.executor(None)?;
+        pyo3_async_runtimes::tokio::future_into_py(py, run(executor, input, None))
+    }
+
+    fn stream(&self, py: Python<'_>, input: String, callback: Py<PyAny>) -> PyResult<String> {
+        let callback = Arc::new(callback);
+        let executor = self.executor(Some(&callback))?;
+        py.allow_threads(|| {
+            pyo3_async_runtimes::tokio::get_runtime().block_on(run(executor, input, Some(callback)))
+        })
+    }
+
+    fn astream<'py>(
+        &self,
+        py: Python<'py>,
+        input: String,
+        callback: Py<PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let callback = Arc::new(callback);
+        let executor = self.executor(Some(&callback))?;
+        pyo3_async_runtimes::tokio::future_into_py(py, run(executor, input, Some(callback)))
+    }
+}
@@ -0,0 +1,65 @@
+//! Python bindings for langchain-rust.
+//!
+//! Build the extension with `maturin develop` from this directory, then:
+//!
+//! ```python
+//! import langchain_rust as lc
+//!
+//! def word_count(text: str) -> str:
+//!     return str(len(text.split()))
+//!
+//! executor = lc.AgentExecutor(
+//!     lc.OpenAI(model="gpt-4o-mini"),
+//!     tools=[lc.Tool("word_count", "Counts the words in a text", word_count)],
+//! )
+//! print(executor.invoke("How many words are in 'the quick brown fox'?"))
+//! ```
+//!
+//! Every blocking method has an `a`-prefixed twin returning an awaitable, and the `stream`
+//! variants report progress as dictionaries passed to a Python callback.
+//!
+//! Chains and agents take any subclass of `LLM`, though `OpenAI` is the only one exposed so
+//! far. `AgentExecutor` always runs a conversational agent, which reads actions from the
+//! reply text; native tool calling agents are not available from Python.
+use pyo3::{exceptions::PyRuntimeError, prelude::*};
+use serde_json::Value;
+
+mod chain;
+mod events;
+mod executor;
+mod llm;
+mod tool;
+
+pub use chain::PyLLMChain;
+pub use executor::PyAgentExecutor;
+pub use llm::{PyLLM, PyOpenAI};
+pub use tool::{CallableTool, PyTool};
+
+pub(crate) fn to_py_err<E: std::fmt::Display>(err: E) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+pub(crate) fn json_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    py.import("json")?
+        .call_method1("loads", (value.to_string(),))
+}
+
+pub(crate) fn py_to_json(obj: &Bound<'_, PyAny>) -> PyResult<Value> {
+    let json: String = obj
+        .py()
+        .import("json")?
+        .call_method1("dumps", (obj,))?
+        .extract()?;
+    serde_json::from_str(&json).map_err(to_py_err)
+}
+
+#[pymodule]
+#[pyo3(name = "langchain_rust")]
+fn langchain_rust_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyLLM>()?;
+    m.add_class::<PyOpenAI>()?;
+    m.add_class::<PyTool>()?;
+    m.add_class::<PyAgentExecutor>()?;
+    m.add_class::<PyLLMChain>()?;
+    Ok(())
+}
@@ -0,0 +1,49 @@
+use langchain_rust::{
+    language_models::llm::LLM,
+    llm::openai::{OpenAI, OpenAIConfig},
+};
+use pyo3::prelude::*;
+
+/// Base class of the chat models. Chains and agents accept any subclass.
+#[pyclass(name = "LLM", module = "langchain_rust", subclass, frozen)]
+pub struct PyLLM {
+    llm: Box<dyn LLM>,
+}
+
+impl PyLLM {
+    pub(crate) fn new(llm: impl LLM + 'static) -> Self {
+        Self { llm: Box::new(llm) }
+    }
+
+    pub(crate) fn llm(&self) -> Box<dyn LLM> {
+        self.llm.clone_box()
+    }
+}
+
+/// An OpenAI (or OpenAI-compatible) chat model.
+#[pyclass(name = "OpenAI", module = "langchain_rust", extends = PyLLM, frozen)]
+pub struct PyOpenAI;
+
+#[pymethods]
+impl PyOpenAI {
+    #[new]
+    #[pyo3(signature = (model=None, api_key=None, api_base=None))]
+    fn new(
+        model: Option<String>,
+        api_key: Option<String>,
+        api_base: Option<String>,
+    ) -> (Self, PyLLM) {
+        let mut config = OpenAIConfig::default();
+        if let Some(api_key) = api_key {
+            config = config.with_api_key(api_key);
+        }
+        if let Some(api_base) = api_base {
+            config = config.with_api_base(api_base);
+        }
+        let mut llm = OpenAI::new(config);
+        if let Some(model) = model {
+            llm = llm.with_model(model);
+        }
+        (Self, PyLLM::new(llm))
+    }
+}
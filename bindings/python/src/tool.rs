@@ -0,0 +1,195 @@
+use std::{error::Error, future::Future, pin::Pin, sync::Arc};
+
+use async_trait::async_trait;
+use langchain_rust::tools::Tool;
+use pyo3::{prelude::*, types::PyString};
+use serde_json::{json, Value};
+
+use crate::{json_to_py, py_to_json};
+
+/// A Python callable exposed to agents as a [`Tool`].
+///
+/// The callable receives the tool input (a string, or the decoded JSON object when the model
+/// sends one) and may be a plain function or a coroutine function. Non-string results are
+/// converted with `str()`.
+pub struct CallableTool {
+    name: String,
+    description: String,
+    parameters: Option<Value>,
+    func: Py<PyAny>,
+}
+
+impl CallableTool {
+    pub fn new(
+        name: String,
+        description: String,
+        parameters: Option<Value>,
+        func: Py<PyAny>,
+    ) -> Self {
+        Self {
+            name,
+            description,
+            parameters,
+            func,
+        }
+    }
+}
+
+/// What calling the Python function gave.
+enum Call {
+    /// The result of a plain function.
+    Ready(String),
+    /// A coroutine awaited on the caller's event loop.
+    Awaiting(Pin<Box<dyn Future<Output = PyResult<Py<PyAny>>> + Send>>),
+    /// A coroutine called outside of any event loop.
+    Detached(Py<PyAny>),
+}
+
+fn output_to_string(output: &Bound<'_, PyAny>) -> PyResult<String> {
+    match output.downcast::<PyString>() {
+        Ok(output) => output.extract(),
+        Err(_) => Ok(output.str()?.to_string()),
+    }
+}
+
+#[async_trait]
+impl Tool for CallableTool {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn description(&self) -> String {
+        self.description.clone()
+    }
+
+    fn parameters(&self) -> Value {
+        self.parameters.clone().unwrap_or_else(|| {
+            json!({
+                "type": "object",
+                "properties": {
+                    "input": {
+                        "type": "string",
+                        "description": self.description,
+                    }
+                },
+                "required": ["input"]
+            })
+        })
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let call = Python::with_gil(|py| -> PyResult<_> {
+            let output = self.func.bind(py).call1((json_to_py(py, &input)?,))?;
+            if !output.hasattr("__await__")? {
+                return Ok(Call::Ready(output_to_string(&output)?));
+            }
+            // Inside `ainvoke` the coroutine runs on the caller's event loop; from the blocking
+            // API there is no running loop, so it gets one of its own.
+            let call = match pyo3_async_runtimes::tokio::into_future(output.clone()) {
+                Ok(future) => Call::Awaiting(Box::pin(future)),
+                Err(_) => Call::Detached(output.unbind()),
+            };
+            Ok(call)
+        })?;
+
+        let output = match call {
+            Call::Ready(output) => return Ok(output),
+            Call::Awaiting(future) => future.await?,
+            // `asyncio.run` blocks until the coroutine is done, so it must not hold up a worker
+            // of the async runtime.
+            Call::Detached(coroutine) => {
+                tokio::task::spawn_blocking(move || {
+                    Python::with_gil(|py| {
+                        py.import("asyncio")?
+                            .call_method1("run", (coroutine,))
+                            .map(Bound::unbind)
+                    })
+                })
+                .await??
+            }
+        };
+        Ok(Python::with_gil(|py| output_to_string(output.bind(py)))?)
+    }
+}
+
+/// Python-facing handle for a [`CallableTool`].
+#[pyclass(name = "Tool", module = "langchain_rust", frozen)]
+pub struct PyTool {
+    tool: Arc<CallableTool>,
+}
+
+#[pymethods]
+impl PyTool {
+    #[new]
+    #[pyo3(signature = (name, description, func, parameters=None))]
+    fn new(
+        name: String,
+        description: String,
+        func: Py<PyAny>,
+        parameters: Option<Bound<'_, PyAny>>,
+    ) -> PyResult<Self> {
+        let parameters = parameters
+            .map(|parameters| py_to_json(&parameters))
+            .transpose()?;
+        Ok(Self {
+            tool: Arc::new(CallableTool::new(name, description, parameters, func)),
+        })
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.tool.name.clone()
+    }
+
+    #[getter]
+    fn description(&self) -> String {
+        self.tool.description.clone()
+    }
+}
+
+impl PyTool {
+    pub(crate) fn tool(&self) -> Arc<dyn Tool> {
+        self.tool.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn callable(source: &str) -> Py<PyAny> {
+        Python::with_gil(|py| {
+            py.eval(&std::ffi::CString::new(source).unwrap(), None, None)
+                .unwrap()
+                .unbind()
+        })
+    }
+
+    #[test]
+    fn test_callable_tool_runs_sync_and_async_functions() {
+        pyo3::prepare_freethreaded_python();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        let upper = CallableTool::new(
+            "upper".into(),
+            "Uppercases text".into(),
+            None,
+            callable("lambda text: text.upper()"),
+        );
+        let output = runtime.block_on(upper.call("hello")).unwrap();
+        assert_eq!(output, "HELLO");
+
+        let length = CallableTool::new(
+            "length".into(),
+            "Counts keys".into(),
+            None,
+            callable("(lambda: (lambda args: __import__('asyncio').sleep(0, len(args))))()"),
+        );
+        let output = runtime
+            .block_on(length.run(json!({"a": 1, "b": 2})))
+            .unwrap();
+        assert_eq!(output, "2");
+    }
+}
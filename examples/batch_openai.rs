@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use langchain_rust::{
+    batch::{BatchProcessor, BatchRequest, OpenAIBatch},
+    schemas::Message,
+};
+
+#[tokio::main]
+async fn main() {
+    let batch = OpenAIBatch::default();
+
+    let questions = ["What is the capital of France?", "Why is the sky blue?"];
+    let requests = questions
+        .iter()
+        .enumerate()
+        .map(|(i, question)| {
+            BatchRequest::generation(
+                format!("q{}", i),
+                vec![Message::new_human_message(question)],
+            )
+        })
+        .collect();
+
+    let job = batch.submit(requests).await.unwrap();
+    println!("Submitted batch {}", job.id);
+
+    // Batches complete within 24 hours, usually much sooner.
+    let results = batch.wait(&job.id, Duration::from_secs(60)).await.unwrap();
+    for result in results {
+        match result.generation() {
+            Some(generation) => println!("{}: {}", result.custom_id, generation.generation),
+            None => println!("{} failed: {:?}", result.custom_id, result.error),
+        }
+    }
+}
@@ -2,10 +2,8 @@
 
 #[cfg(feature = "qdrant")]
 use langchain_rust::{
-    embedding::openai::openai_embedder::OpenAiEmbedder,
-    schemas::Document,
-    vectorstore::qdrant::{Qdrant, StoreBuilder},
-    vectorstore::VectorStore,
+    embedding::openai::openai_embedder::OpenAiEmbedder, schemas::Document,
+    vectorstore::qdrant::StoreBuilder, vectorstore::VectorStore,
 };
 #[cfg(feature = "qdrant")]
 use std::io::Write;
@@ -20,14 +18,11 @@ async fn main() {
     // Requires OpenAI API key to be set in the environment variable OPENAI_API_KEY
     let embedder = OpenAiEmbedder::default();
 
-    // Initialize the qdrant_client::Qdrant
-    // Ensure Qdrant is running at localhost, with gRPC port at 6334
+    // Connects to the Qdrant gRPC endpoint in QDRANT_URL, e.g. http://localhost:6334 for
     // docker run -p 6334:6334 qdrant/qdrant
-    let client = Qdrant::from_url("http://localhost:6334").build().unwrap();
-
-    let store = StoreBuilder::new()
+    let store = StoreBuilder::from_env()
+        .unwrap()
         .embedder(embedder)
-        .client(client)
         .collection_name("langchain-rs")
         .build()
         .await
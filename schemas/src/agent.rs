@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum ToolInput {
+    //Will implement this in the future
+    StrInput(String),
+    DictInput(HashMap<String, String>),
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AgentAction {
+    pub tool: String,
+    pub tool_input: String, //this should be ToolInput in the future
+    pub log: String,
+}
+
+///Log tools is a struct used by the openai-like agents
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct LogTools {
+    pub tool_id: String,
+    pub tools: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AgentFinish {
+    pub output: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub enum AgentEvent {
+    Action(Vec<AgentAction>),
+    Finish(AgentFinish),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn agent_event_roundtrips_through_json() {
+        let event = AgentEvent::Action(vec![AgentAction {
+            tool: "search".into(),
+            tool_input: "rust".into(),
+            log: String::new(),
+        }]);
+
+        let json = serde_json::to_string(&event).unwrap();
+        match serde_json::from_str::<AgentEvent>(&json).unwrap() {
+            AgentEvent::Action(actions) => assert_eq!(actions[0].tool, "search"),
+            AgentEvent::Finish(_) => panic!("expected an action"),
+        }
+    }
+}
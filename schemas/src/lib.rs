@@ -0,0 +1,19 @@
+//! Data types exchanged between langchain-rust and its clients.
+//!
+//! Only `serde` and `serde_json` are required, so WASM frontends and edge services can share
+//! messages, documents, agent events and stream chunks with an agent backend without compiling
+//! the whole framework. `langchain_rust::schemas` re-exports everything defined here.
+pub mod agent;
+pub use agent::*;
+
+pub mod document;
+pub use document::*;
+
+pub mod messages;
+pub use messages::*;
+
+mod stream;
+pub use stream::*;
+
+mod usage;
+pub use usage::*;
@@ -11,8 +11,9 @@ use serde_json::Value;
 /// let ai_message_type = MessageType::AIMessage;
 /// let human_message_type = MessageType::HumanMessage;
 /// ```
-#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, Clone)]
+#[derive(PartialEq, Eq, Serialize, Deserialize, Debug, Clone, Default)]
 pub enum MessageType {
+    #[default]
     #[serde(rename = "system")]
     SystemMessage,
     #[serde(rename = "ai")]
@@ -23,20 +24,14 @@ pub enum MessageType {
     ToolMessage,
 }
 
-impl Default for MessageType {
-    fn default() -> Self {
-        Self::SystemMessage
-    }
-}
-
-impl MessageType {
-    pub fn to_string(&self) -> String {
-        match self {
-            MessageType::SystemMessage => "system".to_owned(),
-            MessageType::AIMessage => "ai".to_owned(),
-            MessageType::HumanMessage => "human".to_owned(),
-            MessageType::ToolMessage => "tool".to_owned(),
-        }
+impl std::fmt::Display for MessageType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            MessageType::SystemMessage => "system",
+            MessageType::AIMessage => "ai",
+            MessageType::HumanMessage => "human",
+            MessageType::ToolMessage => "tool",
+        })
     }
 }
 
@@ -142,8 +137,14 @@ impl Message {
         self
     }
 
+    /// Sets the message id, e.g. to let feedback or traces refer to this message.
+    pub fn with_id<S: Into<String>>(mut self, id: S) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
     pub fn messages_from_value(value: &Value) -> Result<Vec<Message>, serde_json::error::Error> {
-        serde_json::from_value(value.clone())
+        Vec::<Message>::deserialize(value)
     }
 
     pub fn messages_to_string(messages: &[Message]) -> String {
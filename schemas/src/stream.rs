@@ -1,13 +1,17 @@
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io::{self, Write};
 
-use crate::language_models::TokenUsage;
+use crate::TokenUsage;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StreamData {
     pub value: Value,
     pub tokens: Option<TokenUsage>,
     pub content: String,
+    /// Reasoning carried by this chunk, for providers that stream it apart from the answer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
 }
 
 impl StreamData {
@@ -16,9 +20,15 @@ impl StreamData {
             value,
             tokens,
             content: content.into(),
+            reasoning: None,
         }
     }
 
+    pub fn with_reasoning<S: Into<String>>(mut self, reasoning: S) -> Self {
+        self.reasoning = Some(reasoning.into());
+        self
+    }
+
     pub fn to_stdout(&self) -> io::Result<()> {
         let stdout = io::stdout();
         let mut handle = stdout.lock();
@@ -0,0 +1,356 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::{
+    agent::{Agent, AgentError, ConversationalAgent, OpenAiToolAgent},
+    prompt::PromptArgs,
+    schemas::{
+        agent::{AgentAction, AgentEvent, AgentFinish, LogTools},
+        FunctionCallResponse, FunctionDetail,
+    },
+    tools::Tool,
+};
+
+use super::ParseTelemetry;
+
+/// How the model is asked to express its next action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepFormat {
+    /// Native tool calls, as used by [`OpenAiToolAgent`].
+    ToolCalling,
+    /// A bare JSON action object with the provider's JSON mode enabled.
+    JsonMode,
+    /// A JSON action in a markdown fence, as used by [`ConversationalAgent`].
+    ReAct,
+}
+
+/// Picks the response format for every step instead of fixing it when the agent is built.
+///
+/// Formats are tried in the order tool calling, JSON mode, ReAct, skipping the ones the
+/// provider does not support. A format whose recorded failure rate for the model exceeds
+/// `max_failure_rate` is moved to the back once it has `min_attempts` outcomes. When the
+/// output of one format cannot be parsed the same step is retried with the next one, and if
+/// none parses the last raw output is returned as the final answer.
+///
+/// Actions are always logged as tool calls, so steps taken in one format are replayed
+/// correctly when a later step switches to another.
+pub struct AdaptiveAgent {
+    pub(crate) tool_calling: Option<OpenAiToolAgent>,
+    pub(crate) json_mode: Option<ConversationalAgent>,
+    pub(crate) react: ConversationalAgent,
+    pub(crate) tools: Vec<Arc<dyn Tool>>,
+    pub(crate) model: String,
+    pub(crate) telemetry: ParseTelemetry,
+    pub(crate) min_attempts: u64,
+    pub(crate) max_failure_rate: f64,
+}
+
+impl AdaptiveAgent {
+    pub fn telemetry(&self) -> &ParseTelemetry {
+        &self.telemetry
+    }
+
+    /// The formats the next step will try, in order.
+    pub async fn step_formats(&self) -> Vec<StepFormat> {
+        let mut healthy = Vec::new();
+        let mut failing = Vec::new();
+        for format in self.supported_formats() {
+            let stats = self.telemetry.stats(&self.model, format).await;
+            if stats.attempts >= self.min_attempts && stats.failure_rate() > self.max_failure_rate {
+                failing.push(format);
+            } else {
+                healthy.push(format);
+            }
+        }
+        healthy.extend(failing);
+        healthy
+    }
+
+    fn supported_formats(&self) -> Vec<StepFormat> {
+        let mut formats = Vec::new();
+        if self.tool_calling.is_some() {
+            formats.push(StepFormat::ToolCalling);
+        }
+        if self.json_mode.is_some() {
+            formats.push(StepFormat::JsonMode);
+        }
+        formats.push(StepFormat::ReAct);
+        formats
+    }
+
+    async fn plan_with(
+        &self,
+        format: StepFormat,
+        intermediate_steps: &[(AgentAction, String)],
+        inputs: PromptArgs,
+    ) -> Result<AgentEvent, AgentError> {
+        let agent = match (format, &self.tool_calling, &self.json_mode) {
+            (StepFormat::ToolCalling, Some(agent), _) => {
+                return agent.plan(intermediate_steps, inputs).await;
+            }
+            (StepFormat::JsonMode, _, Some(agent)) => agent,
+            _ => &self.react,
+        };
+        let event = agent
+            .plan(&as_react_steps(intermediate_steps), inputs)
+            .await?;
+        as_tool_call_event(event, intermediate_steps.len())
+    }
+}
+
+#[async_trait]
+impl Agent for AdaptiveAgent {
+    async fn plan(
+        &self,
+        intermediate_steps: &[(AgentAction, String)],
+        inputs: PromptArgs,
+    ) -> Result<AgentEvent, AgentError> {
+        let mut last_output = String::new();
+        for format in self.step_formats().await {
+            match self
+                .plan_with(format, intermediate_steps, inputs.clone())
+                .await
+            {
+                Ok(event) => {
+                    self.telemetry.record(&self.model, format, true).await;
+                    return Ok(event);
+                }
+                Err(AgentError::OutputParsingError(output)) => {
+                    log::debug!("{:?} output of {} did not parse", format, self.model);
+                    self.telemetry.record(&self.model, format, false).await;
+                    last_output = output;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(AgentEvent::Finish(AgentFinish {
+            output: last_output,
+        }))
+    }
+
+    fn get_tools(&self) -> Vec<Arc<dyn Tool>> {
+        self.tools.clone()
+    }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.react.max_context_tokens
+    }
+}
+
+/// Rewrites tool-call logs into the fenced JSON the ReAct prompt expects from the model.
+fn as_react_steps(intermediate_steps: &[(AgentAction, String)]) -> Vec<(AgentAction, String)> {
+    intermediate_steps
+        .iter()
+        .map(|(action, observation)| {
+            let mut action = action.clone();
+            if serde_json::from_str::<LogTools>(&action.log).is_ok() {
+                action.log = format!(
+                    "```json\n{}\n```",
+                    json!({ "action": action.tool, "action_input": action.tool_input })
+                );
+            }
+            (action, observation.clone())
+        })
+        .collect()
+}
+
+/// Logs ReAct actions as tool calls so the tool-calling agent and the executor's memory can
+/// replay them. `step` numbers the synthesized call ids.
+fn as_tool_call_event(event: AgentEvent, step: usize) -> Result<AgentEvent, AgentError> {
+    let AgentEvent::Action(actions) = event else {
+        return Ok(event);
+    };
+    actions
+        .into_iter()
+        .enumerate()
+        .map(|(i, mut action)| -> Result<AgentAction, AgentError> {
+            let tool_id = format!("call_{}", step + i);
+            let tools = serde_json::to_string(&[FunctionCallResponse {
+                id: tool_id.clone(),
+                type_field: "function".to_string(),
+                function: FunctionDetail {
+                    name: action.tool.clone(),
+                    arguments: action.tool_input.clone(),
+                },
+            }])?;
+            action.log = serde_json::to_string(&LogTools { tool_id, tools })?;
+            Ok(action)
+        })
+        .collect::<Result<_, _>>()
+        .map(AgentEvent::Action)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        agent::AdaptiveAgentBuilder, language_models::GenerateResult, prompt_args,
+        test_utils::FakeLLM,
+    };
+
+    struct Calculator;
+
+    #[async_trait]
+    impl Tool for Calculator {
+        fn name(&self) -> String {
+            "Calculator".to_string()
+        }
+
+        fn description(&self) -> String {
+            "Evaluates an arithmetic expression".to_string()
+        }
+
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            Ok("4".to_string())
+        }
+    }
+
+    const REACT_ACTION: &str =
+        "```json\n{\"action\": \"Calculator\", \"action_input\": \"2+2\"}\n```";
+
+    fn inputs() -> PromptArgs {
+        prompt_args! {"input" => "What is 2+2?", "chat_history" => json!([])}
+    }
+
+    fn react_action(tool: &str, input: &str) -> AgentAction {
+        AgentAction {
+            tool: tool.to_string(),
+            tool_input: input.to_string(),
+            log: "thinking".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_react_actions_are_logged_as_tool_calls() {
+        let event = AgentEvent::Action(vec![react_action("Calculator", "2+2")]);
+
+        let AgentEvent::Action(actions) = as_tool_call_event(event, 3).unwrap() else {
+            panic!("expected an action");
+        };
+        let log: LogTools = serde_json::from_str(&actions[0].log).unwrap();
+        assert_eq!(log.tool_id, "call_3");
+        let calls: Vec<FunctionCallResponse> = serde_json::from_str(&log.tools).unwrap();
+        assert_eq!(calls[0].function.name, "Calculator");
+        assert_eq!(calls[0].function.arguments, "2+2");
+    }
+
+    #[test]
+    fn test_tool_call_logs_round_trip_to_react() {
+        let event = AgentEvent::Action(vec![react_action("Calculator", "2+2")]);
+        let AgentEvent::Action(actions) = as_tool_call_event(event, 0).unwrap() else {
+            panic!("expected an action");
+        };
+
+        let steps = as_react_steps(&[(actions[0].clone(), "4".to_string())]);
+
+        assert!(steps[0].0.log.starts_with("```json"));
+        assert!(steps[0].0.log.contains("\"action\":\"Calculator\""));
+        assert_eq!(steps[0].1, "4");
+    }
+
+    #[tokio::test]
+    async fn test_native_tool_call_is_taken_as_is() {
+        let llm = FakeLLM::fixed(
+            r#"[{"id":"call_7","type":"function","function":{"name":"Calculator","arguments":"2+2"}}]"#,
+        );
+        let agent = AdaptiveAgentBuilder::new()
+            .tools(&[Arc::new(Calculator)])
+            .tool_calling(true)
+            .build(llm)
+            .unwrap();
+
+        let AgentEvent::Action(actions) = agent.plan(&[], inputs()).await.unwrap() else {
+            panic!("expected an action");
+        };
+        assert_eq!(actions[0].tool, "Calculator");
+        let log: LogTools = serde_json::from_str(&actions[0].log).unwrap();
+        assert_eq!(log.tool_id, "call_7");
+        let stats = agent
+            .telemetry()
+            .stats("default", StepFormat::ToolCalling)
+            .await;
+        assert_eq!((stats.attempts, stats.failures), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_output_falls_back_to_react() {
+        let llm = FakeLLM::scripted(vec![
+            GenerateResult {
+                generation: "Let me use the calculator.".to_string(),
+                ..Default::default()
+            },
+            GenerateResult {
+                generation: REACT_ACTION.to_string(),
+                ..Default::default()
+            },
+        ]);
+        let agent = AdaptiveAgentBuilder::new()
+            .tools(&[Arc::new(Calculator)])
+            .json_mode(true)
+            .build(llm.clone())
+            .unwrap();
+
+        let AgentEvent::Action(actions) = agent.plan(&[], inputs()).await.unwrap() else {
+            panic!("expected an action");
+        };
+        assert_eq!(actions[0].tool, "Calculator");
+        assert!(serde_json::from_str::<LogTools>(&actions[0].log).is_ok());
+        assert_eq!(llm.calls(), 2);
+        let telemetry = agent.telemetry();
+        let json_mode = telemetry.stats("default", StepFormat::JsonMode).await;
+        assert_eq!((json_mode.attempts, json_mode.failures), (1, 1));
+        let react = telemetry.stats("default", StepFormat::ReAct).await;
+        assert_eq!((react.attempts, react.failures), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_demotion_persists_across_steps() {
+        let llm = FakeLLM::scripted(vec![
+            GenerateResult {
+                generation: "Let me use the calculator.".to_string(),
+                ..Default::default()
+            },
+            GenerateResult {
+                generation: REACT_ACTION.to_string(),
+                ..Default::default()
+            },
+            GenerateResult {
+                generation: "```json\n{\"action\": \"Final Answer\", \"action_input\": \"4\"}\n```"
+                    .to_string(),
+                ..Default::default()
+            },
+        ]);
+        let agent = AdaptiveAgentBuilder::new()
+            .tools(&[Arc::new(Calculator)])
+            .json_mode(true)
+            .demote_after(1, 0.5)
+            .build(llm.clone())
+            .unwrap();
+        assert_eq!(
+            agent.step_formats().await,
+            [StepFormat::JsonMode, StepFormat::ReAct]
+        );
+
+        let AgentEvent::Action(actions) = agent.plan(&[], inputs()).await.unwrap() else {
+            panic!("expected an action");
+        };
+        assert_eq!(
+            agent.step_formats().await,
+            [StepFormat::ReAct, StepFormat::JsonMode]
+        );
+
+        let steps = [(actions[0].clone(), "4".to_string())];
+        let AgentEvent::Finish(finish) = agent.plan(&steps, inputs()).await.unwrap() else {
+            panic!("expected a final answer");
+        };
+        assert_eq!(finish.output, "4");
+        // The second step went to ReAct directly.
+        assert_eq!(llm.calls(), 3);
+    }
+}
@@ -0,0 +1,178 @@
+use std::sync::Arc;
+
+use crate::{
+    agent::{
+        chat::ChatOutputParser, AgentError, ConversationalAgentBuilder, OpenAiToolAgentBuilder,
+        PromptLayer, SystemPrompt,
+    },
+    chain::options::ChainCallOptions,
+    language_models::{llm::LLM, options::CallOptions},
+    schemas::ResponseFormat,
+    tools::Tool,
+};
+
+use super::{AdaptiveAgent, ParseTelemetry};
+
+pub struct AdaptiveAgentBuilder {
+    tools: Option<Vec<Arc<dyn Tool>>>,
+    system_prompt: SystemPrompt,
+    options: Option<ChainCallOptions>,
+    model: Option<String>,
+    telemetry: Option<ParseTelemetry>,
+    tool_calling: bool,
+    json_mode: bool,
+    min_attempts: u64,
+    max_failure_rate: f64,
+}
+
+impl AdaptiveAgentBuilder {
+    pub fn new() -> Self {
+        Self {
+            tools: None,
+            system_prompt: SystemPrompt::new(),
+            options: None,
+            model: None,
+            telemetry: None,
+            tool_calling: false,
+            json_mode: false,
+            min_attempts: 3,
+            max_failure_rate: 0.5,
+        }
+    }
+
+    pub fn tools(mut self, tools: &[Arc<dyn Tool>]) -> Self {
+        self.tools = Some(tools.to_vec());
+        self
+    }
+
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.system_prompt.replace(PromptLayer::Persona, prefix);
+        self
+    }
+
+    /// Appends `text` to one layer of the system prompt, leaving the prefix and the other
+    /// layers as they are.
+    pub fn system_layer<S: Into<String>>(mut self, layer: PromptLayer, text: S) -> Self {
+        self.system_prompt.add(layer, text);
+        self
+    }
+
+    pub fn system_prompt(mut self, system_prompt: SystemPrompt) -> Self {
+        self.system_prompt = system_prompt;
+        self
+    }
+
+    /// Chain options for every format's agent. A streaming function is only used by the ReAct
+    /// agent.
+    pub fn options(mut self, options: ChainCallOptions) -> Self {
+        self.options = Some(options);
+        self
+    }
+
+    /// Name the parse outcomes are recorded under. Agents sharing a telemetry should use the
+    /// model name so they learn from each other.
+    pub fn model<S: Into<String>>(mut self, model: S) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    pub fn telemetry(mut self, telemetry: ParseTelemetry) -> Self {
+        self.telemetry = Some(telemetry);
+        self
+    }
+
+    /// Whether the provider supports native tool calls.
+    pub fn tool_calling(mut self, supported: bool) -> Self {
+        self.tool_calling = supported;
+        self
+    }
+
+    /// Whether the provider supports a JSON-object response format.
+    pub fn json_mode(mut self, supported: bool) -> Self {
+        self.json_mode = supported;
+        self
+    }
+
+    /// A format is demoted once it has at least `min_attempts` outcomes and more than
+    /// `max_failure_rate` of them failed to parse.
+    pub fn demote_after(mut self, min_attempts: u64, max_failure_rate: f64) -> Self {
+        self.min_attempts = min_attempts;
+        self.max_failure_rate = max_failure_rate;
+        self
+    }
+
+    pub fn build<L: LLM + Clone + 'static>(self, llm: L) -> Result<AdaptiveAgent, AgentError> {
+        let tools = self.tools.unwrap_or_default();
+        let system_prompt = self.system_prompt;
+        let react_builder = |llm: L, options: Option<ChainCallOptions>| {
+            let mut builder = ConversationalAgentBuilder::new()
+                .tools(&tools)
+                .system_prompt(system_prompt.clone());
+            if let Some(options) = options {
+                builder = builder.options(options);
+            }
+            builder.build(llm).map(|mut agent| {
+                agent.output_parser = ChatOutputParser::new().with_strict(true);
+                agent
+            })
+        };
+
+        let tool_calling = if self.tool_calling {
+            let mut builder = OpenAiToolAgentBuilder::new()
+                .tools(&tools)
+                .system_prompt(system_prompt.clone());
+            if let Some(options) = &self.options {
+                builder = builder.options(copy_options(options));
+            }
+            Some(builder.build(llm.clone())?)
+        } else {
+            None
+        };
+
+        let json_mode = if self.json_mode {
+            let mut json_llm = llm.clone();
+            json_llm
+                .add_options(CallOptions::new().with_response_format(ResponseFormat::JsonObject));
+            Some(react_builder(
+                json_llm,
+                self.options.as_ref().map(copy_options),
+            )?)
+        } else {
+            None
+        };
+
+        Ok(AdaptiveAgent {
+            tool_calling,
+            json_mode,
+            react: react_builder(llm, self.options)?,
+            tools: tools.clone(),
+            model: self.model.unwrap_or_else(|| "default".to_string()),
+            telemetry: self.telemetry.unwrap_or_default(),
+            min_attempts: self.min_attempts,
+            max_failure_rate: self.max_failure_rate,
+        })
+    }
+}
+
+/// `ChainCallOptions` cannot be cloned because of its streaming function, which only the
+/// ReAct agent keeps.
+fn copy_options(options: &ChainCallOptions) -> ChainCallOptions {
+    ChainCallOptions {
+        max_tokens: options.max_tokens,
+        temperature: options.temperature,
+        stop_words: options.stop_words.clone(),
+        streaming_func: None,
+        top_k: options.top_k,
+        top_p: options.top_p,
+        seed: options.seed,
+        min_length: options.min_length,
+        max_length: options.max_length,
+        repetition_penalty: options.repetition_penalty,
+    }
+}
+
+impl Default for AdaptiveAgentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
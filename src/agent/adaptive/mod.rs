@@ -0,0 +1,8 @@
+mod agent;
+pub use agent::*;
+
+mod builder;
+pub use builder::*;
+
+mod telemetry;
+pub use telemetry::*;
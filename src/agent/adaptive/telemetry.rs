@@ -0,0 +1,60 @@
+use std::{collections::HashMap, sync::Arc};
+
+use tokio::sync::Mutex;
+
+use super::StepFormat;
+
+/// Parse outcomes of one response format for one model.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseStats {
+    pub attempts: u64,
+    pub failures: u64,
+}
+
+impl ParseStats {
+    pub fn failure_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.attempts as f64
+        }
+    }
+}
+
+/// Counts how often each model's output could be parsed in each [`StepFormat`].
+///
+/// Cloning shares the counters, so one telemetry can be handed to every agent that talks to
+/// the same models.
+#[derive(Debug, Default, Clone)]
+pub struct ParseTelemetry {
+    stats: Arc<Mutex<HashMap<(String, StepFormat), ParseStats>>>,
+}
+
+impl ParseTelemetry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn record(&self, model: &str, format: StepFormat, parsed: bool) {
+        let mut stats = self.stats.lock().await;
+        let entry = stats.entry((model.to_string(), format)).or_default();
+        entry.attempts += 1;
+        if !parsed {
+            entry.failures += 1;
+        }
+    }
+
+    pub async fn stats(&self, model: &str, format: StepFormat) -> ParseStats {
+        self.stats
+            .lock()
+            .await
+            .get(&(model.to_string(), format))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Forgets the outcomes recorded for `model`, e.g. after switching to a new model version.
+    pub async fn reset(&self, model: &str) {
+        self.stats.lock().await.retain(|(m, _), _| m != model);
+    }
+}
@@ -19,4 +19,9 @@ pub trait Agent: Send + Sync {
     ) -> Result<AgentEvent, AgentError>;
 
     fn get_tools(&self) -> Vec<Arc<dyn Tool>>;
+
+    /// Context window of the agent's model in tokens, when known.
+    fn max_context_tokens(&self) -> Option<usize> {
+        None
+    }
 }
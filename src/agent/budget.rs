@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage},
+    schemas::{agent::AgentAction, Message, StreamData},
+};
+
+use super::{AgentError, RunContext, StepHook};
+
+/// Tokens used and what they cost.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Spend {
+    pub tokens: TokenUsage,
+    pub cost: f64,
+}
+
+impl Spend {
+    fn add(&mut self, tokens: &TokenUsage, cost: f64) {
+        self.tokens.add(tokens);
+        self.cost += cost;
+    }
+}
+
+/// What a [`Budget`] has been spent on.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BudgetReport {
+    pub total: Spend,
+    /// Spend per metered LLM, by the label it was metered with, e.g. the agent or a tool.
+    pub by_source: HashMap<String, Spend>,
+    /// Spend per agent iteration, summed over the runs of the session.
+    pub by_iteration: Vec<Spend>,
+}
+
+#[derive(Default)]
+struct BudgetState {
+    report: BudgetReport,
+    iteration: usize,
+}
+
+/// Token and cost limits of a session, spanning every run of the executors sharing it.
+///
+/// The budget learns what is spent from the usage reported by the LLMs it meters, so the
+/// agent's LLM, and the LLMs used inside tools, are wrapped with [`Budget::meter`]. An
+/// executor given the budget with
+/// [`AgentExecutor::with_budget`](super::AgentExecutor::with_budget) attributes the spend to
+/// iterations and stops the run with [`AgentError::BudgetExceeded`] once a limit is reached.
+/// Clones share the spend.
+///
+/// ```rust,ignore
+/// let budget = Budget::new().with_max_cost(0.50).with_prices(0.15, 0.60);
+/// let agent = ConversationalAgentBuilder::new().build(budget.meter("agent", llm))?;
+/// let executor = AgentExecutor::from_agent(agent).with_budget(budget.clone());
+/// ```
+#[derive(Clone, Default)]
+pub struct Budget {
+    max_tokens: Option<u32>,
+    max_cost: Option<f64>,
+    prompt_price: f64,
+    completion_price: f64,
+    state: Arc<Mutex<BudgetState>>,
+}
+
+impl Budget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    pub fn with_max_cost(mut self, max_cost: f64) -> Self {
+        self.max_cost = Some(max_cost);
+        self
+    }
+
+    /// Prices per thousand prompt and completion tokens of the metered LLMs, unless set per
+    /// LLM with [`MeteredLLM::with_prices`].
+    pub fn with_prices(mut self, prompt_per_1k: f64, completion_per_1k: f64) -> Self {
+        self.prompt_price = prompt_per_1k;
+        self.completion_price = completion_per_1k;
+        self
+    }
+
+    /// Wraps `llm` so its usage is spent from the budget under `label`.
+    pub fn meter<S: Into<String>, L: Into<Box<dyn LLM>>>(&self, label: S, llm: L) -> MeteredLLM {
+        MeteredLLM {
+            label: label.into(),
+            llm: llm.into(),
+            prompt_price: self.prompt_price,
+            completion_price: self.completion_price,
+            budget: self.clone(),
+        }
+    }
+
+    pub fn report(&self) -> BudgetReport {
+        self.state.lock().unwrap().report.clone()
+    }
+
+    /// Forgets what was spent, e.g. when a new session starts.
+    pub fn reset(&self) {
+        *self.state.lock().unwrap() = BudgetState::default();
+    }
+
+    /// Fails once a limit is reached.
+    pub fn check(&self) -> Result<(), AgentError> {
+        let total = self.report().total;
+        if let Some(max_tokens) = self
+            .max_tokens
+            .filter(|max| total.tokens.total_tokens >= *max)
+        {
+            return Err(AgentError::BudgetExceeded(format!(
+                "{} of {} tokens used",
+                total.tokens.total_tokens, max_tokens
+            )));
+        }
+        if let Some(max_cost) = self.max_cost.filter(|max| total.cost >= *max) {
+            return Err(AgentError::BudgetExceeded(format!(
+                "{:.4} of {:.4} spent",
+                total.cost, max_cost
+            )));
+        }
+        Ok(())
+    }
+
+    /// Attributes the spend that follows to `iteration` of the current run.
+    fn set_iteration(&self, iteration: usize) {
+        self.state.lock().unwrap().iteration = iteration;
+    }
+
+    fn spend(&self, label: &str, tokens: &TokenUsage, cost: f64) {
+        let mut state = self.state.lock().unwrap();
+        let iteration = state.iteration;
+        let report = &mut state.report;
+        report.total.add(tokens, cost);
+        report
+            .by_source
+            .entry(label.to_string())
+            .or_default()
+            .add(tokens, cost);
+        if report.by_iteration.len() <= iteration {
+            report.by_iteration.resize(iteration + 1, Spend::default());
+        }
+        report.by_iteration[iteration].add(tokens, cost);
+    }
+}
+
+/// Checked before every plan and tool call.
+#[async_trait]
+impl StepHook for Budget {
+    async fn before_plan(&self, run: &RunContext<'_>) -> Result<(), AgentError> {
+        self.set_iteration(run.iteration);
+        self.check()
+    }
+
+    async fn before_action(
+        &self,
+        _run: &RunContext<'_>,
+        _action: &AgentAction,
+    ) -> Result<(), AgentError> {
+        self.check()
+    }
+}
+
+/// An LLM whose usage is spent from a [`Budget`], see [`Budget::meter`].
+pub struct MeteredLLM {
+    label: String,
+    llm: Box<dyn LLM>,
+    prompt_price: f64,
+    completion_price: f64,
+    budget: Budget,
+}
+
+impl Clone for MeteredLLM {
+    fn clone(&self) -> Self {
+        Self {
+            label: self.label.clone(),
+            llm: self.llm.clone_box(),
+            prompt_price: self.prompt_price,
+            completion_price: self.completion_price,
+            budget: self.budget.clone(),
+        }
+    }
+}
+
+impl MeteredLLM {
+    /// Prices per thousand prompt and completion tokens of this LLM.
+    pub fn with_prices(mut self, prompt_per_1k: f64, completion_per_1k: f64) -> Self {
+        self.prompt_price = prompt_per_1k;
+        self.completion_price = completion_per_1k;
+        self
+    }
+
+    fn spend(&self, tokens: &TokenUsage) {
+        let cost = (tokens.prompt_tokens as f64 * self.prompt_price
+            + tokens.completion_tokens as f64 * self.completion_price)
+            / 1000.0;
+        self.budget.spend(&self.label, tokens, cost);
+    }
+}
+
+#[async_trait]
+impl LLM for MeteredLLM {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let result = self.llm.generate(messages).await?;
+        if let Some(tokens) = &result.tokens {
+            self.spend(tokens);
+        }
+        Ok(result)
+    }
+
+    /// Spends the usage carried by the stream's chunks, which most providers only report in
+    /// the last one.
+    async fn stream(
+        &self,
+        messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        let stream = self.llm.stream(messages).await?;
+        let metered = self.clone();
+        Ok(Box::pin(stream.inspect(move |chunk| {
+            if let Some(tokens) = chunk.as_ref().ok().and_then(|chunk| chunk.tokens.as_ref()) {
+                metered.spend(tokens);
+            }
+        })))
+    }
+
+    fn add_options(&mut self, options: CallOptions) {
+        self.llm.add_options(options);
+    }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.llm.max_context_tokens()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::FakeLLM;
+
+    fn fixed() -> FakeLLM {
+        FakeLLM::new(|_| {
+            Ok(GenerateResult {
+                generation: "ok".to_string(),
+                tokens: Some(TokenUsage::new(1000, 500)),
+                ..Default::default()
+            })
+        })
+    }
+
+    #[tokio::test]
+    async fn test_spend_is_broken_down_and_limited() {
+        let budget = Budget::new().with_max_cost(5.0).with_prices(1.0, 2.0);
+        let agent = budget.meter("agent", fixed());
+        let search = budget.meter("search", fixed()).with_prices(0.5, 0.0);
+
+        agent.invoke("plan").await.unwrap();
+        budget.set_iteration(1);
+        search.invoke("query").await.unwrap();
+        assert!(budget.check().is_ok());
+        agent.invoke("plan").await.unwrap();
+
+        let report = budget.report();
+        assert_eq!(report.total.tokens.total_tokens, 4500);
+        assert_eq!(report.total.cost, 4.5);
+        assert_eq!(report.by_source["agent"].cost, 4.0);
+        assert_eq!(report.by_source["search"].cost, 0.5);
+        assert_eq!(report.by_iteration[1].cost, 2.5);
+        assert!(budget.check().is_ok());
+
+        agent.invoke("plan").await.unwrap();
+        assert!(matches!(budget.check(), Err(AgentError::BudgetExceeded(_))));
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::{
-    agent::AgentError,
+    agent::{AgentError, PromptLayer, SystemPrompt},
     chain::{llm_chain::LLMChainBuilder, options::ChainCallOptions},
     language_models::llm::LLM,
     tools::Tool,
@@ -15,7 +15,7 @@ use super::{
 
 pub struct ConversationalAgentBuilder {
     tools: Option<Vec<Arc<dyn Tool>>>,
-    prefix: Option<String>,
+    system_prompt: SystemPrompt,
     suffix: Option<String>,
     options: Option<ChainCallOptions>,
 }
@@ -24,7 +24,7 @@ impl ConversationalAgentBuilder {
     pub fn new() -> Self {
         Self {
             tools: None,
-            prefix: None,
+            system_prompt: SystemPrompt::new(),
             suffix: None,
             options: None,
         }
@@ -36,7 +36,19 @@ impl ConversationalAgentBuilder {
     }
 
     pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
-        self.prefix = Some(prefix.into());
+        self.system_prompt.replace(PromptLayer::Persona, prefix);
+        self
+    }
+
+    /// Appends `text` to one layer of the system prompt, leaving the prefix and the other
+    /// layers as they are.
+    pub fn system_layer<S: Into<String>>(mut self, layer: PromptLayer, text: S) -> Self {
+        self.system_prompt.add(layer, text);
+        self
+    }
+
+    pub fn system_prompt(mut self, system_prompt: SystemPrompt) -> Self {
+        self.system_prompt = system_prompt;
         self
     }
 
@@ -52,9 +64,12 @@ impl ConversationalAgentBuilder {
 
     pub fn build<L: Into<Box<dyn LLM>>>(self, llm: L) -> Result<ConversationalAgent, AgentError> {
         let tools = self.tools.unwrap_or_default();
-        let prefix = self.prefix.unwrap_or_else(|| PREFIX.to_string());
+        let prefix = self.system_prompt.render_with_default_persona(PREFIX);
         let suffix = self.suffix.unwrap_or_else(|| SUFFIX.to_string());
 
+        let llm = llm.into();
+        let max_context_tokens = llm.max_context_tokens();
+
         let prompt = ConversationalAgent::create_prompt(&tools, &suffix, &prefix)?;
         let default_options = ChainCallOptions::default().with_max_tokens(1000);
         let chain = Box::new(
@@ -68,6 +83,7 @@ impl ConversationalAgentBuilder {
         Ok(ConversationalAgent {
             chain,
             tools,
+            max_context_tokens,
             output_parser: ChatOutputParser::new(),
         })
     }
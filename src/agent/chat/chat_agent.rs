@@ -25,6 +25,7 @@ use super::{output_parser::ChatOutputParser, prompt::TEMPLATE_TOOL_RESPONSE};
 pub struct ConversationalAgent {
     pub(crate) chain: Box<dyn Chain>,
     pub(crate) tools: Vec<Arc<dyn Tool>>,
+    pub(crate) max_context_tokens: Option<usize>,
     pub(crate) output_parser: ChatOutputParser,
 }
 
@@ -89,12 +90,11 @@ impl Agent for ConversationalAgent {
     async fn plan(
         &self,
         intermediate_steps: &[(AgentAction, String)],
-        inputs: PromptArgs,
+        mut inputs: PromptArgs,
     ) -> Result<AgentEvent, AgentError> {
         let scratchpad = self.construct_scratchpad(intermediate_steps)?;
-        let mut inputs = inputs.clone();
         inputs.insert("agent_scratchpad".to_string(), json!(scratchpad));
-        let output = self.chain.call(inputs.clone()).await?.generation;
+        let output = self.chain.call(inputs).await?.generation;
         let parsed_output = self.output_parser.parse(&output)?;
         Ok(parsed_output)
     }
@@ -102,6 +102,10 @@ impl Agent for ConversationalAgent {
     fn get_tools(&self) -> Vec<Arc<dyn Tool>> {
         self.tools.clone()
     }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.max_context_tokens
+    }
 }
 
 #[cfg(test)]
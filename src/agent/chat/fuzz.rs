@@ -0,0 +1,107 @@
+//! Property tests for the agent output parser.
+//!
+//! Valid model outputs are mutated the way local models tend to corrupt them (truncation, quote
+//! swaps, extra fences, unicode noise) and the parser must either recover the original event or
+//! fail with an error, never panic. Run with `cargo test --features fuzz`.
+
+use proptest::prelude::*;
+
+use crate::schemas::agent::AgentEvent;
+
+use super::output_parser::ChatOutputParser;
+
+#[derive(Debug, Clone)]
+enum Mutation {
+    /// Cut the output after the given number of chars.
+    Truncate(usize),
+    /// Replace every double quote with a single quote.
+    SwapQuotes,
+    /// Remove the closing braces of the JSON blob but keep the fence.
+    DropClosingBraces,
+    /// Surround the fenced block with prose and an extra, unrelated fence.
+    InsertFence(String),
+    /// Add noise outside the fenced block.
+    UnicodeNoise(String),
+}
+
+fn valid_output(action: &str, action_input: &str) -> String {
+    format!(
+        "```json\n{}\n```",
+        serde_json::json!({ "action": action, "action_input": action_input })
+    )
+}
+
+fn apply(output: &str, mutation: &Mutation) -> String {
+    match mutation {
+        Mutation::Truncate(len) => output.chars().take(*len).collect(),
+        Mutation::SwapQuotes => output.replace('"', "'"),
+        Mutation::DropClosingBraces => {
+            let end = output.rfind("\n```").unwrap_or(output.len());
+            let (json, fence) = output.split_at(end);
+            format!("{}{}", json.trim_end_matches('}'), fence)
+        }
+        Mutation::InsertFence(prose) => format!("{}\n{}\n```text\nnote\n```", prose, output),
+        Mutation::UnicodeNoise(noise) => format!("{}{}{}", noise, output, noise),
+    }
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9 _.,:!?éüß漢字🙂-]{0,40}"
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    prop_oneof![
+        (0usize..200).prop_map(Mutation::Truncate),
+        Just(Mutation::SwapQuotes),
+        Just(Mutation::DropClosingBraces),
+        "[a-zA-Z ]{0,40}".prop_map(Mutation::InsertFence),
+        "[^`{}\"]{0,20}".prop_map(Mutation::UnicodeNoise),
+    ]
+}
+
+fn is_repairable(mutation: &Mutation) -> bool {
+    matches!(
+        mutation,
+        Mutation::DropClosingBraces | Mutation::InsertFence(_) | Mutation::UnicodeNoise(_)
+    )
+}
+
+proptest! {
+    #[test]
+    fn parser_never_panics(input in "\\PC{0,300}") {
+        let _ = ChatOutputParser::new().parse(&input);
+    }
+
+    #[test]
+    fn mutated_outputs_are_repaired_or_rejected(
+        action in "[a-zA-Z_]{1,20}",
+        action_input in text(),
+        mutation in mutation(),
+    ) {
+        let output = apply(&valid_output(&action, &action_input), &mutation);
+        let parsed = ChatOutputParser::new().parse(&output);
+
+        if is_repairable(&mutation) {
+            match parsed {
+                Ok(AgentEvent::Action(actions)) => {
+                    prop_assert_eq!(actions.len(), 1);
+                    prop_assert_eq!(&actions[0].tool, &action);
+                    prop_assert_eq!(&actions[0].tool_input, &action_input);
+                }
+                other => prop_assert!(false, "{:?} was not repaired: {:?}", mutation, other),
+            }
+        }
+    }
+
+    #[test]
+    fn final_answer_survives_noise(answer in text(), noise in "[^`{}\"]{0,20}") {
+        let output = apply(
+            &valid_output("Final Answer", &answer),
+            &Mutation::UnicodeNoise(noise),
+        );
+        match ChatOutputParser::new().parse(&output) {
+            Ok(AgentEvent::Finish(finish)) => prop_assert_eq!(finish.output, answer),
+            other => prop_assert!(false, "final answer lost: {:?}", other),
+        }
+    }
+}
@@ -3,5 +3,9 @@ mod chat_agent;
 mod output_parser;
 mod prompt;
 
+#[cfg(all(test, feature = "fuzz"))]
+mod fuzz;
+
 pub use builder::*;
 pub use chat_agent::*;
+pub(crate) use output_parser::ChatOutputParser;
@@ -1,4 +1,4 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, sync::OnceLock};
 
 use regex::Regex;
 use serde::Deserialize;
@@ -17,20 +17,36 @@ struct AgentOutput {
     action_input: String,
 }
 
-pub struct ChatOutputParser {}
+pub struct ChatOutputParser {
+    strict: bool,
+}
 impl ChatOutputParser {
     pub fn new() -> Self {
-        Self {}
+        Self { strict: false }
+    }
+
+    /// In strict mode output without a valid action blob is an
+    /// [`AgentError::OutputParsingError`] instead of being taken as the final answer, and a
+    /// bare JSON action object without a fence, as returned by JSON mode, is an action blob.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
     }
 }
 
 impl ChatOutputParser {
     pub fn parse(&self, text: &str) -> Result<AgentEvent, AgentError> {
         log::debug!("Parsing to Agent Action: {}", text);
-        match parse_json_markdown(text) {
+        match parse_json_markdown(text, self.strict) {
             Some(value) => {
                 // Deserialize the Value into AgentOutput
-                let agent_output: AgentOutput = serde_json::from_value(value)?;
+                let agent_output: AgentOutput = match serde_json::from_value(value) {
+                    Ok(agent_output) => agent_output,
+                    Err(_) if self.strict => {
+                        return Err(AgentError::OutputParsingError(text.to_string()))
+                    }
+                    Err(e) => return Err(e.into()),
+                };
 
                 if agent_output.action == "Final Answer" {
                     Ok(AgentEvent::Finish(AgentFinish {
@@ -44,6 +60,7 @@ impl ChatOutputParser {
                     }]))
                 }
             }
+            None if self.strict => Err(AgentError::OutputParsingError(text.to_string())),
             None => {
                 log::debug!("No JSON found or malformed JSON in text: {}", text);
                 Ok(AgentEvent::Finish(AgentFinish {
@@ -100,12 +117,56 @@ fn parse_partial_json(s: &str, strict: bool) -> Option<Value> {
     serde_json::from_str(&new_s).ok()
 }
 
-fn parse_json_markdown(json_markdown: &str) -> Option<Value> {
-    let re = Regex::new(r"```(?:json)?\s*([\s\S]+?)\s*```").unwrap();
+fn parse_json_markdown(json_markdown: &str, accept_bare: bool) -> Option<Value> {
+    static JSON_BLOCK: OnceLock<Regex> = OnceLock::new();
+    let re = JSON_BLOCK.get_or_init(|| Regex::new(r"```(?:json)?\s*([\s\S]+?)\s*```").unwrap());
     if let Some(caps) = re.captures(json_markdown) {
         if let Some(json_str) = caps.get(1) {
             return parse_partial_json(json_str.as_str(), false);
         }
     }
+    // JSON-mode responses are a bare action object without a fence.
+    let trimmed = json_markdown.trim();
+    if accept_bare && trimmed.starts_with('{') && !trimmed.contains("```") {
+        return parse_partial_json(trimmed, true).filter(|value| value.get("action").is_some());
+    }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bare_json_is_an_action_only_in_strict_mode() {
+        let output = r#"{"action": "Calculator", "action_input": "2 + 2"}"#;
+
+        match ChatOutputParser::new().with_strict(true).parse(output) {
+            Ok(AgentEvent::Action(actions)) => assert_eq!(actions[0].tool, "Calculator"),
+            other => panic!("expected an action, got {:?}", other),
+        }
+        match ChatOutputParser::new().parse(output) {
+            Ok(AgentEvent::Finish(finish)) => assert_eq!(finish.output, output),
+            other => panic!("expected a final answer, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fenced_block_wins_over_leading_json() {
+        let output = "{\"action\": \"search\"} is what I would do, but:\n\
+            ```json\n{\"action\": \"Calculator\", \"action_input\": \"2 + 2\"}\n```";
+
+        for parser in [
+            ChatOutputParser::new(),
+            ChatOutputParser::new().with_strict(true),
+        ] {
+            match parser.parse(output) {
+                Ok(AgentEvent::Action(actions)) => {
+                    assert_eq!(actions[0].tool, "Calculator");
+                    assert_eq!(actions[0].tool_input, "2 + 2");
+                }
+                other => panic!("expected the fenced action, got {:?}", other),
+            }
+        }
+    }
+}
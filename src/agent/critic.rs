@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{language_models::llm::LLM, schemas::agent::AgentAction};
+
+use super::{AgentError, RunContext, StepHook, StepReview};
+
+const CRITIQUE_PROMPT: &str = "Goal: {goal}\n\n\
+Steps taken so far:\n{steps}\n\n\
+Rate how well the last step moves towards the goal, from 0 (useless or harmful) to 10 \
+(exactly what was needed). If it scores below 10, say what the agent should do instead.\n\n\
+Reply in this format:\n\
+SCORE: <0-10>\n\
+CORRECTION: <instruction for the next step, or NONE>";
+
+/// How a step was judged by a [`StepCritic`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Critique {
+    /// Quality of the step, from 0 to 1.
+    pub score: f64,
+    /// What the agent should do instead, if anything.
+    pub correction: Option<String>,
+}
+
+/// Scores every step of an agent against the user's goal with an LLM, usually a different
+/// model than the agent's, so the agent can correct course before it wastes more steps.
+///
+/// Given to an executor with
+/// [`AgentExecutor::with_critic`](super::AgentExecutor::with_critic), the correction of a step
+/// scoring below the threshold is appended to its observation. A step scoring below the veto
+/// threshold also vetoes the next action: it is not run and the agent plans again.
+pub struct StepCritic {
+    llm: Box<dyn LLM>,
+    threshold: f64,
+    veto_threshold: Option<f64>,
+}
+
+impl StepCritic {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            threshold: 0.5,
+            veto_threshold: None,
+        }
+    }
+
+    /// Score, from 0 to 1, below which the correction is given to the agent.
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Score, from 0 to 1, below which the next action is vetoed. No action is vetoed by
+    /// default.
+    pub fn with_veto_threshold(mut self, veto_threshold: f64) -> Self {
+        self.veto_threshold = Some(veto_threshold);
+        self
+    }
+
+    fn needs_correction(&self, critique: &Critique) -> bool {
+        critique.score < self.threshold
+    }
+
+    fn vetoes(&self, critique: &Critique) -> bool {
+        self.veto_threshold
+            .is_some_and(|veto_threshold| critique.score < veto_threshold)
+    }
+
+    /// Judges the last of `steps` against `goal`.
+    pub async fn critique(
+        &self,
+        goal: &str,
+        steps: &[(AgentAction, String)],
+    ) -> Result<Critique, AgentError> {
+        let steps = steps
+            .iter()
+            .enumerate()
+            .map(|(i, (action, observation))| {
+                format!(
+                    "{}. {}({}) -> {}",
+                    i + 1,
+                    action.tool,
+                    action.tool_input,
+                    observation
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let reply = self
+            .llm
+            .invoke(
+                &CRITIQUE_PROMPT
+                    .replace("{goal}", goal)
+                    .replace("{steps}", &steps),
+            )
+            .await?;
+        parse_critique(&reply)
+            .ok_or_else(|| AgentError::OutputParsingError(format!("Invalid critique: {}", reply)))
+    }
+}
+
+#[async_trait]
+impl StepHook for StepCritic {
+    async fn after_step(&self, run: &RunContext<'_>) -> StepReview {
+        let critique = match self
+            .critique(run.input().unwrap_or_default(), run.steps)
+            .await
+        {
+            Ok(critique) => critique,
+            Err(e) => {
+                log::warn!("Could not critique the step: {}", e);
+                return StepReview::default();
+            }
+        };
+        log::debug!("Critique: {:?}", critique);
+        if !self.needs_correction(&critique) {
+            return StepReview::default();
+        }
+        let correction = critique
+            .correction
+            .clone()
+            .unwrap_or_else(|| "This step did not help, try another approach.".to_string());
+        StepReview {
+            note: Some(format!("Reviewer: {}", correction)),
+            veto: self.vetoes(&critique).then_some(correction),
+        }
+    }
+}
+
+fn parse_critique(reply: &str) -> Option<Critique> {
+    let field = |name: &str| {
+        reply.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim().eq_ignore_ascii_case(name)).then(|| value.trim())
+        })
+    };
+    let score = field("SCORE")?
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .next()?
+        .parse::<f64>()
+        .ok()?;
+    let correction = field("CORRECTION")
+        .filter(|correction| !correction.is_empty() && !correction.eq_ignore_ascii_case("none"))
+        .map(str::to_string);
+    Some(Critique {
+        score: score.clamp(0.0, 10.0) / 10.0,
+        correction,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_critique() {
+        assert_eq!(
+            parse_critique("SCORE: 3/10\nCORRECTION: Search the docs first."),
+            Some(Critique {
+                score: 0.3,
+                correction: Some("Search the docs first.".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_critique("score: 9\ncorrection: NONE"),
+            Some(Critique {
+                score: 0.9,
+                correction: None,
+            })
+        );
+        assert_eq!(parse_critique("Looks good"), None);
+    }
+}
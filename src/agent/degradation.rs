@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{chain::ChainError, language_models::llm::LLM, schemas::Retriever};
+
+/// What an executor answers with when a subsystem it depends on is unhealthy, instead of
+/// failing the request.
+pub enum DegradationProfile {
+    /// Answers with the LLM alone, without tools.
+    NoTools(Box<dyn LLM>),
+    /// Answers from the documents retrieved for the input.
+    RetrievalOnly {
+        retriever: Box<dyn Retriever>,
+        llm: Box<dyn LLM>,
+    },
+    /// Replies with a fixed message, e.g. an apology.
+    Canned(String),
+}
+
+impl DegradationProfile {
+    pub fn no_tools<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self::NoTools(llm.into())
+    }
+
+    pub fn retrieval_only<R: Into<Box<dyn Retriever>>, L: Into<Box<dyn LLM>>>(
+        retriever: R,
+        llm: L,
+    ) -> Self {
+        Self::RetrievalOnly {
+            retriever: retriever.into(),
+            llm: llm.into(),
+        }
+    }
+
+    pub fn canned<S: Into<String>>(message: S) -> Self {
+        Self::Canned(message.into())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::NoTools(_) => "no-tools",
+            Self::RetrievalOnly { .. } => "retrieval-only",
+            Self::Canned(_) => "canned",
+        }
+    }
+
+    pub(crate) async fn answer(&self, input: &str) -> Result<String, ChainError> {
+        match self {
+            Self::NoTools(llm) => Ok(llm.invoke(input).await?),
+            Self::RetrievalOnly { retriever, llm } => {
+                let documents = retriever
+                    .get_relevant_documents(input)
+                    .await
+                    .map_err(|e| ChainError::RetrieverError(e.to_string()))?;
+                let context = documents
+                    .iter()
+                    .map(|document| document.page_content.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                Ok(llm
+                    .invoke(&format!(
+                        "Answer the question using only the documents below. If they do not \
+                         contain the answer, say so.\n\nDocuments:\n{}\n\nQuestion: {}",
+                        context, input
+                    ))
+                    .await?)
+            }
+            Self::Canned(message) => Ok(message.clone()),
+        }
+    }
+}
+
+/// Emitted when an executor answers with a degradation profile.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DegradationEvent {
+    /// The unhealthy subsystem, a preflight check name such as `llm` or `tool:search`.
+    pub subsystem: String,
+    pub profile: String,
+    pub reason: String,
+}
+
+pub type DegradationListener = Arc<dyn Fn(&DegradationEvent) + Send + Sync>;
+
+/// Whether a profile registered for `pattern` covers `subsystem`: the names must be equal, or
+/// `pattern` must be the part before the colon, so `tool` covers every `tool:<name>`.
+pub(crate) fn covers(pattern: &str, subsystem: &str) -> bool {
+    pattern == subsystem
+        || subsystem
+            .split_once(':')
+            .is_some_and(|(kind, _)| kind == pattern)
+}
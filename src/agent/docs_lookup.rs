@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+
+use crate::{chain::ChainError, language_models::llm::LLM, schemas::Retriever};
+
+use super::InputEnricher;
+
+const GUIDANCE_PROMPT: &str = "Below are excerpts of internal documentation and tool manuals.\n\n\
+{documents}\n\n\
+Task: {task}\n\n\
+Summarize what the excerpts say about carrying out the task, in particular which tools to \
+call and how to fill in their inputs. Reply with NONE if they are not relevant to the task.";
+
+/// Looks up the internal documentation and tool manuals relevant to a task, so the agent plans
+/// with them instead of guessing how complex internal APIs are called.
+///
+/// Give it to an executor with
+/// [`AgentExecutor::with_docs_lookup`](super::AgentExecutor::with_docs_lookup). The documents
+/// come from a retriever, usually the `Retriever` of a vector store holding the indexed
+/// manuals. With a summarizer, the agent gets a short guidance written from them instead of the
+/// documents themselves.
+pub struct DocsLookup {
+    retriever: Box<dyn Retriever>,
+    summarizer: Option<Box<dyn LLM>>,
+}
+
+impl DocsLookup {
+    pub fn new<R: Into<Box<dyn Retriever>>>(retriever: R) -> Self {
+        Self {
+            retriever: retriever.into(),
+            summarizer: None,
+        }
+    }
+
+    pub fn with_summarizer<L: Into<Box<dyn LLM>>>(mut self, summarizer: L) -> Self {
+        self.summarizer = Some(summarizer.into());
+        self
+    }
+
+    /// What the documentation says about `task`, or `None` when nothing relevant was found.
+    pub async fn guidance(&self, task: &str) -> Result<Option<String>, ChainError> {
+        let documents = self
+            .retriever
+            .get_relevant_documents(task)
+            .await
+            .map_err(|e| ChainError::RetrieverError(e.to_string()))?;
+        if documents.is_empty() {
+            return Ok(None);
+        }
+        let documents = documents
+            .iter()
+            .map(|document| document.page_content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let Some(summarizer) = &self.summarizer else {
+            return Ok(Some(documents));
+        };
+        let guidance = summarizer
+            .invoke(
+                &GUIDANCE_PROMPT
+                    .replace("{documents}", &documents)
+                    .replace("{task}", task),
+            )
+            .await?;
+        let guidance = guidance.trim();
+        if guidance.is_empty() || guidance.eq_ignore_ascii_case("none") {
+            return Ok(None);
+        }
+        Ok(Some(guidance.to_string()))
+    }
+}
+
+#[async_trait]
+impl InputEnricher for DocsLookup {
+    async fn enrich(&self, input: &str, task: String) -> String {
+        match self.guidance(input).await {
+            Ok(Some(guidance)) => format!("Relevant documentation:\n{}\n\n{}", guidance, task),
+            Ok(None) => task,
+            Err(e) => {
+                log::warn!("Could not look up documentation: {}", e);
+                task
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{schemas::Document, test_utils::FakeLLM};
+
+    /// Returns the manual of the billing API for billing tasks.
+    struct Manuals;
+
+    #[async_trait]
+    impl Retriever for Manuals {
+        async fn get_relevant_documents(
+            &self,
+            query: &str,
+        ) -> Result<Vec<Document>, Box<dyn Error>> {
+            Ok(if query.contains("invoice") {
+                vec![Document::new("billing_api takes an ISO 8601 date.")]
+            } else {
+                Vec::new()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guidance_from_relevant_docs() {
+        // Keeps the first excerpt as the guidance.
+        let first_line =
+            FakeLLM::replying(|messages| messages[0].content.lines().nth(2).unwrap().to_string());
+        let lookup = DocsLookup::new(Manuals).with_summarizer(first_line);
+        assert_eq!(
+            lookup.guidance("Send the March invoice").await.unwrap(),
+            Some("billing_api takes an ISO 8601 date.".to_string())
+        );
+        assert_eq!(lookup.guidance("Say hello").await.unwrap(), None);
+    }
+}
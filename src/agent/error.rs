@@ -22,6 +22,14 @@ pub enum AgentError {
     #[error("Missing input variable: {0}")]
     MissingInputVariable(String),
 
+    /// The model output did not follow the expected response format. Holds the raw output.
+    #[error("Could not parse agent output: {0}")]
+    OutputParsingError(String),
+
+    /// A limit of the run's [`Budget`](super::Budget) was reached.
+    #[error("Budget exceeded: {0}")]
+    BudgetExceeded(String),
+
     #[error("Serde json error: {0}")]
     SerdeJsonError(#[from] serde_json::Error),
 
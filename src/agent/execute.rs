@@ -0,0 +1,66 @@
+use crate::chain::Chain;
+
+/// Configuration shared by every executor implementation.
+///
+/// Keeping these knobs in a single struct lets middleware (serving, checkpointing,
+/// budget enforcement, ...) read and apply them without knowing which executor it wraps.
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    /// Maximum number of agent steps before the run is stopped. `None` means unbounded.
+    pub max_iterations: Option<i32>,
+    /// Stop the run on the first tool error instead of feeding the error back to the agent.
+    pub break_if_error: bool,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ExecutorConfig {
+    pub fn new() -> Self {
+        Self {
+            max_iterations: Some(10),
+            break_if_error: false,
+        }
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: i32) -> Self {
+        self.max_iterations = Some(max_iterations);
+        self
+    }
+
+    pub fn without_max_iterations(mut self) -> Self {
+        self.max_iterations = None;
+        self
+    }
+
+    pub fn with_break_if_error(mut self, break_if_error: bool) -> Self {
+        self.break_if_error = break_if_error;
+        self
+    }
+
+    /// Returns true when `iterations` has reached the configured limit.
+    pub fn iterations_exhausted(&self, iterations: usize) -> bool {
+        self.max_iterations
+            .is_some_and(|max_iterations| iterations >= max_iterations as usize)
+    }
+}
+
+/// Common interface for everything that runs an agent loop.
+///
+/// `invoke`, `call` and `stream` come from [`Chain`], so any executor can be used wherever a
+/// chain is expected; `config` exposes the shared [`ExecutorConfig`].
+pub trait Execute: Chain {
+    fn config(&self) -> &ExecutorConfig;
+}
+
+impl<E> From<E> for Box<dyn Execute>
+where
+    E: Execute + 'static,
+{
+    fn from(executor: E) -> Self {
+        Box::new(executor)
+    }
+}
@@ -1,30 +1,74 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use async_trait::async_trait;
+use futures::{future::join_all, stream, Stream};
 use serde_json::json;
 use tokio::sync::Mutex;
 
-use super::{agent::Agent, AgentError};
-use crate::schemas::{LogTools, Message};
+use super::{
+    agent::Agent,
+    budget::Budget,
+    degradation::covers,
+    exemplars::SimilarExemplars,
+    open_ai_tools::tool_call_messages,
+    preflight::{check_tools, run_check},
+    AgentError, DegradationEvent, DegradationListener, DegradationProfile, DocsLookup, Execute,
+    ExecutorConfig, ExemplarStore, Finish, InputEnricher, Probe, ReadinessReport, RepetitionGuard,
+    RunContext, StepCritic, StepHook, ToolSimulator,
+};
+use crate::schemas::{Message, StreamData};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::tools::{new_run_id, ArtifactStore, JobManager, RunArtifacts};
 use crate::{
-    chain::{chain_trait::Chain, ChainError},
-    language_models::GenerateResult,
-    memory::SimpleMemory,
+    chain::{chain_trait::Chain, AnswerVerifier, ChainError},
+    feedback::{Feedback, FeedbackError, FeedbackStore},
+    language_models::{strip_reasoning, GenerateResult, Stopwatch},
+    memory::{prune_messages, LongTermMemory, SimpleMemory},
+    output_parsers::OutputParser,
     prompt::PromptArgs,
     schemas::{
         agent::{AgentAction, AgentEvent},
         memory::BaseMemory,
     },
-    tools::Tool,
+    tools::{IdempotencyStore, KeyState, Tool, ToolStats},
 };
 
+const PREFLIGHT_TIMEOUT: Duration = Duration::from_secs(30);
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub struct AgentExecutor<A>
 where
     A: Agent,
 {
     agent: A,
-    max_iterations: Option<i32>,
-    break_if_error: bool,
+    config: ExecutorConfig,
+    simulator: Option<Arc<dyn ToolSimulator>>,
+    feedback_store: Option<Arc<dyn FeedbackStore>>,
+    tool_stats: Option<ToolStats>,
+    idempotency: Option<Arc<dyn IdempotencyStore>>,
+    enrichers: Vec<Box<dyn InputEnricher>>,
+    hooks: Vec<Box<dyn StepHook>>,
+    answer_formatter: Option<Box<dyn OutputParser>>,
+    probes: Vec<Box<dyn Probe>>,
+    degradations: Vec<(String, DegradationProfile)>,
+    degradation_listener: Option<DegradationListener>,
+    /// Subsystems that failed the last preflight, with the reason.
+    unhealthy: Arc<std::sync::Mutex<HashMap<String, String>>>,
+    max_history_tokens: Option<usize>,
+    reasoning_tags: bool,
+    shut_down: AtomicBool,
+    #[cfg(not(target_arch = "wasm32"))]
+    artifacts: Option<ArtifactStore>,
+    #[cfg(not(target_arch = "wasm32"))]
+    jobs: Option<JobManager>,
     pub memory: Option<Arc<Mutex<dyn BaseMemory>>>,
 }
 
@@ -35,14 +79,36 @@ where
     pub fn from_agent(agent: A) -> Self {
         Self {
             agent,
-            max_iterations: Some(10),
-            break_if_error: false,
+            config: ExecutorConfig::default(),
+            simulator: None,
+            feedback_store: None,
+            tool_stats: None,
+            idempotency: None,
+            enrichers: Vec::new(),
+            hooks: Vec::new(),
+            answer_formatter: None,
+            probes: Vec::new(),
+            degradations: Vec::new(),
+            degradation_listener: None,
+            unhealthy: Arc::default(),
+            max_history_tokens: None,
+            reasoning_tags: false,
+            shut_down: AtomicBool::new(false),
+            #[cfg(not(target_arch = "wasm32"))]
+            artifacts: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            jobs: None,
             memory: None,
         }
     }
 
+    pub fn with_config(mut self, config: ExecutorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
     pub fn with_max_iterations(mut self, max_iterations: i32) -> Self {
-        self.max_iterations = Some(max_iterations);
+        self.config.max_iterations = Some(max_iterations);
         self
     }
 
@@ -51,50 +117,586 @@ where
         self
     }
 
+    /// Caps the tokens of chat history loaded from memory on every turn, keeping the latest
+    /// messages. Defaults to half the context window of the agent's model, when known.
+    pub fn with_max_history_tokens(mut self, max_history_tokens: usize) -> Self {
+        self.max_history_tokens = Some(max_history_tokens);
+        self
+    }
+
+    /// Gives every run a directory of its own in `store`, which tools reach through
+    /// [`RunArtifacts::current`]. The run is named after the `run_id` input variable, or a
+    /// generated id. Runs of executors nested as tools share the outer run's directory.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_artifact_store(mut self, store: ArtifactStore) -> Self {
+        self.artifacts = Some(store);
+        self
+    }
+
+    /// Background jobs started by the agent's tools. The executor answers `check_job` calls
+    /// even if the agent was not given the tool, and cancels the running jobs on shutdown.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_job_manager(mut self, jobs: JobManager) -> Self {
+        self.jobs = Some(jobs);
+        self
+    }
+
     pub fn with_break_if_error(mut self, break_if_error: bool) -> Self {
-        self.break_if_error = break_if_error;
+        self.config.break_if_error = break_if_error;
+        self
+    }
+
+    /// Set when the agent's model writes its reasoning inline as `<think>` blocks, so they
+    /// are stripped from tool inputs and the final answer. Off by default, since other models
+    /// may use the tag in real text.
+    pub fn with_reasoning_tags(mut self, reasoning_tags: bool) -> Self {
+        self.reasoning_tags = reasoning_tags;
+        self
+    }
+
+    /// Runs the executor in dry-run mode: tool calls are answered by the simulator instead of
+    /// being executed, and the memory is read but never updated.
+    pub fn with_simulator<S: ToolSimulator + 'static>(mut self, simulator: S) -> Self {
+        self.simulator = Some(Arc::new(simulator));
+        self
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.simulator.is_some()
+    }
+
+    /// Store used by [`Self::record_feedback`].
+    ///
+    /// Pass a `run_id` input variable to tie a run to its feedback: the final answer stored in
+    /// memory gets the run id as its message id.
+    pub fn with_feedback_store(mut self, store: Arc<dyn FeedbackStore>) -> Self {
+        self.feedback_store = Some(store);
+        self
+    }
+
+    /// Records the outcome and latency of every tool call into `stats`. A `task_type` input
+    /// variable, when present, breaks the stats down by task type.
+    pub fn with_tool_stats(mut self, stats: ToolStats) -> Self {
+        self.tool_stats = Some(stats);
+        self
+    }
+
+    /// Performs the action of each [`Tool::idempotency_key`] once. Later calls with the same
+    /// key get the first call's output without running the tool, and a key whose action
+    /// started but never finished, e.g. because the process stopped, is not run again either.
+    /// A failed action may be retried.
+    pub fn with_idempotency_store<S: IdempotencyStore + 'static>(mut self, store: S) -> Self {
+        self.idempotency = Some(Arc::new(store));
+        self
+    }
+
+    /// Learns from successful runs: every run that finishes without a tool error is stored in
+    /// `store`, and the `k` stored runs most similar to a new input are shown to the agent as
+    /// worked examples ahead of that input. Memory keeps the input as the user wrote it.
+    pub fn with_exemplars(self, store: ExemplarStore, k: usize) -> Self {
+        self.with_input_enricher(SimilarExemplars::new(store, k))
+    }
+
+    /// Remembers every answered request in `memory`, and shows the agent what it remembers
+    /// about a new input ahead of that input: the summary of earlier sessions and the most
+    /// similar earlier exchanges. Memory keeps the input as the user wrote it.
+    pub fn with_long_term_memory(self, memory: LongTermMemory) -> Self {
+        self.with_input_enricher(memory)
+    }
+
+    /// Looks up the documentation relevant to the user's input before planning and gives it
+    /// to the agent with the input.
+    pub fn with_docs_lookup(self, docs_lookup: DocsLookup) -> Self {
+        self.with_input_enricher(docs_lookup)
+    }
+
+    /// Enriches the input the agent plans with, after the enrichers added before it.
+    pub fn with_input_enricher<E: InputEnricher + 'static>(mut self, enricher: E) -> Self {
+        self.enrichers.push(Box::new(enricher));
+        self
+    }
+
+    /// Has every step judged against the user's input, see [`StepCritic`].
+    pub fn with_critic(self, critic: StepCritic) -> Self {
+        self.with_step_hook(critic)
+    }
+
+    /// Stops the run with [`AgentError::BudgetExceeded`] once a limit of `budget` is reached,
+    /// checked before every plan and tool call.
+    pub fn with_budget(self, budget: Budget) -> Self {
+        self.with_step_hook(budget)
+    }
+
+    /// Recovers when the agent answers a new input with its previous answer, see
+    /// [`RepetitionGuard`].
+    pub fn with_repetition_guard(self, guard: RepetitionGuard) -> Self {
+        self.with_step_hook(guard)
+    }
+
+    /// Checks the final answer against the tool outputs of the run. Unsupported claims are
+    /// logged, or corrected if the verifier is set to.
+    pub fn with_verification(self, verifier: AnswerVerifier) -> Self {
+        self.with_step_hook(verifier)
+    }
+
+    /// Watches every step of a run, see [`StepHook`]. Hooks are called in the order they were
+    /// added.
+    pub fn with_step_hook<H: StepHook + 'static>(mut self, hook: H) -> Self {
+        self.hooks.push(Box::new(hook));
         self
     }
 
+    /// Last stage applied to the final answer, typically an
+    /// [`AnswerFormatter`](crate::output_parsers::AnswerFormatter) enforcing an output contract.
+    /// An answer it rejects fails the run.
+    pub fn with_answer_formatter<P: OutputParser + 'static>(mut self, formatter: P) -> Self {
+        self.answer_formatter = Some(Box::new(formatter));
+        self
+    }
+
+    /// A dependency checked by [`Self::preflight`], e.g. an [`LLMProbe`] for the agent's LLM or
+    /// an MCP client.
+    pub fn with_probe<P: Probe + 'static>(mut self, probe: P) -> Self {
+        self.probes.push(Box::new(probe));
+        self
+    }
+
+    /// Answers with `profile` instead of failing when `subsystem` is unhealthy.
+    ///
+    /// `subsystem` is a preflight check name, e.g. a probe's name, or the kind of check before
+    /// the colon, so `tool` covers every `tool:<name>`. A subsystem is unhealthy
+    /// while it fails the last [`Self::preflight`]. At runtime, a planning error counts as an
+    /// unhealthy `llm` for that request, and with `break_if_error` a tool error counts as an
+    /// unhealthy `tool:<name>`. Degraded answers skip verification, formatting and memory.
+    /// Profiles are tried in the order they were added.
+    pub fn with_degradation<S: Into<String>>(
+        mut self,
+        subsystem: S,
+        profile: DegradationProfile,
+    ) -> Self {
+        self.degradations.push((subsystem.into(), profile));
+        self
+    }
+
+    /// Called whenever a request is answered with a degradation profile.
+    pub fn with_degradation_listener<F: Fn(&DegradationEvent) + Send + Sync + 'static>(
+        mut self,
+        listener: F,
+    ) -> Self {
+        self.degradation_listener = Some(Arc::new(listener));
+        self
+    }
+
+    /// Checks the deployment before it serves its first request: the tool schemas are
+    /// validated and every probe must pass. Probes run
+    /// concurrently, and any check taking longer than 30 seconds fails. The failed checks
+    /// decide which degradation profiles apply until the next preflight.
+    pub async fn preflight(&self) -> ReadinessReport {
+        let mut checks = check_tools(&self.agent.get_tools());
+        checks.extend(
+            join_all(
+                self.probes
+                    .iter()
+                    .map(|probe| run_check(probe.name(), PREFLIGHT_TIMEOUT, probe.check())),
+            )
+            .await,
+        );
+        let report = ReadinessReport { checks };
+        *self.unhealthy.lock().unwrap() = report
+            .failures()
+            .into_iter()
+            .map(|check| (check.name.clone(), check.error.clone().unwrap_or_default()))
+            .collect();
+        report
+    }
+
+    /// Shuts down every tool of the agent concurrently so they release their resources, and
+    /// stops the executor from serving further requests. A tool taking longer than 10 seconds
+    /// is given up on, and background jobs are cancelled. Fails with the tools that could not
+    /// shut down cleanly.
+    pub async fn shutdown(&self) -> Result<(), AgentError> {
+        self.shut_down.store(true, Ordering::SeqCst);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(jobs) = &self.jobs {
+            let cancelled = jobs.cancel_all();
+            if cancelled > 0 {
+                log::info!("Cancelled {} running jobs", cancelled);
+            }
+        }
+        let tools = self.agent.get_tools();
+        let checks = join_all(tools.iter().map(|tool| {
+            run_check(tool.name(), SHUTDOWN_TIMEOUT, async {
+                tool.shutdown().await.map_err(|e| e.to_string())
+            })
+        }))
+        .await;
+        let failures = checks
+            .into_iter()
+            .filter_map(|check| Some(format!("{}: {}", check.name, check.error?)))
+            .collect::<Vec<_>>();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AgentError::ToolError(format!(
+                "Could not shut down {}",
+                failures.join(", ")
+            )))
+        }
+    }
+
+    /// The first profile covering an unhealthy subsystem, with the subsystem and the reason.
+    fn preflight_degradation(&self) -> Option<(String, String, &DegradationProfile)> {
+        let unhealthy = self.unhealthy.lock().unwrap();
+        self.degradations.iter().find_map(|(pattern, profile)| {
+            unhealthy
+                .iter()
+                .find(|(subsystem, _)| covers(pattern, subsystem))
+                .map(|(subsystem, reason)| (subsystem.clone(), reason.clone(), profile))
+        })
+    }
+
+    /// Answers with the profile covering `subsystem`, or fails with `reason` if there is none.
+    async fn degrade(
+        &self,
+        subsystem: &str,
+        reason: String,
+        input_variables: &PromptArgs,
+    ) -> Result<GenerateResult, ChainError> {
+        let Some((_, profile)) = self
+            .degradations
+            .iter()
+            .find(|(pattern, _)| covers(pattern, subsystem))
+        else {
+            return Err(ChainError::AgentError(reason));
+        };
+        self.answer_degraded(subsystem, reason, profile, input_variables)
+            .await
+    }
+
+    async fn answer_degraded(
+        &self,
+        subsystem: &str,
+        reason: String,
+        profile: &DegradationProfile,
+        input_variables: &PromptArgs,
+    ) -> Result<GenerateResult, ChainError> {
+        let event = DegradationEvent {
+            subsystem: subsystem.to_string(),
+            profile: profile.name().to_string(),
+            reason,
+        };
+        log::warn!(
+            "Answering in {} mode, {} is unhealthy: {}",
+            event.profile,
+            event.subsystem,
+            event.reason
+        );
+        if let Some(listener) = &self.degradation_listener {
+            listener(&event);
+        }
+        let input = match input_variables.get("input") {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(x) => x.to_string(),
+            None => String::new(),
+        };
+        Ok(GenerateResult {
+            generation: profile.answer(&input).await?,
+            ..Default::default()
+        })
+    }
+
+    /// The inputs the agent plans with: the user's input enriched with the exemplars, memories
+    /// and documentation retrieved for it.
+    async fn agent_inputs(&self, input_variables: &PromptArgs) -> PromptArgs {
+        let mut inputs = input_variables.clone();
+        let Some(input) = input_variables.get("input").and_then(|i| i.as_str()) else {
+            return inputs;
+        };
+        let mut task = input.to_string();
+        for enricher in &self.enrichers {
+            task = enricher.enrich(input, task).await;
+        }
+        inputs.insert("input".to_string(), json!(task));
+        inputs
+    }
+
+    /// Puts the chat history from memory into `input_variables`, pruned to the history limit.
+    async fn load_chat_history(&self, input_variables: &mut PromptArgs) {
+        if let Some(memory) = &self.memory {
+            let mut messages = memory.lock().await.messages();
+            let max_history_tokens = self.max_history_tokens.or_else(|| {
+                self.agent
+                    .max_context_tokens()
+                    .map(|max_context_tokens| max_context_tokens / 2)
+            });
+            if let Some(max_history_tokens) = max_history_tokens {
+                messages = prune_messages(messages, max_history_tokens);
+            }
+            input_variables.insert("chat_history".to_string(), json!(messages));
+        } else if !input_variables.contains_key("chat_history") {
+            input_variables.insert(
+                "chat_history".to_string(),
+                json!(SimpleMemory::new().messages()),
+            );
+        }
+    }
+
+    /// Runs `action`, or simulates it in a dry run, and records the outcome in the tool stats.
+    async fn run_action(
+        &self,
+        tool: &dyn Tool,
+        action: &AgentAction,
+        input_variables: &PromptArgs,
+    ) -> Result<String, String> {
+        let stopwatch = Stopwatch::start();
+        // Box<dyn Error> is not Send and must not be held across the awaits below.
+        let result = match &self.simulator {
+            Some(simulator) => simulator
+                .simulate(tool, action)
+                .await
+                .map_err(|e| e.to_string()),
+            None => self.call_tool(tool, &action.tool_input).await,
+        };
+        if let Some(stats) = self.tool_stats.as_ref().filter(|_| !self.is_dry_run()) {
+            let task_type = input_variables.get("task_type").and_then(|t| t.as_str());
+            stats
+                .record(
+                    &tool.name(),
+                    task_type,
+                    result.is_ok(),
+                    stopwatch.elapsed_ms(),
+                )
+                .await;
+        }
+        result
+    }
+
+    /// Lets the hooks review the last step, appending their notes to its observation.
+    async fn review_last_step(&self, run: &mut Run, input_variables: &PromptArgs) {
+        let mut veto = None;
+        for hook in &self.hooks {
+            let review = hook
+                .after_step(&run.context(input_variables, self.is_dry_run()))
+                .await;
+            if let (Some(note), Some((_, observation))) = (review.note, run.steps.last_mut()) {
+                observation.push_str(&format!("\n\n{}", note));
+            }
+            veto = veto.or(review.veto);
+        }
+        run.veto = veto;
+    }
+
+    /// Passes the agent's final answer through the hooks, stopping at the first replan.
+    async fn review_answer(
+        &self,
+        run: &Run,
+        input_variables: &PromptArgs,
+        mut answer: String,
+    ) -> Result<Finish, ChainError> {
+        for hook in &self.hooks {
+            match hook
+                .on_finish(&run.context(input_variables, self.is_dry_run()), answer)
+                .await?
+            {
+                Finish::Answer(reviewed) => answer = reviewed,
+                replan => return Ok(replan),
+            }
+        }
+        Ok(Finish::Answer(answer))
+    }
+
+    /// Formats the final answer, then lets the enrichers and hooks learn from the run and
+    /// stores it in memory, unless this is a dry run.
+    async fn answer(
+        &self,
+        run: &Run,
+        input_variables: &PromptArgs,
+        mut answer: String,
+    ) -> Result<GenerateResult, ChainError> {
+        if let Some(formatter) = &self.answer_formatter {
+            answer = formatter.parse(&answer).await?;
+        }
+        if self.is_dry_run() {
+            return Ok(GenerateResult {
+                generation: answer,
+                ..Default::default()
+            });
+        }
+        let context = run.context(input_variables, false);
+        for enricher in &self.enrichers {
+            enricher.learn(&context, &answer).await;
+        }
+        for hook in &self.hooks {
+            hook.after_answer(&context, &answer).await;
+        }
+        if let Some(memory) = &self.memory {
+            let mut memory = memory.lock().await;
+
+            memory.add_user_message(match &input_variables["input"] {
+                // This avoids adding extra quotes to the user input in the history.
+                serde_json::Value::String(s) => s,
+                x => x, // this the json encoded value.
+            });
+
+            // Stored in the native tool-call shape so later turns replay them as such.
+            for message in tool_call_messages(&run.steps)? {
+                memory.add_message(message);
+            }
+
+            let message = Message::new_ai_message(&answer);
+            memory.add_message(
+                match input_variables.get("run_id").and_then(|id| id.as_str()) {
+                    Some(run_id) => message.with_id(run_id),
+                    None => message,
+                },
+            );
+        }
+        Ok(GenerateResult {
+            generation: answer,
+            ..Default::default()
+        })
+    }
+
+    pub async fn record_feedback(&self, feedback: Feedback) -> Result<(), FeedbackError> {
+        self.feedback_store
+            .as_ref()
+            .ok_or(FeedbackError::MissingStore)?
+            .add(feedback)
+            .await
+    }
+
+    /// Calls `tool`, unless it has an idempotency key that was already issued.
+    async fn call_tool(&self, tool: &dyn Tool, input: &str) -> Result<String, String> {
+        let key = match &self.idempotency {
+            Some(store) => tool
+                .idempotency_key(&tool.parse_input(input).await)
+                .map(|key| (store, format!("{}:{}", tool.name(), key))),
+            None => None,
+        };
+        let Some((store, key)) = key else {
+            return tool.call(input).await.map_err(|e| e.to_string());
+        };
+        match store.issue(&key).await.map_err(|e| e.to_string())? {
+            Some(KeyState::Completed { output }) => {
+                log::info!("Action {} was already performed, reusing its output", key);
+                return Ok(output);
+            }
+            Some(KeyState::Pending) => {
+                return Err(format!(
+                    "Action {} was already started and its outcome is unknown, \
+                     it was not performed again",
+                    key
+                ))
+            }
+            None => {}
+        }
+        let result = tool.call(input).await.map_err(|e| e.to_string());
+        let recorded = match &result {
+            Ok(output) => store.complete(&key, output).await,
+            Err(_) => store.release(&key).await,
+        };
+        if let Err(e) = recorded {
+            log::warn!("Could not record the outcome of action {}: {}", key, e);
+        }
+        result
+    }
+
     fn get_name_to_tools(&self) -> HashMap<String, Arc<dyn Tool>> {
         let mut name_to_tool = HashMap::new();
         for tool in self.agent.get_tools().iter() {
             log::debug!("Loading Tool:{}", tool.name());
             name_to_tool.insert(tool.name().trim().replace(" ", "_"), tool.clone());
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(jobs) = &self.jobs {
+            name_to_tool
+                .entry("check_job".to_string())
+                .or_insert_with(|| Arc::new(jobs.check_job_tool()));
+        }
         name_to_tool
     }
 }
 
+/// The state of one run of the agent loop.
+#[derive(Default)]
+struct Run {
+    steps: Vec<(AgentAction, String)>,
+    iteration: usize,
+    replanned: bool,
+    tool_failed: bool,
+    /// Why a hook vetoed the next action.
+    veto: Option<String>,
+}
+
+impl Run {
+    fn context<'a>(&'a self, input_variables: &'a PromptArgs, dry_run: bool) -> RunContext<'a> {
+        RunContext {
+            input_variables,
+            steps: &self.steps,
+            iteration: self.iteration,
+            dry_run,
+            replanned: self.replanned,
+            tool_failed: self.tool_failed,
+        }
+    }
+}
+
 #[async_trait]
 impl<A> Chain for AgentExecutor<A>
 where
     A: Agent + Send + Sync,
 {
-    async fn call(&self, input_variables: PromptArgs) -> Result<GenerateResult, ChainError> {
-        let mut input_variables = input_variables.clone();
-        let name_to_tools = self.get_name_to_tools();
-        let mut steps: Vec<(AgentAction, String)> = Vec::new();
-        log::debug!("steps: {:?}", steps);
-        if let Some(memory) = &self.memory {
-            let memory = memory.lock().await;
-            input_variables.insert("chat_history".to_string(), json!(memory.messages()));
-        } else {
-            input_variables.insert(
-                "chat_history".to_string(),
-                json!(SimpleMemory::new().messages()),
-            );
+    async fn call(&self, mut input_variables: PromptArgs) -> Result<GenerateResult, ChainError> {
+        if self.shut_down.load(Ordering::SeqCst) {
+            return Err(ChainError::AgentError(
+                "The executor has been shut down".to_string(),
+            ));
         }
+        // The run starts over inside the scope, where it is current.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(store) = self
+            .artifacts
+            .as_ref()
+            .filter(|_| RunArtifacts::current().is_none())
+        {
+            let run_id = input_variables
+                .get("run_id")
+                .and_then(|id| id.as_str())
+                .map(str::to_string)
+                .unwrap_or_else(new_run_id);
+            return store.run(&run_id).scope(self.call(input_variables)).await;
+        }
+        if let Some((subsystem, reason, profile)) = self.preflight_degradation() {
+            return self
+                .answer_degraded(&subsystem, reason, profile, &input_variables)
+                .await;
+        }
+        self.load_chat_history(&mut input_variables).await;
+        let name_to_tools = self.get_name_to_tools();
+        let mut agent_inputs = self.agent_inputs(&input_variables).await;
+        let mut run = Run::default();
 
         loop {
-            let agent_event = self
-                .agent
-                .plan(&steps, input_variables.clone())
-                .await
-                .map_err(|e| ChainError::AgentError(format!("Error in agent planning: {}", e)))?;
+            for hook in &self.hooks {
+                hook.before_plan(&run.context(&input_variables, self.is_dry_run()))
+                    .await
+                    .map_err(|e| ChainError::AgentError(e.to_string()))?;
+            }
+            run.iteration += 1;
+            let agent_event = match self.agent.plan(&run.steps, agent_inputs.clone()).await {
+                Ok(agent_event) => agent_event,
+                Err(e) => {
+                    let reason = format!("Error in agent planning: {}", e);
+                    return self.degrade("llm", reason, &input_variables).await;
+                }
+            };
             match agent_event {
                 AgentEvent::Action(actions) => {
-                    for action in actions {
+                    for mut action in actions {
+                        if self.reasoning_tags {
+                            // Reasoning must never reach a tool.
+                            action.tool_input = strip_reasoning(&action.tool_input);
+                        }
                         log::debug!("Action: {:?}", action.tool_input);
                         let tool = name_to_tools
                             .get(&action.tool.trim().replace(" ", "_"))
@@ -103,66 +705,76 @@ where
                             })
                             .map_err(|e| ChainError::AgentError(e.to_string()))?;
 
-                        let observation_result = tool.call(&action.tool_input).await;
+                        if let Some(reason) = run.veto.take() {
+                            log::info!("A hook vetoed the action {}", action.tool);
+                            let observation =
+                                format!("The action was not run, a reviewer vetoed it: {}", reason);
+                            run.steps.push((action, observation));
+                            continue;
+                        }
+
+                        for hook in &self.hooks {
+                            hook.before_action(
+                                &run.context(&input_variables, self.is_dry_run()),
+                                &action,
+                            )
+                            .await
+                            .map_err(|e| ChainError::AgentError(e.to_string()))?;
+                        }
 
-                        let observation = match observation_result {
-                            Ok(result) => result,
+                        let observation = match self
+                            .run_action(tool.as_ref(), &action, &input_variables)
+                            .await
+                        {
+                            Ok(observation) => observation,
                             Err(err) => {
-                                log::info!(
-                                    "The tool return the following error: {}",
-                                    err.to_string()
-                                );
-                                if self.break_if_error {
-                                    return Err(ChainError::AgentError(
-                                        AgentError::ToolError(err.to_string()).to_string(),
-                                    ));
-                                } else {
-                                    format!("The tool return the following error: {}", err)
+                                run.tool_failed = true;
+                                log::info!("The tool return the following error: {}", err);
+                                if self.config.break_if_error {
+                                    return self
+                                        .degrade(
+                                            &format!("tool:{}", tool.name()),
+                                            AgentError::ToolError(err).to_string(),
+                                            &input_variables,
+                                        )
+                                        .await;
                                 }
+                                format!("The tool return the following error: {}", err)
                             }
                         };
 
-                        steps.push((action, observation));
+                        run.steps.push((action, observation));
+                        self.review_last_step(&mut run, &input_variables).await;
                     }
                 }
                 AgentEvent::Finish(finish) => {
-                    if let Some(memory) = &self.memory {
-                        let mut memory = memory.lock().await;
-
-                        memory.add_user_message(match &input_variables["input"] {
-                            // This avoids adding extra quotes to the user input in the history.
-                            serde_json::Value::String(s) => s,
-                            x => x, // this the json encoded value.
-                        });
-
-                        let mut tools_ai_message_seen: HashMap<String, ()> = HashMap::default();
-                        for (action, observation) in steps {
-                            let LogTools { tool_id, tools } = serde_json::from_str(&action.log)?;
-                            let tools_value: serde_json::Value = serde_json::from_str(&tools)?;
-                            if tools_ai_message_seen.insert(tools, ()).is_none() {
-                                memory.add_message(
-                                    Message::new_ai_message("").with_tool_calls(tools_value),
-                                );
+                    let answer = match self.reasoning_tags {
+                        true => strip_reasoning(&finish.output),
+                        false => finish.output,
+                    };
+                    match self.review_answer(&run, &input_variables, answer).await? {
+                        Finish::Answer(answer) => {
+                            return self.answer(&run, &input_variables, answer).await;
+                        }
+                        Finish::Replan(note) => {
+                            run.replanned = true;
+                            run.steps.clear();
+                            agent_inputs = self.agent_inputs(&input_variables).await;
+                            if let Some(task) = agent_inputs.get("input").and_then(|i| i.as_str()) {
+                                let task = format!("{}\n\n{}", task, note);
+                                agent_inputs.insert("input".to_string(), json!(task));
                             }
-                            memory.add_message(Message::new_tool_message(observation, tool_id));
+                            continue;
                         }
-
-                        memory.add_ai_message(&finish.output);
                     }
-                    return Ok(GenerateResult {
-                        generation: finish.output,
-                        ..Default::default()
-                    });
                 }
             }
 
-            if let Some(max_iterations) = self.max_iterations {
-                if steps.len() >= max_iterations as usize {
-                    return Ok(GenerateResult {
-                        generation: "Max iterations reached".to_string(),
-                        ..Default::default()
-                    });
-                }
+            if self.config.iterations_exhausted(run.steps.len()) {
+                return Ok(GenerateResult {
+                    generation: "Max iterations reached".to_string(),
+                    ..Default::default()
+                });
             }
         }
     }
@@ -171,4 +783,531 @@ where
         let result = self.call(input_variables).await?;
         Ok(result.generation)
     }
+
+    /// The agent loop itself is not streamed; the final result is emitted as a single chunk so
+    /// executors can be consumed through the same streaming interface as chains.
+    async fn stream(
+        &self,
+        input_variables: PromptArgs,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+    {
+        let result = self.call(input_variables).await?;
+        let data = StreamData::new(json!(result), result.tokens, result.generation);
+        Ok(Box::pin(stream::once(async { Ok(data) })))
+    }
+}
+
+impl<A> Execute for AgentExecutor<A>
+where
+    A: Agent + Send + Sync,
+{
+    fn config(&self) -> &ExecutorConfig {
+        &self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use futures::StreamExt;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::{
+        agent::StaticSimulator, feedback::InMemoryFeedbackStore, prompt_args,
+        schemas::agent::AgentFinish, test_utils::FakeLLM,
+    };
+
+    struct Echo {
+        panic_on_run: bool,
+    }
+
+    #[async_trait]
+    impl Tool for Echo {
+        fn name(&self) -> String {
+            "Echo".to_string()
+        }
+        fn description(&self) -> String {
+            "Returns its input".to_string()
+        }
+        async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+            assert!(!self.panic_on_run, "tool executed during dry run");
+            Ok(input.as_str().unwrap_or_default().to_string())
+        }
+    }
+
+    /// Calls `Echo` until `steps_before_finish` observations were collected, then finishes with
+    /// the last observation.
+    struct ScriptedAgent {
+        steps_before_finish: usize,
+        panic_on_run: bool,
+        tool_input: String,
+    }
+
+    #[async_trait]
+    impl Agent for ScriptedAgent {
+        async fn plan(
+            &self,
+            intermediate_steps: &[(AgentAction, String)],
+            _inputs: PromptArgs,
+        ) -> Result<AgentEvent, AgentError> {
+            if intermediate_steps.len() >= self.steps_before_finish {
+                let output = intermediate_steps
+                    .last()
+                    .map(|(_, observation)| observation.clone())
+                    .unwrap_or_default();
+                return Ok(AgentEvent::Finish(AgentFinish { output }));
+            }
+            Ok(AgentEvent::Action(vec![AgentAction {
+                tool: "Echo".to_string(),
+                tool_input: self.tool_input.clone(),
+                log: String::new(),
+            }]))
+        }
+
+        fn get_tools(&self) -> Vec<Arc<dyn Tool>> {
+            vec![Arc::new(Echo {
+                panic_on_run: self.panic_on_run,
+            })]
+        }
+    }
+
+    fn scripted_agent(steps_before_finish: usize) -> ScriptedAgent {
+        ScriptedAgent {
+            steps_before_finish,
+            panic_on_run: false,
+            tool_input: "ping".to_string(),
+        }
+    }
+
+    struct Kernel(Arc<AtomicBool>);
+
+    #[async_trait]
+    impl Tool for Kernel {
+        fn name(&self) -> String {
+            "Kernel".to_string()
+        }
+        fn description(&self) -> String {
+            "Runs code".to_string()
+        }
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            Ok(String::new())
+        }
+        async fn shutdown(&self) -> Result<(), Box<dyn Error>> {
+            self.0.store(true, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    /// Finishes right away, holding a single tool.
+    struct Holding(Arc<dyn Tool>);
+
+    #[async_trait]
+    impl Agent for Holding {
+        async fn plan(
+            &self,
+            _intermediate_steps: &[(AgentAction, String)],
+            _inputs: PromptArgs,
+        ) -> Result<AgentEvent, AgentError> {
+            Ok(AgentEvent::Finish(AgentFinish {
+                output: "done".to_string(),
+            }))
+        }
+
+        fn get_tools(&self) -> Vec<Arc<dyn Tool>> {
+            vec![self.0.clone()]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_config_limits_iterations() {
+        let executor = AgentExecutor::from_agent(scripted_agent(5))
+            .with_config(ExecutorConfig::new().with_max_iterations(2));
+
+        assert_eq!(executor.config().max_iterations, Some(2));
+        let result = executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(result, "Max iterations reached");
+    }
+
+    #[tokio::test]
+    async fn test_stream_emits_final_answer() {
+        let executor: Box<dyn Execute> = AgentExecutor::from_agent(scripted_agent(1)).into();
+
+        let mut stream = executor
+            .stream(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        let chunk = stream.next().await.unwrap().unwrap();
+        assert_eq!(chunk.content, "ping");
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_uses_simulator() {
+        let memory: Arc<Mutex<dyn BaseMemory>> = SimpleMemory::new().into();
+        let agent = ScriptedAgent {
+            panic_on_run: true,
+            ..scripted_agent(1)
+        };
+        let executor = AgentExecutor::from_agent(agent)
+            .with_memory(memory.clone())
+            .with_simulator(StaticSimulator::new().with_fixture("Echo", "simulated"));
+
+        let result = executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(result, "simulated");
+        assert!(memory.lock().await.messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reasoning_tags_are_stripped_only_when_set() {
+        let literal = "Use the <think> tag to open a reasoning block.";
+        let agent = ScriptedAgent {
+            tool_input: literal.to_string(),
+            ..scripted_agent(1)
+        };
+        let result = AgentExecutor::from_agent(agent)
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(result, literal);
+
+        let agent = ScriptedAgent {
+            tool_input: "<think>Echo it.</think>ping".to_string(),
+            ..scripted_agent(1)
+        };
+        let result = AgentExecutor::from_agent(agent)
+            .with_reasoning_tags(true)
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(result, "ping");
+    }
+
+    #[tokio::test]
+    async fn test_tool_calls_feed_tool_stats() {
+        let stats = ToolStats::new();
+        let executor = AgentExecutor::from_agent(scripted_agent(2)).with_tool_stats(stats.clone());
+
+        executor
+            .invoke(prompt_args! {"input" => "hi", "task_type" => "echo"})
+            .await
+            .unwrap();
+
+        let summary = stats.summary("Echo", Some("echo")).await.unwrap();
+        assert_eq!(summary.calls, 2);
+        assert_eq!(summary.success_rate, 1.0);
+    }
+
+    struct ConstantEmbedder;
+
+    #[async_trait]
+    impl crate::embedding::Embedder for ConstantEmbedder {
+        async fn embed_documents(
+            &self,
+            documents: &[String],
+        ) -> Result<Vec<Vec<f64>>, crate::embedding::EmbedderError> {
+            Ok(vec![vec![1.0]; documents.len()])
+        }
+        async fn embed_query(
+            &self,
+            _text: &str,
+        ) -> Result<Vec<f64>, crate::embedding::EmbedderError> {
+            Ok(vec![1.0])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_successful_runs_become_exemplars() {
+        let store = ExemplarStore::new(ConstantEmbedder);
+        let executor =
+            AgentExecutor::from_agent(scripted_agent(1)).with_exemplars(store.clone(), 2);
+
+        executor
+            .invoke(prompt_args! {"input" => "say ping"})
+            .await
+            .unwrap();
+
+        let exemplars = store.exemplars().await;
+        assert_eq!(exemplars.len(), 1);
+        assert_eq!(exemplars[0].input, "say ping");
+        assert_eq!(
+            exemplars[0].actions,
+            [("Echo".to_string(), "ping".to_string())]
+        );
+        let inputs = executor
+            .agent_inputs(&prompt_args! {"input" => "say pong"})
+            .await;
+        let input = inputs["input"].as_str().unwrap();
+        assert!(input.contains("Input: say ping"));
+        assert!(input.ends_with("Task: say pong"));
+    }
+
+    #[tokio::test]
+    async fn test_feedback_is_tied_to_run() {
+        let memory: Arc<Mutex<dyn BaseMemory>> = SimpleMemory::new().into();
+        let store = Arc::new(InMemoryFeedbackStore::new());
+        let executor = AgentExecutor::from_agent(scripted_agent(0))
+            .with_memory(memory.clone())
+            .with_feedback_store(store.clone());
+
+        executor
+            .invoke(prompt_args! {"input" => "hi", "run_id" => "run-42"})
+            .await
+            .unwrap();
+        let answer_id = memory.lock().await.messages().last().unwrap().id.clone();
+        assert_eq!(answer_id.as_deref(), Some("run-42"));
+
+        executor
+            .record_feedback(Feedback::thumbs_down("run-42").with_comment("wrong"))
+            .await
+            .unwrap();
+        assert_eq!(store.for_run("run-42").await.unwrap().len(), 1);
+    }
+
+    struct Unreachable;
+
+    #[async_trait]
+    impl Probe for Unreachable {
+        fn name(&self) -> String {
+            "search-api".to_string()
+        }
+
+        async fn check(&self) -> Result<(), String> {
+            Err("connection refused".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_preflight_reports_failed_probes() {
+        let executor = AgentExecutor::from_agent(scripted_agent(0)).with_probe(Unreachable);
+
+        let report = executor.preflight().await;
+
+        let names = report
+            .checks
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["tool:Echo", "search-api"]);
+        assert!(!report.is_ready());
+        assert_eq!(
+            report.failures()[0].error.as_deref(),
+            Some("connection refused")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_degrades_when_preflight_fails() {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let executor = AgentExecutor::from_agent(scripted_agent(1))
+            .with_probe(Unreachable)
+            .with_degradation(
+                "search-api",
+                DegradationProfile::canned("Sorry, try later."),
+            )
+            .with_degradation_listener(move |event| recorded.lock().unwrap().push(event.clone()));
+
+        assert_eq!(
+            executor
+                .invoke(prompt_args! {"input" => "hi"})
+                .await
+                .unwrap(),
+            "ping"
+        );
+
+        executor.preflight().await;
+        assert_eq!(
+            executor
+                .invoke(prompt_args! {"input" => "hi"})
+                .await
+                .unwrap(),
+            "Sorry, try later."
+        );
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].subsystem, "search-api");
+        assert_eq!(events[0].profile, "canned");
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_releases_tools() {
+        let released = Arc::new(AtomicBool::new(false));
+        let executor = AgentExecutor::from_agent(Holding(Arc::new(Kernel(released.clone()))));
+        assert!(executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .is_ok());
+
+        executor.shutdown().await.unwrap();
+        assert!(released.load(Ordering::SeqCst));
+        assert!(executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .is_err());
+    }
+
+    /// Counts the emails it sends, one per distinct body.
+    struct Mailer(Arc<std::sync::atomic::AtomicUsize>);
+
+    #[async_trait]
+    impl Tool for Mailer {
+        fn name(&self) -> String {
+            "Mailer".to_string()
+        }
+        fn description(&self) -> String {
+            "Sends an email".to_string()
+        }
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            let sent = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(format!("email {} sent", sent))
+        }
+        fn idempotency_key(&self, input: &Value) -> Option<String> {
+            input.as_str().map(String::from)
+        }
+    }
+
+    /// Sends the same email twice, then finishes with the observations.
+    struct Retrying(Arc<dyn Tool>);
+
+    #[async_trait]
+    impl Agent for Retrying {
+        async fn plan(
+            &self,
+            intermediate_steps: &[(AgentAction, String)],
+            _inputs: PromptArgs,
+        ) -> Result<AgentEvent, AgentError> {
+            if intermediate_steps.len() == 2 {
+                let observations = intermediate_steps
+                    .iter()
+                    .map(|(_, observation)| observation.clone())
+                    .collect::<Vec<_>>();
+                return Ok(AgentEvent::Finish(AgentFinish {
+                    output: observations.join(", "),
+                }));
+            }
+            Ok(AgentEvent::Action(vec![AgentAction {
+                tool: "Mailer".to_string(),
+                tool_input: "hello".to_string(),
+                log: String::new(),
+            }]))
+        }
+
+        fn get_tools(&self) -> Vec<Arc<dyn Tool>> {
+            vec![self.0.clone()]
+        }
+    }
+
+    #[tokio::test]
+    async fn test_idempotent_actions_run_once() {
+        let sent = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let store = crate::tools::InMemoryIdempotencyStore::new();
+        let executor = AgentExecutor::from_agent(Retrying(Arc::new(Mailer(sent.clone()))))
+            .with_idempotency_store(store.clone());
+        let result = executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(result, "email 1 sent, email 1 sent");
+
+        // A replay with the same store does not send it again either.
+        let executor = AgentExecutor::from_agent(Retrying(Arc::new(Mailer(sent.clone()))))
+            .with_idempotency_store(store);
+        executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(sent.load(Ordering::SeqCst), 1);
+    }
+
+    /// Scores every step 2 out of 10.
+    fn harsh() -> FakeLLM {
+        FakeLLM::fixed("SCORE: 2\nCORRECTION: Ask the user instead.")
+    }
+
+    #[tokio::test]
+    async fn test_critic_corrects_and_vetoes() {
+        let executor =
+            AgentExecutor::from_agent(scripted_agent(1)).with_critic(StepCritic::new(harsh()));
+        let result = executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert_eq!(result, "ping\n\nReviewer: Ask the user instead.");
+
+        let executor = AgentExecutor::from_agent(scripted_agent(2))
+            .with_critic(StepCritic::new(harsh()).with_veto_threshold(0.3));
+        let result = executor
+            .invoke(prompt_args! {"input" => "hi"})
+            .await
+            .unwrap();
+        assert!(result.starts_with("The action was not run, a reviewer vetoed it: Ask the user"));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_answer_escalates_to_fallback() {
+        let executor = AgentExecutor::from_agent(scripted_agent(0))
+            .with_repetition_guard(RepetitionGuard::new().with_fallback(harsh()));
+        let first = executor
+            .invoke(prompt_args! {"input" => "What is Rust?"})
+            .await
+            .unwrap();
+        assert_eq!(first, "");
+
+        // The agent answers the same again, even after planning again.
+        let second = executor
+            .invoke(prompt_args! {"input" => "What is Go?"})
+            .await
+            .unwrap();
+        assert!(second.starts_with("SCORE: 2"));
+    }
+
+    /// Labels every answer, so formatted answers differ from what the agent said.
+    struct Labelled;
+
+    #[async_trait]
+    impl OutputParser for Labelled {
+        async fn parse(
+            &self,
+            output: &str,
+        ) -> Result<String, crate::output_parsers::OutputParserError> {
+            Ok(format!("Answer: {}", output))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repeated_formatted_answer_escalates_to_fallback() {
+        let executor = AgentExecutor::from_agent(scripted_agent(1))
+            .with_answer_formatter(Labelled)
+            .with_repetition_guard(RepetitionGuard::new().with_fallback(harsh()));
+        let first = executor
+            .invoke(prompt_args! {"input" => "What is Rust?"})
+            .await
+            .unwrap();
+        assert_eq!(first, "Answer: ping");
+
+        let second = executor
+            .invoke(prompt_args! {"input" => "What is Go?"})
+            .await
+            .unwrap();
+        assert!(second.starts_with("Answer: SCORE: 2"), "{}", second);
+
+        // The answer is remembered as returned as well, so repeating the formatted one counts.
+        let guard = RepetitionGuard::new();
+        let executor = AgentExecutor::from_agent(scripted_agent(1))
+            .with_answer_formatter(Labelled)
+            .with_repetition_guard(guard.clone());
+        executor
+            .invoke(prompt_args! {"input" => "What is Rust?"})
+            .await
+            .unwrap();
+        assert!(guard.is_repeated("What is Go?", "Answer: ping").await);
+    }
 }
@@ -0,0 +1,233 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::{
+    embedding::{Embedder, EmbedderError},
+    semantic_router::utils::cosine_similarity,
+};
+
+use super::{InputEnricher, RunContext};
+
+/// A solved task: the input, the `(tool, tool input)` calls that solved it and the answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exemplar {
+    pub input: String,
+    pub actions: Vec<(String, String)>,
+    pub answer: String,
+}
+
+impl Exemplar {
+    pub fn new<S: Into<String>, T: Into<String>>(
+        input: S,
+        actions: Vec<(String, String)>,
+        answer: T,
+    ) -> Self {
+        Self {
+            input: input.into(),
+            actions,
+            answer: answer.into(),
+        }
+    }
+
+    /// Renders the exemplar as a worked example for a prompt.
+    pub fn format(&self) -> String {
+        let mut out = format!("Input: {}\n", self.input);
+        for (tool, tool_input) in &self.actions {
+            out.push_str(&format!("Action: {}\nAction Input: {}\n", tool, tool_input));
+        }
+        out.push_str(&format!("Answer: {}", self.answer));
+        out
+    }
+}
+
+/// Exemplars mined from successful runs, retrieved by embedding similarity to a new input.
+///
+/// An exemplar whose input is nearly identical to a stored one (cosine similarity at or above
+/// the dedup threshold) only replaces it when it solved the task in fewer tool calls. Cloning
+/// shares the underlying store.
+#[derive(Clone)]
+pub struct ExemplarStore {
+    embedder: Arc<dyn Embedder>,
+    dedup_threshold: f64,
+    capacity: usize,
+    exemplars: Arc<Mutex<Vec<(Exemplar, Vec<f64>)>>>,
+}
+
+impl ExemplarStore {
+    pub fn new<E: Embedder + 'static>(embedder: E) -> Self {
+        Self {
+            embedder: Arc::new(embedder),
+            dedup_threshold: 0.95,
+            capacity: 500,
+            exemplars: Arc::default(),
+        }
+    }
+
+    pub fn with_dedup_threshold(mut self, dedup_threshold: f64) -> Self {
+        self.dedup_threshold = dedup_threshold;
+        self
+    }
+
+    /// Maximum number of exemplars kept. Once full the oldest exemplar is dropped.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Stores `exemplar`. Returns `false` when a duplicate that is at least as short was kept
+    /// instead.
+    pub async fn add(&self, exemplar: Exemplar) -> Result<bool, EmbedderError> {
+        let embedding = self.embedder.embed_query(&exemplar.input).await?;
+        let mut exemplars = self.exemplars.lock().await;
+
+        let duplicate = exemplars
+            .iter()
+            .position(|(_, stored)| cosine_similarity(stored, &embedding) >= self.dedup_threshold);
+        match duplicate {
+            Some(i) if exemplars[i].0.actions.len() <= exemplar.actions.len() => Ok(false),
+            Some(i) => {
+                exemplars[i] = (exemplar, embedding);
+                Ok(true)
+            }
+            None => {
+                if exemplars.len() == self.capacity {
+                    exemplars.remove(0);
+                }
+                exemplars.push((exemplar, embedding));
+                Ok(true)
+            }
+        }
+    }
+
+    /// The `k` exemplars whose inputs are most similar to `input`, most similar first.
+    pub async fn similar(&self, input: &str, k: usize) -> Result<Vec<Exemplar>, EmbedderError> {
+        if k == 0 || self.is_empty().await {
+            return Ok(Vec::new());
+        }
+        let embedding = self.embedder.embed_query(input).await?;
+        let exemplars = self.exemplars.lock().await;
+
+        let mut scored = exemplars
+            .iter()
+            .map(|(exemplar, stored)| (cosine_similarity(stored, &embedding), exemplar))
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        Ok(scored
+            .into_iter()
+            .take(k)
+            .map(|(_, exemplar)| exemplar.clone())
+            .collect())
+    }
+
+    pub async fn len(&self) -> usize {
+        self.exemplars.lock().await.len()
+    }
+
+    pub async fn is_empty(&self) -> bool {
+        self.exemplars.lock().await.is_empty()
+    }
+
+    /// Every stored exemplar, oldest first.
+    pub async fn exemplars(&self) -> Vec<Exemplar> {
+        self.exemplars
+            .lock()
+            .await
+            .iter()
+            .map(|(exemplar, _)| exemplar.clone())
+            .collect()
+    }
+}
+
+/// Shows the agent the `k` stored exemplars most similar to its input, and stores every run
+/// that finishes without a tool error.
+pub(crate) struct SimilarExemplars {
+    store: ExemplarStore,
+    k: usize,
+}
+
+impl SimilarExemplars {
+    pub(crate) fn new(store: ExemplarStore, k: usize) -> Self {
+        Self { store, k }
+    }
+}
+
+#[async_trait]
+impl InputEnricher for SimilarExemplars {
+    async fn enrich(&self, input: &str, task: String) -> String {
+        match self.store.similar(input, self.k).await {
+            Ok(exemplars) if !exemplars.is_empty() => {
+                let examples = exemplars
+                    .iter()
+                    .map(Exemplar::format)
+                    .collect::<Vec<_>>()
+                    .join("\n\n");
+                format!(
+                    "Solved examples of similar tasks:\n\n{}\n\nTask: {}",
+                    examples, task
+                )
+            }
+            Ok(_) => task,
+            Err(e) => {
+                log::warn!("Could not retrieve exemplars: {}", e);
+                task
+            }
+        }
+    }
+
+    async fn learn(&self, run: &RunContext<'_>, answer: &str) {
+        let Some(input) = run.input().filter(|_| !run.tool_failed) else {
+            return;
+        };
+        let actions = run
+            .steps
+            .iter()
+            .map(|(action, _)| (action.tool.clone(), action.tool_input.clone()))
+            .collect();
+        if let Err(e) = self.store.add(Exemplar::new(input, actions, answer)).await {
+            log::warn!("Could not store exemplar: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::LetterEmbedder;
+
+    fn exemplar(input: &str, calls: usize) -> Exemplar {
+        let actions = (0..calls)
+            .map(|_| ("Echo".to_string(), input.to_string()))
+            .collect();
+        Exemplar::new(input, actions, input)
+    }
+
+    #[tokio::test]
+    async fn test_duplicates_keep_shortest_solution() {
+        let store = ExemplarStore::new(LetterEmbedder);
+
+        assert!(store.add(exemplar("aaa", 2)).await.unwrap());
+        assert!(!store.add(exemplar("aaa", 3)).await.unwrap());
+        assert!(store.add(exemplar("aaa", 1)).await.unwrap());
+        assert!(store.add(exemplar("bbb", 1)).await.unwrap());
+
+        assert_eq!(store.len().await, 2);
+        assert_eq!(store.exemplars().await[0].actions.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_similar_ranks_by_input() {
+        let store = ExemplarStore::new(LetterEmbedder).with_capacity(2);
+        for input in ["aaa", "bbb", "ccc"] {
+            store.add(exemplar(input, 1)).await.unwrap();
+        }
+
+        let similar = store.similar("cc", 1).await.unwrap();
+
+        assert_eq!(store.len().await, 2);
+        assert_eq!(similar[0].input, "ccc");
+        assert!(store.similar("cc", 0).await.unwrap().is_empty());
+    }
+}
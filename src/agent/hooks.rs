@@ -0,0 +1,144 @@
+use async_trait::async_trait;
+
+use crate::{
+    chain::{AnswerVerifier, ChainError},
+    memory::LongTermMemory,
+    prompt::PromptArgs,
+    schemas::agent::AgentAction,
+};
+
+use super::AgentError;
+
+/// The run of an [`AgentExecutor`](super::AgentExecutor) as seen by its hooks.
+pub struct RunContext<'a> {
+    pub input_variables: &'a PromptArgs,
+    /// The actions taken so far with their observations.
+    pub steps: &'a [(AgentAction, String)],
+    /// How many times the agent planned before, counting from 0.
+    pub iteration: usize,
+    pub dry_run: bool,
+    /// Whether the agent already planned again from scratch, see [`Finish::Replan`].
+    pub replanned: bool,
+    /// Whether a tool call of the run failed.
+    pub tool_failed: bool,
+}
+
+impl RunContext<'_> {
+    /// The user's input as written, when it is a string.
+    pub fn input(&self) -> Option<&str> {
+        self.input_variables.get("input").and_then(|i| i.as_str())
+    }
+}
+
+/// Adds what is known about the user's input to the task the agent plans with, e.g. examples
+/// or documentation retrieved for it. Memory keeps the input as the user wrote it.
+#[async_trait]
+pub trait InputEnricher: Send + Sync {
+    /// Enriches `task`, which is `input` as enriched by the enrichers added before this one.
+    /// An enricher that cannot retrieve anything returns `task` as it is.
+    async fn enrich(&self, input: &str, task: String) -> String;
+
+    /// Called with the final answer of every run that is not a dry run, to learn from it.
+    async fn learn(&self, _run: &RunContext<'_>, _answer: &str) {}
+}
+
+/// What a [`StepHook`] makes of the last step.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepReview {
+    /// Appended to the observation of the step.
+    pub note: Option<String>,
+    /// Why the next action must not run. The agent is told and plans again.
+    pub veto: Option<String>,
+}
+
+/// What a [`StepHook`] makes of the final answer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finish {
+    /// The answer, possibly rewritten, passed on to the next hook.
+    Answer(String),
+    /// Discards the steps and has the agent plan again, with this note after its input.
+    Replan(String),
+}
+
+/// Watches the steps of an agent run and may stop, amend or veto them.
+///
+/// Hooks are called in the order they were added to the executor. Every method does nothing
+/// by default.
+#[async_trait]
+pub trait StepHook: Send + Sync {
+    /// Called before the agent plans. An error stops the run.
+    async fn before_plan(&self, _run: &RunContext<'_>) -> Result<(), AgentError> {
+        Ok(())
+    }
+
+    /// Called before a tool runs `action`. An error stops the run.
+    async fn before_action(
+        &self,
+        _run: &RunContext<'_>,
+        _action: &AgentAction,
+    ) -> Result<(), AgentError> {
+        Ok(())
+    }
+
+    /// Called once the observation of an action is the last of `run.steps`.
+    async fn after_step(&self, _run: &RunContext<'_>) -> StepReview {
+        StepReview::default()
+    }
+
+    /// Called with the final answer, before it is formatted and stored.
+    async fn on_finish(&self, _run: &RunContext<'_>, answer: String) -> Result<Finish, ChainError> {
+        Ok(Finish::Answer(answer))
+    }
+
+    /// Called with the answer as returned to the user, after every hook and the formatter, for
+    /// runs that are not dry runs.
+    async fn after_answer(&self, _run: &RunContext<'_>, _answer: &str) {}
+}
+
+#[async_trait]
+impl InputEnricher for LongTermMemory {
+    async fn enrich(&self, input: &str, task: String) -> String {
+        match self.recall(input).await {
+            Ok(Some(memories)) => format!("{}\n\n{}", memories, task),
+            Ok(None) => task,
+            Err(e) => {
+                log::warn!("Could not recall long-term memories: {}", e);
+                task
+            }
+        }
+    }
+
+    async fn learn(&self, run: &RunContext<'_>, answer: &str) {
+        let Some(input) = run.input() else {
+            return;
+        };
+        if let Err(e) = self.remember(input, answer).await {
+            log::warn!("Could not store long-term memory: {}", e);
+        }
+    }
+}
+
+/// Checks the final answer against the tool outputs of the run.
+#[async_trait]
+impl StepHook for AnswerVerifier {
+    async fn on_finish(&self, run: &RunContext<'_>, answer: String) -> Result<Finish, ChainError> {
+        if run.steps.is_empty() {
+            return Ok(Finish::Answer(answer));
+        }
+        let sources = run
+            .steps
+            .iter()
+            .map(|(_, observation)| observation.clone())
+            .collect::<Vec<_>>();
+        let (answer, report) = self
+            .check(run.input().unwrap_or_default(), &answer, &sources)
+            .await?;
+        if !report.is_supported() {
+            log::warn!(
+                "Unsupported claims in the final answer: {:?}",
+                report.unsupported()
+            );
+        }
+        Ok(Finish::Answer(answer))
+    }
+}
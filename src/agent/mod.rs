@@ -4,11 +4,50 @@ pub use agent::*;
 mod executor;
 pub use executor::*;
 
+mod hooks;
+pub use hooks::*;
+
+mod execute;
+pub use execute::*;
+
+mod simulator;
+pub use simulator::*;
+
 mod chat;
 pub use chat::*;
 
 mod open_ai_tools;
 pub use open_ai_tools::*;
 
+mod system_prompt;
+pub use system_prompt::*;
+
+mod exemplars;
+pub use exemplars::*;
+
+mod critic;
+pub use critic::*;
+
+mod docs_lookup;
+pub use docs_lookup::*;
+
+mod budget;
+pub use budget::*;
+
+mod repetition;
+pub use repetition::*;
+
+mod prompt_probe;
+pub use prompt_probe::*;
+
+mod preflight;
+pub use preflight::*;
+
+mod degradation;
+pub use degradation::*;
+
+mod adaptive;
+pub use adaptive::*;
+
 mod error;
 pub use error::*;
@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -21,6 +20,7 @@ use crate::{
 pub struct OpenAiToolAgent {
     pub(crate) chain: Box<dyn Chain>,
     pub(crate) tools: Vec<Arc<dyn Tool>>,
+    pub(crate) max_context_tokens: Option<usize>,
 }
 
 impl OpenAiToolAgent {
@@ -37,34 +37,34 @@ impl OpenAiToolAgent {
 
         Ok(prompt)
     }
+}
 
-    fn construct_scratchpad(
-        &self,
-        intermediate_steps: &[(AgentAction, String)],
-    ) -> Result<Vec<Message>, AgentError> {
-        let mut thoughts: Vec<Message> = Vec::new();
-
-        let mut tools_ai_message_seen: HashMap<String, ()> = HashMap::default();
-        for (action, observation) in intermediate_steps {
-            // Deserialize directly and embed in method calls to streamline code.
-            // Extract the tool ID and tool calls from the log.
-            let LogTools { tool_id, tools } = serde_json::from_str(&action.log)?;
-            let tools_vec: Vec<FunctionCallResponse> = serde_json::from_str(&tools)?;
-
-            // one action can trigger multiple observations.  But make sure we add it to
-            // the scratchpad before the related observations.  There can also be multiple
-            // different actions in the same thought chain.
-            if tools_ai_message_seen.insert(tools, ()).is_none() {
-                thoughts.push(Message::new_ai_message("").with_tool_calls(json!(tools_vec)));
-            }
-
-            // Add a tool message for each observation. Observation is the ouput of the tool call.
-            // tool_id is the id of the tool.
-            thoughts.push(Message::new_tool_message(observation, tool_id));
+/// Replays tool-call steps in the provider-native shape: one assistant message carrying every
+/// call of a model turn, followed by one tool message per call.
+///
+/// Steps from the same turn share their `tools` log and are consecutive, so a new assistant
+/// message starts whenever the log changes. Consecutive turns can make identical calls, so one
+/// also starts when the call was already answered since the last assistant message. Comparing
+/// with the current turn only, rather than every step seen, keeps turns apart when a provider
+/// reuses call ids across iterations.
+pub(crate) fn tool_call_messages(
+    steps: &[(AgentAction, String)],
+) -> Result<Vec<Message>, serde_json::Error> {
+    let mut messages = Vec::new();
+    let mut previous_tools: Option<String> = None;
+    let mut answered: Vec<String> = Vec::new();
+    for (action, observation) in steps {
+        let LogTools { tool_id, tools } = serde_json::from_str(&action.log)?;
+        if previous_tools.as_deref() != Some(tools.as_str()) || answered.contains(&tool_id) {
+            let calls: Vec<FunctionCallResponse> = serde_json::from_str(&tools)?;
+            messages.push(Message::new_ai_message("").with_tool_calls(json!(calls)));
+            previous_tools = Some(tools);
+            answered.clear();
         }
-
-        Ok(thoughts)
+        answered.push(tool_id.clone());
+        messages.push(Message::new_tool_message(observation, tool_id));
     }
+    Ok(messages)
 }
 
 #[async_trait]
@@ -72,10 +72,9 @@ impl Agent for OpenAiToolAgent {
     async fn plan(
         &self,
         intermediate_steps: &[(AgentAction, String)],
-        inputs: PromptArgs,
+        mut inputs: PromptArgs,
     ) -> Result<AgentEvent, AgentError> {
-        let mut inputs = inputs.clone();
-        let scratchpad = self.construct_scratchpad(intermediate_steps)?;
+        let scratchpad = tool_call_messages(intermediate_steps)?;
         inputs.insert("agent_scratchpad".to_string(), json!(scratchpad));
         let output = self.chain.call(inputs).await?.generation;
         match serde_json::from_str::<Vec<FunctionCallResponse>>(&output) {
@@ -103,4 +102,57 @@ impl Agent for OpenAiToolAgent {
     fn get_tools(&self) -> Vec<Arc<dyn Tool>> {
         self.tools.clone()
     }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.max_context_tokens
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(tool_id: &str, tools: &str, observation: &str) -> (AgentAction, String) {
+        let log = LogTools {
+            tool_id: tool_id.to_string(),
+            tools: tools.to_string(),
+        };
+        (
+            AgentAction {
+                tool: "search".to_string(),
+                tool_input: "{}".to_string(),
+                log: serde_json::to_string(&log).unwrap(),
+            },
+            observation.to_string(),
+        )
+    }
+
+    #[test]
+    fn test_tool_call_messages_group_calls_by_turn() {
+        let parallel = r#"[{"id":"call_0","type":"function","function":{"name":"search","arguments":"{}"}},{"id":"call_1","type":"function","function":{"name":"search","arguments":"{}"}}]"#;
+        let repeated =
+            r#"[{"id":"call_0","type":"function","function":{"name":"search","arguments":"{}"}}]"#;
+        let steps = [
+            step("call_0", parallel, "a"),
+            step("call_1", parallel, "b"),
+            step("call_0", repeated, "c"),
+            step("call_0", repeated, "d"),
+        ];
+
+        let messages = tool_call_messages(&steps[..3]).unwrap();
+
+        let types = messages
+            .iter()
+            .map(|m| m.tool_calls.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(types, [true, false, false, true, false]);
+        assert_eq!(messages[0].tool_calls.as_ref().unwrap()[1]["id"], "call_1");
+        assert_eq!(messages[4].id.as_deref(), Some("call_0"));
+
+        // The identical second turn gets an assistant message of its own.
+        let messages = tool_call_messages(&steps).unwrap();
+        assert_eq!(messages.len(), 7);
+        assert!(messages[5].tool_calls.is_some());
+        assert_eq!(messages[6].id.as_deref(), Some("call_0"));
+    }
 }
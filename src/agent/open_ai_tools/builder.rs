@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use crate::{
-    agent::AgentError,
+    agent::{AgentError, PromptLayer, SystemPrompt},
     chain::{options::ChainCallOptions, LLMChainBuilder},
     language_models::{llm::LLM, options::CallOptions},
     schemas::FunctionDefinition,
@@ -12,7 +12,7 @@ use super::{prompt::PREFIX, OpenAiToolAgent};
 
 pub struct OpenAiToolAgentBuilder {
     tools: Option<Vec<Arc<dyn Tool>>>,
-    prefix: Option<String>,
+    system_prompt: SystemPrompt,
     options: Option<ChainCallOptions>,
 }
 
@@ -20,7 +20,7 @@ impl OpenAiToolAgentBuilder {
     pub fn new() -> Self {
         Self {
             tools: None,
-            prefix: None,
+            system_prompt: SystemPrompt::new(),
             options: None,
         }
     }
@@ -31,7 +31,19 @@ impl OpenAiToolAgentBuilder {
     }
 
     pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
-        self.prefix = Some(prefix.into());
+        self.system_prompt.replace(PromptLayer::Persona, prefix);
+        self
+    }
+
+    /// Appends `text` to one layer of the system prompt, leaving the prefix and the other
+    /// layers as they are.
+    pub fn system_layer<S: Into<String>>(mut self, layer: PromptLayer, text: S) -> Self {
+        self.system_prompt.add(layer, text);
+        self
+    }
+
+    pub fn system_prompt(mut self, system_prompt: SystemPrompt) -> Self {
+        self.system_prompt = system_prompt;
         self
     }
 
@@ -42,8 +54,9 @@ impl OpenAiToolAgentBuilder {
 
     pub fn build<L: LLM + 'static>(self, llm: L) -> Result<OpenAiToolAgent, AgentError> {
         let tools = self.tools.unwrap_or_default();
-        let prefix = self.prefix.unwrap_or_else(|| PREFIX.to_string());
+        let prefix = self.system_prompt.render_with_default_persona(PREFIX);
         let mut llm = llm;
+        let max_context_tokens = llm.max_context_tokens();
 
         let prompt = OpenAiToolAgent::create_prompt(&prefix)?;
         let default_options = ChainCallOptions::default().with_max_tokens(1000);
@@ -60,6 +73,10 @@ impl OpenAiToolAgentBuilder {
                 .build()?,
         );
 
-        Ok(OpenAiToolAgent { chain, tools })
+        Ok(OpenAiToolAgent {
+            chain,
+            tools,
+            max_context_tokens,
+        })
     }
 }
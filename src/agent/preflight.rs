@@ -0,0 +1,226 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    language_models::{llm::LLM, Stopwatch},
+    tools::Tool,
+};
+
+/// A dependency an agent needs at runtime, checked by
+/// [`AgentExecutor::preflight`](super::AgentExecutor::preflight).
+#[async_trait]
+pub trait Probe: Send + Sync {
+    fn name(&self) -> String;
+
+    /// Returns why the dependency is not usable.
+    async fn check(&self) -> Result<(), String>;
+}
+
+/// Checks that an LLM is reachable and its model available with a one-word prompt.
+pub struct LLMProbe {
+    name: String,
+    llm: Box<dyn LLM>,
+}
+
+impl LLMProbe {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            name: "llm".to_string(),
+            llm: llm.into(),
+        }
+    }
+
+    /// Name of the check in the report, to tell several LLMs apart.
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+}
+
+#[async_trait]
+impl Probe for LLMProbe {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        self.llm
+            .invoke("Reply with OK.")
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Pings the MCP server, after the `initialize` handshake.
+#[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
+#[async_trait]
+impl Probe for crate::llm::mcp::McpClient {
+    fn name(&self) -> String {
+        "mcp".to_string()
+    }
+
+    async fn check(&self) -> Result<(), String> {
+        self.ping().await.map_err(|e| e.to_string())
+    }
+}
+
+/// The outcome of one preflight check.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    pub name: String,
+    /// Why the check failed, `None` if it passed.
+    pub error: Option<String>,
+    /// `None` on wasm32, where there is no clock.
+    pub duration_ms: Option<u64>,
+}
+
+impl PreflightCheck {
+    pub fn passed(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl ReadinessReport {
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(PreflightCheck::passed)
+    }
+
+    pub fn failures(&self) -> Vec<&PreflightCheck> {
+        self.checks.iter().filter(|check| !check.passed()).collect()
+    }
+}
+
+/// Runs `check`, failing it if it takes longer than `timeout`. wasm32 has no timer, so there
+/// the check is awaited without a limit.
+pub(crate) async fn run_check<F>(name: String, timeout: Duration, check: F) -> PreflightCheck
+where
+    F: std::future::Future<Output = Result<(), String>>,
+{
+    let stopwatch = Stopwatch::start();
+    let result = if cfg!(target_arch = "wasm32") {
+        check.await
+    } else {
+        tokio::time::timeout(timeout, check)
+            .await
+            .unwrap_or_else(|_| Err(format!("Timed out after {:?}", timeout)))
+    };
+    PreflightCheck {
+        name,
+        error: result.err(),
+        duration_ms: stopwatch.elapsed_ms(),
+    }
+}
+
+/// One check per tool: the name must be usable in a function call and unique, and the
+/// parameters must be an object schema whose required fields are declared.
+pub(crate) fn check_tools(tools: &[Arc<dyn Tool>]) -> Vec<PreflightCheck> {
+    let mut seen = HashSet::new();
+    tools
+        .iter()
+        .map(|tool| {
+            let stopwatch = Stopwatch::start();
+            let name = tool.name();
+            let error = if name.is_empty()
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                Some("The name may only contain letters, digits, '_' and '-'".to_string())
+            } else if !seen.insert(name.clone()) {
+                Some("Another tool has the same name".to_string())
+            } else {
+                check_parameters(&tool.parameters()).err()
+            };
+            PreflightCheck {
+                name: format!("tool:{}", name),
+                error,
+                duration_ms: stopwatch.elapsed_ms(),
+            }
+        })
+        .collect()
+}
+
+fn check_parameters(schema: &Value) -> Result<(), String> {
+    if schema.get("type").and_then(Value::as_str) != Some("object") {
+        return Err("The parameters must be a schema of type object".to_string());
+    }
+    let properties = match schema.get("properties") {
+        None => None,
+        Some(Value::Object(properties)) => Some(properties),
+        Some(_) => return Err("The parameters' properties must be an object".to_string()),
+    };
+    if let Some(required) = schema.get("required") {
+        let required = required
+            .as_array()
+            .ok_or("The parameters' required fields must be an array")?;
+        for field in required {
+            let declared = field
+                .as_str()
+                .is_some_and(|field| properties.is_some_and(|p| p.contains_key(field)));
+            if !declared {
+                return Err(format!("The required field {} is not a property", field));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use serde_json::json;
+
+    use super::*;
+
+    struct Schema(&'static str, Value);
+
+    #[async_trait]
+    impl Tool for Schema {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+        fn description(&self) -> String {
+            String::new()
+        }
+        fn parameters(&self) -> Value {
+            self.1.clone()
+        }
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            Ok(String::new())
+        }
+    }
+
+    #[test]
+    fn test_check_tools() {
+        let valid = json!({"type": "object", "properties": {"q": {}}, "required": ["q"]});
+        let tools: Vec<Arc<dyn Tool>> = vec![
+            Arc::new(Schema("search", valid.clone())),
+            Arc::new(Schema("search", valid.clone())),
+            Arc::new(Schema("web search", valid)),
+            Arc::new(Schema(
+                "lookup",
+                json!({"type": "object", "required": ["q"]}),
+            )),
+        ];
+
+        let errors = check_tools(&tools)
+            .into_iter()
+            .map(|check| check.error)
+            .collect::<Vec<_>>();
+
+        assert_eq!(errors[0], None);
+        assert!(errors[1].as_ref().unwrap().contains("same name"));
+        assert!(errors[2].as_ref().unwrap().contains("name"));
+        assert!(errors[3].as_ref().unwrap().contains("required field"));
+    }
+}
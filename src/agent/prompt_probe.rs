@@ -0,0 +1,168 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde_json::json;
+
+use crate::{
+    language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError},
+    prompt::{PromptArgs, RenderedPrompt},
+    schemas::{FunctionDefinition, Message, StreamData},
+};
+
+use super::{Agent, AgentError};
+
+#[derive(Default)]
+struct Recorded {
+    messages: Option<Vec<Message>>,
+    functions: Vec<FunctionDefinition>,
+}
+
+/// A stand-in model that records the prompt an agent plans with instead of answering it, to
+/// test agent prompts without calling a model.
+///
+/// Build the agent with a clone of the probe, then [`Self::render`] its planning prompt and
+/// check it with the assertions of [`RenderedPrompt`]. The tools the builder gives the model
+/// as function definitions are recorded too. Clones share the recording.
+///
+/// ```rust,ignore
+/// let probe = PromptProbe::new();
+/// let agent = OpenAiToolAgentBuilder::new().tools(&tools).build(probe.clone())?;
+/// probe
+///     .render(&agent, prompt_args! {"input" => "What is 2 + 2?"})
+///     .await?
+///     .assert_tool(&tools[0])
+///     .assert_max_tokens(1500);
+/// ```
+#[derive(Clone, Default)]
+pub struct PromptProbe {
+    recorded: Arc<Mutex<Recorded>>,
+}
+
+impl PromptProbe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs one planning step of `agent` on `inputs`, with no intermediate steps and an empty
+    /// chat history unless given, and returns the prompt it sent.
+    pub async fn render(
+        &self,
+        agent: &dyn Agent,
+        mut inputs: PromptArgs,
+    ) -> Result<RenderedPrompt, AgentError> {
+        inputs
+            .entry("chat_history".to_string())
+            .or_insert_with(|| json!([]));
+        self.recorded.lock().unwrap().messages = None;
+        // The plan made of the canned answer does not matter, only the prompt does.
+        let planned = agent.plan(&[], inputs).await;
+        let recorded = self.recorded.lock().unwrap();
+        match &recorded.messages {
+            Some(messages) => Ok(RenderedPrompt::new(
+                messages.clone(),
+                recorded.functions.clone(),
+            )),
+            None => Err(planned.err().unwrap_or_else(|| {
+                AgentError::OtherError("The agent did not plan with the probe".to_string())
+            })),
+        }
+    }
+}
+
+#[async_trait]
+impl LLM for PromptProbe {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        self.recorded.lock().unwrap().messages = Some(messages.to_vec());
+        Ok(GenerateResult {
+            generation: r#"{"action": "Final Answer", "action_input": ""}"#.to_string(),
+            ..Default::default()
+        })
+    }
+
+    async fn stream(
+        &self,
+        _messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        Err(LLMError::OtherError(
+            "A prompt probe does not stream".to_string(),
+        ))
+    }
+
+    fn add_options(&mut self, options: CallOptions) {
+        if let Some(functions) = options.functions {
+            self.recorded.lock().unwrap().functions.extend(functions);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use serde_json::Value;
+
+    use crate::{
+        agent::{ConversationalAgentBuilder, OpenAiToolAgentBuilder},
+        prompt_args,
+        schemas::MessageType,
+        tools::Tool,
+    };
+
+    use super::*;
+
+    struct Weather;
+
+    #[async_trait]
+    impl Tool for Weather {
+        fn name(&self) -> String {
+            "weather".to_string()
+        }
+
+        fn description(&self) -> String {
+            "Current weather of a city".to_string()
+        }
+
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            Ok("Sunny".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_renders_planning_prompts() {
+        let tools: Vec<Arc<dyn Tool>> = vec![Arc::new(Weather)];
+        let inputs = prompt_args! {"input" => "Weather in Lima?"};
+
+        let probe = PromptProbe::new();
+        let agent = ConversationalAgentBuilder::new()
+            .tools(&tools)
+            .build(probe.clone())
+            .unwrap();
+        let prompt = probe.render(&agent, inputs.clone()).await.unwrap();
+        prompt
+            .assert_section("TOOLS")
+            .assert_section("RESPONSE FORMAT INSTRUCTIONS")
+            .assert_contains("Weather in Lima?")
+            .assert_tool(&tools[0])
+            .assert_max_tokens(1000);
+        assert!(prompt.functions().is_empty());
+
+        let probe = PromptProbe::new();
+        let agent = OpenAiToolAgentBuilder::new()
+            .tools(&tools)
+            .build(probe.clone())
+            .unwrap();
+        let prompt = probe.render(&agent, inputs).await.unwrap();
+        prompt
+            .assert_tool(&tools[0])
+            .assert_not_contains("RESPONSE FORMAT INSTRUCTIONS");
+        assert_eq!(prompt.functions().len(), 1);
+        assert_eq!(
+            prompt.messages().last().unwrap().message_type,
+            MessageType::HumanMessage
+        );
+    }
+}
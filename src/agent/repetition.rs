@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{
+    chain::ChainError, embedding::Embedder, language_models::llm::LLM,
+    semantic_router::utils::cosine_similarity,
+};
+
+use super::{Finish, RunContext, StepHook};
+
+const REPLAN_NOTE: &str = "Your previous answer repeated the answer to an earlier question. \
+Answer this question on its own.";
+
+struct Answer {
+    text: String,
+    embedding: Option<Vec<f64>>,
+}
+
+/// The last input with the answer the guard saw and, when later hooks or the formatter changed
+/// it, the answer the user got.
+struct Turn {
+    input: String,
+    answers: Vec<Answer>,
+}
+
+/// Catches an agent answering a new question with the answer it gave to the previous one, a
+/// common failure of small local models stuck on their chat history.
+///
+/// Given to an executor with
+/// [`AgentExecutor::with_repetition_guard`](super::AgentExecutor::with_repetition_guard), a
+/// repeated answer makes the agent plan again from a cleared scratchpad, with the context
+/// retrieved again and a warning about the repetition. If the new answer repeats too, the
+/// fallback LLM, when set, answers the input instead.
+///
+/// Answers are the same when their normalized text is equal or, with an embedder, when their
+/// embeddings are similar enough. A new answer is compared both with the previous answer as
+/// the guard saw it and as it was returned, after the formatter. Clones share the last turn.
+#[derive(Clone, Default)]
+pub struct RepetitionGuard {
+    embedder: Option<Arc<dyn Embedder>>,
+    threshold: f64,
+    fallback: Option<Arc<dyn LLM>>,
+    last_turn: Arc<Mutex<Option<Turn>>>,
+}
+
+impl RepetitionGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compares the answers by the cosine similarity of their embeddings, the same above
+    /// `threshold`, e.g. 0.95.
+    pub fn with_embedder<E: Embedder + 'static>(mut self, embedder: E, threshold: f64) -> Self {
+        self.embedder = Some(Arc::new(embedder));
+        self.threshold = threshold;
+        self
+    }
+
+    /// The model escalated to when planning again did not help, usually a larger one.
+    pub fn with_fallback<L: Into<Box<dyn LLM>>>(mut self, fallback: L) -> Self {
+        self.fallback = Some(Arc::from(fallback.into()));
+        self
+    }
+
+    async fn embed(&self, answer: &str) -> Option<Vec<f64>> {
+        let embedder = self.embedder.as_ref()?;
+        match embedder.embed_query(answer).await {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
+                log::warn!("Could not embed the answer: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Whether `answer` to `input` repeats the answer to a different previous input.
+    pub async fn is_repeated(&self, input: &str, answer: &str) -> bool {
+        let embedding = self.embed(answer).await;
+        let last_turn = self.last_turn.lock().await;
+        let Some(last_turn) = last_turn
+            .as_ref()
+            .filter(|turn| normalize(&turn.input) != normalize(input))
+        else {
+            return false;
+        };
+        last_turn
+            .answers
+            .iter()
+            .any(|last| match (&last.embedding, &embedding) {
+                (Some(last), Some(embedding)) => {
+                    cosine_similarity(last, embedding) >= self.threshold
+                }
+                _ => normalize(&last.text) == normalize(answer),
+            })
+    }
+
+    /// Records the answer given to `input`, to compare the next answer with.
+    pub async fn remember(&self, input: &str, answer: &str) {
+        let answer = Answer {
+            text: answer.to_string(),
+            embedding: self.embed(answer).await,
+        };
+        *self.last_turn.lock().await = Some(Turn {
+            input: input.to_string(),
+            answers: vec![answer],
+        });
+    }
+
+    /// Adds the answer `input` finally got to the last turn, when it differs from the one
+    /// remembered for it.
+    async fn remember_final(&self, input: &str, answer: &str) {
+        {
+            let last_turn = self.last_turn.lock().await;
+            if let Some(turn) = last_turn.as_ref().filter(|turn| turn.input == input) {
+                if turn
+                    .answers
+                    .iter()
+                    .any(|last| normalize(&last.text) == normalize(answer))
+                {
+                    return;
+                }
+            }
+        }
+        let answer = Answer {
+            text: answer.to_string(),
+            embedding: self.embed(answer).await,
+        };
+        let mut last_turn = self.last_turn.lock().await;
+        match last_turn.as_mut().filter(|turn| turn.input == input) {
+            Some(turn) => turn.answers.push(answer),
+            None => {
+                *last_turn = Some(Turn {
+                    input: input.to_string(),
+                    answers: vec![answer],
+                })
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl StepHook for RepetitionGuard {
+    async fn on_finish(
+        &self,
+        run: &RunContext<'_>,
+        mut answer: String,
+    ) -> Result<Finish, ChainError> {
+        let Some(input) = run.input() else {
+            return Ok(Finish::Answer(answer));
+        };
+        if self.is_repeated(input, &answer).await {
+            if !run.replanned {
+                log::warn!("The agent repeated its previous answer, planning again");
+                return Ok(Finish::Replan(REPLAN_NOTE.to_string()));
+            }
+            if let Some(fallback) = &self.fallback {
+                log::warn!("The agent repeated its previous answer again, escalating");
+                answer = fallback.invoke(input).await?;
+            }
+        }
+        if !run.dry_run {
+            self.remember(input, &answer).await;
+        }
+        Ok(Finish::Answer(answer))
+    }
+
+    async fn after_answer(&self, run: &RunContext<'_>, answer: &str) {
+        if let Some(input) = run.input() {
+            self.remember_final(input, answer).await;
+        }
+    }
+}
+
+/// Lowercase words without punctuation, so formatting differences do not count.
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_detects_same_answer_to_new_input() {
+        let guard = RepetitionGuard::new();
+        assert!(!guard.is_repeated("What is Rust?", "A language.").await);
+        guard.remember("What is Rust?", "A language.").await;
+
+        assert!(guard.is_repeated("What is Go?", "a  language").await);
+        assert!(!guard.is_repeated("what is rust", "A language.").await);
+        assert!(!guard.is_repeated("What is Go?", "Another language.").await);
+
+        guard
+            .remember_final("What is Rust?", "Answer: A systems language.")
+            .await;
+        assert!(guard.is_repeated("What is Go?", "A language.").await);
+        assert!(
+            guard
+                .is_repeated("What is Go?", "Answer: a systems language")
+                .await
+        );
+    }
+}
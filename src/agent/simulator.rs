@@ -0,0 +1,151 @@
+use std::{collections::HashMap, error::Error};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    language_models::llm::LLM,
+    schemas::{agent::AgentAction, Message},
+    tools::Tool,
+};
+
+/// Answers tool calls on behalf of the real tool.
+///
+/// When an executor is given a simulator it runs in dry-run mode: the agent plans as usual but
+/// no tool is ever executed, which makes it safe to preview an agent against production systems.
+#[async_trait]
+pub trait ToolSimulator: Send + Sync {
+    async fn simulate(
+        &self,
+        tool: &dyn Tool,
+        action: &AgentAction,
+    ) -> Result<String, Box<dyn Error>>;
+}
+
+/// Replies with a fixed observation per tool name.
+pub struct StaticSimulator {
+    fixtures: HashMap<String, String>,
+    default_response: Option<String>,
+}
+
+impl Default for StaticSimulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StaticSimulator {
+    pub fn new() -> Self {
+        Self {
+            fixtures: HashMap::new(),
+            default_response: None,
+        }
+    }
+
+    pub fn with_fixture<S: Into<String>, R: Into<String>>(mut self, tool: S, response: R) -> Self {
+        self.fixtures.insert(tool.into(), response.into());
+        self
+    }
+
+    /// Response used for tools without a fixture. Without it, unknown tools return an error.
+    pub fn with_default_response<S: Into<String>>(mut self, response: S) -> Self {
+        self.default_response = Some(response.into());
+        self
+    }
+}
+
+#[async_trait]
+impl ToolSimulator for StaticSimulator {
+    async fn simulate(
+        &self,
+        tool: &dyn Tool,
+        _action: &AgentAction,
+    ) -> Result<String, Box<dyn Error>> {
+        self.fixtures
+            .get(&tool.name())
+            .or(self.default_response.as_ref())
+            .cloned()
+            .ok_or_else(|| format!("No fixture for tool {}", tool.name()).into())
+    }
+}
+
+/// A recorded tool invocation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ToolTrace {
+    pub tool: String,
+    pub input: String,
+    pub output: String,
+}
+
+/// Replays observations from previously recorded traces.
+///
+/// A trace matching both tool and input wins; otherwise the first trace recorded for the tool
+/// is used.
+pub struct TraceSimulator {
+    traces: Vec<ToolTrace>,
+}
+
+impl TraceSimulator {
+    pub fn new(traces: Vec<ToolTrace>) -> Self {
+        Self { traces }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(serde_json::from_str(json)?))
+    }
+}
+
+#[async_trait]
+impl ToolSimulator for TraceSimulator {
+    async fn simulate(
+        &self,
+        tool: &dyn Tool,
+        action: &AgentAction,
+    ) -> Result<String, Box<dyn Error>> {
+        let name = tool.name();
+        let same_tool = |trace: &&ToolTrace| trace.tool == name;
+        self.traces
+            .iter()
+            .filter(same_tool)
+            .find(|trace| trace.input.trim() == action.tool_input.trim())
+            .or_else(|| self.traces.iter().find(same_tool))
+            .map(|trace| trace.output.clone())
+            .ok_or_else(|| format!("No recorded trace for tool {}", name).into())
+    }
+}
+
+const SIMULATOR_PROMPT: &str = "You are simulating a software tool so an agent can be tested \
+without side effects. Reply only with the raw output the tool would most plausibly return for \
+the given input, without explanations.";
+
+/// Asks an LLM to pretend to be the tool.
+pub struct LLMSimulator {
+    llm: Box<dyn LLM>,
+}
+
+impl LLMSimulator {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self { llm: llm.into() }
+    }
+}
+
+#[async_trait]
+impl ToolSimulator for LLMSimulator {
+    async fn simulate(
+        &self,
+        tool: &dyn Tool,
+        action: &AgentAction,
+    ) -> Result<String, Box<dyn Error>> {
+        let messages = [
+            Message::new_system_message(SIMULATOR_PROMPT),
+            Message::new_human_message(format!(
+                "Tool name: {}\nTool description: {}\nTool parameters: {}\nInput: {}",
+                tool.name(),
+                tool.description(),
+                tool.parameters(),
+                action.tool_input
+            )),
+        ];
+        Ok(self.llm.generate(&messages).await?.generation)
+    }
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+
+/// The layers a system prompt is assembled from, in the order they are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PromptLayer {
+    /// Instructions the framework itself needs, e.g. how to call tools.
+    Framework,
+    /// Instructions contributed by capabilities such as guardrails.
+    Capability,
+    /// Who the assistant is. The builders' `prefix` sets this layer, and the agent's default
+    /// prefix is used when it is empty.
+    Persona,
+    /// Instructions specific to where the agent runs, e.g. the tenant or the current date.
+    Deployment,
+}
+
+/// A system prompt made of layers, so capabilities and deployments can add instructions
+/// without overwriting the user's prefix.
+///
+/// Contributions to a layer are appended in order, unless the layer is explicitly replaced.
+/// Layers are rendered framework first and deployment last, separated by blank lines, and
+/// empty layers are skipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemPrompt {
+    layers: BTreeMap<PromptLayer, Vec<String>>,
+}
+
+impl SystemPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `text` to `layer`.
+    pub fn with_layer<S: Into<String>>(mut self, layer: PromptLayer, text: S) -> Self {
+        self.add(layer, text);
+        self
+    }
+
+    pub fn add<S: Into<String>>(&mut self, layer: PromptLayer, text: S) {
+        self.layers.entry(layer).or_default().push(text.into());
+    }
+
+    /// Drops what `layer` had so far and sets it to `text`.
+    pub fn replace<S: Into<String>>(&mut self, layer: PromptLayer, text: S) {
+        self.layers.insert(layer, vec![text.into()]);
+    }
+
+    pub fn layer(&self, layer: PromptLayer) -> &[String] {
+        self.layers
+            .get(&layer)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Renders the prompt, using `default_persona` if no persona was given.
+    pub fn render_with_default_persona(&self, default_persona: &str) -> String {
+        let mut prompt = self.clone();
+        if prompt.layer(PromptLayer::Persona).is_empty() {
+            prompt.replace(PromptLayer::Persona, default_persona);
+        }
+        prompt.render()
+    }
+
+    pub fn render(&self) -> String {
+        self.layers
+            .values()
+            .flatten()
+            .map(|text| text.trim())
+            .filter(|text| !text.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layers_render_in_order() {
+        let mut prompt = SystemPrompt::new()
+            .with_layer(PromptLayer::Deployment, "Today is Monday.")
+            .with_layer(PromptLayer::Capability, "Never reveal secrets.")
+            .with_layer(PromptLayer::Persona, "You are a pirate.");
+        prompt.add(PromptLayer::Capability, "Answer in English.");
+
+        assert_eq!(
+            prompt.render(),
+            "Never reveal secrets.\n\nAnswer in English.\n\nYou are a pirate.\n\nToday is Monday."
+        );
+    }
+
+    #[test]
+    fn test_default_persona_only_fills_an_empty_layer() {
+        let prompt = SystemPrompt::new().with_layer(PromptLayer::Capability, "Be safe.");
+        assert_eq!(
+            prompt.render_with_default_persona("You help."),
+            "Be safe.\n\nYou help."
+        );
+
+        let mut prompt = prompt.with_layer(PromptLayer::Persona, "You joke.");
+        assert_eq!(
+            prompt.render_with_default_persona("You help."),
+            "Be safe.\n\nYou joke."
+        );
+        prompt.replace(PromptLayer::Persona, "You teach.");
+        assert_eq!(prompt.render(), "Be safe.\n\nYou teach.");
+    }
+}
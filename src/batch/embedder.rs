@@ -0,0 +1,83 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::embedding::{embedder_trait::Embedder, EmbedderError};
+
+use super::{BatchOutput, BatchProcessor, BatchRequest};
+
+/// An [`Embedder`] that sends `embed_documents` through a batch job and waits for it.
+///
+/// Give it to a vector store to index a large corpus through the batch endpoint, which is
+/// cheaper but can take hours. Queries are embedded through the `query_embedder` so searches
+/// are not delayed.
+pub struct BatchEmbedder {
+    processor: Arc<dyn BatchProcessor>,
+    query_embedder: Arc<dyn Embedder>,
+    poll_interval: Duration,
+}
+
+impl BatchEmbedder {
+    pub fn new<P, E>(processor: P, query_embedder: E) -> Self
+    where
+        P: BatchProcessor + 'static,
+        E: Embedder + 'static,
+    {
+        Self {
+            processor: Arc::new(processor),
+            query_embedder: Arc::new(query_embedder),
+            poll_interval: Duration::from_secs(30),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+#[async_trait]
+impl Embedder for BatchEmbedder {
+    async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
+        if documents.is_empty() {
+            return Ok(Vec::new());
+        }
+        let requests = documents
+            .iter()
+            .enumerate()
+            .map(|(i, document)| BatchRequest::embedding(i.to_string(), document.as_str()))
+            .collect();
+        let job = self.processor.submit(requests).await?;
+        let results = self.processor.wait(&job.id, self.poll_interval).await?;
+
+        let mut embeddings = vec![None; documents.len()];
+        for result in results {
+            let index = result
+                .custom_id
+                .parse::<usize>()
+                .ok()
+                .filter(|&i| i < embeddings.len());
+            if let Some(error) = result.error {
+                return Err(EmbedderError::BatchFailed(error));
+            }
+            if let (Some(index), Some(BatchOutput::Embedding { embedding })) =
+                (index, result.output)
+            {
+                embeddings[index] = Some(embedding);
+            }
+        }
+        embeddings
+            .into_iter()
+            .enumerate()
+            .map(|(i, embedding)| {
+                embedding.ok_or_else(|| {
+                    EmbedderError::BatchFailed(format!("No embedding returned for document {}", i))
+                })
+            })
+            .collect()
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
+        self.query_embedder.embed_query(text).await
+    }
+}
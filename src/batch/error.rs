@@ -0,0 +1,27 @@
+#[cfg(feature = "openai")]
+use async_openai::error::OpenAIError;
+use thiserror::Error;
+
+use super::BatchStatus;
+
+#[derive(Error, Debug)]
+pub enum BatchError {
+    #[cfg(feature = "openai")]
+    #[error("OpenAI error: {0}")]
+    OpenAIError(#[from] OpenAIError),
+
+    #[error("Serde json error: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("Batch job not found: {0}")]
+    JobNotFound(String),
+
+    #[error("Batch job {id} has no results yet, status: {status}")]
+    NotReady { id: String, status: BatchStatus },
+
+    #[error("Batch job {id} ended with status {status}")]
+    JobFailed { id: String, status: BatchStatus },
+
+    #[error("Invalid batch: {0}")]
+    InvalidBatch(String),
+}
@@ -0,0 +1,131 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{language_models::GenerateResult, schemas::Message};
+
+/// What a single batch request asks for.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchInput {
+    Generation { messages: Vec<Message> },
+    Embedding { text: String },
+}
+
+/// One request of a batch job. `custom_id` must be unique within the job and is used to match
+/// results back to their inputs, as results may come back in any order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchRequest {
+    pub custom_id: String,
+    pub input: BatchInput,
+}
+
+impl BatchRequest {
+    pub fn generation<S: Into<String>>(custom_id: S, messages: Vec<Message>) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            input: BatchInput::Generation { messages },
+        }
+    }
+
+    pub fn embedding<S: Into<String>, T: Into<String>>(custom_id: S, text: T) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            input: BatchInput::Embedding { text: text.into() },
+        }
+    }
+
+    pub fn is_embedding(&self) -> bool {
+        matches!(self.input, BatchInput::Embedding { .. })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    /// Accepted but not started yet.
+    Queued,
+    InProgress,
+    Completed,
+    Failed,
+    Cancelled,
+    Expired,
+}
+
+impl BatchStatus {
+    /// Whether the job will not change anymore.
+    pub fn is_terminal(&self) -> bool {
+        !matches!(self, BatchStatus::Queued | BatchStatus::InProgress)
+    }
+}
+
+impl fmt::Display for BatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self {
+            BatchStatus::Queued => "queued",
+            BatchStatus::InProgress => "in_progress",
+            BatchStatus::Completed => "completed",
+            BatchStatus::Failed => "failed",
+            BatchStatus::Cancelled => "cancelled",
+            BatchStatus::Expired => "expired",
+        };
+        write!(f, "{}", status)
+    }
+}
+
+/// A submitted batch job and its progress.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BatchJob {
+    pub id: String,
+    pub status: BatchStatus,
+    pub total: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BatchOutput {
+    Generation { result: GenerateResult },
+    Embedding { embedding: Vec<f64> },
+}
+
+/// Outcome of one request. Exactly one of `output` and `error` is set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    pub custom_id: String,
+    pub output: Option<BatchOutput>,
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    pub fn success<S: Into<String>>(custom_id: S, output: BatchOutput) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            output: Some(output),
+            error: None,
+        }
+    }
+
+    pub fn failure<S: Into<String>, E: Into<String>>(custom_id: S, error: E) -> Self {
+        Self {
+            custom_id: custom_id.into(),
+            output: None,
+            error: Some(error.into()),
+        }
+    }
+
+    pub fn generation(&self) -> Option<&GenerateResult> {
+        match &self.output {
+            Some(BatchOutput::Generation { result }) => Some(result),
+            _ => None,
+        }
+    }
+
+    pub fn embedding(&self) -> Option<&[f64]> {
+        match &self.output {
+            Some(BatchOutput::Embedding { embedding }) => Some(embedding),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use futures::{stream, StreamExt};
+use tokio::sync::Mutex;
+
+use crate::{embedding::embedder_trait::Embedder, language_models::llm::LLM};
+
+use super::{
+    BatchError, BatchInput, BatchJob, BatchOutput, BatchProcessor, BatchRequest, BatchResult,
+    BatchStatus,
+};
+
+struct LocalJob {
+    /// Submission order, to evict the oldest finished jobs first.
+    order: usize,
+    job: BatchJob,
+    results: Vec<BatchResult>,
+}
+
+type Jobs = Arc<Mutex<HashMap<String, LocalJob>>>;
+
+/// Runs batch jobs in-process against any [`LLM`] and [`Embedder`], for providers without a
+/// batch endpoint.
+///
+/// Each submitted job is processed in the background with at most `concurrency` requests in
+/// flight, and exposes the same submit/poll/fetch interface as the hosted batch APIs, so code
+/// written against [`BatchProcessor`] can switch providers. Jobs live in memory and are lost
+/// when the queue is dropped. Only the latest finished jobs are kept, see
+/// [`LocalBatchQueue::with_max_finished_jobs`].
+#[derive(Clone)]
+pub struct LocalBatchQueue {
+    llm: Option<Arc<dyn LLM>>,
+    embedder: Option<Arc<dyn Embedder>>,
+    concurrency: usize,
+    max_finished_jobs: usize,
+    jobs: Jobs,
+    counter: Arc<AtomicUsize>,
+}
+
+impl Default for LocalBatchQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalBatchQueue {
+    pub fn new() -> Self {
+        Self {
+            llm: None,
+            embedder: None,
+            concurrency: 4,
+            max_finished_jobs: 100,
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            counter: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn with_llm<L: Into<Box<dyn LLM>>>(mut self, llm: L) -> Self {
+        self.llm = Some(Arc::from(llm.into()));
+        self
+    }
+
+    pub fn with_embedder<E: Embedder + 'static>(mut self, embedder: E) -> Self {
+        self.embedder = Some(Arc::new(embedder));
+        self
+    }
+
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// How many completed, failed or cancelled jobs are kept with their results, 100 by
+    /// default. Older ones are dropped when a new job is submitted.
+    pub fn with_max_finished_jobs(mut self, max_finished_jobs: usize) -> Self {
+        self.max_finished_jobs = max_finished_jobs;
+        self
+    }
+
+    /// Drops the oldest finished jobs beyond `max_finished_jobs`.
+    fn evict_finished(&self, jobs: &mut HashMap<String, LocalJob>) {
+        let mut finished = jobs
+            .iter()
+            .filter(|(_, entry)| entry.job.status.is_terminal())
+            .map(|(id, entry)| (entry.order, id.clone()))
+            .collect::<Vec<_>>();
+        if finished.len() <= self.max_finished_jobs {
+            return;
+        }
+        finished.sort();
+        let evicted = finished.len() - self.max_finished_jobs;
+        for (_, id) in finished.into_iter().take(evicted) {
+            jobs.remove(&id);
+        }
+    }
+
+    async fn run(
+        jobs: Jobs,
+        id: String,
+        requests: Vec<BatchRequest>,
+        llm: Option<Arc<dyn LLM>>,
+        embedder: Option<Arc<dyn Embedder>>,
+        concurrency: usize,
+    ) {
+        let mut results = stream::iter(requests)
+            .map(|request| {
+                let llm = llm.clone();
+                let embedder = embedder.clone();
+                async move { process(request, llm, embedder).await }
+            })
+            .buffer_unordered(concurrency);
+
+        while let Some(result) = results.next().await {
+            let mut jobs = jobs.lock().await;
+            let Some(entry) = jobs.get_mut(&id) else {
+                return;
+            };
+            if entry.job.status == BatchStatus::Cancelled {
+                return;
+            }
+            entry.job.status = BatchStatus::InProgress;
+            if result.error.is_some() {
+                entry.job.failed += 1;
+            } else {
+                entry.job.completed += 1;
+            }
+            entry.results.push(result);
+        }
+
+        if let Some(entry) = jobs.lock().await.get_mut(&id) {
+            if entry.job.status != BatchStatus::Cancelled {
+                entry.job.status = BatchStatus::Completed;
+            }
+        }
+    }
+}
+
+async fn process(
+    request: BatchRequest,
+    llm: Option<Arc<dyn LLM>>,
+    embedder: Option<Arc<dyn Embedder>>,
+) -> BatchResult {
+    let output = match (&request.input, llm, embedder) {
+        (BatchInput::Generation { messages }, Some(llm), _) => llm
+            .generate(messages)
+            .await
+            .map(|result| BatchOutput::Generation { result })
+            .map_err(|e| e.to_string()),
+        (BatchInput::Embedding { text }, _, Some(embedder)) => embedder
+            .embed_query(text)
+            .await
+            .map(|embedding| BatchOutput::Embedding { embedding })
+            .map_err(|e| e.to_string()),
+        _ => Err("The queue has no model for this request".to_string()),
+    };
+    match output {
+        Ok(output) => BatchResult::success(request.custom_id, output),
+        Err(error) => BatchResult::failure(request.custom_id, error),
+    }
+}
+
+#[async_trait]
+impl BatchProcessor for LocalBatchQueue {
+    async fn submit(&self, requests: Vec<BatchRequest>) -> Result<BatchJob, BatchError> {
+        let order = self.counter.fetch_add(1, Ordering::Relaxed);
+        let id = format!("local-batch-{}", order);
+        let job = BatchJob {
+            id: id.clone(),
+            status: BatchStatus::Queued,
+            total: requests.len(),
+            completed: 0,
+            failed: 0,
+        };
+        {
+            let mut jobs = self.jobs.lock().await;
+            self.evict_finished(&mut jobs);
+            jobs.insert(
+                id.clone(),
+                LocalJob {
+                    order,
+                    job: job.clone(),
+                    results: Vec::new(),
+                },
+            );
+        }
+
+        let run = Self::run(
+            self.jobs.clone(),
+            id,
+            requests,
+            self.llm.clone(),
+            self.embedder.clone(),
+            self.concurrency,
+        );
+        // There is no runtime to spawn on in the browser, so the job runs before returning.
+        #[cfg(not(target_arch = "wasm32"))]
+        tokio::spawn(run);
+        #[cfg(target_arch = "wasm32")]
+        run.await;
+
+        Ok(job)
+    }
+
+    async fn status(&self, job_id: &str) -> Result<BatchJob, BatchError> {
+        self.jobs
+            .lock()
+            .await
+            .get(job_id)
+            .map(|entry| entry.job.clone())
+            .ok_or_else(|| BatchError::JobNotFound(job_id.to_string()))
+    }
+
+    async fn results(&self, job_id: &str) -> Result<Vec<BatchResult>, BatchError> {
+        let jobs = self.jobs.lock().await;
+        let entry = jobs
+            .get(job_id)
+            .ok_or_else(|| BatchError::JobNotFound(job_id.to_string()))?;
+        if entry.job.status != BatchStatus::Completed {
+            return Err(BatchError::NotReady {
+                id: job_id.to_string(),
+                status: entry.job.status,
+            });
+        }
+        Ok(entry.results.clone())
+    }
+
+    async fn cancel(&self, job_id: &str) -> Result<BatchJob, BatchError> {
+        let mut jobs = self.jobs.lock().await;
+        let entry = jobs
+            .get_mut(job_id)
+            .ok_or_else(|| BatchError::JobNotFound(job_id.to_string()))?;
+        if !entry.job.status.is_terminal() {
+            entry.job.status = BatchStatus::Cancelled;
+        }
+        Ok(entry.job.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{embedding::EmbedderError, schemas::Message, test_utils::FakeLLM};
+
+    fn echo() -> FakeLLM {
+        FakeLLM::replying(|messages| messages[0].content.to_uppercase())
+    }
+
+    struct Length;
+
+    #[async_trait]
+    impl Embedder for Length {
+        async fn embed_documents(
+            &self,
+            documents: &[String],
+        ) -> Result<Vec<Vec<f64>>, EmbedderError> {
+            Ok(documents.iter().map(|d| vec![d.len() as f64]).collect())
+        }
+
+        async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
+            Ok(vec![text.len() as f64])
+        }
+    }
+
+    #[tokio::test]
+    async fn test_local_queue_runs_mixed_jobs() {
+        let queue = LocalBatchQueue::new()
+            .with_llm(echo())
+            .with_embedder(Length);
+        let job = queue
+            .submit(vec![
+                BatchRequest::generation("a", vec![Message::new_human_message("hi")]),
+                BatchRequest::embedding("b", "four"),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(job.total, 2);
+
+        let mut results = queue.wait(&job.id, Duration::from_millis(5)).await.unwrap();
+        results.sort_by(|a, b| a.custom_id.cmp(&b.custom_id));
+        assert_eq!(results[0].generation().unwrap().generation, "HI");
+        assert_eq!(results[1].embedding(), Some(&[4.0][..]));
+
+        let job = queue.status(&job.id).await.unwrap();
+        assert_eq!(
+            (job.status, job.completed, job.failed),
+            (BatchStatus::Completed, 2, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_a_model_fail_individually() {
+        let queue = LocalBatchQueue::new().with_llm(echo());
+        let job = queue
+            .submit(vec![BatchRequest::embedding("a", "text")])
+            .await
+            .unwrap();
+        let results = queue.wait(&job.id, Duration::from_millis(5)).await.unwrap();
+        assert!(results[0].error.is_some());
+        assert_eq!(queue.status(&job.id).await.unwrap().failed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_evicts_oldest_finished_jobs() {
+        let queue = LocalBatchQueue::new()
+            .with_llm(echo())
+            .with_max_finished_jobs(1);
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let job = queue
+                .submit(vec![BatchRequest::generation(
+                    "a",
+                    vec![Message::new_human_message("hi")],
+                )])
+                .await
+                .unwrap();
+            queue.wait(&job.id, Duration::from_millis(5)).await.unwrap();
+            ids.push(job.id);
+        }
+
+        assert!(matches!(
+            queue.status(&ids[0]).await,
+            Err(BatchError::JobNotFound(_))
+        ));
+        assert!(queue.results(&ids[1]).await.is_ok());
+        assert!(queue.results(&ids[2]).await.is_ok());
+    }
+}
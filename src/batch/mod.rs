@@ -0,0 +1,19 @@
+mod error;
+pub use error::*;
+
+mod job;
+pub use job::*;
+
+mod processor;
+pub use processor::*;
+
+mod local;
+pub use local::*;
+
+mod embedder;
+pub use embedder::*;
+
+#[cfg(feature = "openai")]
+mod openai;
+#[cfg(feature = "openai")]
+pub use openai::*;
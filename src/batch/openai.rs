@@ -0,0 +1,330 @@
+use async_openai::{
+    config::{Config, OpenAIConfig},
+    types::{
+        Batch, BatchCompletionWindow, BatchEndpoint, BatchRequest as OpenAIBatchRequest,
+        BatchRequestInput, BatchRequestInputMethod, BatchRequestOutput,
+        BatchStatus as OpenAIBatchStatus, CreateChatCompletionResponse, CreateFileRequest,
+        FileInput, FilePurpose, InputSource,
+    },
+    Client,
+};
+use async_trait::async_trait;
+use serde_json::json;
+
+use crate::{
+    language_models::options::CallOptions,
+    llm::openai::{generate_result_from_response, OpenAI},
+};
+
+use super::{
+    BatchError, BatchInput, BatchJob, BatchOutput, BatchProcessor, BatchRequest, BatchResult,
+    BatchStatus,
+};
+
+/// Runs batch jobs on the [OpenAI Batch API](https://platform.openai.com/docs/guides/batch),
+/// which costs half the price of regular requests and completes within 24 hours.
+///
+/// Generation requests are built exactly like [`OpenAI`] builds them, so model and
+/// [`CallOptions`] behave the same. A job holds either generation or embedding requests,
+/// as the API runs each batch against a single endpoint.
+pub struct OpenAIBatch<C: Config> {
+    config: C,
+    llm: OpenAI<C>,
+    embedding_model: String,
+}
+
+impl<C: Config> OpenAIBatch<C> {
+    pub fn new(config: C) -> Self {
+        Self {
+            llm: OpenAI::new(config.clone()),
+            config,
+            embedding_model: String::from("text-embedding-ada-002"),
+        }
+    }
+
+    pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
+        self.llm = self.llm.with_model(model);
+        self
+    }
+
+    pub fn with_options(mut self, options: CallOptions) -> Self {
+        self.llm = self.llm.with_options(options);
+        self
+    }
+
+    pub fn with_embedding_model<S: Into<String>>(mut self, model: S) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    fn request_body(&self, request: &BatchRequest) -> Result<serde_json::Value, BatchError> {
+        match &request.input {
+            BatchInput::Generation { messages } => {
+                let body = self
+                    .llm
+                    .generate_request(messages, false)
+                    .map_err(|e| BatchError::InvalidBatch(e.to_string()))?;
+                Ok(serde_json::to_value(body)?)
+            }
+            BatchInput::Embedding { text } => Ok(json!({
+                "model": self.embedding_model,
+                "input": text,
+            })),
+        }
+    }
+}
+
+impl Default for OpenAIBatch<OpenAIConfig> {
+    fn default() -> Self {
+        Self::new(OpenAIConfig::default())
+    }
+}
+
+fn job_from_batch(batch: Batch) -> BatchJob {
+    let status = match batch.status {
+        OpenAIBatchStatus::Validating => BatchStatus::Queued,
+        OpenAIBatchStatus::InProgress | OpenAIBatchStatus::Finalizing => BatchStatus::InProgress,
+        OpenAIBatchStatus::Completed => BatchStatus::Completed,
+        OpenAIBatchStatus::Failed => BatchStatus::Failed,
+        OpenAIBatchStatus::Expired => BatchStatus::Expired,
+        OpenAIBatchStatus::Cancelling | OpenAIBatchStatus::Cancelled => BatchStatus::Cancelled,
+    };
+    let counts = batch.request_counts.as_ref();
+    BatchJob {
+        id: batch.id,
+        status,
+        total: counts.map_or(0, |c| c.total as usize),
+        completed: counts.map_or(0, |c| c.completed as usize),
+        failed: counts.map_or(0, |c| c.failed as usize),
+    }
+}
+
+fn parse_output(output: BatchRequestOutput, embeddings: bool) -> BatchResult {
+    let custom_id = output.custom_id;
+    let response = match (output.response, output.error) {
+        (_, Some(error)) => return BatchResult::failure(custom_id, error.message),
+        (None, None) => return BatchResult::failure(custom_id, "Empty batch response"),
+        (Some(response), None) => response,
+    };
+    if response.status_code != 200 {
+        let message = response.body["error"]["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("HTTP {}", response.status_code));
+        return BatchResult::failure(custom_id, message);
+    }
+
+    let output = if embeddings {
+        response
+            .body
+            .pointer("/data/0/embedding")
+            .and_then(|embedding| serde_json::from_value(embedding.clone()).ok())
+            .map(|embedding| BatchOutput::Embedding { embedding })
+            .ok_or_else(|| "Missing embedding in batch response".to_string())
+    } else {
+        serde_json::from_value::<CreateChatCompletionResponse>(response.body)
+            .map(|response| BatchOutput::Generation {
+                result: generate_result_from_response(response),
+            })
+            .map_err(|e| e.to_string())
+    };
+    match output {
+        Ok(output) => BatchResult::success(custom_id, output),
+        Err(error) => BatchResult::failure(custom_id, error),
+    }
+}
+
+#[async_trait]
+impl<C: Config + Send + Sync + 'static> BatchProcessor for OpenAIBatch<C> {
+    async fn submit(&self, requests: Vec<BatchRequest>) -> Result<BatchJob, BatchError> {
+        let embeddings = match requests.first() {
+            Some(request) => request.is_embedding(),
+            None => {
+                return Err(BatchError::InvalidBatch(
+                    "A batch needs at least one request".to_string(),
+                ))
+            }
+        };
+        if requests.iter().any(|r| r.is_embedding() != embeddings) {
+            return Err(BatchError::InvalidBatch(
+                "OpenAI batches cannot mix generation and embedding requests".to_string(),
+            ));
+        }
+        let endpoint = if embeddings {
+            BatchEndpoint::V1Embeddings
+        } else {
+            BatchEndpoint::V1ChatCompletions
+        };
+
+        let mut jsonl = String::new();
+        for request in &requests {
+            let line = BatchRequestInput {
+                custom_id: request.custom_id.clone(),
+                method: BatchRequestInputMethod::POST,
+                url: endpoint.clone(),
+                body: Some(self.request_body(request)?),
+            };
+            jsonl.push_str(&serde_json::to_string(&line)?);
+            jsonl.push('\n');
+        }
+
+        let client = Client::with_config(self.config.clone());
+        let file = client
+            .files()
+            .create(CreateFileRequest {
+                file: FileInput {
+                    source: InputSource::VecU8 {
+                        filename: "batch.jsonl".to_string(),
+                        vec: jsonl.into_bytes(),
+                    },
+                },
+                purpose: FilePurpose::Batch,
+            })
+            .await?;
+        let batch = client
+            .batches()
+            .create(OpenAIBatchRequest {
+                input_file_id: file.id,
+                endpoint,
+                completion_window: BatchCompletionWindow::W24H,
+                metadata: None,
+            })
+            .await?;
+        Ok(job_from_batch(batch))
+    }
+
+    async fn status(&self, job_id: &str) -> Result<BatchJob, BatchError> {
+        let client = Client::with_config(self.config.clone());
+        Ok(job_from_batch(client.batches().retrieve(job_id).await?))
+    }
+
+    /// Also returns the partial results of expired and cancelled jobs.
+    async fn results(&self, job_id: &str) -> Result<Vec<BatchResult>, BatchError> {
+        let client = Client::with_config(self.config.clone());
+        let batch = client.batches().retrieve(job_id).await?;
+        let embeddings = batch.endpoint == "/v1/embeddings";
+        let files = [batch.output_file_id.clone(), batch.error_file_id.clone()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        let job = job_from_batch(batch);
+        if files.is_empty() {
+            check_finished(job)?;
+        }
+
+        let mut results = Vec::new();
+        for file_id in files {
+            let content = client.files().content(&file_id).await?;
+            for line in String::from_utf8_lossy(&content).lines() {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let output = serde_json::from_str::<BatchRequestOutput>(line)?;
+                results.push(parse_output(output, embeddings));
+            }
+        }
+        Ok(results)
+    }
+
+    async fn cancel(&self, job_id: &str) -> Result<BatchJob, BatchError> {
+        let client = Client::with_config(self.config.clone());
+        Ok(job_from_batch(client.batches().cancel(job_id).await?))
+    }
+}
+
+/// Fails for a job without output files unless it completed: with
+/// [`BatchError::JobFailed`] once it ended, with [`BatchError::NotReady`] while it runs.
+fn check_finished(job: BatchJob) -> Result<(), BatchError> {
+    match job.status {
+        BatchStatus::Completed => Ok(()),
+        status if status.is_terminal() => Err(BatchError::JobFailed { id: job.id, status }),
+        status => Err(BatchError::NotReady { id: job.id, status }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jobs_without_output_files() {
+        let job = |status| BatchJob {
+            id: "batch_1".to_string(),
+            status,
+            total: 1,
+            completed: 0,
+            failed: 0,
+        };
+        assert!(check_finished(job(BatchStatus::Completed)).is_ok());
+        assert!(matches!(
+            check_finished(job(BatchStatus::Failed)),
+            Err(BatchError::JobFailed { .. })
+        ));
+        assert!(matches!(
+            check_finished(job(BatchStatus::InProgress)),
+            Err(BatchError::NotReady { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_batch_output_lines() {
+        let chat = serde_json::from_value(json!({
+            "id": "batch_req_1",
+            "custom_id": "q1",
+            "response": {
+                "status_code": 200,
+                "request_id": "req_1",
+                "body": {
+                    "id": "chatcmpl-1",
+                    "object": "chat.completion",
+                    "created": 1711471533,
+                    "model": "gpt-4o-mini",
+                    "choices": [{
+                        "index": 0,
+                        "message": {"role": "assistant", "content": "Paris"},
+                        "finish_reason": "stop"
+                    }],
+                    "usage": {"prompt_tokens": 9, "completion_tokens": 1, "total_tokens": 10}
+                }
+            },
+            "error": null
+        }))
+        .unwrap();
+        let result = parse_output(chat, false);
+        let generation = result.generation().unwrap();
+        assert_eq!(generation.generation, "Paris");
+        assert_eq!(generation.tokens.as_ref().unwrap().total_tokens, 10);
+
+        let embedding = serde_json::from_value(json!({
+            "id": "batch_req_2",
+            "custom_id": "d1",
+            "response": {
+                "status_code": 200,
+                "request_id": "req_2",
+                "body": {"data": [{"embedding": [0.5, -0.25]}]}
+            },
+            "error": null
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_output(embedding, true).embedding(),
+            Some(&[0.5, -0.25][..])
+        );
+
+        let failed = serde_json::from_value(json!({
+            "id": "batch_req_3",
+            "custom_id": "q2",
+            "response": {
+                "status_code": 400,
+                "request_id": "req_3",
+                "body": {"error": {"message": "Invalid model"}}
+            },
+            "error": null
+        }))
+        .unwrap();
+        assert_eq!(
+            parse_output(failed, false).error.as_deref(),
+            Some("Invalid model")
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{BatchError, BatchJob, BatchRequest, BatchResult, BatchStatus};
+
+/// Runs large offline generation and embedding jobs.
+///
+/// Jobs are submitted once, polled with [`BatchProcessor::status`] and their results fetched
+/// with [`BatchProcessor::results`] after completion. [`BatchProcessor::wait`] does the polling.
+#[async_trait]
+pub trait BatchProcessor: Send + Sync {
+    async fn submit(&self, requests: Vec<BatchRequest>) -> Result<BatchJob, BatchError>;
+
+    async fn status(&self, job_id: &str) -> Result<BatchJob, BatchError>;
+
+    /// Results of a completed job. Returns [`BatchError::NotReady`] while it is running.
+    async fn results(&self, job_id: &str) -> Result<Vec<BatchResult>, BatchError>;
+
+    async fn cancel(&self, job_id: &str) -> Result<BatchJob, BatchError>;
+
+    /// Polls the job every `poll_interval` until it ends, then returns its results.
+    ///
+    /// wasm32 has no timer to sleep on, so there the job is checked once and a running job
+    /// fails with [`BatchError::NotReady`].
+    async fn wait(
+        &self,
+        job_id: &str,
+        poll_interval: Duration,
+    ) -> Result<Vec<BatchResult>, BatchError> {
+        loop {
+            let job = self.status(job_id).await?;
+            match job.status {
+                BatchStatus::Completed => return self.results(job_id).await,
+                status if status.is_terminal() => {
+                    return Err(BatchError::JobFailed { id: job.id, status })
+                }
+                status if cfg!(target_arch = "wasm32") => {
+                    return Err(BatchError::NotReady { id: job.id, status })
+                }
+                _ => tokio::time::sleep(poll_interval).await,
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+//! Command line helpers for langchain-rust projects.
+//!
+//! ```text
+//! langchain-rust scaffold <tool|llm> <name> [--dir <path>] [--in-tree] [--force]
+//! ```
+
+use std::{env, path::PathBuf, process::ExitCode};
+
+use langchain_rust::scaffold::{Scaffold, ScaffoldKind};
+
+const USAGE: &str =
+    "Usage: langchain-rust scaffold <tool|llm> <name> [--dir <path>] [--in-tree] [--force]
+
+Generates the boilerplate for a new tool or LLM provider.
+
+Options:
+  --dir <path>  Project root to write into (default: current directory)
+  --in-tree     Lay the files out as a module of the langchain-rust crate itself
+  --force       Overwrite existing files";
+
+fn scaffold(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let mut positional = Vec::new();
+    let mut dir = PathBuf::from(".");
+    let mut in_tree = false;
+    let mut force = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dir" => dir = args.next().ok_or("--dir needs a path")?.into(),
+            "--in-tree" => in_tree = true,
+            "--force" => force = true,
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag).into()),
+            value => positional.push(value),
+        }
+    }
+    let [kind, name] = positional[..] else {
+        return Err("Expected a kind and a name".into());
+    };
+
+    let scaffold = Scaffold::new(kind.parse::<ScaffoldKind>()?, name)?.in_tree(in_tree);
+    for path in scaffold.write_to(&dir, force)? {
+        println!("created {}", path.display());
+    }
+    println!("\nNext steps:");
+    for step in scaffold.next_steps() {
+        println!("  - {}", step);
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let result = match args.first().map(String::as_str) {
+        Some("scaffold") => scaffold(&args[1..]),
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        _ => Err(USAGE.into()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
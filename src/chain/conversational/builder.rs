@@ -24,6 +24,7 @@ pub struct ConversationalChainBuilder {
     output_parser: Option<Box<dyn OutputParser>>,
     input_key: Option<String>,
     prompt: Option<Box<dyn FormatPrompter>>,
+    reasoning_in_memory: bool,
 }
 
 impl ConversationalChainBuilder {
@@ -36,6 +37,7 @@ impl ConversationalChainBuilder {
             output_parser: None,
             input_key: None,
             prompt: None,
+            reasoning_in_memory: false,
         }
     }
 
@@ -69,6 +71,13 @@ impl ConversationalChainBuilder {
         self
     }
 
+    /// Keep the reasoning of reasoning models in memory, as a `<think>` block ahead of the
+    /// answer. By default only the answer is remembered.
+    pub fn reasoning_in_memory(mut self, reasoning_in_memory: bool) -> Self {
+        self.reasoning_in_memory = reasoning_in_memory;
+        self
+    }
+
     ///If you want to add a custom prompt,keep in mind which variables are obligatory.
     pub fn prompt<P: Into<Box<dyn FormatPrompter>>>(mut self, prompt: P) -> Self {
         self.prompt = Some(prompt.into());
@@ -111,6 +120,7 @@ impl ConversationalChainBuilder {
         Ok(ConversationalChain {
             llm: llm_chain,
             memory,
+            reasoning_in_memory: self.reasoning_in_memory,
             input_key: self
                 .input_key
                 .unwrap_or_else(|| DEFAULT_INPUT_VARIABLE.to_string()),
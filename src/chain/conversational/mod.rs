@@ -7,7 +7,7 @@ use futures_util::{pin_mut, StreamExt};
 use tokio::sync::Mutex;
 
 use crate::{
-    language_models::GenerateResult,
+    language_models::{split_reasoning, GenerateResult},
     prompt::PromptArgs,
     prompt_args,
     schemas::{memory::BaseMemory, messages::Message, StreamData},
@@ -47,6 +47,7 @@ impl ConversationalChainPromptBuilder {
 pub struct ConversationalChain {
     llm: LLMChain,
     input_key: String,
+    reasoning_in_memory: bool,
     pub memory: Arc<Mutex<dyn BaseMemory>>,
 }
 
@@ -57,6 +58,20 @@ impl ConversationalChain {
     }
 }
 
+/// The AI message remembered for an answer, with its reasoning only if `reasoning_in_memory`.
+/// `<think>` blocks left in the answer count as reasoning.
+fn memory_message(reasoning_in_memory: bool, reasoning: Option<&str>, answer: &str) -> Message {
+    let (inline_reasoning, answer) = split_reasoning(answer);
+    let reasoning = reasoning
+        .map(str::to_string)
+        .or(inline_reasoning)
+        .filter(|_| reasoning_in_memory);
+    Message::new_ai_message(match reasoning {
+        Some(reasoning) => format!("<think>\n{}\n</think>\n\n{}", reasoning, answer),
+        None => answer,
+    })
+}
+
 #[async_trait]
 impl Chain for ConversationalChain {
     async fn call(&self, input_variables: PromptArgs) -> Result<GenerateResult, ChainError> {
@@ -75,7 +90,11 @@ impl Chain for ConversationalChain {
 
         let mut memory = self.memory.lock().await;
         memory.add_message(human_message);
-        memory.add_message(Message::new_ai_message(&result.generation));
+        memory.add_message(memory_message(
+            self.reasoning_in_memory,
+            result.reasoning.as_deref(),
+            &result.generation,
+        ));
         Ok(result)
     }
 
@@ -99,8 +118,11 @@ impl Chain for ConversationalChain {
 
         let complete_ai_message = Arc::new(Mutex::new(String::new()));
         let complete_ai_message_clone = complete_ai_message.clone();
+        let reasoning = Arc::new(Mutex::new(String::new()));
+        let reasoning_clone = reasoning.clone();
 
         let memory = self.memory.clone();
+        let reasoning_in_memory = self.reasoning_in_memory;
 
         let stream = self.llm.stream(input_variables).await?;
         let output_stream = stream! {
@@ -108,9 +130,11 @@ impl Chain for ConversationalChain {
             while let Some(result) = stream.next().await {
                 match result {
                     Ok(data) => {
-                        let mut complete_ai_message_clone =
-                            complete_ai_message_clone.lock().await;
-                        complete_ai_message_clone.push_str(&data.content);
+                        // Chunks carrying reasoning are shown but not part of the answer.
+                        match &data.reasoning {
+                            Some(chunk) => reasoning_clone.lock().await.push_str(chunk),
+                            None => complete_ai_message_clone.lock().await.push_str(&data.content),
+                        }
 
                         yield Ok(data);
                     },
@@ -122,7 +146,12 @@ impl Chain for ConversationalChain {
 
             let mut memory = memory.lock().await;
             memory.add_message(human_message);
-            memory.add_message(Message::new_ai_message(&complete_ai_message.lock().await));
+            let reasoning = reasoning.lock().await;
+            memory.add_message(memory_message(
+                reasoning_in_memory,
+                Some(reasoning.as_str()).filter(|r| !r.is_empty()),
+                &complete_ai_message.lock().await,
+            ));
         };
 
         Ok(Box::pin(output_stream))
@@ -133,7 +162,7 @@ impl Chain for ConversationalChain {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "openai"))]
 mod tests {
     use crate::{
         chain::conversational::builder::ConversationalChainBuilder,
@@ -143,6 +172,20 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_reasoning_is_kept_out_of_memory_by_default() {
+        let answer = "<think>Greeting.</think>Hello!";
+        assert_eq!(memory_message(false, None, answer).content, "Hello!");
+        assert_eq!(
+            memory_message(true, None, answer).content,
+            "<think>\nGreeting.\n</think>\n\nHello!"
+        );
+        assert_eq!(
+            memory_message(true, Some("Streamed."), "Hello!").content,
+            "<think>\nStreamed.\n</think>\n\nHello!"
+        );
+    }
+
     #[tokio::test]
     #[ignore]
     async fn test_invoke_conversational() {
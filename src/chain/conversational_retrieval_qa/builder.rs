@@ -3,7 +3,8 @@ use tokio::sync::Mutex;
 
 use crate::{
     chain::{
-        Chain, ChainError, CondenseQuestionGeneratorChain, StuffDocumentBuilder, DEFAULT_OUTPUT_KEY,
+        AnswerVerifier, Chain, ChainError, CondenseQuestionGeneratorChain, StuffDocumentBuilder,
+        DEFAULT_OUTPUT_KEY,
     },
     language_models::llm::LLM,
     memory::SimpleMemory,
@@ -55,6 +56,8 @@ pub struct ConversationalRetrieverChainBuilder {
     return_source_documents: bool,
     input_key: String,
     output_key: String,
+    verifier: Option<AnswerVerifier>,
+    max_context_tokens: Option<usize>,
 }
 impl ConversationalRetrieverChainBuilder {
     pub fn new() -> Self {
@@ -69,6 +72,8 @@ impl ConversationalRetrieverChainBuilder {
             return_source_documents: true,
             input_key: CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_INPUT_KEY.to_string(),
             output_key: DEFAULT_OUTPUT_KEY.to_string(),
+            verifier: None,
+            max_context_tokens: None,
         }
     }
 
@@ -126,6 +131,21 @@ impl ConversationalRetrieverChainBuilder {
         self
     }
 
+    ///Checks every answer against the retrieved documents. The report is returned under the
+    ///`verification` output key.
+    pub fn with_verification(mut self, verifier: AnswerVerifier) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    ///Token budget of the retrieved documents in the prompt, see
+    ///[`StuffDocument::with_max_context_tokens`](crate::chain::StuffDocument::with_max_context_tokens).
+    ///Only applies to the combine documents chain built from the llm.
+    pub fn max_context_tokens(mut self, max_context_tokens: usize) -> Self {
+        self.max_context_tokens = Some(max_context_tokens);
+        self
+    }
+
     pub fn build(mut self) -> Result<ConversationalRetrieverChain, ChainError> {
         if let Some(llm) = self.llm {
             let combine_documents_chain = {
@@ -133,6 +153,9 @@ impl ConversationalRetrieverChainBuilder {
                 if let Some(prompt) = self.prompt {
                     builder = builder.prompt(prompt);
                 }
+                if let Some(max_context_tokens) = self.max_context_tokens {
+                    builder = builder.max_context_tokens(max_context_tokens);
+                }
                 builder.build()?
             };
             let condense_question_chain = CondenseQuestionGeneratorChain::new(llm.clone_box());
@@ -167,6 +190,7 @@ impl ConversationalRetrieverChainBuilder {
             return_source_documents: self.return_source_documents,
             input_key: self.input_key,
             output_key: self.output_key,
+            verifier: self.verifier,
         })
     }
 }
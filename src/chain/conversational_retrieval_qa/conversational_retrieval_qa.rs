@@ -9,7 +9,8 @@ use tokio::sync::Mutex;
 
 use crate::{
     chain::{
-        Chain, ChainError, CondenseQuestionPromptBuilder, StuffQAPromptBuilder, DEFAULT_RESULT_KEY,
+        AnswerVerifier, Chain, ChainError, CondenseQuestionPromptBuilder, StuffQAPromptBuilder,
+        DEFAULT_RESULT_KEY,
     },
     language_models::{GenerateResult, TokenUsage},
     prompt::PromptArgs,
@@ -22,6 +23,7 @@ use crate::{
 
 const CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_SOURCE_DOCUMENT_KEY: &str = "source_documents";
 const CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_GENERATED_QUESTION_KEY: &str = "generated_question";
+const CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_VERIFICATION_KEY: &str = "verification";
 
 pub struct ConversationalRetrieverChain {
     pub(crate) retriever: Box<dyn Retriever>,
@@ -32,6 +34,7 @@ pub struct ConversationalRetrieverChain {
     pub(crate) return_source_documents: bool,
     pub(crate) input_key: String,  //Default is `question`
     pub(crate) output_key: String, //default is output
+    pub(crate) verifier: Option<AnswerVerifier>,
 }
 impl ConversationalRetrieverChain {
     async fn get_question(
@@ -120,6 +123,21 @@ impl Chain for ConversationalRetrieverChain {
             None => {}
         }
 
+        let verification = match &self.verifier {
+            Some(verifier) => {
+                let sources = documents
+                    .iter()
+                    .map(|document| document.page_content.clone())
+                    .collect::<Vec<_>>();
+                let (answer, report) = verifier
+                    .check(&question, &output.generation, &sources)
+                    .await?;
+                output.generation = answer;
+                Some(report)
+            }
+            None => None,
+        };
+
         {
             let mut memory = self.memory.lock().await;
             memory.add_message(human_message);
@@ -145,9 +163,17 @@ impl Chain for ConversationalRetrieverChain {
             );
         }
 
+        if let Some(report) = verification {
+            result.insert(
+                CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_VERIFICATION_KEY.to_string(),
+                json!(report),
+            );
+        }
+
         Ok(result)
     }
 
+    /// Streamed answers are not verified, since they reach the caller as they are generated.
     async fn stream(
         &self,
         input_variables: PromptArgs,
@@ -223,6 +249,10 @@ impl Chain for ConversationalRetrieverChain {
             keys.push(CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_GENERATED_QUESTION_KEY.to_string());
         }
 
+        if self.verifier.is_some() {
+            keys.push(CONVERSATIONAL_RETRIEVAL_QA_DEFAULT_VERIFICATION_KEY.to_string());
+        }
+
         keys.push(self.output_key.clone());
         keys.push(DEFAULT_RESULT_KEY.to_string());
 
@@ -230,7 +260,7 @@ impl Chain for ConversationalRetrieverChain {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "openai"))]
 mod tests {
     use std::error::Error;
 
@@ -101,7 +101,7 @@ impl Chain for LLMChain {
     }
 
     async fn call(&self, input_variables: PromptArgs) -> Result<GenerateResult, ChainError> {
-        let prompt = self.prompt.format_prompt(input_variables.clone())?;
+        let prompt = self.prompt.format_prompt(input_variables)?;
         log::debug!("Prompt: {:?}", prompt);
         let mut output = self.llm.generate(&prompt.to_chat_messages()).await?;
         output.generation = self.output_parser.parse(&output.generation).await?;
@@ -110,7 +110,7 @@ impl Chain for LLMChain {
     }
 
     async fn invoke(&self, input_variables: PromptArgs) -> Result<String, ChainError> {
-        let prompt = self.prompt.format_prompt(input_variables.clone())?;
+        let prompt = self.prompt.format_prompt(input_variables)?;
         log::debug!("Prompt: {:?}", prompt);
         let output = self
             .llm
@@ -125,7 +125,7 @@ impl Chain for LLMChain {
         input_variables: PromptArgs,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
     {
-        let prompt = self.prompt.format_prompt(input_variables.clone())?;
+        let prompt = self.prompt.format_prompt(input_variables)?;
         log::debug!("Prompt: {:?}", prompt);
         let llm_stream = self.llm.stream(&prompt.to_chat_messages()).await?;
 
@@ -136,7 +136,7 @@ impl Chain for LLMChain {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "openai"))]
 mod tests {
     use crate::{
         chain::options::ChainCallOptions,
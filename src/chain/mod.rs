@@ -19,6 +19,9 @@ pub use stuff_documents::*;
 mod question_answering;
 pub use question_answering::*;
 
+mod verification;
+pub use verification::*;
+
 mod conversational_retrieval_qa;
 pub use conversational_retrieval_qa::*;
 
@@ -148,7 +148,7 @@ pub(crate) fn load_stuff_qa<L: Into<Box<dyn LLM>>>(
     StuffDocument::new(llm_chain)
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "openai"))]
 mod tests {
     use crate::{
         chain::{Chain, StuffDocument},
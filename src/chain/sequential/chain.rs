@@ -85,7 +85,7 @@ impl Chain for SequentialChain {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "openai"))]
 mod tests {
     use crate::{
         chain::{Chain, LLMChainBuilder},
@@ -14,6 +14,7 @@ pub struct StuffDocumentBuilder {
     output_key: Option<String>,
     output_parser: Option<Box<dyn OutputParser>>,
     prompt: Option<Box<dyn FormatPrompter>>,
+    max_context_tokens: Option<usize>,
 }
 impl StuffDocumentBuilder {
     pub fn new() -> Self {
@@ -23,6 +24,7 @@ impl StuffDocumentBuilder {
             output_key: None,
             output_parser: None,
             prompt: None,
+            max_context_tokens: None,
         }
     }
 
@@ -47,6 +49,12 @@ impl StuffDocumentBuilder {
         self
     }
 
+    ///Token budget of the documents, see [`StuffDocument::with_max_context_tokens`].
+    pub fn max_context_tokens(mut self, max_context_tokens: usize) -> Self {
+        self.max_context_tokens = Some(max_context_tokens);
+        self
+    }
+
     pub fn build(self) -> Result<StuffDocument, ChainError> {
         let llm = self
             .llm
@@ -72,7 +80,11 @@ impl StuffDocumentBuilder {
             builder.build()?
         };
 
-        Ok(StuffDocument::new(llm_chain))
+        let chain = StuffDocument::new(llm_chain);
+        Ok(match self.max_context_tokens {
+            Some(max_context_tokens) => chain.with_max_context_tokens(max_context_tokens),
+            None => chain,
+        })
     }
 }
 
@@ -9,7 +9,7 @@ use crate::{
         load_stuff_qa, options::ChainCallOptions, Chain, ChainError, LLMChain, StuffQAPromptBuilder,
     },
     language_models::{llm::LLM, GenerateResult},
-    prompt::PromptArgs,
+    prompt::{ContextPacker, PromptArgs, PromptSection},
     schemas::{Document, StreamData},
 };
 
@@ -23,6 +23,7 @@ pub struct StuffDocument {
     input_key: String,
     document_variable_name: String,
     separator: String,
+    max_context_tokens: Option<usize>,
 }
 
 impl StuffDocument {
@@ -32,14 +33,29 @@ impl StuffDocument {
             input_key: COMBINE_DOCUMENTS_DEFAULT_INPUT_KEY.to_string(),
             document_variable_name: COMBINE_DOCUMENTS_DEFAULT_DOCUMENT_VARIABLE_NAME.to_string(),
             separator: STUFF_DOCUMENTS_DEFAULT_SEPARATOR.to_string(),
+            max_context_tokens: None,
         }
     }
 
-    fn join_documents(&self, docs: Vec<Document>) -> String {
-        docs.iter()
-            .map(|doc| doc.page_content.clone())
-            .collect::<Vec<_>>()
-            .join(&self.separator)
+    /// Packs the documents into `max_context_tokens` with a [`ContextPacker`], keeping the
+    /// first ones, which retrievers rank best, and cutting the last kept one.
+    pub fn with_max_context_tokens(mut self, max_context_tokens: usize) -> Self {
+        self.max_context_tokens = Some(max_context_tokens);
+        self
+    }
+
+    fn join_documents(&self, docs: Vec<Document>) -> Result<String, ChainError> {
+        let contents = docs
+            .into_iter()
+            .map(|doc| doc.page_content)
+            .collect::<Vec<_>>();
+        let Some(max_context_tokens) = self.max_context_tokens else {
+            return Ok(contents.join(&self.separator));
+        };
+        let packed = ContextPacker::new(max_context_tokens)
+            .section(PromptSection::new("documents", contents))
+            .pack()?;
+        Ok(packed.render(&self.separator))
     }
 
     ///Inly use thi if you use the deafult prompt
@@ -131,7 +147,7 @@ impl Chain for StuffDocument {
         let mut input_values = input_variables.clone();
         input_values.insert(
             self.document_variable_name.clone(),
-            Value::String(self.join_documents(documents)),
+            Value::String(self.join_documents(documents)?),
         );
 
         self.llm_chain.call(input_values).await
@@ -156,7 +172,7 @@ impl Chain for StuffDocument {
         let mut input_values = input_variables.clone();
         input_values.insert(
             self.document_variable_name.clone(),
-            Value::String(self.join_documents(documents)),
+            Value::String(self.join_documents(documents)?),
         );
         self.llm_chain.stream(input_values).await
     }
@@ -165,3 +181,36 @@ impl Chain for StuffDocument {
         vec![self.input_key.clone()]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chain::StuffDocumentBuilder, test_utils::FakeLLM};
+
+    #[tokio::test]
+    async fn test_packs_documents_into_the_budget() {
+        let chain = StuffDocumentBuilder::new()
+            .llm(FakeLLM::replying(|messages| messages[0].content.clone()))
+            .max_context_tokens(6)
+            .build()
+            .unwrap();
+        let documents = [
+            Document::new("Paris is in France."),
+            Document::new("Berlin is in Germany."),
+            Document::new("Rome is in Italy."),
+        ];
+
+        let prompt = chain
+            .invoke(
+                StuffQAPromptBuilder::new()
+                    .documents(&documents)
+                    .question("Where is Paris?")
+                    .build(),
+            )
+            .await
+            .unwrap();
+        assert!(prompt.contains("Paris is in France."));
+        assert!(!prompt.contains("Germany"));
+        assert!(!prompt.contains("Rome"));
+    }
+}
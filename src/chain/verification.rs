@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+
+use crate::language_models::llm::LLM;
+
+use super::ChainError;
+
+fn entailment_prompt(sources: &str, claim: &str) -> String {
+    format!(
+        "Sources:\n{sources}\n\n\
+        Claim: {claim}\n\n\
+        Is the claim supported by the sources above? Answer with SUPPORTED or UNSUPPORTED only."
+    )
+}
+
+fn correction_prompt(sources: &str, question: &str, answer: &str, claims: &str) -> String {
+    format!(
+        "Sources:\n{sources}\n\n\
+        Question: {question}\n\
+        Answer: {answer}\n\n\
+        These claims of the answer are not supported by the sources:\n{claims}\n\n\
+        Rewrite the answer so it only states what the sources support. Reply with the new answer only."
+    )
+}
+
+/// Whether the sources back one claim of an answer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClaimCheck {
+    pub claim: String,
+    pub supported: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub claims: Vec<ClaimCheck>,
+    /// Whether the answer was rewritten to drop unsupported claims.
+    pub corrected: bool,
+}
+
+impl VerificationReport {
+    pub fn unsupported(&self) -> Vec<&str> {
+        self.claims
+            .iter()
+            .filter(|check| !check.supported)
+            .map(|check| check.claim.as_str())
+            .collect()
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.claims.iter().all(|check| check.supported)
+    }
+}
+
+/// Checks the claims of an answer against the sources it was built from, the retrieved
+/// documents or the tool outputs, with one entailment prompt per claim.
+///
+/// Every sentence of the answer is a claim. With correction enabled, an answer with
+/// unsupported claims gets one rewrite that keeps to the sources.
+pub struct AnswerVerifier {
+    llm: Box<dyn LLM>,
+    correct: bool,
+}
+
+impl AnswerVerifier {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            correct: false,
+        }
+    }
+
+    pub fn with_correction(mut self, correct: bool) -> Self {
+        self.correct = correct;
+        self
+    }
+
+    pub async fn verify(
+        &self,
+        answer: &str,
+        sources: &[String],
+    ) -> Result<VerificationReport, ChainError> {
+        let sources = sources.join("\n\n");
+        let mut claims = Vec::new();
+        for claim in split_claims(answer) {
+            let verdict = self
+                .llm
+                .invoke(&entailment_prompt(&sources, &claim))
+                .await?
+                .to_uppercase();
+            claims.push(ClaimCheck {
+                claim,
+                supported: verdict.contains("SUPPORTED") && !verdict.contains("UNSUPPORTED"),
+            });
+        }
+        Ok(VerificationReport {
+            claims,
+            corrected: false,
+        })
+    }
+
+    /// Verifies `answer` and, if correction is enabled and some claims are unsupported,
+    /// returns the rewritten answer.
+    pub async fn check(
+        &self,
+        question: &str,
+        answer: &str,
+        sources: &[String],
+    ) -> Result<(String, VerificationReport), ChainError> {
+        let mut report = self.verify(answer, sources).await?;
+        if report.is_supported() || !self.correct {
+            return Ok((answer.to_string(), report));
+        }
+
+        let claims = report
+            .unsupported()
+            .iter()
+            .map(|claim| format!("- {}", claim))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let corrected = self
+            .llm
+            .invoke(&correction_prompt(
+                &sources.join("\n\n"),
+                question,
+                answer,
+                &claims,
+            ))
+            .await?;
+        report.corrected = true;
+        Ok((corrected.trim().to_string(), report))
+    }
+}
+
+/// Splits text into sentences at `.`, `!` or `?` followed by whitespace, and at line breaks.
+fn split_claims(text: &str) -> Vec<String> {
+    let mut claims = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        current.push(c);
+        let ends_sentence =
+            matches!(c, '.' | '!' | '?') && chars.peek().is_none_or(|c| c.is_whitespace());
+        if ends_sentence || c == '\n' {
+            claims.push(std::mem::take(&mut current));
+        }
+    }
+    claims.push(current);
+    claims
+        .into_iter()
+        .map(|claim| claim.trim().to_string())
+        .filter(|claim| !claim.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::test_utils::FakeLLM;
+
+    /// Supports claims that appear verbatim in the sources and corrects to a fixed answer.
+    fn judge() -> FakeLLM {
+        FakeLLM::replying(|messages| {
+            let prompt = &messages[0].content;
+            if prompt.contains("Rewrite the answer") {
+                "Paris is the capital of France.".to_string()
+            } else {
+                let (sources, claim) = prompt.split_once("\n\nClaim: ").unwrap();
+                let claim = claim.lines().next().unwrap();
+                if sources.contains(claim) {
+                    "SUPPORTED"
+                } else {
+                    "UNSUPPORTED"
+                }
+                .to_string()
+            }
+        })
+    }
+
+    #[test]
+    fn test_split_claims() {
+        assert_eq!(
+            split_claims("Pi is 3.14. It is irrational!\n- Listed"),
+            ["Pi is 3.14.", "It is irrational!", "- Listed"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_flags_and_corrects_unsupported_claims() {
+        let sources = vec!["Paris is the capital of France.".to_string()];
+        let answer = "Paris is the capital of France. It has 40 million people.";
+
+        let report = AnswerVerifier::new(judge())
+            .verify(answer, &sources)
+            .await
+            .unwrap();
+        assert_eq!(report.unsupported(), ["It has 40 million people."]);
+
+        let (corrected, report) = AnswerVerifier::new(judge())
+            .with_correction(true)
+            .check("What is Paris?", answer, &sources)
+            .await
+            .unwrap();
+        assert!(report.corrected);
+        assert_eq!(corrected, "Paris is the capital of France.");
+    }
+
+    #[tokio::test]
+    async fn test_placeholders_in_sources_are_kept_verbatim() {
+        let prompts = Arc::new(Mutex::new(Vec::new()));
+        let seen = prompts.clone();
+        let llm = FakeLLM::replying(move |messages| {
+            seen.lock().unwrap().push(messages[0].content.clone());
+            "SUPPORTED".to_string()
+        });
+        let sources = vec!["The template reads {claim} and {sources}.".to_string()];
+        AnswerVerifier::new(llm)
+            .verify("It is a template.", &sources)
+            .await
+            .unwrap();
+
+        let prompt = prompts.lock().unwrap()[0].clone();
+        assert!(prompt.contains("The template reads {claim} and {sources}."));
+        assert!(prompt.contains("Claim: It is a template.\n"));
+    }
+}
@@ -18,6 +18,7 @@ pub enum LoaderError {
     #[error(transparent)]
     FromUtf8Error(#[from] FromUtf8Error),
 
+    #[cfg(feature = "loaders-csv")]
     #[error(transparent)]
     CSVError(#[from] csv::Error),
 
@@ -34,6 +35,7 @@ pub enum LoaderError {
     #[error(transparent)]
     PdfExtractOutputError(#[from] pdf_extract::OutputError),
 
+    #[cfg(feature = "loaders-html")]
     #[error(transparent)]
     ReadabilityError(#[from] readability::error::Error),
 
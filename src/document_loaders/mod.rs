@@ -4,7 +4,9 @@ pub use document_loader::*;
 mod text_loader;
 pub use text_loader::*;
 
+#[cfg(feature = "loaders-csv")]
 mod csv_loader;
+#[cfg(feature = "loaders-csv")]
 pub use csv_loader::*;
 
 #[cfg(feature = "git")]
@@ -20,7 +22,9 @@ mod pdf_loader;
 #[cfg(any(feature = "lopdf", feature = "pdf-extract"))]
 pub use pdf_loader::*;
 
+#[cfg(feature = "loaders-html")]
 mod html_loader;
+#[cfg(feature = "loaders-html")]
 pub use html_loader::*;
 
 #[cfg(feature = "html-to-markdown")]
@@ -31,7 +35,9 @@ pub use html_to_markdown_loader::*;
 mod error;
 pub use error::*;
 
+#[cfg(feature = "loaders-dir")]
 mod dir_loader;
+#[cfg(feature = "loaders-dir")]
 pub use dir_loader::*;
 
 #[cfg(feature = "tree-sitter")]
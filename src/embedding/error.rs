@@ -1,3 +1,4 @@
+#[cfg(feature = "openai")]
 use async_openai::error::OpenAIError;
 #[cfg(feature = "mistralai")]
 use mistralai_client::v1::error::{ApiError, ClientError};
@@ -11,6 +12,7 @@ pub enum EmbedderError {
     #[error("Network request failed: {0}")]
     RequestError(#[from] ReqwestError),
 
+    #[cfg(feature = "openai")]
     #[error("OpenAI error: {0}")]
     OpenAIError(#[from] OpenAIError),
 
@@ -26,6 +28,12 @@ pub enum EmbedderError {
     #[error("FastEmbed error: {0}")]
     FastEmbedError(String),
 
+    #[error("Batch error: {0}")]
+    BatchError(#[from] crate::batch::BatchError),
+
+    #[error("Batch request failed: {0}")]
+    BatchFailed(String),
+
     #[cfg(feature = "ollama")]
     #[error("Ollama error: {0}")]
     OllamaError(#[from] OllamaError),
@@ -0,0 +1,92 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use super::{EncryptionError, KeyProvider};
+
+const MAGIC: &[u8; 4] = b"LCE1";
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM encryption for data at rest.
+///
+/// Every payload gets a fresh random nonce and is laid out as `LCE1 || nonce || ciphertext`,
+/// so tampering or a wrong key is detected on decryption.
+#[derive(Clone)]
+pub struct Cipher {
+    aead: Aes256Gcm,
+}
+
+impl Cipher {
+    pub fn new<K: KeyProvider + ?Sized>(provider: &K) -> Result<Self, EncryptionError> {
+        let key = provider.key()?;
+        Ok(Self {
+            aead: Aes256Gcm::new(&key.into()),
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .aead
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| EncryptionError::EncryptFailed)?;
+
+        let mut payload = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        payload.extend_from_slice(MAGIC);
+        payload.extend_from_slice(&nonce);
+        payload.extend_from_slice(&ciphertext);
+        Ok(payload)
+    }
+
+    pub fn decrypt(&self, payload: &[u8]) -> Result<Vec<u8>, EncryptionError> {
+        let rest = payload
+            .strip_prefix(MAGIC)
+            .filter(|rest| rest.len() >= NONCE_LEN)
+            .ok_or(EncryptionError::InvalidFormat)?;
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        self.aead
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| EncryptionError::DecryptFailed)
+    }
+
+    pub fn encrypt_json<T: Serialize + ?Sized>(
+        &self,
+        value: &T,
+    ) -> Result<Vec<u8>, EncryptionError> {
+        self.encrypt(&serde_json::to_vec(value)?)
+    }
+
+    pub fn decrypt_json<T: DeserializeOwned>(&self, payload: &[u8]) -> Result<T, EncryptionError> {
+        Ok(serde_json::from_slice(&self.decrypt(payload)?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::StaticKey;
+
+    #[test]
+    fn test_roundtrip_and_tamper_detection() {
+        let cipher = Cipher::new(&StaticKey::new([7; 32])).unwrap();
+        let mut payload = cipher.encrypt(b"secret transcript").unwrap();
+        assert!(!payload.windows(6).any(|w| w == b"secret"));
+        assert_eq!(cipher.decrypt(&payload).unwrap(), b"secret transcript");
+
+        let other = Cipher::new(&StaticKey::new([8; 32])).unwrap();
+        assert!(matches!(
+            other.decrypt(&payload),
+            Err(EncryptionError::DecryptFailed)
+        ));
+
+        let last = payload.len() - 1;
+        payload[last] ^= 1;
+        assert!(cipher.decrypt(&payload).is_err());
+        assert!(matches!(
+            cipher.decrypt(b"plain"),
+            Err(EncryptionError::InvalidFormat)
+        ));
+    }
+}
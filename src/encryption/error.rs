@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("Encryption key error: {0}")]
+    KeyError(String),
+
+    #[error("Failed to encrypt data")]
+    EncryptFailed,
+
+    #[error("Failed to decrypt data: wrong key or corrupted ciphertext")]
+    DecryptFailed,
+
+    #[error("Not an encrypted payload")]
+    InvalidFormat,
+
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    EnvError(#[from] crate::env::EnvError),
+
+    #[error("Serde json error: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+}
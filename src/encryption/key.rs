@@ -0,0 +1,67 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use super::EncryptionError;
+
+/// Supplies the 256-bit key used to encrypt data at rest.
+///
+/// Implement it on top of a secrets manager (Vault, AWS KMS data keys, ...) so keys never have
+/// to live in configuration files.
+pub trait KeyProvider: Send + Sync {
+    fn key(&self) -> Result<[u8; 32], EncryptionError>;
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32], EncryptionError> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| EncryptionError::KeyError(e.to_string()))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        EncryptionError::KeyError(format!("expected 32 bytes, got {}", bytes.len()))
+    })
+}
+
+/// A key held in memory.
+pub struct StaticKey([u8; 32]);
+
+impl StaticKey {
+    pub fn new(key: [u8; 32]) -> Self {
+        Self(key)
+    }
+
+    /// Parses a base64 encoded key.
+    pub fn from_base64(encoded: &str) -> Result<Self, EncryptionError> {
+        decode_key(encoded).map(Self)
+    }
+}
+
+impl KeyProvider for StaticKey {
+    fn key(&self) -> Result<[u8; 32], EncryptionError> {
+        Ok(self.0)
+    }
+}
+
+/// Reads a base64 encoded key from an environment variable each time it is needed.
+pub struct EnvKey {
+    variable: String,
+}
+
+impl EnvKey {
+    pub fn new<S: Into<String>>(variable: S) -> Self {
+        Self {
+            variable: variable.into(),
+        }
+    }
+}
+
+impl Default for EnvKey {
+    fn default() -> Self {
+        Self::new("LANGCHAIN_ENCRYPTION_KEY")
+    }
+}
+
+impl KeyProvider for EnvKey {
+    fn key(&self) -> Result<[u8; 32], EncryptionError> {
+        let encoded = crate::env::var(&self.variable)
+            .ok_or_else(|| EncryptionError::KeyError(format!("{} is not set", self.variable)))?;
+        decode_key(&encoded)
+    }
+}
@@ -0,0 +1,8 @@
+mod cipher;
+pub use cipher::*;
+
+mod error;
+pub use error::*;
+
+mod key;
+pub use key::*;
@@ -0,0 +1,79 @@
+//! Configuration read from environment variables, behind the `from_env()` constructors of
+//! LLMs, vector stores, memories and the MCP client, so hosts and keys stay out of the source.
+//!
+//! With the `dotenv` feature, a `.env` file in the working directory or one of its parents is
+//! loaded the first time a variable is read. Variables already set take precedence over it.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EnvError {
+    #[error("Environment variable {0} is not set")]
+    Missing(String),
+
+    #[error("Environment variable {name} has an invalid value: {value}")]
+    Invalid { name: String, value: String },
+}
+
+/// Loads the `.env` file, once. Called by every read, and only needed to load it earlier.
+#[cfg(feature = "dotenv")]
+pub fn load_dotenv() {
+    static LOADED: std::sync::Once = std::sync::Once::new();
+    LOADED.call_once(|| {
+        if let Ok(path) = dotenvy::dotenv() {
+            log::debug!("Loaded environment from {}", path.display());
+        }
+    });
+}
+
+/// The variable `name`, unless it is unset or empty.
+pub fn var(name: &str) -> Option<String> {
+    #[cfg(feature = "dotenv")]
+    load_dotenv();
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// The first of `names` that is set, e.g. a library's own variable before a provider's.
+pub fn any_var(names: &[&str]) -> Option<String> {
+    names.iter().find_map(|name| var(name))
+}
+
+pub fn require(name: &str) -> Result<String, EnvError> {
+    var(name).ok_or_else(|| EnvError::Missing(name.to_string()))
+}
+
+/// The variable `name` parsed as a `T`, if it is set.
+pub fn parse<T: FromStr>(name: &str) -> Result<Option<T>, EnvError> {
+    var(name)
+        .map(|value| {
+            value.trim().parse().map_err(|_| EnvError::Invalid {
+                name: name.to_string(),
+                value,
+            })
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_variables() {
+        std::env::set_var("LANGCHAIN_ENV_TEST_SIZE", "12");
+        std::env::set_var("LANGCHAIN_ENV_TEST_EMPTY", "");
+        assert_eq!(parse::<usize>("LANGCHAIN_ENV_TEST_SIZE"), Ok(Some(12)));
+        assert!(parse::<bool>("LANGCHAIN_ENV_TEST_SIZE").is_err());
+        assert_eq!(var("LANGCHAIN_ENV_TEST_EMPTY"), None);
+        assert_eq!(
+            any_var(&["LANGCHAIN_ENV_TEST_EMPTY", "LANGCHAIN_ENV_TEST_SIZE"]).as_deref(),
+            Some("12")
+        );
+        assert_eq!(
+            require("LANGCHAIN_ENV_TEST_UNSET"),
+            Err(EnvError::Missing("LANGCHAIN_ENV_TEST_UNSET".to_string()))
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    pin::Pin,
+};
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    chain::{Chain, ChainError},
+    feedback::Feedback,
+    language_models::GenerateResult,
+    prompt::PromptArgs,
+    schemas::StreamData,
+};
+
+use super::ExperimentReport;
+
+/// Name under which the control chain is reported.
+pub const CONTROL_VARIANT: &str = "control";
+
+/// Which variant served a run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Assignment {
+    pub run_id: String,
+    pub variant: String,
+    pub success: bool,
+    pub total_tokens: Option<u32>,
+}
+
+struct Variant {
+    name: String,
+    percent: u8,
+    chain: Box<dyn Chain>,
+}
+
+pub struct ExperimentBuilder {
+    name: String,
+    control: Option<Box<dyn Chain>>,
+    variants: Vec<Variant>,
+    routing_key: String,
+    max_assignments: usize,
+}
+
+impl ExperimentBuilder {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        Self {
+            name: name.into(),
+            control: None,
+            variants: Vec::new(),
+            routing_key: "run_id".to_string(),
+            max_assignments: 10_000,
+        }
+    }
+
+    /// Chain serving every invocation not routed to a variant.
+    pub fn control<C: Into<Box<dyn Chain>>>(mut self, chain: C) -> Self {
+        self.control = Some(chain.into());
+        self
+    }
+
+    /// Routes `percent` of invocations to `chain`. Build variants with a different prompt
+    /// version, model or temperature to compare them against the control.
+    pub fn variant<S: Into<String>, C: Into<Box<dyn Chain>>>(
+        mut self,
+        name: S,
+        percent: u8,
+        chain: C,
+    ) -> Self {
+        self.variants.push(Variant {
+            name: name.into(),
+            percent,
+            chain: chain.into(),
+        });
+        self
+    }
+
+    /// Input variable whose value decides the bucket, `run_id` by default. Use a user or
+    /// session id to keep a user on the same variant across runs.
+    pub fn routing_key<S: Into<String>>(mut self, routing_key: S) -> Self {
+        self.routing_key = routing_key.into();
+        self
+    }
+
+    /// How many assignments are kept for reports, 10 000 by default. Older ones are dropped
+    /// first.
+    pub fn max_assignments(mut self, max_assignments: usize) -> Self {
+        self.max_assignments = max_assignments;
+        self
+    }
+
+    pub fn build(self) -> Result<Experiment, ChainError> {
+        let control = self
+            .control
+            .ok_or_else(|| ChainError::MissingObject("Control chain must be set".into()))?;
+        let allocated: u32 = self.variants.iter().map(|v| u32::from(v.percent)).sum();
+        if allocated > 100 {
+            return Err(ChainError::OtherError(format!(
+                "Experiment {} allocates {}% of traffic to variants",
+                self.name, allocated
+            )));
+        }
+        if let Some(variant) = self
+            .variants
+            .iter()
+            .find(|v| v.name == CONTROL_VARIANT || v.name.is_empty())
+        {
+            return Err(ChainError::OtherError(format!(
+                "Invalid variant name {:?}",
+                variant.name
+            )));
+        }
+        let mut names = HashSet::new();
+        if let Some(variant) = self
+            .variants
+            .iter()
+            .find(|v| !names.insert(v.name.as_str()))
+        {
+            return Err(ChainError::OtherError(format!(
+                "Variant {:?} is declared twice",
+                variant.name
+            )));
+        }
+        Ok(Experiment {
+            name: self.name,
+            control,
+            variants: self.variants,
+            routing_key: self.routing_key,
+            max_assignments: self.max_assignments,
+            assignments: Mutex::new(VecDeque::new()),
+        })
+    }
+}
+
+/// A/B test over chains.
+///
+/// Each invocation is assigned to a bucket from a hash of the experiment name and the routing
+/// key, so the same key always lands on the same variant. Invocations without a `run_id` get one
+/// generated, which is added to the inputs so executors downstream tag their records with it.
+pub struct Experiment {
+    name: String,
+    control: Box<dyn Chain>,
+    variants: Vec<Variant>,
+    routing_key: String,
+    max_assignments: usize,
+    assignments: Mutex<VecDeque<Assignment>>,
+}
+
+impl Experiment {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the variant serving `key`.
+    pub fn variant_for(&self, key: &str) -> &str {
+        let bucket = (fnv1a(&format!("{}:{}", self.name, key)) % 100) as u32;
+        let mut upper = 0;
+        for variant in &self.variants {
+            upper += u32::from(variant.percent);
+            if bucket < upper {
+                return &variant.name;
+            }
+        }
+        CONTROL_VARIANT
+    }
+
+    fn chain(&self, variant: &str) -> &dyn Chain {
+        self.variants
+            .iter()
+            .find(|v| v.name == variant)
+            .map(|v| v.chain.as_ref())
+            .unwrap_or(self.control.as_ref())
+    }
+
+    /// Ensures a run id is present and returns `(run_id, variant)`.
+    fn assign(&self, input_variables: &mut PromptArgs) -> (String, String) {
+        let run_id = match input_variables.get("run_id").and_then(|id| id.as_str()) {
+            Some(run_id) => run_id.to_string(),
+            None => {
+                let run_id = Uuid::new_v4().to_string();
+                input_variables.insert("run_id".to_string(), json!(run_id));
+                run_id
+            }
+        };
+        let key = input_variables
+            .get(&self.routing_key)
+            .map(|value| match value {
+                serde_json::Value::String(s) => s.clone(),
+                value => value.to_string(),
+            })
+            .unwrap_or_else(|| run_id.clone());
+        let variant = self.variant_for(&key).to_string();
+        log::debug!("Experiment {}: run {} -> {}", self.name, run_id, variant);
+        (run_id, variant)
+    }
+
+    async fn record(&self, assignment: Assignment) {
+        let mut assignments = self.assignments.lock().await;
+        if assignments.len() >= self.max_assignments {
+            assignments.pop_front();
+        }
+        assignments.push_back(assignment);
+    }
+
+    /// The most recent assignments, oldest first.
+    pub async fn assignments(&self) -> Vec<Assignment> {
+        self.assignments.lock().await.iter().cloned().collect()
+    }
+
+    /// Compares variants using user feedback on their runs.
+    pub async fn report(&self, feedback: &[Feedback]) -> ExperimentReport {
+        self.report_with_scores(
+            feedback
+                .iter()
+                .filter_map(|f| f.score().map(|score| (f.run_id.clone(), score))),
+        )
+        .await
+    }
+
+    /// Compares variants using arbitrary `(run_id, score)` pairs, e.g. evaluation results.
+    pub async fn report_with_scores<I>(&self, scores: I) -> ExperimentReport
+    where
+        I: IntoIterator<Item = (String, f64)>,
+    {
+        let variants = std::iter::once(CONTROL_VARIANT)
+            .chain(self.variants.iter().map(|v| v.name.as_str()))
+            .collect::<Vec<_>>();
+        ExperimentReport::compute(&variants, &self.assignments().await, scores)
+    }
+}
+
+#[async_trait]
+impl Chain for Experiment {
+    async fn call(&self, mut input_variables: PromptArgs) -> Result<GenerateResult, ChainError> {
+        let (run_id, variant) = self.assign(&mut input_variables);
+        let result = self.chain(&variant).call(input_variables).await;
+        self.record(Assignment {
+            run_id,
+            variant,
+            success: result.is_ok(),
+            total_tokens: result
+                .as_ref()
+                .ok()
+                .and_then(|r| r.tokens.as_ref())
+                .map(|t| t.total_tokens),
+        })
+        .await;
+        result
+    }
+
+    async fn stream(
+        &self,
+        mut input_variables: PromptArgs,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+    {
+        let (run_id, variant) = self.assign(&mut input_variables);
+        let result = self.chain(&variant).stream(input_variables).await;
+        self.record(Assignment {
+            run_id,
+            variant,
+            success: result.is_ok(),
+            total_tokens: None,
+        })
+        .await;
+        result
+    }
+
+    fn get_input_keys(&self) -> Vec<String> {
+        self.control.get_input_keys()
+    }
+
+    fn get_output_keys(&self) -> Vec<String> {
+        self.control.get_output_keys()
+    }
+}
+
+fn fnv1a(key: &str) -> u64 {
+    key.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prompt_args;
+
+    struct Fixed(&'static str);
+
+    #[async_trait]
+    impl Chain for Fixed {
+        async fn call(&self, _input: PromptArgs) -> Result<GenerateResult, ChainError> {
+            Ok(GenerateResult {
+                generation: self.0.to_string(),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_routes_by_percentage_and_reports() {
+        let experiment = ExperimentBuilder::new("prompt-v2")
+            .control(Fixed("a"))
+            .variant("v2", 30, Fixed("b"))
+            .build()
+            .unwrap();
+
+        for i in 0..1000 {
+            let run_id = format!("run-{}", i);
+            let output = experiment
+                .invoke(prompt_args! {"run_id" => run_id})
+                .await
+                .unwrap();
+            let expected = if experiment.variant_for(&run_id) == "v2" {
+                "b"
+            } else {
+                "a"
+            };
+            assert_eq!(output, expected);
+        }
+
+        let assignments = experiment.assignments().await;
+        let v2 = assignments.iter().filter(|a| a.variant == "v2").count();
+        assert!((250..350).contains(&v2), "v2 served {} runs", v2);
+
+        let feedback = assignments
+            .iter()
+            .map(|a| match a.variant.as_str() {
+                "v2" => Feedback::thumbs_up(&a.run_id),
+                _ => Feedback::thumbs_down(&a.run_id),
+            })
+            .collect::<Vec<_>>();
+        let report = experiment.report(&feedback).await;
+        assert_eq!(report.variant("v2").unwrap().runs, v2);
+        assert_eq!(report.variant("v2").unwrap().mean_score, Some(1.0));
+        assert_eq!(report.lift("v2"), Some(1.0));
+    }
+
+    #[test]
+    fn test_rejects_over_allocation() {
+        let result = ExperimentBuilder::new("too-much")
+            .control(Fixed("a"))
+            .variant("b", 60, Fixed("b"))
+            .variant("c", 60, Fixed("c"))
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_rejects_duplicate_variants() {
+        let result = ExperimentBuilder::new("twice")
+            .control(Fixed("a"))
+            .variant("b", 10, Fixed("b"))
+            .variant("b", 10, Fixed("c"))
+            .build();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_generated_run_ids_and_assignment_cap() {
+        let experiment = ExperimentBuilder::new("capped")
+            .control(Fixed("a"))
+            .max_assignments(2)
+            .build()
+            .unwrap();
+        for _ in 0..3 {
+            experiment.invoke(prompt_args! {}).await.unwrap();
+        }
+
+        let assignments = experiment.assignments().await;
+        assert_eq!(assignments.len(), 2);
+        assert_ne!(assignments[0].run_id, assignments[1].run_id);
+        assert!(Uuid::parse_str(&assignments[0].run_id).is_ok());
+    }
+}
@@ -0,0 +1,8 @@
+mod experiment;
+pub use experiment::*;
+
+mod report;
+pub use report::*;
+
+mod optimizer;
+pub use optimizer::*;
@@ -0,0 +1,349 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    chain::ChainError,
+    language_models::llm::LLM,
+    prompt::{PromptArgs, PromptFromatter, PromptTemplate},
+};
+
+/// A labelled input the optimizer scores prompts against.
+#[derive(Clone, Debug)]
+pub struct OptimizationExample {
+    pub inputs: PromptArgs,
+    pub expected: String,
+}
+
+impl OptimizationExample {
+    pub fn new<S: Into<String>>(inputs: PromptArgs, expected: S) -> Self {
+        Self {
+            inputs,
+            expected: expected.into(),
+        }
+    }
+}
+
+/// Scores `(output, expected)` between 0.0 (wrong) and 1.0 (right).
+pub type Metric = Arc<dyn Fn(&str, &str) -> f64 + Send + Sync>;
+
+/// Default metric: 1.0 when the trimmed output equals the expected answer, ignoring case.
+pub fn exact_match(output: &str, expected: &str) -> f64 {
+    if output.trim().eq_ignore_ascii_case(expected.trim()) {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+/// An instruction plus worked examples, placed in front of the task template.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PromptCandidate {
+    pub instruction: String,
+    /// `(rendered task, answer)` pairs shown before the actual task.
+    pub demos: Vec<(String, String)>,
+}
+
+impl PromptCandidate {
+    fn prefix(&self) -> String {
+        let mut prefix = format!("{}\n\n", self.instruction.trim());
+        for (task, answer) in &self.demos {
+            prefix.push_str(&format!("{}\n{}\n\n", task, answer));
+        }
+        prefix
+    }
+
+    /// The task template with the instruction and demos baked in, ready to be stored as the
+    /// new prompt version.
+    pub fn to_prompt_template(&self, task: &PromptTemplate) -> PromptTemplate {
+        task.prepend(&self.prefix())
+    }
+}
+
+/// Outcome of [`PromptOptimizer::optimize`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OptimizationResult {
+    pub best: PromptCandidate,
+    pub best_score: f64,
+    /// Every evaluated candidate with its mean score, in evaluation order.
+    pub history: Vec<(PromptCandidate, f64)>,
+}
+
+struct Evaluation {
+    score: f64,
+    /// `(example index, output, score)`
+    outcomes: Vec<(usize, String, f64)>,
+}
+
+/// DSPy-style optimization of a prompt's instruction and few-shot examples.
+///
+/// Each round the proposer LLM rewrites the best instruction so far, given the examples it got
+/// wrong, and the best instruction is also retried with demos bootstrapped from the examples it
+/// got right. Every candidate is scored on all examples with the metric, and the best one is
+/// kept. The search stops early once a candidate scores 1.0.
+pub struct PromptOptimizer {
+    llm: Box<dyn LLM>,
+    proposer: Option<Box<dyn LLM>>,
+    template: PromptTemplate,
+    examples: Vec<OptimizationExample>,
+    metric: Metric,
+    rounds: usize,
+    candidates_per_round: usize,
+    max_demos: usize,
+}
+
+impl PromptOptimizer {
+    /// Optimizes the prompt `template` renders for `llm`, scored on `examples`.
+    pub fn new<L: Into<Box<dyn LLM>>>(
+        llm: L,
+        template: PromptTemplate,
+        examples: Vec<OptimizationExample>,
+    ) -> Self {
+        Self {
+            llm: llm.into(),
+            proposer: None,
+            template,
+            examples,
+            metric: Arc::new(exact_match),
+            rounds: 3,
+            candidates_per_round: 3,
+            max_demos: 3,
+        }
+    }
+
+    /// LLM that writes instruction rewrites. Defaults to the LLM being optimized for.
+    pub fn with_proposer<L: Into<Box<dyn LLM>>>(mut self, proposer: L) -> Self {
+        self.proposer = Some(proposer.into());
+        self
+    }
+
+    pub fn with_metric<F: Fn(&str, &str) -> f64 + Send + Sync + 'static>(
+        mut self,
+        metric: F,
+    ) -> Self {
+        self.metric = Arc::new(metric);
+        self
+    }
+
+    pub fn with_rounds(mut self, rounds: usize) -> Self {
+        self.rounds = rounds;
+        self
+    }
+
+    pub fn with_candidates_per_round(mut self, candidates_per_round: usize) -> Self {
+        self.candidates_per_round = candidates_per_round;
+        self
+    }
+
+    pub fn with_max_demos(mut self, max_demos: usize) -> Self {
+        self.max_demos = max_demos;
+        self
+    }
+
+    pub async fn optimize(&self, instruction: &str) -> Result<OptimizationResult, ChainError> {
+        if self.examples.is_empty() {
+            return Err(ChainError::MissingObject(
+                "Prompt optimization needs at least one example".into(),
+            ));
+        }
+
+        let mut best = PromptCandidate {
+            instruction: instruction.to_string(),
+            demos: Vec::new(),
+        };
+        let mut best_evaluation = self.evaluate(&best).await?;
+        let mut history = vec![(best.clone(), best_evaluation.score)];
+
+        for round in 0..self.rounds {
+            if best_evaluation.score >= 1.0 {
+                break;
+            }
+
+            let mut candidates = self
+                .propose(&best, &best_evaluation)
+                .await?
+                .into_iter()
+                .map(|instruction| PromptCandidate {
+                    instruction,
+                    demos: best.demos.clone(),
+                })
+                .collect::<Vec<_>>();
+            let demos = self.bootstrap_demos(&best_evaluation, round)?;
+            if !demos.is_empty() && demos != best.demos {
+                candidates.push(PromptCandidate {
+                    instruction: best.instruction.clone(),
+                    demos,
+                });
+            }
+
+            for candidate in candidates {
+                let evaluation = self.evaluate(&candidate).await?;
+                log::debug!(
+                    "Prompt optimizer round {}: {:.3} for {:?}",
+                    round,
+                    evaluation.score,
+                    candidate.instruction
+                );
+                history.push((candidate.clone(), evaluation.score));
+                if evaluation.score > best_evaluation.score {
+                    best = candidate;
+                    best_evaluation = evaluation;
+                }
+            }
+        }
+
+        Ok(OptimizationResult {
+            best,
+            best_score: best_evaluation.score,
+            history,
+        })
+    }
+
+    async fn evaluate(&self, candidate: &PromptCandidate) -> Result<Evaluation, ChainError> {
+        let template = candidate.to_prompt_template(&self.template);
+        let mut outcomes = Vec::with_capacity(self.examples.len());
+        for (i, example) in self.examples.iter().enumerate() {
+            let prompt = template.format(example.inputs.clone())?;
+            let output = self.llm.invoke(&prompt).await?;
+            let score = (self.metric)(&output, &example.expected);
+            outcomes.push((i, output, score));
+        }
+
+        let score = outcomes.iter().map(|(_, _, score)| score).sum::<f64>() / outcomes.len() as f64;
+        Ok(Evaluation { score, outcomes })
+    }
+
+    async fn propose(
+        &self,
+        best: &PromptCandidate,
+        evaluation: &Evaluation,
+    ) -> Result<Vec<String>, ChainError> {
+        if self.candidates_per_round == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut mistakes = String::new();
+        for (i, output, _) in evaluation
+            .outcomes
+            .iter()
+            .filter(|(_, _, score)| *score < 1.0)
+            .take(3)
+        {
+            let example = &self.examples[*i];
+            mistakes.push_str(&format!(
+                "Input:\n{}\nExpected: {}\nGot: {}\n\n",
+                self.template.format(example.inputs.clone())?,
+                example.expected,
+                output
+            ));
+        }
+        let request = format!(
+            "You are improving the instruction of a prompt.\n\n\
+             Current instruction:\n{}\n\n\
+             It scores {:.2} out of 1.0. Examples it got wrong:\n\n{}\
+             Write {} alternative instructions that would avoid these mistakes. Put each \
+             instruction on a single line starting with \"INSTRUCTION:\" and write nothing else.",
+            best.instruction, evaluation.score, mistakes, self.candidates_per_round
+        );
+
+        let proposer = self.proposer.as_ref().unwrap_or(&self.llm);
+        let response = proposer.invoke(&request).await?;
+        Ok(response
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("INSTRUCTION:"))
+            .map(|instruction| instruction.trim().to_string())
+            .filter(|instruction| !instruction.is_empty() && *instruction != best.instruction)
+            .take(self.candidates_per_round)
+            .collect())
+    }
+
+    /// Picks up to `max_demos` examples the best candidate answered correctly, rotating
+    /// through them from round to round.
+    fn bootstrap_demos(
+        &self,
+        evaluation: &Evaluation,
+        round: usize,
+    ) -> Result<Vec<(String, String)>, ChainError> {
+        let solved = evaluation
+            .outcomes
+            .iter()
+            .filter(|(_, _, score)| *score >= 1.0)
+            .map(|(i, _, _)| *i)
+            .collect::<Vec<_>>();
+        if solved.is_empty() || self.max_demos == 0 {
+            return Ok(Vec::new());
+        }
+
+        let offset = (round * self.max_demos) % solved.len();
+        solved
+            .iter()
+            .cycle()
+            .skip(offset)
+            .take(self.max_demos.min(solved.len()))
+            .map(|i| -> Result<(String, String), ChainError> {
+                let example = &self.examples[*i];
+                Ok((
+                    self.template.format(example.inputs.clone())?,
+                    example.expected.clone(),
+                ))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prompt_args, template_fstring, test_utils::FakeLLM};
+
+    /// Answers in upper case only when told to shout, and proposes exactly that.
+    fn shouter() -> FakeLLM {
+        FakeLLM::replying(|messages| {
+            let prompt = &messages[0].content;
+            if prompt.starts_with("You are improving") {
+                "INSTRUCTION: Be polite.\nINSTRUCTION: Shout the word.".to_string()
+            } else {
+                let word = prompt.lines().last().unwrap_or_default();
+                if prompt.starts_with("Shout") {
+                    word.to_uppercase()
+                } else {
+                    word.to_string()
+                }
+            }
+        })
+    }
+
+    fn examples() -> Vec<OptimizationExample> {
+        ["hello", "world"]
+            .into_iter()
+            .map(|word| {
+                OptimizationExample::new(prompt_args! {"word" => word}, word.to_uppercase())
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_finds_better_instruction() {
+        let optimizer =
+            PromptOptimizer::new(shouter(), template_fstring!("{word}", "word"), examples())
+                .with_metric(|output, expected| if output == expected { 1.0 } else { 0.0 });
+
+        let result = optimizer.optimize("Repeat the word.").await.unwrap();
+
+        assert_eq!(result.best.instruction, "Shout the word.");
+        assert_eq!(result.best_score, 1.0);
+        assert_eq!(result.history.len(), 3);
+        let template = result.best.to_prompt_template(&optimizer.template);
+        assert_eq!(
+            template.format(prompt_args! {"word" => "hi"}).unwrap(),
+            "Shout the word.\n\nhi"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_requires_examples() {
+        let optimizer =
+            PromptOptimizer::new(shouter(), template_fstring!("{word}", "word"), vec![]);
+        assert!(optimizer.optimize("Repeat the word.").await.is_err());
+    }
+}
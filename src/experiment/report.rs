@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Assignment, CONTROL_VARIANT};
+
+/// Aggregated outcome of one variant.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariantMetrics {
+    pub variant: String,
+    pub runs: usize,
+    pub errors: usize,
+    /// Mean total tokens over runs that reported usage.
+    pub mean_tokens: Option<f64>,
+    /// Number of scores (feedback or eval results) matched to this variant's runs.
+    pub samples: usize,
+    pub mean_score: Option<f64>,
+}
+
+/// Per-variant comparison of an experiment.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExperimentReport {
+    pub variants: Vec<VariantMetrics>,
+}
+
+impl ExperimentReport {
+    pub(crate) fn compute<I>(variants: &[&str], assignments: &[Assignment], scores: I) -> Self
+    where
+        I: IntoIterator<Item = (String, f64)>,
+    {
+        let mut metrics = variants
+            .iter()
+            .map(|variant| VariantMetrics {
+                variant: variant.to_string(),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        let positions = variants
+            .iter()
+            .enumerate()
+            .map(|(i, variant)| (*variant, i))
+            .collect::<HashMap<_, _>>();
+
+        let mut token_sums = vec![(0u64, 0usize); metrics.len()];
+        let mut run_variant = HashMap::new();
+        for assignment in assignments {
+            let Some(i) = positions.get(assignment.variant.as_str()) else {
+                continue;
+            };
+            let m = &mut metrics[*i];
+            m.runs += 1;
+            if !assignment.success {
+                m.errors += 1;
+            }
+            if let Some(tokens) = assignment.total_tokens {
+                token_sums[*i].0 += u64::from(tokens);
+                token_sums[*i].1 += 1;
+            }
+            run_variant.insert(assignment.run_id.as_str(), *i);
+        }
+
+        let mut score_sums = vec![0.0; metrics.len()];
+        for (run_id, score) in scores {
+            if let Some(i) = run_variant.get(run_id.as_str()) {
+                score_sums[*i] += score;
+                metrics[*i].samples += 1;
+            }
+        }
+
+        for (i, m) in metrics.iter_mut().enumerate() {
+            let (tokens, with_usage) = token_sums[i];
+            if with_usage > 0 {
+                m.mean_tokens = Some(tokens as f64 / with_usage as f64);
+            }
+            if m.samples > 0 {
+                m.mean_score = Some(score_sums[i] / m.samples as f64);
+            }
+        }
+        Self { variants: metrics }
+    }
+
+    pub fn variant(&self, name: &str) -> Option<&VariantMetrics> {
+        self.variants.iter().find(|m| m.variant == name)
+    }
+
+    /// Difference between the variant's mean score and the control's.
+    pub fn lift(&self, name: &str) -> Option<f64> {
+        Some(self.variant(name)?.mean_score? - self.variant(CONTROL_VARIANT)?.mean_score?)
+    }
+}
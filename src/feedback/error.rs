@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FeedbackError {
+    #[error("Serde json error: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("Invalid feedback: {0}")]
+    InvalidFeedback(String),
+
+    #[error("No feedback store configured")]
+    MissingStore,
+
+    #[error("Feedback export failed: {0}")]
+    ExportFailed(String),
+}
@@ -0,0 +1,127 @@
+use std::{
+    collections::HashMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::FeedbackError;
+
+/// What the user said about a run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FeedbackKind {
+    ThumbsUp,
+    ThumbsDown,
+    /// A rating on a `1..=max` scale.
+    Rating {
+        value: u8,
+        max: u8,
+    },
+    /// The answer the user expected instead.
+    Correction {
+        text: String,
+    },
+}
+
+/// User feedback attached to a run, and optionally to a single message of that run.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Feedback {
+    pub run_id: String,
+    pub message_id: Option<String>,
+    pub kind: FeedbackKind,
+    pub comment: Option<String>,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+    pub metadata: HashMap<String, Value>,
+}
+
+impl Feedback {
+    pub fn new<S: Into<String>>(run_id: S, kind: FeedbackKind) -> Self {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        Self {
+            run_id: run_id.into(),
+            message_id: None,
+            kind,
+            comment: None,
+            created_at,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn thumbs_up<S: Into<String>>(run_id: S) -> Self {
+        Self::new(run_id, FeedbackKind::ThumbsUp)
+    }
+
+    pub fn thumbs_down<S: Into<String>>(run_id: S) -> Self {
+        Self::new(run_id, FeedbackKind::ThumbsDown)
+    }
+
+    pub fn rating<S: Into<String>>(run_id: S, value: u8, max: u8) -> Self {
+        Self::new(run_id, FeedbackKind::Rating { value, max })
+    }
+
+    pub fn correction<S: Into<String>, T: Into<String>>(run_id: S, text: T) -> Self {
+        Self::new(run_id, FeedbackKind::Correction { text: text.into() })
+    }
+
+    pub fn with_message_id<S: Into<String>>(mut self, message_id: S) -> Self {
+        self.message_id = Some(message_id.into());
+        self
+    }
+
+    pub fn with_comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn with_metadata<K: Into<String>>(mut self, key: K, value: Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+
+    /// Normalized score in `0.0..=1.0`, so thumbs and ratings can be aggregated together.
+    /// Corrections carry no score. Ratings outside `1..=max` are clamped into the scale.
+    pub fn score(&self) -> Option<f64> {
+        match &self.kind {
+            FeedbackKind::ThumbsUp => Some(1.0),
+            FeedbackKind::ThumbsDown => Some(0.0),
+            FeedbackKind::Rating { value, max } if *max > 1 => {
+                Some(f64::from(value.min(max).saturating_sub(1)) / f64::from(max - 1))
+            }
+            FeedbackKind::Rating { .. } => Some(1.0),
+            FeedbackKind::Correction { .. } => None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), FeedbackError> {
+        if self.run_id.is_empty() {
+            return Err(FeedbackError::InvalidFeedback("run_id is empty".into()));
+        }
+        if let FeedbackKind::Rating { value, max } = self.kind {
+            if max == 0 || value == 0 || value > max {
+                return Err(FeedbackError::InvalidFeedback(format!(
+                    "rating {} is outside 1..={}",
+                    value, max
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rating_score_stays_in_range() {
+        assert_eq!(Feedback::rating("run", 3, 5).score(), Some(0.5));
+        assert_eq!(Feedback::rating("run", 0, 5).score(), Some(0.0));
+        assert_eq!(Feedback::rating("run", 9, 5).score(), Some(1.0));
+    }
+}
@@ -0,0 +1,8 @@
+mod error;
+pub use error::*;
+
+mod feedback;
+pub use feedback::*;
+
+mod store;
+pub use store::*;
@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use super::{Feedback, FeedbackError};
+
+/// Persists feedback so it can later feed evaluations and reflection.
+#[async_trait]
+pub trait FeedbackStore: Send + Sync {
+    async fn add(&self, feedback: Feedback) -> Result<(), FeedbackError>;
+
+    async fn for_run(&self, run_id: &str) -> Result<Vec<Feedback>, FeedbackError>;
+
+    async fn all(&self) -> Result<Vec<Feedback>, FeedbackError>;
+
+    /// Exports every record as JSON lines.
+    async fn export_jsonl(&self) -> Result<String, FeedbackError> {
+        let mut out = String::new();
+        for feedback in self.all().await? {
+            out.push_str(&serde_json::to_string(&feedback)?);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+/// Hook notified of every feedback record as it is stored, e.g. to forward it to an
+/// evaluation pipeline.
+#[async_trait]
+pub trait FeedbackExporter: Send + Sync {
+    async fn export(&self, feedback: &Feedback) -> Result<(), FeedbackError>;
+}
+
+/// Keeps feedback in memory and forwards every record to the registered exporters.
+#[derive(Default, Clone)]
+pub struct InMemoryFeedbackStore {
+    records: Arc<Mutex<Vec<Feedback>>>,
+    exporters: Vec<Arc<dyn FeedbackExporter>>,
+}
+
+impl InMemoryFeedbackStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_exporter<E: FeedbackExporter + 'static>(mut self, exporter: E) -> Self {
+        self.exporters.push(Arc::new(exporter));
+        self
+    }
+}
+
+#[async_trait]
+impl FeedbackStore for InMemoryFeedbackStore {
+    async fn add(&self, feedback: Feedback) -> Result<(), FeedbackError> {
+        feedback.validate()?;
+        for exporter in &self.exporters {
+            if let Err(e) = exporter.export(&feedback).await {
+                log::warn!("Feedback exporter failed: {}", e);
+            }
+        }
+        self.records.lock().await.push(feedback);
+        Ok(())
+    }
+
+    async fn for_run(&self, run_id: &str) -> Result<Vec<Feedback>, FeedbackError> {
+        Ok(self
+            .records
+            .lock()
+            .await
+            .iter()
+            .filter(|feedback| feedback.run_id == run_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn all(&self) -> Result<Vec<Feedback>, FeedbackError> {
+        Ok(self.records.lock().await.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Collect(Arc<Mutex<Vec<String>>>);
+
+    #[async_trait]
+    impl FeedbackExporter for Collect {
+        async fn export(&self, feedback: &Feedback) -> Result<(), FeedbackError> {
+            self.0.lock().await.push(feedback.run_id.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_filters_by_run_and_exports() {
+        let exported = Arc::new(Mutex::new(Vec::new()));
+        let store = InMemoryFeedbackStore::new().with_exporter(Collect(exported.clone()));
+
+        store.add(Feedback::thumbs_up("run-1")).await.unwrap();
+        store.add(Feedback::rating("run-2", 3, 5)).await.unwrap();
+        store
+            .add(Feedback::correction("run-1", "Paris").with_message_id("msg-1"))
+            .await
+            .unwrap();
+        assert!(store.add(Feedback::rating("run-3", 6, 5)).await.is_err());
+
+        let run_1 = store.for_run("run-1").await.unwrap();
+        assert_eq!(run_1.len(), 2);
+        assert_eq!(run_1[0].score(), Some(1.0));
+        assert_eq!(store.for_run("run-2").await.unwrap()[0].score(), Some(0.5));
+        assert_eq!(*exported.lock().await, vec!["run-1", "run-2", "run-1"]);
+        assert_eq!(store.export_jsonl().await.unwrap().lines().count(), 3);
+    }
+}
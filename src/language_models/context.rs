@@ -0,0 +1,44 @@
+/// Context window sizes of well-known models, by model name prefix. More specific prefixes
+/// come first.
+const CONTEXT_WINDOWS: &[(&str, usize)] = &[
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4-32k", 32_768),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("claude", 200_000),
+    ("deepseek", 65_536),
+    ("qwen-turbo", 1_000_000),
+    ("qwen-plus", 131_072),
+    ("qwen-max", 32_768),
+    ("qwen-long", 10_000_000),
+    ("llama3.1", 131_072),
+    ("llama3.2", 131_072),
+    ("llama3", 8_192),
+];
+
+/// The context window of `model` in tokens, if it is a known model.
+pub fn context_window(model: &str) -> Option<usize> {
+    let model = model.to_lowercase();
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, tokens)| *tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window() {
+        assert_eq!(context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window("gpt-4-0613"), Some(8_192));
+        assert_eq!(context_window("claude-3-5-sonnet-20240620"), Some(200_000));
+        assert_eq!(context_window("my-finetune"), None);
+    }
+}
@@ -0,0 +1,269 @@
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use futures::Stream;
+
+use crate::schemas::{Message, StreamData};
+
+use super::{llm::LLM, options::CallOptions, GenerateResult, LLMError};
+
+const DEFAULT_CONTINUATION_PROMPT: &str = "Your previous answer was cut off. Continue exactly \
+where it stopped, without repeating anything and without any preamble.";
+
+/// Bounds of the overlap removed when a continuation repeats the end of the previous chunk.
+/// Shorter matches are too likely to be coincidental, e.g. a repeated quote or space.
+const MIN_OVERLAP: usize = 8;
+const MAX_OVERLAP: usize = 200;
+
+/// Wraps an LLM so completions cut off by the token limit are continued automatically.
+///
+/// While the result's finish reason is `length`, the partial answer is sent back as an AI
+/// message followed by a request to continue, and the chunks are stitched together. This
+/// prevents agents from receiving half a JSON action. To use it in an executor, build the
+/// agent on the wrapped LLM.
+///
+/// Continuation stops after `max_continuations` requests, when the stitched answer exceeds
+/// `max_chars`, or when a request makes no progress.
+pub struct ContinuationLLM {
+    llm: Box<dyn LLM>,
+    max_continuations: usize,
+    max_chars: Option<usize>,
+    prompt: String,
+}
+
+impl Clone for ContinuationLLM {
+    fn clone(&self) -> Self {
+        Self {
+            llm: self.llm.clone_box(),
+            max_continuations: self.max_continuations,
+            max_chars: self.max_chars,
+            prompt: self.prompt.clone(),
+        }
+    }
+}
+
+impl ContinuationLLM {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            max_continuations: 3,
+            max_chars: None,
+            prompt: DEFAULT_CONTINUATION_PROMPT.to_string(),
+        }
+    }
+
+    pub fn with_max_continuations(mut self, max_continuations: usize) -> Self {
+        self.max_continuations = max_continuations;
+        self
+    }
+
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    pub fn with_prompt<S: Into<String>>(mut self, prompt: S) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+}
+
+#[async_trait]
+impl LLM for ContinuationLLM {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let mut result = self.llm.generate(messages).await?;
+        let mut latency_ms = result.latency_ms;
+        let mut continuations = 0;
+
+        while result.is_truncated() && continuations < self.max_continuations {
+            if self
+                .max_chars
+                .is_some_and(|max_chars| result.generation.chars().count() >= max_chars)
+            {
+                log::warn!("Stopping continuation: answer reached the character limit");
+                break;
+            }
+            continuations += 1;
+            log::debug!("Completion truncated, continuation {}", continuations);
+
+            let mut followup = messages.to_vec();
+            followup.push(Message::new_ai_message(&result.generation));
+            followup.push(Message::new_human_message(&self.prompt));
+            let next = self.llm.generate(&followup).await?;
+
+            let chunk = strip_overlap(&result.generation, &next.generation);
+            if chunk.trim().is_empty() {
+                log::warn!("Stopping continuation: no progress");
+                break;
+            }
+            result.generation.push_str(chunk);
+            result.tokens = match (result.tokens.take(), next.tokens) {
+                (Some(tokens), Some(next_tokens)) => Some(tokens.sum(&next_tokens)),
+                (tokens, next_tokens) => tokens.or(next_tokens),
+            };
+            latency_ms = match (latency_ms, next.latency_ms) {
+                (Some(total), Some(next_latency)) => Some(total + next_latency),
+                (total, next_latency) => total.or(next_latency),
+            };
+            if let Some(next_logprobs) = next.logprobs {
+                result
+                    .logprobs
+                    .get_or_insert_with(Vec::new)
+                    .extend(next_logprobs);
+            }
+            result.reasoning = match (result.reasoning.take(), next.reasoning) {
+                (Some(reasoning), Some(next_reasoning)) => {
+                    Some(format!("{}\n\n{}", reasoning, next_reasoning))
+                }
+                (reasoning, next_reasoning) => reasoning.or(next_reasoning),
+            };
+            result.finish_reason = next.finish_reason;
+        }
+
+        result.latency_ms = latency_ms;
+        Ok(result)
+    }
+
+    /// Streams are forwarded unchanged; continuation only applies to `generate`.
+    async fn stream(
+        &self,
+        messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        self.llm.stream(messages).await
+    }
+
+    fn add_options(&mut self, options: CallOptions) {
+        self.llm.add_options(options)
+    }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.llm.max_context_tokens()
+    }
+}
+
+/// Drops the start of `next` when it repeats the end of `previous`.
+fn strip_overlap<'a>(previous: &str, next: &'a str) -> &'a str {
+    let longest = MAX_OVERLAP.min(previous.len()).min(next.len());
+    (MIN_OVERLAP..=longest)
+        .rev()
+        .filter(|&len| {
+            next.is_char_boundary(len) && previous.is_char_boundary(previous.len() - len)
+        })
+        .find(|&len| previous.ends_with(&next[..len]))
+        .map(|len| &next[len..])
+        .unwrap_or(next)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        language_models::{FinishReason, TokenLogprob, TokenUsage},
+        test_utils::FakeLLM,
+    };
+
+    /// Replies with the scripted chunks in order, the last one repeating forever.
+    fn scripted(chunks: Vec<(&'static str, FinishReason)>) -> FakeLLM {
+        FakeLLM::scripted(
+            chunks
+                .into_iter()
+                .map(|(generation, finish_reason)| GenerateResult {
+                    generation: generation.to_string(),
+                    finish_reason: Some(finish_reason),
+                    tokens: Some(TokenUsage::new(10, 5)),
+                    ..Default::default()
+                })
+                .collect(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_stitches_truncated_json() {
+        let llm = scripted(vec![
+            (r#"{"action": "sea"#, FinishReason::Length),
+            (
+                r#""action": "search", "action_input": "#,
+                FinishReason::Length,
+            ),
+            (r#""rust"}"#, FinishReason::Stop),
+        ]);
+        let result = ContinuationLLM::new(llm.clone())
+            .generate(&[])
+            .await
+            .unwrap();
+
+        assert_eq!(
+            result.generation,
+            r#"{"action": "search", "action_input": "rust"}"#
+        );
+        assert_eq!(result.finish_reason, Some(FinishReason::Stop));
+        assert_eq!(result.tokens.unwrap().total_tokens, 45);
+        assert_eq!(llm.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_the_limit() {
+        let llm = scripted(vec![
+            ("a", FinishReason::Length),
+            ("b", FinishReason::Length),
+        ]);
+        let result = ContinuationLLM::new(llm.clone())
+            .with_max_continuations(2)
+            .generate(&[])
+            .await
+            .unwrap();
+
+        assert_eq!(result.generation, "abb");
+        assert!(result.is_truncated());
+        assert_eq!(llm.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_max_chars_counts_characters() {
+        let llm = scripted(vec![
+            ("日本語", FinishReason::Length),
+            ("の答え", FinishReason::Stop),
+        ]);
+        let result = ContinuationLLM::new(llm)
+            .with_max_chars(4)
+            .generate(&[])
+            .await
+            .unwrap();
+
+        assert_eq!(result.generation, "日本語の答え");
+    }
+
+    #[tokio::test]
+    async fn test_keeps_logprobs_of_every_chunk() {
+        let logprob = |token: &str, logprob: f32| TokenLogprob {
+            token: token.to_string(),
+            logprob,
+            top_logprobs: None,
+        };
+        let llm = FakeLLM::scripted(vec![
+            GenerateResult {
+                generation: "Hello".to_string(),
+                finish_reason: Some(FinishReason::Length),
+                logprobs: Some(vec![logprob("Hello", 0.0)]),
+                reasoning: Some("Greet.".to_string()),
+                ..Default::default()
+            },
+            GenerateResult {
+                generation: " world".to_string(),
+                finish_reason: Some(FinishReason::Stop),
+                logprobs: Some(vec![logprob(" world", -2.0)]),
+                reasoning: Some("Finish the greeting.".to_string()),
+                ..Default::default()
+            },
+        ]);
+        let result = ContinuationLLM::new(llm).generate(&[]).await.unwrap();
+
+        assert_eq!(result.generation, "Hello world");
+        assert_eq!(result.logprobs.as_ref().unwrap().len(), 2);
+        assert_eq!(result.confidence(), Some((-1.0f64).exp()));
+        assert_eq!(
+            result.reasoning.as_deref(),
+            Some("Greet.\n\nFinish the greeting.")
+        );
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "openai")]
 use async_openai::error::OpenAIError;
 #[cfg(feature = "ollama")]
 use ollama_rs::error::OllamaError;
@@ -6,10 +7,13 @@ use serde_json::Error as SerdeJsonError;
 use thiserror::Error;
 use tokio::time::error::Elapsed;
 
+#[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
+use crate::llm::mcp::McpError;
 use crate::llm::{AnthropicError, DeepseekError, QwenError};
 
 #[derive(Error, Debug)]
 pub enum LLMError {
+    #[cfg(feature = "openai")]
     #[error("OpenAI error: {0}")]
     OpenAIError(#[from] OpenAIError),
 
@@ -26,6 +30,10 @@ pub enum LLMError {
     #[error("Ollama error: {0}")]
     OllamaError(#[from] OllamaError),
 
+    #[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
+    #[error("MCP error: {0}")]
+    McpError(#[from] McpError),
+
     #[error("Network request failed: {0}")]
     RequestError(#[from] ReqwestError),
 
@@ -50,3 +58,47 @@ pub enum LLMError {
     #[error("Error: {0}")]
     OtherError(String),
 }
+
+impl LLMError {
+    /// Whether the error comes from the endpoint rather than the request, such as a network
+    /// failure, a timeout, a rate limit or an overloaded server, so the same request may
+    /// succeed later or on another endpoint.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            #[cfg(feature = "openai")]
+            Self::OpenAIError(e) => {
+                matches!(e, OpenAIError::Reqwest(_) | OpenAIError::StreamError(_))
+            }
+            Self::AnthropicError(e) => matches!(
+                e,
+                AnthropicError::RateLimitError(_)
+                    | AnthropicError::ApiError(_)
+                    | AnthropicError::OverloadedError(_)
+            ),
+            Self::QwenError(e) => matches!(
+                e,
+                QwenError::NetworkError(_)
+                    | QwenError::ModelUnavailableError(_)
+                    | QwenError::ModelServingError(_)
+                    | QwenError::InternalError(_)
+                    | QwenError::SystemError(_)
+                    | QwenError::APIConnectionError(_)
+                    | QwenError::TimeoutError(_)
+            ),
+            Self::DeepseekError(e) => matches!(
+                e,
+                DeepseekError::RateLimitError(_)
+                    | DeepseekError::ServerError(_)
+                    | DeepseekError::ServerOverloadedError(_)
+            ),
+            #[cfg(feature = "ollama")]
+            Self::OllamaError(_) => true,
+            #[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
+            Self::McpError(e) => {
+                matches!(e, McpError::ConnectionClosed(_) | McpError::Timeout(_))
+            }
+            Self::RequestError(_) | Self::IoError(_) | Self::Timeout(_) => true,
+            _ => false,
+        }
+    }
+}
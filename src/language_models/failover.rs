@@ -0,0 +1,316 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use futures::{future::join_all, Stream};
+use serde::{Deserialize, Serialize};
+
+use crate::schemas::{Message, StreamData};
+
+use super::{llm::LLM, options::CallOptions, GenerateResult, LLMError};
+
+/// Weight of the latest call in an endpoint's latency average.
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+struct Endpoint {
+    name: String,
+    llm: Box<dyn LLM>,
+}
+
+impl Clone for Endpoint {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            llm: self.llm.clone_box(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct EndpointState {
+    latency_ms: Option<f64>,
+    consecutive_failures: u32,
+    down_until: Option<Instant>,
+    last_error: Option<String>,
+}
+
+impl EndpointState {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.down_until.is_none_or(|until| until <= now)
+    }
+}
+
+/// Health of an endpoint of an [`EndpointPool`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndpointStatus {
+    pub name: String,
+    /// False while the endpoint cools down after failing.
+    pub healthy: bool,
+    /// Moving average of the latency of its successful calls.
+    pub latency_ms: Option<f64>,
+    pub consecutive_failures: u32,
+    pub last_error: Option<String>,
+}
+
+/// An LLM serving every call from one of several endpoints of the same provider and model,
+/// such as regions or replicas, failing over to the next endpoint when one is unreachable.
+///
+/// Calls go to the healthy endpoint with the lowest average latency, endpoints never measured
+/// first so each gets measured. An endpoint failing with a transient error, see
+/// [`LLMError::is_transient`], `max_failures` times in a row is skipped during the cooldown,
+/// unless every endpoint is down. Other errors come from the request, so they are returned
+/// without trying another endpoint. Health is shared between clones.
+///
+/// This is independent from falling back to another provider or model: a pool can be a route
+/// of a [`ModelRouter`](super::ModelRouter) or be wrapped by any cross-provider fallback.
+///
+/// ```rust,ignore
+/// let pool = EndpointPool::new()
+///     .with_endpoint("eastus", OpenAI::new(eastus_config))
+///     .with_endpoint("westeurope", OpenAI::new(westeurope_config))
+///     .with_cooldown(Duration::from_secs(60));
+/// ```
+#[derive(Clone)]
+pub struct EndpointPool {
+    endpoints: Vec<Endpoint>,
+    cooldown: Duration,
+    max_failures: u32,
+    states: Arc<Mutex<Vec<EndpointState>>>,
+}
+
+impl Default for EndpointPool {
+    fn default() -> Self {
+        Self {
+            endpoints: Vec::new(),
+            cooldown: Duration::from_secs(30),
+            max_failures: 1,
+            states: Arc::default(),
+        }
+    }
+}
+
+impl EndpointPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_endpoint<S: Into<String>, L: Into<Box<dyn LLM>>>(
+        mut self,
+        name: S,
+        llm: L,
+    ) -> Self {
+        self.endpoints.push(Endpoint {
+            name: name.into(),
+            llm: llm.into(),
+        });
+        self.states.lock().unwrap().push(EndpointState::default());
+        self
+    }
+
+    /// How long a failed endpoint is skipped before it is tried again.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Consecutive transient failures after which an endpoint is marked down.
+    pub fn with_max_failures(mut self, max_failures: u32) -> Self {
+        self.max_failures = max_failures.max(1);
+        self
+    }
+
+    pub fn statuses(&self) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        let states = self.states.lock().unwrap();
+        self.endpoints
+            .iter()
+            .zip(states.iter())
+            .map(|(endpoint, state)| EndpointStatus {
+                name: endpoint.name.clone(),
+                healthy: state.is_healthy(now),
+                latency_ms: state.latency_ms,
+                consecutive_failures: state.consecutive_failures,
+                last_error: state.last_error.clone(),
+            })
+            .collect()
+    }
+
+    /// Probes every endpoint with a minimal prompt, updating their health and latency, e.g.
+    /// from a periodic task so that calls do not pay for discovering a down endpoint.
+    pub async fn check_health(&self) -> Vec<EndpointStatus> {
+        let probe = [Message::new_human_message("ping")];
+        join_all(self.endpoints.iter().enumerate().map(|(index, endpoint)| {
+            let probe = &probe;
+            async move {
+                let start = Instant::now();
+                let result = endpoint.llm.generate(probe).await;
+                self.record(index, start, result.as_ref().err());
+            }
+        }))
+        .await;
+        self.statuses()
+    }
+
+    /// Indices of the endpoints in the order they are tried.
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let states = self.states.lock().unwrap();
+        let mut order = (0..self.endpoints.len()).collect::<Vec<_>>();
+        // Stable, so the first endpoint added wins on ties.
+        order.sort_by(|&a, &b| {
+            let (a, b) = (&states[a], &states[b]);
+            b.is_healthy(now)
+                .cmp(&a.is_healthy(now))
+                .then_with(|| a.latency_ms.is_some().cmp(&b.latency_ms.is_some()))
+                .then_with(|| {
+                    a.latency_ms
+                        .unwrap_or_default()
+                        .total_cmp(&b.latency_ms.unwrap_or_default())
+                })
+        });
+        order
+    }
+
+    fn record(&self, index: usize, start: Instant, error: Option<&LLMError>) {
+        let mut states = self.states.lock().unwrap();
+        let state = &mut states[index];
+        match error {
+            None => {
+                let latency = start.elapsed().as_secs_f64() * 1000.0;
+                state.latency_ms = Some(match state.latency_ms {
+                    Some(average) => average + LATENCY_SMOOTHING * (latency - average),
+                    None => latency,
+                });
+                state.consecutive_failures = 0;
+                state.down_until = None;
+            }
+            Some(error) if error.is_transient() => {
+                state.consecutive_failures += 1;
+                state.last_error = Some(error.to_string());
+                if state.consecutive_failures >= self.max_failures {
+                    log::warn!(
+                        "Endpoint {} is down for {:?}: {}",
+                        self.endpoints[index].name,
+                        self.cooldown,
+                        error
+                    );
+                    state.down_until = Some(Instant::now() + self.cooldown);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+#[async_trait]
+impl LLM for EndpointPool {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let mut last_error = None;
+        for index in self.order() {
+            let start = Instant::now();
+            let result = self.endpoints[index].llm.generate(messages).await;
+            self.record(index, start, result.as_ref().err());
+            match result {
+                Err(e) if e.is_transient() => {
+                    log::debug!("Endpoint {} failed: {}", self.endpoints[index].name, e);
+                    last_error = Some(e);
+                }
+                result => return result,
+            }
+        }
+        Err(last_error.unwrap_or_else(|| LLMError::OtherError("No endpoint configured".into())))
+    }
+
+    /// Tries the endpoints in the same order until one opens a stream. Failures once the
+    /// stream is open are not failed over.
+    async fn stream(
+        &self,
+        messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        let mut last_error = None;
+        for index in self.order() {
+            let start = Instant::now();
+            match self.endpoints[index].llm.stream(messages).await {
+                Ok(stream) => {
+                    self.record(index, start, None);
+                    return Ok(stream);
+                }
+                Err(e) => {
+                    self.record(index, start, Some(&e));
+                    if !e.is_transient() {
+                        return Err(e);
+                    }
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| LLMError::OtherError("No endpoint configured".into())))
+    }
+
+    fn add_options(&mut self, options: CallOptions) {
+        for endpoint in &mut self.endpoints {
+            endpoint.llm.add_options(options.clone());
+        }
+    }
+
+    /// The smallest window among the endpoints, since any of them may serve a call.
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| endpoint.llm.max_context_tokens())
+            .min()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::FakeLLM;
+
+    /// Answers with its name after `delay_ms`, or fails with a network error when `down`.
+    fn region(name: &'static str, down: bool, delay_ms: u64) -> FakeLLM {
+        FakeLLM::new(move |_| {
+            if down {
+                return Err(LLMError::IoError(std::io::Error::other(
+                    "connection refused",
+                )));
+            }
+            Ok(GenerateResult {
+                generation: name.to_string(),
+                ..Default::default()
+            })
+        })
+        .with_delay(Duration::from_millis(delay_ms))
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_skips_down_endpoint() {
+        let down = region("eastus", true, 0);
+        let pool = EndpointPool::new()
+            .with_endpoint("eastus", down.clone())
+            .with_endpoint("westeurope", region("westeurope", false, 0));
+
+        assert_eq!(pool.invoke("hi").await.unwrap(), "westeurope");
+        assert_eq!(pool.invoke("hi").await.unwrap(), "westeurope");
+        assert_eq!(down.calls(), 1);
+
+        let statuses = pool.statuses();
+        assert!(!statuses[0].healthy);
+        assert!(statuses[0].last_error.is_some());
+        assert!(statuses[1].healthy);
+    }
+
+    #[tokio::test]
+    async fn test_prefers_fastest_endpoint() {
+        let pool = EndpointPool::new()
+            .with_endpoint("slow", region("slow", false, 40))
+            .with_endpoint("fast", region("fast", false, 1));
+
+        let statuses = pool.check_health().await;
+        assert!(statuses[0].latency_ms > statuses[1].latency_ms);
+        assert_eq!(pool.invoke("hi").await.unwrap(), "fast");
+    }
+}
@@ -25,6 +25,12 @@ pub trait LLM: Sync + Send + LLMClone {
     fn add_options(&mut self, _options: CallOptions) {
         // No action taken
     }
+    /// Size of the model's context window in tokens, when known. Used to trim the history
+    /// sent with a request instead of letting the provider reject it.
+    fn max_context_tokens(&self) -> Option<usize> {
+        None
+    }
+
     //This is usefull when using non chat models
     fn messages_to_string(&self, messages: &[Message]) -> String {
         messages
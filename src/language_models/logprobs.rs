@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Log probability of one generated token, as reported by providers that support it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f32,
+    /// Most likely alternatives at this position, when requested with `top_logprobs`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<Vec<TopLogprob>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f32,
+}
+
+/// Geometric mean of the token probabilities, in `0.0..=1.0`.
+///
+/// This is `exp` of the mean log probability, so a single very unlikely token lowers the
+/// score without dominating it. Returns `None` for an empty slice.
+pub fn confidence(logprobs: &[TokenLogprob]) -> Option<f64> {
+    if logprobs.is_empty() {
+        return None;
+    }
+    let mean = logprobs.iter().map(|t| f64::from(t.logprob)).sum::<f64>() / logprobs.len() as f64;
+    Some(mean.exp())
+}
+
+/// Reads the `{"content": [...]}` logprobs object of OpenAI-compatible responses.
+pub(crate) fn logprobs_from_openai_value(value: &Value) -> Option<Vec<TokenLogprob>> {
+    value
+        .get("content")
+        .filter(|content| content.is_array())
+        .and_then(|content| serde_json::from_value(content.clone()).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_confidence_from_openai_logprobs() {
+        let logprobs = logprobs_from_openai_value(&json!({
+            "content": [
+                {"token": "Yes", "logprob": 0.0, "bytes": [89, 101, 115], "top_logprobs": []},
+                {"token": ".", "logprob": -2.0794415, "bytes": [46], "top_logprobs": null}
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(logprobs[0].token, "Yes");
+        // exp((0 + ln(1/8)) / 2) = sqrt(1/8)
+        let confidence = confidence(&logprobs).unwrap();
+        assert!((confidence - 0.125f64.sqrt()).abs() < 1e-6);
+        assert_eq!(super::confidence(&[]), None);
+    }
+}
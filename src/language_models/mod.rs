@@ -1,22 +1,169 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt};
 
 use serde::{Deserialize, Serialize};
 
 pub mod llm;
 pub mod options;
 
+mod context;
+pub use context::*;
+
+mod continuation;
+pub use continuation::*;
+
 mod error;
 pub use error::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod failover;
+#[cfg(not(target_arch = "wasm32"))]
+pub use failover::*;
+
+mod logprobs;
+pub use logprobs::*;
+
+mod reasoning;
+pub use reasoning::*;
+
+mod router;
+pub use router::*;
+
+mod speculative;
+pub use speculative::*;
+
+pub use langchain_rust_schemas::TokenUsage;
+
 //TODO: check if its this should have a data:serde::Value to save all other things, like OpenAI
 //function responses
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct GenerateResult {
     pub tokens: Option<TokenUsage>,
     pub generation: String,
+    /// Why the model stopped generating, when the provider reports it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finish_reason: Option<FinishReason>,
+    /// Model that actually served the request, which may differ from the requested alias.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Provider-side id of the request or response, useful when reporting issues.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Wall-clock time of the provider call in milliseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Per-token log probabilities, when requested with [`options::CallOptions::with_logprobs`]
+    /// and supported by the provider.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<Vec<TokenLogprob>>,
+    /// Reasoning of reasoning models, kept apart from the answer in `generation`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reasoning: Option<String>,
+}
+
+/// Why a completion ended, normalized across providers.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FinishReason {
+    /// Natural end of the answer or a stop sequence.
+    Stop,
+    /// The output hit the token limit and is truncated.
+    Length,
+    /// The model asked for tool or function calls.
+    ToolCalls,
+    /// The output was withheld or cut by the provider's safety filters.
+    ContentFilter,
+    /// Any other provider-specific reason.
+    Other(String),
+}
+
+impl FinishReason {
+    /// Maps the stop/finish reason strings used by the supported providers.
+    pub fn from_provider(reason: &str) -> Self {
+        match reason {
+            "stop" | "end_turn" | "stop_sequence" | "eos" | "endTurn" | "stopSequence" => {
+                Self::Stop
+            }
+            "length" | "max_tokens" | "model_length" | "maxTokens" => Self::Length,
+            "tool_calls" | "tool_use" | "function_call" => Self::ToolCalls,
+            "content_filter" | "safety" | "refusal" => Self::ContentFilter,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for FinishReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stop => write!(f, "stop"),
+            Self::Length => write!(f, "length"),
+            Self::ToolCalls => write!(f, "tool_calls"),
+            Self::ContentFilter => write!(f, "content_filter"),
+            Self::Other(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+#[cfg(feature = "openai")]
+impl crate::schemas::convert::LangchainFromOpenAI<async_openai::types::FinishReason>
+    for FinishReason
+{
+    fn from_openai(openai: async_openai::types::FinishReason) -> Self {
+        use async_openai::types::FinishReason as OpenAIFinishReason;
+        match openai {
+            OpenAIFinishReason::Stop => Self::Stop,
+            OpenAIFinishReason::Length => Self::Length,
+            OpenAIFinishReason::ToolCalls | OpenAIFinishReason::FunctionCall => Self::ToolCalls,
+            OpenAIFinishReason::ContentFilter => Self::ContentFilter,
+        }
+    }
 }
 
 impl GenerateResult {
+    /// True when the provider stopped because of the token limit.
+    pub fn is_truncated(&self) -> bool {
+        self.finish_reason == Some(FinishReason::Length)
+    }
+
+    /// True when the provider's content filter stopped the generation.
+    pub fn is_content_filtered(&self) -> bool {
+        self.finish_reason == Some(FinishReason::ContentFilter)
+    }
+
+    /// Aggregate confidence of the generation, see [`confidence`]. `None` when the provider
+    /// returned no logprobs.
+    pub fn confidence(&self) -> Option<f64> {
+        self.logprobs.as_deref().and_then(confidence)
+    }
+
+    /// Copies the response id, model and finish reason from an OpenAI-compatible response or
+    /// stream chunk, keeping previous values for fields the chunk does not carry. Token
+    /// logprobs are appended.
+    pub(crate) fn update_from_openai_value(&mut self, value: &serde_json::Value) {
+        if let Some(id) = value["id"].as_str() {
+            self.request_id = Some(id.to_string());
+        }
+        if let Some(model) = value["model"].as_str() {
+            self.model = Some(model.to_string());
+        }
+        if let Some(reason) = value
+            .pointer("/choices/0/finish_reason")
+            .and_then(|r| r.as_str())
+        {
+            self.finish_reason = Some(FinishReason::from_provider(reason));
+        }
+        if let Some(logprobs) = value
+            .pointer("/choices/0/logprobs")
+            .and_then(logprobs_from_openai_value)
+        {
+            self.logprobs.get_or_insert_with(Vec::new).extend(logprobs);
+        }
+    }
+
+    pub(crate) fn with_latency(mut self, stopwatch: &Stopwatch) -> Self {
+        self.latency_ms = stopwatch.elapsed_ms();
+        self
+    }
+
     pub fn to_hashmap(&self) -> HashMap<String, String> {
         let mut map = HashMap::new();
 
@@ -36,39 +183,92 @@ impl GenerateResult {
             map.insert("total_tokens".to_string(), tokens.total_tokens.to_string());
         }
 
+        if let Some(ref finish_reason) = self.finish_reason {
+            map.insert("finish_reason".to_string(), finish_reason.to_string());
+        }
+        if let Some(ref model) = self.model {
+            map.insert("model".to_string(), model.clone());
+        }
+        if let Some(ref reasoning) = self.reasoning {
+            map.insert("reasoning".to_string(), reasoning.clone());
+        }
+
         map
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct TokenUsage {
-    pub prompt_tokens: u32,
-    pub completion_tokens: u32,
-    pub total_tokens: u32,
+/// Measures provider latency. `Instant` is unavailable on wasm32, where no latency is reported.
+pub(crate) struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
 }
 
-impl TokenUsage {
-    pub fn sum(&self, other: &TokenUsage) -> TokenUsage {
-        TokenUsage {
-            prompt_tokens: self.prompt_tokens + other.prompt_tokens,
-            completion_tokens: self.completion_tokens + other.completion_tokens,
-            total_tokens: self.total_tokens + other.total_tokens,
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
         }
     }
 
-    pub fn add(&mut self, other: &TokenUsage) {
-        self.prompt_tokens += other.prompt_tokens;
-        self.completion_tokens += other.completion_tokens;
-        self.total_tokens += other.total_tokens;
+    pub(crate) fn elapsed_ms(&self) -> Option<u64> {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            Some(self.start.elapsed().as_millis() as u64)
+        }
+        #[cfg(target_arch = "wasm32")]
+        {
+            None
+        }
     }
 }
 
-impl TokenUsage {
-    pub fn new(prompt_tokens: u32, completion_tokens: u32) -> Self {
-        Self {
-            prompt_tokens,
-            completion_tokens,
-            total_tokens: prompt_tokens + completion_tokens,
-        }
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_metadata_from_openai_compatible_chunks() {
+        let mut result = GenerateResult::default();
+        result.update_from_openai_value(&json!({
+            "id": "chatcmpl-1",
+            "model": "deepseek-chat",
+            "choices": [{
+                "delta": {"content": "Hel"},
+                "logprobs": {"content": [{"token": "Hel", "logprob": -0.5}]},
+                "finish_reason": null
+            }]
+        }));
+        assert_eq!(result.finish_reason, None);
+        result.update_from_openai_value(&json!({
+            "choices": [{
+                "delta": {"content": "lo"},
+                "logprobs": {"content": [{"token": "lo", "logprob": -1.5}]},
+                "finish_reason": "length"
+            }]
+        }));
+        assert!(result.is_truncated());
+        assert_eq!(result.logprobs.as_ref().map(Vec::len), Some(2));
+        assert!((result.confidence().unwrap() - (-1.0f64).exp()).abs() < 1e-6);
+        assert_eq!(result.model.as_deref(), Some("deepseek-chat"));
+        assert_eq!(result.request_id.as_deref(), Some("chatcmpl-1"));
+
+        assert_eq!(result.to_hashmap()["finish_reason"], "length");
+        assert_eq!(FinishReason::from_provider("end_turn"), FinishReason::Stop);
+        assert_eq!(
+            FinishReason::from_provider("pause_turn"),
+            FinishReason::Other("pause_turn".to_string())
+        );
+    }
+
+    #[test]
+    fn test_deserializes_results_without_metadata() {
+        let result: GenerateResult =
+            serde_json::from_value(json!({"tokens": null, "generation": "hi"})).unwrap();
+        assert_eq!(result.finish_reason, None);
+        let value = serde_json::to_value(&result).unwrap();
+        assert!(value.get("latency_ms").is_none());
     }
 }
@@ -28,6 +28,8 @@ pub struct CallOptions {
     pub function_call_behavior: Option<FunctionCallBehavior>,
     pub response_format: Option<ResponseFormat>,
     pub stream_usage: Option<bool>,
+    pub logprobs: Option<bool>,
+    pub top_logprobs: Option<u8>,
 }
 
 impl Default for CallOptions {
@@ -56,6 +58,8 @@ impl CallOptions {
             function_call_behavior: None,
             response_format: None,
             stream_usage: None,
+            logprobs: None,
+            top_logprobs: None,
         }
     }
 
@@ -161,6 +165,20 @@ impl CallOptions {
         self
     }
 
+    /// Requests per-token log probabilities, returned in [`super::GenerateResult::logprobs`]
+    /// by providers that support them.
+    pub fn with_logprobs(mut self, logprobs: bool) -> Self {
+        self.logprobs = Some(logprobs);
+        self
+    }
+
+    /// Number of alternatives to return per token; implies `with_logprobs(true)`.
+    pub fn with_top_logprobs(mut self, top_logprobs: u8) -> Self {
+        self.logprobs = Some(true);
+        self.top_logprobs = Some(top_logprobs);
+        self
+    }
+
     pub fn merge_options(&mut self, incoming_options: CallOptions) {
         // For simple scalar types wrapped in Option, prefer incoming option if it is Some
         self.candidate_count = incoming_options.candidate_count.or(self.candidate_count);
@@ -186,6 +204,8 @@ impl CallOptions {
             .response_format
             .or(self.response_format.clone());
         self.stream_usage = incoming_options.stream_usage.or(self.stream_usage);
+        self.logprobs = incoming_options.logprobs.or(self.logprobs);
+        self.top_logprobs = incoming_options.top_logprobs.or(self.top_logprobs);
 
         // For `Vec<String>`, merge if both are Some; prefer incoming if only incoming is Some
         if let Some(mut new_stop_words) = incoming_options.stop_words {
@@ -0,0 +1,94 @@
+use super::GenerateResult;
+
+const THINK_OPEN: &str = "<think>";
+const THINK_CLOSE: &str = "</think>";
+
+/// Splits the `<think>...</think>` blocks of reasoning models (R1 and its distillations) from
+/// the answer.
+///
+/// Returns the reasoning, if any, and the answer with every block removed. An opening tag
+/// without a closing one means the answer was cut off while reasoning, so everything after
+/// it is reasoning. A closing tag without an opening one, as sent by templates that open the
+/// block in the prompt, ends a reasoning block starting at the beginning of the text.
+pub fn split_reasoning(text: &str) -> (Option<String>, String) {
+    let mut reasoning = Vec::new();
+    let mut answer = String::new();
+    let mut rest = text;
+
+    if let Some(close) = rest.find(THINK_CLOSE) {
+        if !rest[..close].contains(THINK_OPEN) {
+            reasoning.push(rest[..close].trim());
+            rest = &rest[close + THINK_CLOSE.len()..];
+        }
+    }
+    while let Some(open) = rest.find(THINK_OPEN) {
+        answer.push_str(&rest[..open]);
+        let inner = &rest[open + THINK_OPEN.len()..];
+        match inner.find(THINK_CLOSE) {
+            Some(close) => {
+                reasoning.push(inner[..close].trim());
+                rest = &inner[close + THINK_CLOSE.len()..];
+            }
+            None => {
+                reasoning.push(inner.trim());
+                rest = "";
+            }
+        }
+    }
+    answer.push_str(rest);
+
+    let reasoning = reasoning
+        .into_iter()
+        .filter(|r| !r.is_empty())
+        .collect::<Vec<_>>();
+    let reasoning = (!reasoning.is_empty()).then(|| reasoning.join("\n\n"));
+    (reasoning, answer.trim().to_string())
+}
+
+/// The answer of `text` without its reasoning, see [`split_reasoning`]. Text without
+/// reasoning is returned unchanged.
+pub fn strip_reasoning(text: &str) -> String {
+    match split_reasoning(text) {
+        (Some(_), answer) => answer,
+        (None, _) => text.to_string(),
+    }
+}
+
+impl GenerateResult {
+    /// Moves `<think>` blocks from the generation to `reasoning`.
+    pub fn extract_reasoning(&mut self) {
+        if let (Some(reasoning), answer) = split_reasoning(&self.generation) {
+            self.reasoning = Some(match self.reasoning.take() {
+                Some(previous) => format!("{}\n\n{}", previous, reasoning),
+                None => reasoning,
+            });
+            self.generation = answer;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reasoning() {
+        assert_eq!(
+            split_reasoning("<think>\nThe user greets.\n</think>\n\nHello!"),
+            (Some("The user greets.".to_string()), "Hello!".to_string())
+        );
+        assert_eq!(
+            split_reasoning("Opened in the prompt</think>Answer"),
+            (
+                Some("Opened in the prompt".to_string()),
+                "Answer".to_string()
+            )
+        );
+        assert_eq!(
+            split_reasoning("Answer <think>cut off"),
+            (Some("cut off".to_string()), "Answer".to_string())
+        );
+        assert_eq!(split_reasoning(" Plain "), (None, "Plain".to_string()));
+        assert_eq!(strip_reasoning(" Plain "), " Plain ");
+    }
+}
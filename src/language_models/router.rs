@@ -0,0 +1,366 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prompt::estimate_tokens,
+    schemas::{Message, MessageType, StreamData},
+};
+
+use super::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage};
+
+/// What a call needs from a model, as seen by [`ModelRouter`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteRequest {
+    /// Estimated size of the prompt.
+    pub prompt_tokens: usize,
+    /// Functions are set in the options or the conversation contains tool calls.
+    pub requires_tools: bool,
+    /// A response format is set in the options or the prompt asks for JSON.
+    pub requires_json: bool,
+}
+
+impl RouteRequest {
+    pub fn from_messages(messages: &[Message], options: &CallOptions) -> Self {
+        Self {
+            prompt_tokens: messages.iter().map(|m| estimate_tokens(&m.content)).sum(),
+            requires_tools: options.functions.as_ref().is_some_and(|f| !f.is_empty())
+                || messages
+                    .iter()
+                    .any(|m| m.message_type == MessageType::ToolMessage || m.tool_calls.is_some()),
+            requires_json: options.response_format.is_some()
+                || messages.iter().any(|m| m.content.contains("JSON")),
+        }
+    }
+}
+
+/// A model [`ModelRouter`] can send calls to, and what it is good for.
+pub struct ModelRoute {
+    name: String,
+    llm: Box<dyn LLM>,
+    description: String,
+    max_prompt_tokens: Option<usize>,
+    supports_tools: bool,
+    supports_json: bool,
+    cost_per_1k_tokens: f64,
+    latency_ms: Option<u64>,
+}
+
+impl Clone for ModelRoute {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            llm: self.llm.clone_box(),
+            description: self.description.clone(),
+            max_prompt_tokens: self.max_prompt_tokens,
+            supports_tools: self.supports_tools,
+            supports_json: self.supports_json,
+            cost_per_1k_tokens: self.cost_per_1k_tokens,
+            latency_ms: self.latency_ms,
+        }
+    }
+}
+
+impl ModelRoute {
+    pub fn new<S: Into<String>, L: Into<Box<dyn LLM>>>(name: S, llm: L) -> Self {
+        Self {
+            name: name.into(),
+            llm: llm.into(),
+            description: String::new(),
+            max_prompt_tokens: None,
+            supports_tools: true,
+            supports_json: true,
+            cost_per_1k_tokens: 0.0,
+            latency_ms: None,
+        }
+    }
+
+    /// What the model is good at, shown to the classifier, e.g. "planning and reasoning".
+    pub fn with_description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    pub fn with_max_prompt_tokens(mut self, max_prompt_tokens: usize) -> Self {
+        self.max_prompt_tokens = Some(max_prompt_tokens);
+        self
+    }
+
+    pub fn with_tools(mut self, supports_tools: bool) -> Self {
+        self.supports_tools = supports_tools;
+        self
+    }
+
+    pub fn with_json(mut self, supports_json: bool) -> Self {
+        self.supports_json = supports_json;
+        self
+    }
+
+    /// Blended price per thousand tokens, used to prefer cheaper routes and for accounting.
+    pub fn with_cost_per_1k_tokens(mut self, cost: f64) -> Self {
+        self.cost_per_1k_tokens = cost;
+        self
+    }
+
+    /// Typical latency of a call, compared with the router's latency target.
+    pub fn with_latency_ms(mut self, latency_ms: u64) -> Self {
+        self.latency_ms = Some(latency_ms);
+        self
+    }
+
+    fn can_serve(&self, request: &RouteRequest, max_latency_ms: Option<u64>) -> bool {
+        (self.supports_tools || !request.requires_tools)
+            && (self.supports_json || !request.requires_json)
+            && self
+                .max_prompt_tokens
+                .is_none_or(|max| request.prompt_tokens <= max)
+            && match (self.latency_ms, max_latency_ms) {
+                (Some(latency), Some(max)) => latency <= max,
+                _ => true,
+            }
+    }
+}
+
+/// Calls and spend of one route.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteUsage {
+    pub calls: u64,
+    pub errors: u64,
+    pub tokens: TokenUsage,
+    pub cost: f64,
+}
+
+/// An LLM that sends every call to one of several models, so planning can run on a big model
+/// and summarization on a cheap one.
+///
+/// The routes able to serve a call are those supporting what it needs (tools, JSON, prompt
+/// length) within the latency target. The cheapest of them wins, the first added on ties. With
+/// a classifier, a small LLM picks among them from their descriptions instead, falling back to
+/// the cheapest if its reply names none. Usage is accounted per route, shared between clones.
+///
+/// ```rust,ignore
+/// let router = ModelRouter::new()
+///     .with_route(ModelRoute::new("small", small).with_cost_per_1k_tokens(0.1).with_tools(false))
+///     .with_route(ModelRoute::new("large", large).with_cost_per_1k_tokens(2.0));
+/// ```
+#[derive(Clone, Default)]
+pub struct ModelRouter {
+    routes: Vec<ModelRoute>,
+    classifier: Option<Arc<dyn LLM>>,
+    max_latency_ms: Option<u64>,
+    options: CallOptions,
+    usage: Arc<Mutex<HashMap<String, RouteUsage>>>,
+}
+
+impl ModelRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_route(mut self, route: ModelRoute) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    pub fn with_classifier<L: LLM + 'static>(mut self, classifier: L) -> Self {
+        self.classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Skips routes whose typical latency is above `max_latency_ms`.
+    pub fn with_max_latency_ms(mut self, max_latency_ms: u64) -> Self {
+        self.max_latency_ms = Some(max_latency_ms);
+        self
+    }
+
+    pub fn usage(&self) -> HashMap<String, RouteUsage> {
+        self.usage.lock().unwrap().clone()
+    }
+
+    /// The route a call with `messages` goes to.
+    pub async fn route(&self, messages: &[Message]) -> Result<&ModelRoute, LLMError> {
+        let request = RouteRequest::from_messages(messages, &self.options);
+        let mut candidates = self
+            .routes
+            .iter()
+            .filter(|route| route.can_serve(&request, self.max_latency_ms))
+            .collect::<Vec<_>>();
+        // Stable, so the first route added wins on equal costs.
+        candidates.sort_by(|a, b| a.cost_per_1k_tokens.total_cmp(&b.cost_per_1k_tokens));
+        let Some(cheapest) = candidates.first().copied() else {
+            return Err(LLMError::OtherError(format!(
+                "No route can serve the request: {:?}",
+                request
+            )));
+        };
+
+        let Some(classifier) = self.classifier.as_ref().filter(|_| candidates.len() > 1) else {
+            return Ok(cheapest);
+        };
+        let choice = classifier
+            .invoke(&classifier_prompt(&candidates, messages))
+            .await?;
+        let choice = choice.trim();
+        Ok(candidates
+            .into_iter()
+            .find(|route| route.name == choice)
+            .unwrap_or_else(|| {
+                log::warn!("The classifier picked an unknown route: {}", choice);
+                cheapest
+            }))
+    }
+
+    fn record(&self, route: &ModelRoute, result: Option<&GenerateResult>) {
+        let mut usage = self.usage.lock().unwrap();
+        let usage = usage.entry(route.name.clone()).or_default();
+        usage.calls += 1;
+        match result {
+            Some(result) => {
+                if let Some(tokens) = &result.tokens {
+                    usage.tokens = usage.tokens.sum(tokens);
+                    usage.cost += tokens.total_tokens as f64 / 1000.0 * route.cost_per_1k_tokens;
+                }
+            }
+            None => usage.errors += 1,
+        }
+    }
+}
+
+fn classifier_prompt(routes: &[&ModelRoute], messages: &[Message]) -> String {
+    let routes = routes
+        .iter()
+        .map(|route| format!("- {}: {}", route.name, route.description))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let task = messages
+        .iter()
+        .rev()
+        .find(|m| m.message_type == MessageType::HumanMessage)
+        .map(|m| m.content.as_str())
+        .unwrap_or_default();
+    format!(
+        "Pick the model best suited to the task below.\n\nModels:\n{}\n\nTask:\n{}\n\n\
+         Reply with the model name only.",
+        routes, task
+    )
+}
+
+#[async_trait]
+impl LLM for ModelRouter {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let route = self.route(messages).await?;
+        log::debug!("Routing the call to {}", route.name);
+        match route.llm.generate(messages).await {
+            Ok(result) => {
+                self.record(route, Some(&result));
+                Ok(result)
+            }
+            Err(e) => {
+                self.record(route, None);
+                Err(e)
+            }
+        }
+    }
+
+    /// Streams are routed like `generate`, but only their calls are accounted.
+    async fn stream(
+        &self,
+        messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        let route = self.route(messages).await?;
+        self.usage
+            .lock()
+            .unwrap()
+            .entry(route.name.clone())
+            .or_default()
+            .calls += 1;
+        route.llm.stream(messages).await
+    }
+
+    /// The options are kept to route on, and forwarded to every route.
+    fn add_options(&mut self, options: CallOptions) {
+        for route in &mut self.routes {
+            route.llm.add_options(options.clone());
+        }
+        self.options.merge_options(options);
+    }
+
+    /// The largest window among the routes, since long prompts are routed to the routes able
+    /// to take them.
+    fn max_context_tokens(&self) -> Option<usize> {
+        self.routes
+            .iter()
+            .filter_map(|route| {
+                route
+                    .llm
+                    .max_context_tokens()
+                    .into_iter()
+                    .chain(route.max_prompt_tokens)
+                    .min()
+            })
+            .max()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::FakeLLM;
+
+    /// Answers with its name.
+    fn named(name: &'static str) -> FakeLLM {
+        FakeLLM::new(move |_| {
+            Ok(GenerateResult {
+                generation: name.to_string(),
+                tokens: Some(TokenUsage::new(500, 500)),
+                ..Default::default()
+            })
+        })
+    }
+
+    fn router() -> ModelRouter {
+        ModelRouter::new()
+            .with_route(
+                ModelRoute::new("large", named("large"))
+                    .with_description("planning")
+                    .with_cost_per_1k_tokens(2.0),
+            )
+            .with_route(
+                ModelRoute::new("small", named("small"))
+                    .with_description("summarization")
+                    .with_cost_per_1k_tokens(0.1)
+                    .with_max_prompt_tokens(100)
+                    .with_json(false),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_routes_to_cheapest_capable_model() {
+        let router = router();
+
+        assert_eq!(router.invoke("Summarize: hi").await.unwrap(), "small");
+        assert_eq!(router.invoke(&"word ".repeat(200)).await.unwrap(), "large");
+        assert_eq!(router.invoke("Reply in JSON").await.unwrap(), "large");
+
+        let usage = router.usage();
+        assert_eq!(usage["small"].calls, 1);
+        assert_eq!(usage["large"].calls, 2);
+        assert_eq!(usage["large"].tokens.total_tokens, 2000);
+        assert_eq!(usage["large"].cost, 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_classifier_picks_among_candidates() {
+        let router = router().with_classifier(named("large"));
+        assert_eq!(router.invoke("Plan a trip").await.unwrap(), "large");
+
+        let router = router.with_classifier(named("unknown"));
+        assert_eq!(router.invoke("Plan a trip").await.unwrap(), "small");
+    }
+}
@@ -0,0 +1,253 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::schemas::{Message, StreamData};
+
+use super::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage};
+
+const SELF_CHECK_PROMPT: &str = "Is the answer above correct and complete? Reply with YES or NO \
+only.";
+
+const VERIFY_PROMPT: &str = "Above is a draft answer. If it is correct, reply with it \
+unchanged. Otherwise reply with the corrected answer only.";
+
+type ConfidenceCheck = Arc<dyn Fn(&str) -> Option<f64> + Send + Sync>;
+
+/// How often drafts were accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpeculativeStats {
+    pub drafts: u64,
+    /// Drafts the verifier had to review.
+    pub escalations: u64,
+}
+
+/// Drafts with a small model and only asks a large one when the draft looks unreliable, which
+/// makes routine agent steps cheap.
+///
+/// The draft's confidence is the lowest of the available signals: the token log
+/// probabilities, the custom check (e.g. whether the output parser accepts it) and, if
+/// enabled, the drafter's own verdict on its answer. A draft below `min_confidence`, cut off
+/// by the token limit or without any signal is sent to the verifier, which returns it
+/// unchanged or edited.
+pub struct SpeculativeLLM {
+    drafter: Box<dyn LLM>,
+    verifier: Box<dyn LLM>,
+    min_confidence: f64,
+    checks: Vec<ConfidenceCheck>,
+    self_check: bool,
+    stats: Arc<Mutex<SpeculativeStats>>,
+}
+
+impl Clone for SpeculativeLLM {
+    fn clone(&self) -> Self {
+        Self {
+            drafter: self.drafter.clone_box(),
+            verifier: self.verifier.clone_box(),
+            min_confidence: self.min_confidence,
+            checks: self.checks.clone(),
+            self_check: self.self_check,
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl SpeculativeLLM {
+    pub fn new<D: Into<Box<dyn LLM>>, V: Into<Box<dyn LLM>>>(drafter: D, verifier: V) -> Self {
+        Self {
+            drafter: drafter.into(),
+            verifier: verifier.into(),
+            min_confidence: 0.8,
+            checks: Vec::new(),
+            self_check: false,
+            stats: Arc::default(),
+        }
+    }
+
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+
+    /// A confidence in `0.0..=1.0` for a draft, or `None` if the check has no opinion.
+    pub fn with_check<F: Fn(&str) -> Option<f64> + Send + Sync + 'static>(
+        mut self,
+        check: F,
+    ) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    /// Asks the drafter whether its answer is correct, at the cost of one more small call.
+    pub fn with_self_check(mut self, self_check: bool) -> Self {
+        self.self_check = self_check;
+        self
+    }
+
+    /// Shared between clones.
+    pub fn stats(&self) -> SpeculativeStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// The draft's confidence, and the tokens of the self-check if there was one.
+    async fn confidence(
+        &self,
+        messages: &[Message],
+        draft: &GenerateResult,
+    ) -> Result<(Option<f64>, Option<TokenUsage>), LLMError> {
+        let mut signals = self
+            .checks
+            .iter()
+            .filter_map(|check| check(&draft.generation))
+            .collect::<Vec<_>>();
+        signals.extend(draft.confidence());
+        let mut tokens = None;
+        if self.self_check {
+            let mut followup = messages.to_vec();
+            followup.push(Message::new_ai_message(&draft.generation));
+            followup.push(Message::new_human_message(SELF_CHECK_PROMPT));
+            let verdict = self.drafter.generate(&followup).await?;
+            signals.push(if verdict.generation.to_uppercase().contains("YES") {
+                1.0
+            } else {
+                0.0
+            });
+            tokens = verdict.tokens;
+        }
+        Ok((signals.into_iter().reduce(f64::min), tokens))
+    }
+}
+
+#[async_trait]
+impl LLM for SpeculativeLLM {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        let mut draft = self.drafter.generate(messages).await?;
+        let (confidence, check_tokens) = self.confidence(messages, &draft).await?;
+        draft.tokens = add_tokens(draft.tokens, check_tokens);
+        let accepted = !draft.is_truncated()
+            && confidence.is_some_and(|confidence| confidence >= self.min_confidence);
+        {
+            let mut stats = self.stats.lock().unwrap();
+            stats.drafts += 1;
+            stats.escalations += u64::from(!accepted);
+        }
+        if accepted {
+            return Ok(draft);
+        }
+
+        log::debug!("Escalating draft with confidence {:?}", confidence);
+        let mut review = messages.to_vec();
+        review.push(Message::new_ai_message(&draft.generation));
+        review.push(Message::new_human_message(VERIFY_PROMPT));
+        let mut result = self.verifier.generate(&review).await?;
+        result.tokens = add_tokens(draft.tokens, result.tokens);
+        Ok(result)
+    }
+
+    /// Drafts cannot be judged before they are complete, so the answer arrives as a single
+    /// chunk.
+    async fn stream(
+        &self,
+        messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        let result = self.generate(messages).await?;
+        let data = StreamData::new(json!(result), result.tokens.clone(), &result.generation);
+        Ok(Box::pin(futures::stream::once(async { Ok(data) })))
+    }
+
+    fn add_options(&mut self, options: CallOptions) {
+        self.drafter.add_options(options.clone());
+        self.verifier.add_options(options);
+    }
+
+    /// The smaller of the two windows, since either model may see the prompt.
+    fn max_context_tokens(&self) -> Option<usize> {
+        match (
+            self.drafter.max_context_tokens(),
+            self.verifier.max_context_tokens(),
+        ) {
+            (Some(drafter), Some(verifier)) => Some(drafter.min(verifier)),
+            (drafter, verifier) => drafter.or(verifier),
+        }
+    }
+}
+
+fn add_tokens(a: Option<TokenUsage>, b: Option<TokenUsage>) -> Option<TokenUsage> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.sum(&b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{language_models::TokenLogprob, test_utils::FakeLLM};
+
+    /// Answers `answer` with the given log probability, and verifies when asked to.
+    fn fixed(answer: &'static str, logprob: Option<f32>) -> FakeLLM {
+        FakeLLM::new(move |messages| {
+            let last = &messages.last().unwrap().content;
+            let generation = if last == VERIFY_PROMPT {
+                format!("{} (verified)", answer)
+            } else {
+                answer.to_string()
+            };
+            Ok(GenerateResult {
+                generation,
+                logprobs: logprob.map(|logprob| {
+                    vec![TokenLogprob {
+                        token: answer.to_string(),
+                        logprob,
+                        top_logprobs: None,
+                    }]
+                }),
+                tokens: Some(TokenUsage::new(10, 1)),
+                ..Default::default()
+            })
+        })
+    }
+
+    fn speculative(logprob: Option<f32>) -> SpeculativeLLM {
+        SpeculativeLLM::new(fixed("draft", logprob), fixed("large", None))
+    }
+
+    #[tokio::test]
+    async fn test_escalates_low_confidence_drafts() {
+        assert_eq!(speculative(Some(-0.01)).invoke("q").await.unwrap(), "draft");
+        assert_eq!(
+            speculative(Some(-2.0)).invoke("q").await.unwrap(),
+            "large (verified)"
+        );
+
+        let llm = speculative(None);
+        assert_eq!(llm.invoke("q").await.unwrap(), "large (verified)");
+        let llm = llm.with_check(|draft| Some(if draft == "draft" { 1.0 } else { 0.0 }));
+        assert_eq!(llm.invoke("q").await.unwrap(), "draft");
+        assert_eq!(
+            llm.stats(),
+            SpeculativeStats {
+                drafts: 2,
+                escalations: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_counts_self_check_tokens() {
+        let llm = speculative(Some(-0.01));
+        let result = llm.generate(&[Message::new_human_message("q")]).await;
+        assert_eq!(result.unwrap().tokens.unwrap().total_tokens, 11);
+
+        // Draft, self-check and verification.
+        let llm = speculative(Some(-0.01)).with_self_check(true);
+        let result = llm.generate(&[Message::new_human_message("q")]).await;
+        assert_eq!(result.unwrap().tokens.unwrap().total_tokens, 33);
+    }
+}
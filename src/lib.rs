@@ -1,17 +1,138 @@
 #![allow(dead_code)]
+#[cfg(feature = "agents")]
 pub mod agent;
+pub mod batch;
 pub mod chain;
+#[cfg(feature = "loaders")]
 pub mod document_loaders;
 pub mod embedding;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod env;
+pub mod experiment;
+pub mod feedback;
 pub mod language_models;
 pub mod llm;
 pub mod memory;
 pub mod output_parsers;
 pub mod prompt;
+pub mod retention;
+pub mod scaffold;
 pub mod schemas;
 pub mod semantic_router;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod session;
+#[cfg(feature = "text-splitter")]
 pub mod text_splitter;
 pub mod tools;
+#[cfg(test)]
+mod test_utils;
+#[cfg(feature = "vectorstores")]
 pub mod vectorstore;
 
 pub use url;
+
+/// Every test here only compiles when its feature is enabled, so running the suite with
+/// `--no-default-features` checks that the LLM and chain core stands on its own, and running
+/// it with the defaults checks that each optional module is still wired up.
+#[cfg(test)]
+mod feature_gates {
+    fn assert_available<T: ?Sized>() {
+        assert!(!std::any::type_name::<T>().is_empty());
+    }
+
+    #[test]
+    fn core_is_always_available() {
+        assert_available::<crate::chain::LLMChain>();
+        assert_available::<crate::prompt::PromptTemplate>();
+        assert_available::<crate::memory::SimpleMemory>();
+        assert_available::<crate::llm::deepseek::Deepseek>();
+        assert_available::<crate::llm::claude::Claude>();
+        assert_available::<dyn crate::tools::Tool>();
+        assert_available::<crate::batch::LocalBatchQueue>();
+    }
+
+    #[cfg(feature = "openai")]
+    #[test]
+    fn openai_feature() {
+        assert_available::<crate::llm::openai::OpenAI<crate::llm::openai::OpenAIConfig>>();
+        assert_available::<
+            crate::embedding::openai::OpenAiEmbedder<crate::llm::openai::OpenAIConfig>,
+        >();
+        assert_available::<crate::batch::OpenAIBatch<crate::llm::openai::OpenAIConfig>>();
+    }
+
+    #[cfg(feature = "agents")]
+    #[test]
+    fn agents_feature() {
+        assert_available::<crate::agent::ExecutorConfig>();
+    }
+
+    #[cfg(feature = "mcp")]
+    #[test]
+    fn mcp_feature() {
+        assert_available::<crate::llm::mcp::McpClient>();
+    }
+
+    #[cfg(feature = "tools-web")]
+    #[test]
+    fn tools_web_feature() {
+        assert_available::<crate::tools::DuckDuckGoSearchResults>();
+    }
+
+    #[cfg(feature = "serve")]
+    #[test]
+    fn serve_feature() {
+        assert_available::<crate::serve::ChatCompletions>();
+    }
+
+    #[cfg(feature = "text-splitter")]
+    #[test]
+    fn text_splitter_feature() {
+        assert_available::<crate::text_splitter::TokenSplitter>();
+    }
+
+    #[cfg(feature = "tokenizers")]
+    #[test]
+    fn tokenizers_feature() {
+        let _ = crate::text_splitter::SplitterTokenizer::from_file::<&str>;
+    }
+
+    #[cfg(feature = "loaders")]
+    #[test]
+    fn loaders_feature() {
+        assert_available::<crate::document_loaders::TextLoader>();
+    }
+
+    #[cfg(feature = "loaders-csv")]
+    #[test]
+    fn loaders_csv_feature() {
+        assert_available::<crate::document_loaders::CsvLoader<std::io::Empty>>();
+    }
+
+    #[cfg(feature = "loaders-html")]
+    #[test]
+    fn loaders_html_feature() {
+        assert_available::<crate::document_loaders::HtmlLoader<std::io::Empty>>();
+    }
+
+    #[cfg(feature = "loaders-dir")]
+    #[test]
+    fn loaders_dir_feature() {
+        assert_available::<crate::document_loaders::DirLoaderOptions>();
+    }
+
+    #[cfg(feature = "vectorstores")]
+    #[test]
+    fn vectorstores_feature() {
+        assert_available::<crate::vectorstore::VecStoreOptions<serde_json::Value>>();
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn encryption_feature() {
+        assert_available::<crate::encryption::Cipher>();
+        assert_available::<crate::memory::EncryptedFileMemory>();
+    }
+}
@@ -1,5 +1,9 @@
 use crate::{
-    language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage},
+    env::{self, EnvError},
+    language_models::{
+        context_window, llm::LLM, options::CallOptions, FinishReason, GenerateResult, LLMError,
+        Stopwatch, TokenUsage,
+    },
     llm::AnthropicError,
     schemas::{Message, MessageType, StreamData},
 };
@@ -53,6 +57,18 @@ impl Claude {
         }
     }
 
+    /// Reads the key from `CLAUDE_API_KEY`, or `ANTHROPIC_API_KEY`, and the model from
+    /// `CLAUDE_MODEL` if it is set.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let api_key = env::any_var(&["CLAUDE_API_KEY", "ANTHROPIC_API_KEY"])
+            .ok_or_else(|| EnvError::Missing("CLAUDE_API_KEY".to_string()))?;
+        let claude = Self::new().with_api_key(api_key);
+        Ok(match env::var("CLAUDE_MODEL") {
+            Some(model) => claude.with_model(model),
+            None => claude,
+        })
+    }
+
     pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
         self.model = model.into();
         self
@@ -78,6 +94,7 @@ impl Claude {
         let is_stream = self.options.streaming_func.is_some();
 
         let payload = self.build_payload(messages, is_stream);
+        let stopwatch = Stopwatch::start();
         let res = client
             .post("https://api.anthropic.com/v1/messages")
             .header("x-api-key", &self.api_key)
@@ -117,7 +134,15 @@ impl Claude {
             total_tokens: res.usage.input_tokens + res.usage.output_tokens,
         });
 
-        Ok(GenerateResult { tokens, generation })
+        Ok(GenerateResult {
+            tokens,
+            generation,
+            finish_reason: res.stop_reason.as_deref().map(FinishReason::from_provider),
+            model: Some(res.model),
+            request_id: Some(res.id),
+            ..Default::default()
+        }
+        .with_latency(&stopwatch))
     }
 
     fn build_payload(&self, messages: &[Message], stream: bool) -> Payload {
@@ -150,21 +175,36 @@ impl LLM for Claude {
     async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
         match &self.options.streaming_func {
             Some(func) => {
-                let mut complete_response = String::new();
+                let stopwatch = Stopwatch::start();
+                let mut generate_result = GenerateResult::default();
                 let mut stream = self.stream(messages).await?;
                 while let Some(data) = stream.next().await {
                     match data {
                         Ok(value) => {
+                            match value.value["type"].as_str() {
+                                Some("message_start") => {
+                                    let message = &value.value["message"];
+                                    generate_result.model =
+                                        message["model"].as_str().map(str::to_string);
+                                    generate_result.request_id =
+                                        message["id"].as_str().map(str::to_string);
+                                }
+                                Some("message_delta") => {
+                                    generate_result.finish_reason = value.value["delta"]
+                                        ["stop_reason"]
+                                        .as_str()
+                                        .map(FinishReason::from_provider);
+                                }
+                                _ => {}
+                            }
                             let mut func = func.lock().await;
-                            complete_response.push_str(&value.content);
+                            generate_result.generation.push_str(&value.content);
                             let _ = func(value.content).await;
                         }
                         Err(e) => return Err(e),
                     }
                 }
-                let mut generate_result = GenerateResult::default();
-                generate_result.generation = complete_response;
-                Ok(generate_result)
+                Ok(generate_result.with_latency(&stopwatch))
             }
             None => self.generate(messages).await,
         }
@@ -212,6 +252,10 @@ impl LLM for Claude {
     fn add_options(&mut self, options: CallOptions) {
         self.options.merge_options(options)
     }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        context_window(&self.model)
+    }
 }
 
 fn parse_sse_to_json(sse_data: &str) -> Result<Value, LLMError> {
@@ -1,5 +1,9 @@
 use crate::{
-    language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage},
+    env::{self, EnvError},
+    language_models::{
+        context_window, llm::LLM, logprobs_from_openai_value, options::CallOptions, FinishReason,
+        GenerateResult, LLMError, Stopwatch, TokenUsage,
+    },
     llm::DeepseekError,
     schemas::{Message, StreamData},
 };
@@ -53,6 +57,19 @@ impl Deepseek {
         }
     }
 
+    /// Reads the key from `DEEPSEEK_API_KEY`, and the model and endpoint from `DEEPSEEK_MODEL`
+    /// and `DEEPSEEK_BASE_URL` if they are set.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let mut deepseek = Self::new().with_api_key(env::require("DEEPSEEK_API_KEY")?);
+        if let Some(model) = env::var("DEEPSEEK_MODEL") {
+            deepseek = deepseek.with_model(model);
+        }
+        if let Some(base_url) = env::var("DEEPSEEK_BASE_URL") {
+            deepseek = deepseek.with_base_url(base_url);
+        }
+        Ok(deepseek)
+    }
+
     pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
         self.model = model.into();
         self
@@ -88,6 +105,7 @@ impl Deepseek {
         let is_stream = self.options.streaming_func.is_some();
 
         let payload = self.build_payload(messages, is_stream);
+        let stopwatch = Stopwatch::start();
         let res = client
             .post(&format!("{}/v1/chat/completions", self.base_url))
             .header("Authorization", format!("Bearer {}", self.api_key))
@@ -128,11 +146,12 @@ impl Deepseek {
         let mut generation = choice
             .map(|c| c.message.content.clone())
             .unwrap_or_default();
+        let reasoning = choice.and_then(|c| c.message.reasoning_content.clone());
 
         // If include_reasoning is enabled and the model is deepseek-reasoner,
         // append the reasoning content to the generation if available
         if self.include_reasoning && self.model == DeepseekModel::DeepseekReasoner.to_string() {
-            if let Some(reasoning) = choice.and_then(|c| c.message.reasoning_content.clone()) {
+            if let Some(reasoning) = &reasoning {
                 generation = format!("Reasoning:\n{}\n\nAnswer:\n{}", reasoning, generation);
             }
         }
@@ -143,7 +162,21 @@ impl Deepseek {
             total_tokens: res.usage.total_tokens,
         });
 
-        Ok(GenerateResult { tokens, generation })
+        Ok(GenerateResult {
+            tokens,
+            generation,
+            finish_reason: choice
+                .and_then(|c| c.finish_reason.as_deref())
+                .map(FinishReason::from_provider),
+            logprobs: choice
+                .and_then(|c| c.logprobs.as_ref())
+                .and_then(logprobs_from_openai_value),
+            model: Some(res.model.clone()),
+            request_id: Some(res.id.clone()),
+            reasoning,
+            ..Default::default()
+        }
+        .with_latency(&stopwatch))
     }
 
     fn build_payload(&self, messages: &[Message], stream: bool) -> Payload {
@@ -167,6 +200,8 @@ impl Deepseek {
             frequency_penalty: None,
             presence_penalty: None,
             stop: self.options.stop_words.clone(),
+            logprobs: self.options.logprobs,
+            top_logprobs: self.options.top_logprobs,
             response_format,
         };
 
@@ -217,21 +252,24 @@ impl LLM for Deepseek {
     async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
         match &self.options.streaming_func {
             Some(func) => {
-                let mut complete_response = String::new();
+                let stopwatch = Stopwatch::start();
+                let mut generate_result = GenerateResult::default();
                 let mut stream = self.stream(messages).await?;
                 while let Some(data) = stream.next().await {
                     match data {
                         Ok(value) => {
+                            generate_result.update_from_openai_value(&value.value);
+                            if value.content.is_empty() {
+                                continue;
+                            }
                             let mut func = func.lock().await;
-                            complete_response.push_str(&value.content);
+                            generate_result.generation.push_str(&value.content);
                             let _ = func(value.content).await;
                         }
                         Err(e) => return Err(e),
                     }
                 }
-                let mut generate_result = GenerateResult::default();
-                generate_result.generation = complete_response;
-                Ok(generate_result)
+                Ok(generate_result.with_latency(&stopwatch))
             }
             None => self.generate(messages).await,
         }
@@ -263,7 +301,7 @@ impl LLM for Deepseek {
                         Ok(bytes) => {
                             let chunks = Self::parse_sse_chunk(&bytes)?;
 
-                            for chunk in chunks {
+                            for chunk in &chunks {
                                 if let Some(choices) =
                                     chunk.get("choices").and_then(|c| c.as_array())
                                 {
@@ -304,7 +342,8 @@ impl LLM for Deepseek {
                                                             chunk.clone(),
                                                             usage,
                                                             format!("Reasoning: {}", reasoning),
-                                                        ));
+                                                        )
+                                                        .with_reasoning(reasoning));
                                                     }
                                                 }
                                             }
@@ -349,6 +388,15 @@ impl LLM for Deepseek {
                                 }
                             }
 
+                            // Keep the final chunk so consumers can read the finish reason.
+                            if let Some(chunk) = chunks.iter().find(|chunk| {
+                                chunk
+                                    .pointer("/choices/0/finish_reason")
+                                    .is_some_and(Value::is_string)
+                            }) {
+                                return Ok(StreamData::new(chunk.clone(), None, ""));
+                            }
+
                             // If we didn't return within the loop, return an empty stream data
                             Ok(StreamData::new(Value::Null, None, ""))
                         }
@@ -358,7 +406,12 @@ impl LLM for Deepseek {
             })
             .filter_map(|result| async move {
                 match result {
-                    Ok(data) if !data.content.is_empty() => Some(Ok(data)),
+                    Ok(data)
+                        if !data.content.is_empty()
+                            || data.value.pointer("/choices/0/finish_reason").is_some() =>
+                    {
+                        Some(Ok(data))
+                    }
                     Ok(_) => None,
                     Err(e) => Some(Err(e)),
                 }
@@ -370,6 +423,10 @@ impl LLM for Deepseek {
     fn add_options(&mut self, options: CallOptions) {
         self.options = options;
     }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        context_window(&self.model)
+    }
 }
 
 #[cfg(test)]
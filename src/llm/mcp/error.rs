@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum McpError {
+    #[error("MCP JSON-RPC error {code}: {message}")]
+    RpcError { code: i64, message: String },
+
+    #[error("MCP server closed the connection before answering request {0}")]
+    ConnectionClosed(u64),
+
+    #[error("MCP server returned an unexpected result: {0}")]
+    UnexpectedResult(String),
+
+    /// The server did not answer within the client's timeout.
+    #[error("MCP server did not answer within {0:?}")]
+    Timeout(Duration),
+
+    #[error("MCP roots must be absolute paths: {0}")]
+    InvalidRoot(String),
+}
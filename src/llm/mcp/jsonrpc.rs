@@ -0,0 +1,153 @@
+use std::io;
+
+use serde_json::Value;
+use tokio_util::bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder, LinesCodec};
+
+use super::map_codec_error;
+
+const HEADER_END: &[u8] = b"\r\n\r\n";
+
+/// How JSON-RPC messages are delimited on the wire.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum McpFraming {
+    /// One message per line, as in the MCP stdio transport.
+    #[default]
+    Lines,
+    /// LSP-style `Content-Length` headers followed by the message body.
+    ContentLength,
+}
+
+/// Encodes and decodes JSON-RPC messages with the given framing.
+pub(crate) struct JsonRpcCodec {
+    framing: McpFraming,
+    lines: LinesCodec,
+}
+
+impl JsonRpcCodec {
+    pub(crate) fn new(framing: McpFraming) -> Self {
+        Self {
+            framing,
+            lines: LinesCodec::new(),
+        }
+    }
+
+    fn decode_content_length(&mut self, buf: &mut BytesMut) -> Result<Option<Value>, io::Error> {
+        let Some(header_len) = buf
+            .windows(HEADER_END.len())
+            .position(|window| window == HEADER_END)
+        else {
+            return Ok(None);
+        };
+        let headers = std::str::from_utf8(&buf[..header_len])
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let content_length = headers
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-length"))
+            .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Missing Content-Length header")
+            })?;
+
+        let body_start = header_len + HEADER_END.len();
+        if buf.len() < body_start + content_length {
+            buf.reserve(body_start + content_length - buf.len());
+            return Ok(None);
+        }
+        buf.advance(body_start);
+        let body = buf.split_to(content_length);
+        Ok(Some(serde_json::from_slice(&body)?))
+    }
+}
+
+impl Decoder for JsonRpcCodec {
+    type Item = Value;
+    type Error = io::Error;
+
+    fn decode(&mut self, buf: &mut BytesMut) -> Result<Option<Value>, io::Error> {
+        match self.framing {
+            McpFraming::ContentLength => self.decode_content_length(buf),
+            McpFraming::Lines => loop {
+                match self.lines.decode(buf).map_err(map_codec_error)? {
+                    Some(line) if line.trim().is_empty() => continue,
+                    Some(line) => return Ok(Some(serde_json::from_str(&line)?)),
+                    None => return Ok(None),
+                }
+            },
+        }
+    }
+
+    fn decode_eof(&mut self, buf: &mut BytesMut) -> Result<Option<Value>, io::Error> {
+        match self.framing {
+            McpFraming::ContentLength => self.decode(buf),
+            McpFraming::Lines => match self.lines.decode_eof(buf).map_err(map_codec_error)? {
+                Some(line) if !line.trim().is_empty() => Ok(Some(serde_json::from_str(&line)?)),
+                _ => Ok(None),
+            },
+        }
+    }
+}
+
+impl Encoder<Value> for JsonRpcCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, message: Value, buf: &mut BytesMut) -> Result<(), io::Error> {
+        let body = serde_json::to_vec(&message)?;
+        match self.framing {
+            McpFraming::Lines => {
+                buf.reserve(body.len() + 1);
+                buf.put_slice(&body);
+                buf.put_u8(b'\n');
+            }
+            McpFraming::ContentLength => {
+                let header = format!("Content-Length: {}\r\n\r\n", body.len());
+                buf.reserve(header.len() + body.len());
+                buf.put_slice(header.as_bytes());
+                buf.put_slice(&body);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_content_length_round_trip() {
+        let mut codec = JsonRpcCodec::new(McpFraming::ContentLength);
+        let mut buf = BytesMut::new();
+        codec
+            .encode(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "ping"}),
+                &mut buf,
+            )
+            .unwrap();
+        codec
+            .encode(
+                json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}),
+                &mut buf,
+            )
+            .unwrap();
+
+        // A partial message waits for the rest of its body.
+        let mut partial = buf.split_to(buf.len() - 5);
+        assert_eq!(codec.decode(&mut partial).unwrap().unwrap()["id"], 1);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        partial.unsplit(buf);
+        assert_eq!(codec.decode(&mut partial).unwrap().unwrap()["id"], 2);
+        assert!(partial.is_empty());
+    }
+
+    #[test]
+    fn test_lines_skip_blank_lines() {
+        let mut codec = JsonRpcCodec::new(McpFraming::Lines);
+        let mut buf = BytesMut::from(&b"\n{\"id\":1}\n"[..]);
+
+        assert_eq!(codec.decode(&mut buf).unwrap().unwrap()["id"], 1);
+    }
+}
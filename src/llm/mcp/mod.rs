@@ -1,24 +1,87 @@
 use async_trait::async_trait;
-use futures::{Sink, Stream, StreamExt, SinkExt, TryStreamExt};
-use serde_json;
+use futures::{Sink, SinkExt, Stream, StreamExt};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::future::Future;
 use std::io;
 use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio::process::Command;
-use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec, LinesCodecError};
+use tokio::sync::Semaphore;
+use tokio_util::codec::{FramedRead, FramedWrite, LinesCodecError};
 
-use crate::language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError};
-use crate::schemas::{messages::Message, StreamData};
+use crate::env::{self, EnvError};
+use crate::language_models::{
+    llm::LLM, options::CallOptions, FinishReason, GenerateResult, LLMError,
+};
+use crate::schemas::{messages::Message, MessageType, StreamData};
+
+mod error;
+pub use error::*;
+
+mod roots;
+pub use roots::*;
+
+mod jsonrpc;
+use jsonrpc::JsonRpcCodec;
+pub use jsonrpc::McpFraming;
+
+pub mod server;
+pub use server::McpServer;
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
 
 #[derive(Clone, Debug)]
 pub enum McpTransport {
+    /// A plaintext TCP connection to `host:port`.
     Stream(String),
+    /// A TLS connection to `host:port`. The certificate is checked against the host.
+    #[cfg(feature = "mcp-tls")]
+    Tls {
+        addr: String,
+        config: Arc<tokio_rustls::rustls::ClientConfig>,
+    },
+    /// A Unix domain socket, e.g. of a local daemon.
+    #[cfg(unix)]
+    Unix(std::path::PathBuf),
+    /// A local server spawned for every connection, talking over its stdin and stdout. The
+    /// process is killed when the connection is dropped.
+    Stdio {
+        command: String,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    },
 }
 
+/// An LLM served over the Model Context Protocol, e.g. by an MCP gateway.
+///
+/// Every call sends one JSON-RPC 2.0 request, `sampling/createMessage` by default, on a
+/// connection that performed the `initialize` handshake. The request is matched to its
+/// response by id, notifications in between are skipped, and error objects become
+/// [`McpError::RpcError`].
+///
+/// Connections are opened per call unless pooling is enabled with
+/// [`Self::with_max_idle_connections`]. Clones share the pool and the concurrency limit.
+///
+/// Calls wait for the server as long as it takes unless a timeout is set with
+/// [`Self::with_timeout`]. Dropping a call's future cancels it.
 #[derive(Clone)]
 pub struct McpClient {
     transport: McpTransport,
     options: CallOptions,
+    framing: McpFraming,
+    method: String,
+    initialize: bool,
+    next_id: Arc<AtomicU64>,
+    pool: Arc<ConnectionPool>,
+    limit: Option<Arc<Semaphore>>,
+    roots: Arc<RwLock<Vec<McpRoot>>>,
+    timeout: Option<Duration>,
 }
 
 impl McpClient {
@@ -26,17 +89,359 @@ impl McpClient {
         Self {
             transport,
             options: CallOptions::default(),
+            framing: McpFraming::default(),
+            method: "sampling/createMessage".to_string(),
+            initialize: true,
+            next_id: Arc::new(AtomicU64::new(1)),
+            pool: Arc::default(),
+            limit: None,
+            roots: Arc::default(),
+            timeout: None,
         }
     }
 
+    /// Talks to a server started with `command` and `args`, e.g.
+    /// `npx @modelcontextprotocol/server-filesystem`.
+    pub fn connect_stdio<S: Into<String>>(
+        command: S,
+        args: Vec<String>,
+        env: HashMap<String, String>,
+    ) -> Self {
+        Self::new(McpTransport::Stdio {
+            command: command.into(),
+            args,
+            env,
+        })
+    }
+
+    /// Connects to the server given by the first set of `MCP_SERVER_COMMAND`, with
+    /// whitespace-separated `MCP_SERVER_ARGS`, `MCP_SERVER_SOCKET` on Unix and
+    /// `MCP_SERVER_ADDR`, the latter as `host:port`. `MCP_METHOD`, `MCP_MAX_IDLE_CONNECTIONS`,
+    /// `MCP_MAX_CONCURRENT_CALLS` and `MCP_TIMEOUT_SECS` are applied when set.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let transport = match (
+            env::var("MCP_SERVER_COMMAND"),
+            env::var("MCP_SERVER_SOCKET"),
+        ) {
+            (Some(command), _) => McpTransport::Stdio {
+                command,
+                args: env::var("MCP_SERVER_ARGS")
+                    .map(|args| args.split_whitespace().map(String::from).collect())
+                    .unwrap_or_default(),
+                env: HashMap::new(),
+            },
+            #[cfg(unix)]
+            (None, Some(socket)) => McpTransport::Unix(socket.into()),
+            _ => McpTransport::Stream(env::require("MCP_SERVER_ADDR")?),
+        };
+        let mut client = Self::new(transport);
+        if let Some(method) = env::var("MCP_METHOD") {
+            client = client.with_method(method);
+        }
+        if let Some(max_idle) = env::parse("MCP_MAX_IDLE_CONNECTIONS")? {
+            client = client.with_max_idle_connections(max_idle);
+        }
+        if let Some(max_concurrent_calls) = env::parse("MCP_MAX_CONCURRENT_CALLS")? {
+            client = client.with_max_concurrent_calls(max_concurrent_calls);
+        }
+        if let Some(timeout) = env::parse("MCP_TIMEOUT_SECS")? {
+            client = client.with_timeout(Duration::from_secs(timeout));
+        }
+        Ok(client)
+    }
+
     pub fn with_options(mut self, options: CallOptions) -> Self {
         self.options = options;
         self
     }
+
+    /// Fails calls with [`McpError::Timeout`] when the server has not answered within
+    /// `timeout`, counting from the call, queueing and connecting included. The connection of a
+    /// timed out call is closed rather than pooled, since its answer may still arrive.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    async fn timed<T, F>(&self, call: F) -> Result<T, LLMError>
+    where
+        F: Future<Output = Result<T, LLMError>>,
+    {
+        match self.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, call)
+                .await
+                .map_err(|_| McpError::Timeout(timeout))?,
+            None => call.await,
+        }
+    }
+
+    pub fn with_framing(mut self, framing: McpFraming) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// JSON-RPC method the messages are sent with.
+    pub fn with_method<S: Into<String>>(mut self, method: S) -> Self {
+        self.method = method.into();
+        self
+    }
+
+    /// Keeps up to `max_idle` connections open after their call to serve later ones, so a
+    /// stdio server stays running and the handshake is done once per connection. A pooled
+    /// connection the server closed is replaced transparently.
+    pub fn with_max_idle_connections(mut self, max_idle: usize) -> Self {
+        self.pool = Arc::new(ConnectionPool {
+            idle: Mutex::default(),
+            max_idle,
+        });
+        self
+    }
+
+    /// Bounds the calls in flight to this server. Further calls wait their turn in the order
+    /// they were made.
+    pub fn with_max_concurrent_calls(mut self, max_concurrent_calls: usize) -> Self {
+        self.limit = Some(Arc::new(Semaphore::new(max_concurrent_calls)));
+        self
+    }
+
+    /// Filesystem roots declared to the server, which asks for them with `roots/list`.
+    pub fn with_roots(self, roots: Vec<McpRoot>) -> Self {
+        *self.roots.write().unwrap() = roots;
+        self
+    }
+
+    pub fn roots(&self) -> Vec<McpRoot> {
+        self.roots.read().unwrap().clone()
+    }
+
+    /// Replaces the roots and tells the servers behind pooled connections that they changed.
+    /// Connections opened later see the new roots. Shared between clones.
+    pub async fn set_roots(&self, roots: Vec<McpRoot>) {
+        *self.roots.write().unwrap() = roots;
+
+        let connections = std::mem::take(&mut *self.pool.idle.lock().unwrap());
+        for mut connection in connections {
+            match connection
+                .notify("notifications/roots/list_changed", json!({}))
+                .await
+            {
+                Ok(()) => self.pool.idle.lock().unwrap().push(connection),
+                Err(e) => log::debug!("Dropping MCP connection: {}", e),
+            }
+        }
+    }
+
+    /// Whether to perform the MCP `initialize` handshake. Plain JSON-RPC gateways may not
+    /// support it.
+    pub fn with_initialize(mut self, initialize: bool) -> Self {
+        self.initialize = initialize;
+        self
+    }
+
+    fn create_message_params(&self, messages: &[Message]) -> Value {
+        let system_prompt = messages
+            .iter()
+            .filter(|m| m.message_type == MessageType::SystemMessage)
+            .map(|m| m.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let messages = messages
+            .iter()
+            .filter(|m| m.message_type != MessageType::SystemMessage)
+            .map(|m| {
+                let role = match m.message_type {
+                    MessageType::AIMessage => "assistant",
+                    _ => "user",
+                };
+                json!({ "role": role, "content": { "type": "text", "text": m.content } })
+            })
+            .collect::<Vec<_>>();
+
+        let mut params = json!({
+            "messages": messages,
+            "maxTokens": self.options.max_tokens.unwrap_or(1024),
+        });
+        if !system_prompt.is_empty() {
+            params["systemPrompt"] = json!(system_prompt);
+        }
+        if let Some(temperature) = self.options.temperature {
+            params["temperature"] = json!(temperature);
+        }
+        if let Some(stop_words) = &self.options.stop_words {
+            params["stopSequences"] = json!(stop_words);
+        }
+        params
+    }
+
+    /// Opens a connection, performing the handshake if enabled.
+    async fn connect(&self) -> Result<Connection, LLMError> {
+        let (sink, stream) = create_mcp_stream_sink(&self.transport, self.framing).await?;
+        let mut connection = Connection {
+            sink,
+            stream,
+            next_id: self.next_id.clone(),
+            roots: self.roots.clone(),
+        };
+
+        if self.initialize {
+            connection
+                .request(
+                    "initialize",
+                    json!({
+                        "protocolVersion": PROTOCOL_VERSION,
+                        "capabilities": { "roots": { "listChanged": true } },
+                        "clientInfo": {
+                            "name": "langchain-rust",
+                            "version": env!("CARGO_PKG_VERSION"),
+                        },
+                    }),
+                )
+                .await?;
+            connection
+                .notify("notifications/initialized", json!({}))
+                .await?;
+        }
+        Ok(connection)
+    }
+
+    /// Checks that the server is reachable and answers requests.
+    pub async fn ping(&self) -> Result<(), LLMError> {
+        self.request("ping", json!({})).await?;
+        Ok(())
+    }
+
+    async fn call(&self, messages: &[Message]) -> Result<Value, LLMError> {
+        self.request(&self.method, self.create_message_params(messages))
+            .await
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, LLMError> {
+        self.timed(self.send(method, params)).await
+    }
+
+    /// Sends a request on a pooled connection, or a new one, within the concurrency limit.
+    async fn send(&self, method: &str, params: Value) -> Result<Value, LLMError> {
+        let _permit = match &self.limit {
+            Some(limit) => Some(
+                limit
+                    .acquire()
+                    .await
+                    .map_err(|e| LLMError::OtherError(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        if let Some(mut connection) = self.pool.take() {
+            match connection.request(method, params.clone()).await {
+                Err(
+                    e @ (LLMError::IoError(_) | LLMError::McpError(McpError::ConnectionClosed(_))),
+                ) => {
+                    log::debug!("Pooled MCP connection is gone, reconnecting: {}", e);
+                }
+                result => {
+                    self.pool.put(connection, &result);
+                    return result;
+                }
+            }
+        }
+        let mut connection = self.connect().await?;
+        let result = connection.request(method, params).await;
+        self.pool.put(connection, &result);
+        result
+    }
 }
 
-type McpStream = Pin<Box<dyn Stream<Item = Result<String, io::Error>> + Unpin + Send>>;
-type McpSink = Pin<Box<dyn Sink<String, Error = io::Error> + Unpin + Send>>;
+#[derive(Default)]
+struct ConnectionPool {
+    idle: Mutex<Vec<Connection>>,
+    max_idle: usize,
+}
+
+impl ConnectionPool {
+    fn take(&self) -> Option<Connection> {
+        self.idle.lock().unwrap().pop()
+    }
+
+    /// Keeps `connection` if there is room and the server is still talking over it.
+    fn put(&self, connection: Connection, result: &Result<Value, LLMError>) {
+        let usable = matches!(
+            result,
+            Ok(_) | Err(LLMError::McpError(McpError::RpcError { .. }))
+        );
+        let mut idle = self.idle.lock().unwrap();
+        if usable && idle.len() < self.max_idle {
+            idle.push(connection);
+        }
+    }
+}
+
+type McpStream = Pin<Box<dyn Stream<Item = Result<Value, io::Error>> + Unpin + Send>>;
+type McpSink = Pin<Box<dyn Sink<Value, Error = io::Error> + Unpin + Send>>;
+
+struct Connection {
+    sink: McpSink,
+    stream: McpStream,
+    next_id: Arc<AtomicU64>,
+    roots: Arc<RwLock<Vec<McpRoot>>>,
+}
+
+impl Connection {
+    async fn request(&mut self, method: &str, params: Value) -> Result<Value, LLMError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.sink
+            .send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await?;
+
+        while let Some(message) = self.stream.next().await {
+            let message = message?;
+            if let (Some(method), Some(request_id)) = (
+                message.get("method").and_then(Value::as_str),
+                message.get("id"),
+            ) {
+                let response = self.answer(method, request_id.clone());
+                self.sink.send(response).await?;
+                continue;
+            }
+            if message.get("id").and_then(Value::as_u64) != Some(id) {
+                log::debug!("Skipping MCP message {}", message);
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(McpError::RpcError {
+                    code: error["code"].as_i64().unwrap_or_default(),
+                    message: error["message"].as_str().unwrap_or_default().to_string(),
+                }
+                .into());
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+        Err(McpError::ConnectionClosed(id).into())
+    }
+
+    /// Answers a request the server sent while we wait for a response.
+    fn answer(&self, method: &str, id: Value) -> Value {
+        match method {
+            "roots/list" => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": { "roots": *self.roots.read().unwrap() },
+            }),
+            "ping" => json!({ "jsonrpc": "2.0", "id": id, "result": {} }),
+            _ => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+            }),
+        }
+    }
+
+    async fn notify(&mut self, method: &str, params: Value) -> Result<(), LLMError> {
+        self.sink
+            .send(json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+            .await?;
+        Ok(())
+    }
+}
 
 fn map_codec_error(e: LinesCodecError) -> io::Error {
     match e {
@@ -45,67 +450,256 @@ fn map_codec_error(e: LinesCodecError) -> io::Error {
     }
 }
 
-async fn create_mcp_stream_sink(transport: &McpTransport) -> Result<(McpSink, McpStream), LLMError> {
+fn framed<S>(stream: S, framing: McpFraming) -> (McpSink, McpStream)
+where
+    S: AsyncRead + AsyncWrite + Send + 'static,
+{
+    let (reader, writer) = tokio::io::split(stream);
+    (
+        Box::pin(FramedWrite::new(writer, JsonRpcCodec::new(framing))),
+        Box::pin(FramedRead::new(reader, JsonRpcCodec::new(framing))),
+    )
+}
+
+async fn create_mcp_stream_sink(
+    transport: &McpTransport,
+    framing: McpFraming,
+) -> Result<(McpSink, McpStream), LLMError> {
     match transport {
-        McpTransport::Stream(addr) => {
+        McpTransport::Stream(addr) => Ok(framed(TcpStream::connect(addr).await?, framing)),
+        #[cfg(feature = "mcp-tls")]
+        McpTransport::Tls { addr, config } => {
+            let host = addr
+                .rsplit_once(':')
+                .map_or(addr.as_str(), |(host, _)| host)
+                .trim_start_matches('[')
+                .trim_end_matches(']');
+            let server_name =
+                tokio_rustls::rustls::pki_types::ServerName::try_from(host.to_string()).map_err(
+                    |e| LLMError::OtherError(format!("Invalid TLS server name {}: {}", host, e)),
+                )?;
             let stream = TcpStream::connect(addr).await?;
-            let (reader, writer) = tokio::io::split(stream);
-            let sink = FramedWrite::new(writer, LinesCodec::new());
-            let stream = FramedRead::new(reader, LinesCodec::new());
-
-            let sink = sink.sink_map_err(map_codec_error);
-            let stream = stream.map_err(map_codec_error);
+            let stream = tokio_rustls::TlsConnector::from(config.clone())
+                .connect(server_name, stream)
+                .await?;
+            Ok(framed(stream, framing))
+        }
+        #[cfg(unix)]
+        McpTransport::Unix(path) => Ok(framed(
+            tokio::net::UnixStream::connect(path).await?,
+            framing,
+        )),
+        McpTransport::Stdio { command, args, env } => {
+            let mut child = Command::new(command)
+                .args(args)
+                .envs(env)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .kill_on_drop(true)
+                .spawn()?;
+            let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+                return Err(LLMError::OtherError(format!(
+                    "Could not open the stdio of {}",
+                    command
+                )));
+            };
+            let sink = FramedWrite::new(stdin, JsonRpcCodec::new(framing));
+            // The stream owns the child so the server lives exactly as long as the connection.
+            let stream = FramedRead::new(stdout, JsonRpcCodec::new(framing)).map(move |message| {
+                let _child = &child;
+                message
+            });
 
-            Ok((
-                Box::pin(sink),
-                Box::pin(stream),
-            ))
+            Ok((Box::pin(sink), Box::pin(stream)))
         }
     }
 }
 
+/// Reads a `sampling/createMessage` result, or a bare string from simpler gateways.
+fn generate_result(result: Value) -> Result<GenerateResult, LLMError> {
+    if let Value::String(generation) = result {
+        return Ok(GenerateResult {
+            generation,
+            ..Default::default()
+        });
+    }
+    let generation = match &result["content"] {
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| block["text"].as_str())
+            .collect::<String>(),
+        content => content["text"]
+            .as_str()
+            .ok_or_else(|| McpError::UnexpectedResult(result.to_string()))?
+            .to_string(),
+    };
+    Ok(GenerateResult {
+        generation,
+        finish_reason: result["stopReason"]
+            .as_str()
+            .map(FinishReason::from_provider),
+        model: result["model"].as_str().map(str::to_string),
+        ..Default::default()
+    })
+}
 
 #[async_trait]
 impl LLM for McpClient {
     async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
-        let (mut sink, mut stream) = create_mcp_stream_sink(&self.transport).await?;
-
-        let message_json = serde_json::to_string(messages)?;
-        sink.send(message_json).await?;
-
-        let mut response = String::new();
-        while let Some(line) = stream.next().await {
-            let line = line?;
-            response.push_str(&line);
-        }
-
-        Ok(GenerateResult {
-            generation: response,
-            tokens: None,
-        })
+        generate_result(self.call(messages).await?)
     }
 
+    /// MCP sampling is not streamed, so the whole answer arrives as a single chunk.
     async fn stream(
         &self,
         messages: &[Message],
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
-        let (mut sink, mut stream) = create_mcp_stream_sink(&self.transport).await?;
+        let result = self.generate(messages).await?;
+        let data = StreamData::new(json!(result), result.tokens.clone(), &result.generation);
+        Ok(Box::pin(futures::stream::once(async { Ok(data) })))
+    }
+
+    fn add_options(&mut self, options: CallOptions) {
+        self.options.merge_options(options);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// A server answering the handshake and one sampling request, then exiting.
+    fn scripted_server(response: &str) -> McpClient {
+        let script = format!(
+            r#"read init
+echo '{{"jsonrpc":"2.0","id":1,"result":{{"protocolVersion":"{}","capabilities":{{}}}}}}'
+read initialized
+read request
+echo '{{"jsonrpc":"2.0","method":"notifications/message","params":{{}}}}'
+echo '{}'"#,
+            PROTOCOL_VERSION, response
+        );
+        McpClient::connect_stdio("sh", vec!["-c".to_string(), script], HashMap::new())
+    }
+
+    #[tokio::test]
+    async fn test_stdio_sampling_round_trip() {
+        let client = scripted_server(
+            r#"{"jsonrpc":"2.0","id":2,"result":{"role":"assistant","content":{"type":"text","text":"pong"},"model":"m","stopReason":"endTurn"}}"#,
+        );
+
+        let result = client
+            .generate(&[Message::new_human_message("ping")])
+            .await
+            .unwrap();
+
+        assert_eq!(result.generation, "pong");
+        assert_eq!(result.model.as_deref(), Some("m"));
+        assert_eq!(result.finish_reason, Some(FinishReason::Stop));
+    }
 
-        let message_json = serde_json::to_string(messages)?;
-        sink.send(message_json).await?;
+    #[tokio::test]
+    async fn test_unix_socket_ping() {
+        let path = std::env::temp_dir().join(format!("mcp-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (reader, writer) = tokio::io::split(socket);
+            McpServer::new("daemon", "1.0")
+                .serve(reader, writer)
+                .await
+                .unwrap();
+        });
 
-        let response_stream = async_stream::try_stream! {
-            while let Some(line) = stream.next().await {
-                let line = line?;
-                let data = serde_json::from_str(&line)?;
-                yield StreamData::new(data, None, &line);
+        let result = McpClient::new(McpTransport::Unix(path.clone()))
+            .ping()
+            .await;
+        std::fs::remove_file(&path).unwrap();
+        result.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_pooled_connections_are_reused_within_the_limit() {
+        let path = std::env::temp_dir().join(format!("mcp-pool-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = tokio::net::UnixListener::bind(&path).unwrap();
+        let accepted = Arc::new(AtomicU64::new(0));
+        let counter = accepted.clone();
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::spawn(async move {
+                    let (reader, writer) = tokio::io::split(socket);
+                    McpServer::new("daemon", "1.0").serve(reader, writer).await
+                });
             }
-        };
+        });
+
+        let client = McpClient::new(McpTransport::Unix(path.clone()))
+            .with_max_idle_connections(1)
+            .with_max_concurrent_calls(1);
+        let pings = futures::future::join_all((0..3).map(|_| client.ping())).await;
+        std::fs::remove_file(&path).unwrap();
 
-        Ok(Box::pin(response_stream))
+        assert!(pings.iter().all(Result::is_ok));
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
     }
 
-    fn add_options(&mut self, options: CallOptions) {
-        self.options.merge_options(options);
+    #[tokio::test]
+    async fn test_answers_roots_list() {
+        let script = format!(
+            r#"read init
+echo '{{"jsonrpc":"2.0","id":1,"result":{{"protocolVersion":"{}","capabilities":{{}}}}}}'
+read initialized
+read request
+echo '{{"jsonrpc":"2.0","id":"r1","method":"roots/list"}}'
+read roots
+echo "{{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":$roots}}""#,
+            PROTOCOL_VERSION
+        );
+        let client = McpClient::connect_stdio("sh", vec!["-c".to_string(), script], HashMap::new())
+            .with_roots(vec![McpRoot::from_path("/tmp/project")
+                .unwrap()
+                .with_name("project")]);
+
+        let answer = client.request("ping", json!({})).await.unwrap();
+
+        assert_eq!(answer["id"], "r1");
+        assert_eq!(
+            answer["result"]["roots"],
+            json!([{"uri": "file:///tmp/project", "name": "project"}])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_error_objects_are_reported() {
+        let client = scripted_server(
+            r#"{"jsonrpc":"2.0","id":2,"error":{"code":-32601,"message":"Method not found"}}"#,
+        );
+
+        let error = client
+            .generate(&[Message::new_human_message("ping")])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            LLMError::McpError(McpError::RpcError { code: -32601, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_slow_server_times_out() {
+        let client = McpClient::connect_stdio("sleep", vec!["5".to_string()], HashMap::new())
+            .with_timeout(Duration::from_millis(200));
+
+        let error = client
+            .generate(&[Message::new_human_message("ping")])
+            .await
+            .unwrap_err();
+
+        assert!(matches!(error, LLMError::McpError(McpError::Timeout(_))));
+        assert!(error.is_transient());
     }
 }
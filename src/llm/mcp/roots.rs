@@ -0,0 +1,37 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use super::McpError;
+
+/// A filesystem root the client exposes to MCP servers, which scope their access to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpRoot {
+    /// A `file://` URI.
+    pub uri: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl McpRoot {
+    pub fn new<S: Into<String>>(uri: S) -> Self {
+        Self {
+            uri: uri.into(),
+            name: None,
+        }
+    }
+
+    /// A root for an absolute directory path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, McpError> {
+        let path = path.as_ref();
+        let uri = Url::from_directory_path(path)
+            .map_err(|_| McpError::InvalidRoot(path.display().to_string()))?;
+        Ok(Self::new(uri.as_str().trim_end_matches('/')))
+    }
+
+    pub fn with_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+}
@@ -0,0 +1,275 @@
+use std::{collections::HashMap, io, sync::Arc};
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::codec::{FramedRead, FramedWrite, LinesCodec};
+
+use super::map_codec_error;
+use crate::{chain::Chain, prompt_args, tools::Tool};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+enum Exposed {
+    Tool(Arc<dyn Tool>),
+    Chain {
+        description: String,
+        chain: Arc<dyn Chain>,
+    },
+}
+
+/// Serves tools and agents to MCP hosts such as Claude Desktop or IDEs.
+///
+/// Speaks JSON-RPC over newline-delimited messages, the MCP stdio transport, and answers
+/// `initialize`, `ping`, `tools/list` and `tools/call`. Agents are exposed through their
+/// executor (or any other chain) as a tool taking a single `input` string.
+///
+/// ```rust,ignore
+/// let executor = AgentExecutor::from_agent(agent);
+/// McpServer::new("assistant", "0.1.0")
+///     .with_chain("assistant", "Answers questions using the web", executor)
+///     .serve_stdio()
+///     .await?;
+/// ```
+pub struct McpServer {
+    name: String,
+    version: String,
+    exposed: HashMap<String, Exposed>,
+}
+
+impl McpServer {
+    pub fn new<S: Into<String>, V: Into<String>>(name: S, version: V) -> Self {
+        Self {
+            name: name.into(),
+            version: version.into(),
+            exposed: HashMap::new(),
+        }
+    }
+
+    pub fn with_tool(mut self, tool: Arc<dyn Tool>) -> Self {
+        self.exposed.insert(tool.name(), Exposed::Tool(tool));
+        self
+    }
+
+    pub fn with_tools(self, tools: &[Arc<dyn Tool>]) -> Self {
+        tools
+            .iter()
+            .fold(self, |server, tool| server.with_tool(tool.clone()))
+    }
+
+    /// Exposes `chain`, e.g. an `AgentExecutor`, as the tool `name`.
+    pub fn with_chain<S: Into<String>, D: Into<String>, C: Chain + 'static>(
+        mut self,
+        name: S,
+        description: D,
+        chain: C,
+    ) -> Self {
+        self.exposed.insert(
+            name.into(),
+            Exposed::Chain {
+                description: description.into(),
+                chain: Arc::new(chain),
+            },
+        );
+        self
+    }
+
+    /// Serves the process's stdin and stdout until stdin is closed.
+    pub async fn serve_stdio(&self) -> Result<(), io::Error> {
+        self.serve(tokio::io::stdin(), tokio::io::stdout()).await
+    }
+
+    /// Answers the requests read from `reader` on `writer` until `reader` is exhausted.
+    pub async fn serve<R, W>(&self, reader: R, writer: W) -> Result<(), io::Error>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut requests = FramedRead::new(reader, LinesCodec::new());
+        let mut responses = FramedWrite::new(writer, LinesCodec::new());
+        while let Some(line) = requests.next().await {
+            let line = line.map_err(map_codec_error)?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Value>(&line) {
+                Ok(request) => self.handle(request).await,
+                Err(e) => Some(error_response(Value::Null, PARSE_ERROR, e.to_string())),
+            };
+            if let Some(response) = response {
+                responses
+                    .send(response.to_string())
+                    .await
+                    .map_err(map_codec_error)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The response to one JSON-RPC message, `None` for notifications.
+    pub async fn handle(&self, request: Value) -> Option<Value> {
+        let id = request.get("id").cloned()?;
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = match request["method"].as_str().unwrap_or_default() {
+            "initialize" => Ok(json!({
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": { "tools": {} },
+                "serverInfo": { "name": self.name, "version": self.version },
+            })),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(json!({ "tools": self.list_tools() })),
+            "tools/call" => self.call_tool(&params).await,
+            method => Err((METHOD_NOT_FOUND, format!("Method {} not found", method))),
+        };
+        Some(match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err((code, message)) => error_response(id, code, message),
+        })
+    }
+
+    fn list_tools(&self) -> Vec<Value> {
+        let mut tools = self
+            .exposed
+            .iter()
+            .map(|(name, exposed)| match exposed {
+                Exposed::Tool(tool) => json!({
+                    "name": name,
+                    "description": tool.description(),
+                    "inputSchema": tool.parameters(),
+                }),
+                Exposed::Chain { description, .. } => json!({
+                    "name": name,
+                    "description": description,
+                    "inputSchema": {
+                        "type": "object",
+                        "properties": { "input": { "type": "string" } },
+                        "required": ["input"],
+                    },
+                }),
+            })
+            .collect::<Vec<_>>();
+        tools.sort_by(|a, b| a["name"].as_str().cmp(&b["name"].as_str()));
+        tools
+    }
+
+    /// Tool failures are reported in the result with `isError`, as MCP expects, so the host's
+    /// model can see them.
+    async fn call_tool(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let name = params["name"].as_str().unwrap_or_default();
+        let exposed = self
+            .exposed
+            .get(name)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown tool {}", name)))?;
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+
+        let output = match exposed {
+            Exposed::Tool(tool) => tool
+                .call(&arguments.to_string())
+                .await
+                .map_err(|e| e.to_string()),
+            Exposed::Chain { chain, .. } => {
+                let input = match &arguments["input"] {
+                    Value::String(input) => input.clone(),
+                    input => input.to_string(),
+                };
+                chain
+                    .invoke(prompt_args! { "input" => input })
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        };
+        let (text, is_error) = match output {
+            Ok(text) => (text, false),
+            Err(e) => (e, true),
+        };
+        Ok(json!({
+            "content": [{ "type": "text", "text": text }],
+            "isError": is_error,
+        }))
+    }
+}
+
+fn error_response(id: Value, code: i64, message: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use async_trait::async_trait;
+
+    use super::*;
+
+    struct Upper;
+
+    #[async_trait]
+    impl Tool for Upper {
+        fn name(&self) -> String {
+            "upper".to_string()
+        }
+        fn description(&self) -> String {
+            "Upper-cases its input".to_string()
+        }
+        async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+            Ok(input
+                .as_str()
+                .ok_or("input must be a string")?
+                .to_uppercase())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_serves_tools_over_lines() {
+        let server = McpServer::new("test", "0.1.0").with_tool(Arc::new(Upper));
+        let requests = [
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+            json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+            json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                   "params": {"name": "upper", "arguments": {"input": "hi"}}}),
+            json!({"jsonrpc": "2.0", "id": 4, "method": "resources/list"}),
+        ]
+        .iter()
+        .map(|request| format!("{}\n", request))
+        .collect::<String>();
+
+        let mut output = Vec::new();
+        server
+            .serve(requests.as_bytes(), &mut output)
+            .await
+            .unwrap();
+
+        let responses = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<Value>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["result"]["serverInfo"]["name"], "test");
+        assert_eq!(responses[1]["result"]["tools"][0]["name"], "upper");
+        assert_eq!(responses[2]["result"]["content"][0]["text"], "HI");
+        assert_eq!(responses[3]["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool_is_invalid() {
+        let server = McpServer::new("test", "0.1.0");
+
+        let response = server
+            .handle(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                           "params": {"name": "missing"}}))
+            .await
+            .unwrap();
+
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+}
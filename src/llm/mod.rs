@@ -1,4 +1,6 @@
+#[cfg(feature = "openai")]
 pub mod openai;
+#[cfg(feature = "openai")]
 pub use openai::*;
 
 pub mod claude;
@@ -13,5 +15,7 @@ pub use qwen::*;
 pub mod deepseek;
 pub use deepseek::*;
 
+#[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
 pub mod mcp;
+#[cfg(all(feature = "mcp", not(target_arch = "wasm32")))]
 pub use mcp::*;
@@ -1,5 +1,6 @@
 use crate::{
-    language_models::{llm::LLM, GenerateResult, LLMError, TokenUsage},
+    env::{self, EnvError},
+    language_models::{llm::LLM, GenerateResult, LLMError, Stopwatch, TokenUsage},
     schemas::{Message, MessageType, StreamData},
 };
 use async_trait::async_trait;
@@ -22,6 +23,7 @@ pub struct Ollama {
     pub(crate) client: Arc<OllamaClient>,
     pub(crate) model: String,
     pub(crate) options: Option<GenerationOptions>,
+    pub(crate) reasoning_tags: bool,
 }
 
 /// [llama3.2](https://ollama.com/library/llama3.2) is a 3B parameters, 2.0GB model.
@@ -37,9 +39,24 @@ impl Ollama {
             client,
             model: model.into(),
             options,
+            reasoning_tags: false,
         }
     }
 
+    /// Talks to the server in `OLLAMA_HOST`, e.g. `http://10.0.0.5:11434`, with the model in
+    /// `OLLAMA_MODEL`, each falling back to the default when unset.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let client = match env::var("OLLAMA_HOST") {
+            Some(host) => OllamaClient::try_new(host.as_str()).map_err(|_| EnvError::Invalid {
+                name: "OLLAMA_HOST".to_string(),
+                value: host.clone(),
+            })?,
+            None => OllamaClient::default(),
+        };
+        let model = env::var("OLLAMA_MODEL").unwrap_or_else(|| DEFAULT_MODEL.to_string());
+        Ok(Ollama::new(Arc::new(client), model, None))
+    }
+
     pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
         self.model = model.into();
         self
@@ -50,40 +67,40 @@ impl Ollama {
         self
     }
 
+    /// Moves the `<think>` blocks of reasoning models such as R1 from the generation to
+    /// [`GenerateResult::reasoning`]. Off by default, since other models may mention the tag
+    /// in their answers.
+    pub fn with_reasoning_tags(mut self, reasoning_tags: bool) -> Self {
+        self.reasoning_tags = reasoning_tags;
+        self
+    }
+
     fn generate_request(&self, messages: &[Message]) -> ChatMessageRequest {
-        let mapped_messages = messages.iter().map(|message| message.into()).collect();
+        let mapped_messages = messages.iter().map(chat_message_from).collect();
         ChatMessageRequest::new(self.model.clone(), mapped_messages)
     }
 }
 
-impl From<&Message> for ChatMessage {
-    fn from(message: &Message) -> Self {
-        let images = match message.images.clone() {
-            Some(images) => {
-                let images = images
-                    .iter()
-                    .map(|image| Image::from_base64(&image.image_url))
-                    .collect();
-                Some(images)
-            }
-            None => None,
-        };
-        ChatMessage {
-            content: message.content.clone(),
-            images,
-            role: message.message_type.clone().into(),
-        }
+fn chat_message_from(message: &Message) -> ChatMessage {
+    let images = message.images.as_ref().map(|images| {
+        images
+            .iter()
+            .map(|image| Image::from_base64(&image.image_url))
+            .collect()
+    });
+    ChatMessage {
+        content: message.content.clone(),
+        images,
+        role: message_role_from(&message.message_type),
     }
 }
 
-impl From<MessageType> for MessageRole {
-    fn from(message_type: MessageType) -> Self {
-        match message_type {
-            MessageType::AIMessage => MessageRole::Assistant,
-            MessageType::ToolMessage => MessageRole::Assistant,
-            MessageType::SystemMessage => MessageRole::System,
-            MessageType::HumanMessage => MessageRole::User,
-        }
+fn message_role_from(message_type: &MessageType) -> MessageRole {
+    match message_type {
+        MessageType::AIMessage => MessageRole::Assistant,
+        MessageType::ToolMessage => MessageRole::Assistant,
+        MessageType::SystemMessage => MessageRole::System,
+        MessageType::HumanMessage => MessageRole::User,
     }
 }
 
@@ -98,6 +115,7 @@ impl Default for Ollama {
 impl LLM for Ollama {
     async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
         let request = self.generate_request(messages);
+        let stopwatch = Stopwatch::start();
         let result = self.client.send_chat_messages(request).await?;
 
         let generation = match result.message {
@@ -115,7 +133,17 @@ impl LLM for Ollama {
             }
         });
 
-        Ok(GenerateResult { tokens, generation })
+        let mut result = GenerateResult {
+            tokens,
+            generation,
+            model: Some(result.model),
+            ..Default::default()
+        }
+        .with_latency(&stopwatch);
+        if self.reasoning_tags {
+            result.extract_reasoning();
+        }
+        Ok(result)
     }
 
     async fn stream(
@@ -3,6 +3,8 @@ use reqwest::header::HeaderMap;
 use secrecy::SecretString;
 use serde::Deserialize;
 
+use crate::env::{self, EnvError};
+
 const OLLAMA_API_BASE: &str = "http://localhost:11434/v1";
 
 /// Ollama has [OpenAI compatiblity](https://ollama.com/blog/openai-compatibility), meaning that you can use it as an OpenAI API.
@@ -27,6 +29,15 @@ impl OllamaConfig {
         Self::default()
     }
 
+    /// Points at the server in `OLLAMA_HOST`, e.g. `http://10.0.0.5:11434`, if it is set.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let config = Self::new();
+        Ok(match env::var("OLLAMA_HOST") {
+            Some(host) => config.with_api_base(format!("{}/v1", host.trim_end_matches('/'))),
+            None => config,
+        })
+    }
+
     pub fn with_api_key<S: Into<String>>(mut self, api_key: S) -> Self {
         self.api_key = SecretString::from(api_key.into());
         self
@@ -6,27 +6,70 @@ use async_openai::types::{ChatCompletionToolChoiceOption, ResponseFormat};
 use async_openai::{
     error::OpenAIError,
     types::{
-        ChatChoiceStream, ChatCompletionMessageToolCall, ChatCompletionRequestAssistantMessageArgs,
-        ChatCompletionRequestMessage, ChatCompletionRequestMessageContentPartImageArgs,
-        ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestToolMessageArgs,
-        ChatCompletionRequestUserMessageArgs, ChatCompletionRequestUserMessageContent,
-        ChatCompletionRequestUserMessageContentPart, ChatCompletionStreamOptions,
-        CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
+        ChatChoiceLogprobs, ChatChoiceStream, ChatCompletionMessageToolCall,
+        ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
+        ChatCompletionRequestMessageContentPartImageArgs, ChatCompletionRequestSystemMessageArgs,
+        ChatCompletionRequestToolMessageArgs, ChatCompletionRequestUserMessageArgs,
+        ChatCompletionRequestUserMessageContent, ChatCompletionRequestUserMessageContentPart,
+        ChatCompletionStreamOptions, CreateChatCompletionRequest, CreateChatCompletionRequestArgs,
+        CreateChatCompletionResponse,
     },
     Client,
 };
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
 
-use crate::schemas::convert::{LangchainIntoOpenAI, TryLangchainIntoOpenAI};
+use crate::schemas::convert::{LangchainIntoOpenAI, OpenAiIntoLangchain, TryLangchainIntoOpenAI};
 use crate::{
-    language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage},
+    env::{self, EnvError},
+    language_models::{
+        context_window, llm::LLM, logprobs_from_openai_value, options::CallOptions, GenerateResult,
+        LLMError, Stopwatch, TokenLogprob, TokenUsage,
+    },
     schemas::{
         messages::{Message, MessageType},
         StreamData,
     },
 };
 
+/// Converts a non-streamed chat completion, also used for Batch API results.
+pub(crate) fn generate_result_from_response(
+    response: CreateChatCompletionResponse,
+) -> GenerateResult {
+    let mut generate_result = GenerateResult {
+        request_id: Some(response.id),
+        model: Some(response.model),
+        ..Default::default()
+    };
+
+    if let Some(usage) = response.usage {
+        generate_result.tokens = Some(TokenUsage {
+            prompt_tokens: usage.prompt_tokens,
+            completion_tokens: usage.completion_tokens,
+            total_tokens: usage.total_tokens,
+        });
+    }
+
+    if let Some(choice) = &response.choices.first() {
+        generate_result.finish_reason = choice.finish_reason.map(|r| r.into_langchain());
+        generate_result.logprobs = choice.logprobs.as_ref().map(openai_logprobs);
+        generate_result.generation = choice.message.content.clone().unwrap_or_default();
+        if let Some(function) = &choice.message.tool_calls {
+            generate_result.generation = serde_json::to_string(&function).unwrap_or_default();
+        }
+    }
+
+    generate_result
+}
+
+fn openai_logprobs(logprobs: &ChatChoiceLogprobs) -> Vec<TokenLogprob> {
+    serde_json::to_value(logprobs)
+        .ok()
+        .as_ref()
+        .and_then(logprobs_from_openai_value)
+        .unwrap_or_default()
+}
+
 #[derive(Clone)]
 pub enum OpenAIModel {
     Gpt35,
@@ -59,6 +102,7 @@ pub struct OpenAI<C: Config> {
     config: C,
     options: CallOptions,
     model: String,
+    reasoning_tags: bool,
 }
 
 impl<C: Config> OpenAI<C> {
@@ -67,6 +111,7 @@ impl<C: Config> OpenAI<C> {
             config,
             options: CallOptions::default(),
             model: OpenAIModel::Gpt4oMini.to_string(),
+            reasoning_tags: false,
         }
     }
 
@@ -84,6 +129,14 @@ impl<C: Config> OpenAI<C> {
         self.options = options;
         self
     }
+
+    /// Moves the `<think>` blocks that OpenAI-compatible servers hosting R1-style models return
+    /// inline from the generation to [`GenerateResult::reasoning`]. Off by default, since
+    /// other models may mention the tag in their answers.
+    pub fn with_reasoning_tags(mut self, reasoning_tags: bool) -> Self {
+        self.reasoning_tags = reasoning_tags;
+        self
+    }
 }
 
 impl Default for OpenAI<OpenAIConfig> {
@@ -92,18 +145,40 @@ impl Default for OpenAI<OpenAIConfig> {
     }
 }
 
+impl OpenAI<OpenAIConfig> {
+    /// Reads the key from `OPENAI_API_KEY`, and the endpoint, organization and model from
+    /// `OPENAI_API_BASE`, `OPENAI_ORG_ID` and `OPENAI_MODEL` if they are set.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let mut config = OpenAIConfig::new().with_api_key(env::require("OPENAI_API_KEY")?);
+        if let Some(api_base) = env::var("OPENAI_API_BASE") {
+            config = config.with_api_base(api_base);
+        }
+        if let Some(org_id) = env::var("OPENAI_ORG_ID") {
+            config = config.with_org_id(org_id);
+        }
+        let openai = Self::new(config);
+        Ok(match env::var("OPENAI_MODEL") {
+            Some(model) => openai.with_model(model),
+            None => openai,
+        })
+    }
+}
+
 #[async_trait]
 impl<C: Config + Send + Sync + 'static> LLM for OpenAI<C> {
     async fn generate(&self, prompt: &[Message]) -> Result<GenerateResult, LLMError> {
         let client = Client::with_config(self.config.clone());
         let request = self.generate_request(prompt, self.options.streaming_func.is_some())?;
-        match &self.options.streaming_func {
+        let stopwatch = Stopwatch::start();
+        let mut generate_result = match &self.options.streaming_func {
             Some(func) => {
                 let mut stream = client.chat().create_stream(request).await?;
                 let mut generate_result = GenerateResult::default();
                 while let Some(result) = stream.next().await {
                     match result {
                         Ok(response) => {
+                            generate_result.request_id = Some(response.id.clone());
+                            generate_result.model = Some(response.model.clone());
                             if let Some(usage) = response.usage {
                                 generate_result.tokens = Some(TokenUsage {
                                     prompt_tokens: usage.prompt_tokens,
@@ -120,6 +195,15 @@ impl<C: Config + Send + Sync + 'static> LLM for OpenAI<C> {
                                     )
                                     .await;
                                 }
+                                if let Some(logprobs) = &chat_choice.logprobs {
+                                    generate_result
+                                        .logprobs
+                                        .get_or_insert_with(Vec::new)
+                                        .extend(openai_logprobs(logprobs));
+                                }
+                                if let Some(reason) = chat_choice.finish_reason {
+                                    generate_result.finish_reason = Some(reason.into_langchain());
+                                }
                                 if let Some(content) = chat_choice.delta.content {
                                     generate_result.generation.push_str(&content);
                                 }
@@ -130,33 +214,14 @@ impl<C: Config + Send + Sync + 'static> LLM for OpenAI<C> {
                         }
                     }
                 }
-                Ok(generate_result)
-            }
-            None => {
-                let response = client.chat().create(request).await?;
-                let mut generate_result = GenerateResult::default();
-
-                if let Some(usage) = response.usage {
-                    generate_result.tokens = Some(TokenUsage {
-                        prompt_tokens: usage.prompt_tokens,
-                        completion_tokens: usage.completion_tokens,
-                        total_tokens: usage.total_tokens,
-                    });
-                }
-
-                if let Some(choice) = &response.choices.first() {
-                    generate_result.generation = choice.message.content.clone().unwrap_or_default();
-                    if let Some(function) = &choice.message.tool_calls {
-                        generate_result.generation =
-                            serde_json::to_string(&function).unwrap_or_default();
-                    }
-                } else {
-                    generate_result.generation = "".to_string();
-                }
-
-                Ok(generate_result)
+                generate_result
             }
+            None => generate_result_from_response(client.chat().create(request).await?),
+        };
+        if self.reasoning_tags {
+            generate_result.extract_reasoning();
         }
+        Ok(generate_result.with_latency(&stopwatch))
     }
 
     async fn invoke(&self, prompt: &str) -> Result<String, LLMError> {
@@ -205,6 +270,10 @@ impl<C: Config + Send + Sync + 'static> LLM for OpenAI<C> {
     fn add_options(&mut self, options: CallOptions) {
         self.options.merge_options(options)
     }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        context_window(&self.model)
+    }
 }
 
 impl<C: Config> OpenAI<C> {
@@ -278,7 +347,7 @@ impl<C: Config> OpenAI<C> {
         Ok(openai_messages)
     }
 
-    fn generate_request(
+    pub(crate) fn generate_request(
         &self,
         messages: &[Message],
         stream: bool,
@@ -296,6 +365,12 @@ impl<C: Config> OpenAI<C> {
                 request_builder.stream_options(ChatCompletionStreamOptions { include_usage });
             }
         }
+        if let Some(logprobs) = self.options.logprobs {
+            request_builder.logprobs(logprobs);
+        }
+        if let Some(top_logprobs) = self.options.top_logprobs {
+            request_builder.top_logprobs(top_logprobs);
+        }
         request_builder.model(self.model.to_string());
         if let Some(stop_words) = &self.options.stop_words {
             request_builder.stop(stop_words);
@@ -336,6 +411,31 @@ mod tests {
     use tokio::sync::Mutex;
     use tokio::test;
 
+    #[test]
+    async fn test_think_tag_in_answer_is_kept_by_default() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "created": 1711471533,
+            "model": "gpt-4o-mini",
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "Use the <think> tag to open a reasoning block."
+                },
+                "finish_reason": "stop"
+            }]
+        }))
+        .unwrap();
+        let result = generate_result_from_response(response);
+        assert_eq!(
+            result.generation,
+            "Use the <think> tag to open a reasoning block."
+        );
+        assert_eq!(result.reasoning, None);
+    }
+
     #[test]
     #[ignore]
     async fn test_invoke() {
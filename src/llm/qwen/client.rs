@@ -1,5 +1,9 @@
 use crate::{
-    language_models::{llm::LLM, options::CallOptions, GenerateResult, LLMError, TokenUsage},
+    env::{self, EnvError},
+    language_models::{
+        context_window, llm::LLM, logprobs_from_openai_value, options::CallOptions, FinishReason,
+        GenerateResult, LLMError, Stopwatch, TokenUsage,
+    },
     llm::QwenError,
     schemas::{Message, StreamData},
 };
@@ -215,6 +219,21 @@ impl Qwen {
         }
     }
 
+    /// Create a client from `QWEN_API_KEY`, or `DASHSCOPE_API_KEY`, and from `QWEN_MODEL` and
+    /// `QWEN_BASE_URL` if they are set
+    pub fn from_env() -> Result<Self, EnvError> {
+        let api_key = env::any_var(&["QWEN_API_KEY", "DASHSCOPE_API_KEY"])
+            .ok_or_else(|| EnvError::Missing("QWEN_API_KEY".to_string()))?;
+        let mut qwen = Self::new().with_api_key(api_key);
+        if let Some(model) = env::var("QWEN_MODEL") {
+            qwen = qwen.with_model(model);
+        }
+        if let Some(base_url) = env::var("QWEN_BASE_URL") {
+            qwen = qwen.with_base_url(base_url);
+        }
+        Ok(qwen)
+    }
+
     /// Set the model
     pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
         self.model = model.into();
@@ -245,6 +264,7 @@ impl Qwen {
         let is_stream = self.options.streaming_func.is_some();
 
         let payload = self.build_payload(messages, is_stream);
+        let stopwatch = Stopwatch::start();
         let res = client
             .post(&self.base_url)
             .header("Authorization", format!("Bearer {}", &self.api_key))
@@ -258,7 +278,8 @@ impl Qwen {
                 let api_response = res.json::<ApiResponse>().await?;
 
                 // Extract the first choice content
-                let generation = match api_response.choices.first() {
+                let choice = api_response.choices.first();
+                let generation = match choice {
                     Some(choice) => choice.message.content.clone(),
                     None => {
                         return Err(LLMError::ContentNotFound(
@@ -273,7 +294,20 @@ impl Qwen {
                     total_tokens: api_response.usage.total_tokens,
                 });
 
-                Ok(GenerateResult { tokens, generation })
+                Ok(GenerateResult {
+                    tokens,
+                    generation,
+                    finish_reason: choice
+                        .and_then(|c| c.finish_reason.as_deref())
+                        .map(FinishReason::from_provider),
+                    logprobs: choice
+                        .and_then(|c| c.logprobs.as_ref())
+                        .and_then(logprobs_from_openai_value),
+                    model: Some(api_response.model),
+                    request_id: Some(api_response.id),
+                    ..Default::default()
+                }
+                .with_latency(&stopwatch))
             }
             400 => {
                 let error = res.json::<ErrorResponse>().await?;
@@ -313,6 +347,8 @@ impl Qwen {
             max_tokens: self.options.max_tokens,
             stream: None,
             stop: self.options.stop_words.clone(),
+            logprobs: self.options.logprobs,
+            top_logprobs: self.options.top_logprobs,
             temperature: self.options.temperature,
             top_p: self.options.top_p,
             seed: None,          // Optional
@@ -359,21 +395,24 @@ impl LLM for Qwen {
     async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
         match &self.options.streaming_func {
             Some(func) => {
-                let mut complete_response = String::new();
+                let stopwatch = Stopwatch::start();
+                let mut generate_result = GenerateResult::default();
                 let mut stream = self.stream(messages).await?;
                 while let Some(data) = stream.next().await {
                     match data {
                         Ok(value) => {
+                            generate_result.update_from_openai_value(&value.value);
+                            if value.content.is_empty() {
+                                continue;
+                            }
                             let mut func = func.lock().await;
-                            complete_response.push_str(&value.content);
+                            generate_result.generation.push_str(&value.content);
                             let _ = func(value.content).await;
                         }
                         Err(e) => return Err(e),
                     }
                 }
-                let mut generate_result = GenerateResult::default();
-                generate_result.generation = complete_response;
-                Ok(generate_result)
+                Ok(generate_result.with_latency(&stopwatch))
             }
             None => self.generate(messages).await,
         }
@@ -406,7 +445,7 @@ impl LLM for Qwen {
                                 .map_err(|e| LLMError::OtherError(e.to_string()))?;
                             let chunks = Self::parse_sse_chunk(&bytes)?;
 
-                            for chunk in chunks {
+                            for chunk in &chunks {
                                 if let Some(choices) =
                                     chunk.get("choices").and_then(|c| c.as_array())
                                 {
@@ -452,6 +491,15 @@ impl LLM for Qwen {
                                 }
                             }
 
+                            // Keep the final chunk so consumers can read the finish reason.
+                            if let Some(chunk) = chunks.iter().find(|chunk| {
+                                chunk
+                                    .pointer("/choices/0/finish_reason")
+                                    .is_some_and(Value::is_string)
+                            }) {
+                                return Ok(StreamData::new(chunk.clone(), None, ""));
+                            }
+
                             // If we didn't return within the loop, return an empty stream data
                             Ok(StreamData::new(Value::Null, None, ""))
                         }
@@ -461,7 +509,12 @@ impl LLM for Qwen {
             })
             .filter_map(|result| async move {
                 match result {
-                    Ok(data) if !data.content.is_empty() => Some(Ok(data)),
+                    Ok(data)
+                        if !data.content.is_empty()
+                            || data.value.pointer("/choices/0/finish_reason").is_some() =>
+                    {
+                        Some(Ok(data))
+                    }
                     Ok(_) => None,
                     Err(e) => Some(Err(e)),
                 }
@@ -473,6 +526,10 @@ impl LLM for Qwen {
     fn add_options(&mut self, options: CallOptions) {
         self.options.merge_options(options)
     }
+
+    fn max_context_tokens(&self) -> Option<usize> {
+        context_window(&self.model)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,108 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use tokio::sync::Mutex;
+
+use crate::{
+    encryption::{Cipher, EncryptionError, EnvKey},
+    env,
+    schemas::{memory::BaseMemory, messages::Message},
+};
+
+/// Conversation memory persisted to an AES-GCM encrypted file.
+///
+/// The whole transcript is rewritten (to a temporary file, then renamed) after every change,
+/// so the file on disk is always a complete, encrypted snapshot.
+pub struct EncryptedFileMemory {
+    path: PathBuf,
+    cipher: Cipher,
+    messages: Vec<Message>,
+}
+
+impl EncryptedFileMemory {
+    /// Opens the memory stored at `path`, starting empty if the file does not exist yet.
+    pub fn open<P: AsRef<Path>>(path: P, cipher: Cipher) -> Result<Self, EncryptionError> {
+        let path = path.as_ref().to_path_buf();
+        let messages = match fs::read(&path) {
+            Ok(payload) => cipher.decrypt_json(&payload)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path,
+            cipher,
+            messages,
+        })
+    }
+
+    /// Opens the memory at `LANGCHAIN_MEMORY_PATH`, encrypted with the key in
+    /// `LANGCHAIN_ENCRYPTION_KEY`.
+    pub fn from_env() -> Result<Self, EncryptionError> {
+        let cipher = Cipher::new(&EnvKey::default())?;
+        Self::open(env::require("LANGCHAIN_MEMORY_PATH")?, cipher)
+    }
+
+    fn persist(&self) -> Result<(), EncryptionError> {
+        let payload = self.cipher.encrypt_json(&self.messages)?;
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, payload)?;
+        fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+
+    fn persist_or_log(&self) {
+        if let Err(e) = self.persist() {
+            log::error!("Failed to persist memory to {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+impl From<EncryptedFileMemory> for Arc<Mutex<dyn BaseMemory>> {
+    fn from(memory: EncryptedFileMemory) -> Self {
+        Arc::new(Mutex::new(memory))
+    }
+}
+
+impl BaseMemory for EncryptedFileMemory {
+    fn messages(&self) -> Vec<Message> {
+        self.messages.clone()
+    }
+
+    fn add_message(&mut self, message: Message) {
+        self.messages.push(message);
+        self.persist_or_log();
+    }
+
+    fn clear(&mut self) {
+        self.messages.clear();
+        self.persist_or_log();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encryption::StaticKey;
+
+    #[test]
+    fn test_persists_encrypted_and_reloads() {
+        let path = std::env::temp_dir().join(format!("memory-{}.enc", std::process::id()));
+        let cipher = Cipher::new(&StaticKey::new([3; 32])).unwrap();
+
+        let mut memory = EncryptedFileMemory::open(&path, cipher.clone()).unwrap();
+        memory.add_user_message(&"my card number is 4242");
+
+        let on_disk = fs::read(&path).unwrap();
+        assert!(!String::from_utf8_lossy(&on_disk).contains("4242"));
+
+        let reopened = EncryptedFileMemory::open(&path, cipher).unwrap();
+        assert_eq!(reopened.messages()[0].content, "my card number is 4242");
+
+        let wrong_key = Cipher::new(&StaticKey::new([4; 32])).unwrap();
+        assert!(EncryptedFileMemory::open(&path, wrong_key).is_err());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+use crate::{
+    embedding::{Embedder, EmbedderError},
+    language_models::{llm::LLM, LLMError},
+    semantic_router::utils::cosine_similarity,
+};
+
+const SUMMARY_PROMPT: &str = "Below is a summary of earlier sessions with a user, followed by \
+a new exchange. Rewrite the summary so it also keeps what is worth remembering from the new \
+exchange, such as facts about the user, their preferences and their decisions, in at most \
+{max_words} words.\n\n\
+Summary:\n{summary}\n\n\
+New exchange:\nUser: {input}\nAssistant: {answer}\n\n\
+Reply with the updated summary only.";
+
+#[derive(Error, Debug)]
+pub enum LongTermMemoryError {
+    #[error(transparent)]
+    LLMError(#[from] LLMError),
+
+    #[error(transparent)]
+    EmbedderError(#[from] EmbedderError),
+}
+
+/// A past exchange: the user's input and the answer it got.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Episode {
+    pub input: String,
+    pub answer: String,
+}
+
+#[derive(Default)]
+struct Memories {
+    summary: String,
+    episodes: Vec<(Episode, Vec<f64>)>,
+}
+
+/// Memory spanning sessions: a rolling summary of every past exchange, kept by an LLM
+/// summarizer, and the exchanges themselves, retrieved by embedding similarity to a new input.
+///
+/// Give it to an executor with
+/// [`AgentExecutor::with_long_term_memory`](crate::agent::AgentExecutor::with_long_term_memory)
+/// so the agent plans with what it remembers. Every remembered exchange costs one summarizer
+/// call, which should be a cheap LLM. Cloning shares the memories.
+#[derive(Clone)]
+pub struct LongTermMemory {
+    summarizer: Arc<dyn LLM>,
+    embedder: Arc<dyn Embedder>,
+    k: usize,
+    max_summary_words: usize,
+    capacity: usize,
+    memories: Arc<Mutex<Memories>>,
+}
+
+impl LongTermMemory {
+    pub fn new<L: Into<Box<dyn LLM>>, E: Embedder + 'static>(summarizer: L, embedder: E) -> Self {
+        Self {
+            summarizer: Arc::from(summarizer.into()),
+            embedder: Arc::new(embedder),
+            k: 3,
+            max_summary_words: 200,
+            capacity: 1000,
+            memories: Arc::default(),
+        }
+    }
+
+    /// How many past exchanges are recalled with the summary.
+    pub fn with_k(mut self, k: usize) -> Self {
+        self.k = k;
+        self
+    }
+
+    pub fn with_max_summary_words(mut self, max_summary_words: usize) -> Self {
+        self.max_summary_words = max_summary_words;
+        self
+    }
+
+    /// Maximum number of exchanges kept. Once full the oldest exchange is dropped, though the
+    /// summary still covers it.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub async fn summary(&self) -> String {
+        self.memories.lock().await.summary.clone()
+    }
+
+    /// Every kept exchange, oldest first.
+    pub async fn episodes(&self) -> Vec<Episode> {
+        self.memories
+            .lock()
+            .await
+            .episodes
+            .iter()
+            .map(|(episode, _)| episode.clone())
+            .collect()
+    }
+
+    /// Stores an exchange and folds it into the summary.
+    pub async fn remember(&self, input: &str, answer: &str) -> Result<(), LongTermMemoryError> {
+        let embedding = self.embedder.embed_query(input).await?;
+        let summary = self.summary().await;
+        let summary = self
+            .summarizer
+            .invoke(
+                &SUMMARY_PROMPT
+                    .replace("{max_words}", &self.max_summary_words.to_string())
+                    .replace("{summary}", &summary)
+                    .replace("{input}", input)
+                    .replace("{answer}", answer),
+            )
+            .await?;
+
+        let mut memories = self.memories.lock().await;
+        memories.summary = summary.trim().to_string();
+        if memories.episodes.len() == self.capacity {
+            memories.episodes.remove(0);
+        }
+        memories.episodes.push((
+            Episode {
+                input: input.to_string(),
+                answer: answer.to_string(),
+            },
+            embedding,
+        ));
+        Ok(())
+    }
+
+    /// The `k` exchanges whose inputs are most similar to `input`, most similar first.
+    pub async fn relevant(&self, input: &str) -> Result<Vec<Episode>, LongTermMemoryError> {
+        if self.k == 0 || self.memories.lock().await.episodes.is_empty() {
+            return Ok(Vec::new());
+        }
+        let embedding = self.embedder.embed_query(input).await?;
+        let memories = self.memories.lock().await;
+        let mut scored = memories
+            .episodes
+            .iter()
+            .map(|(episode, stored)| (cosine_similarity(stored, &embedding), episode))
+            .collect::<Vec<_>>();
+        scored.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        Ok(scored
+            .into_iter()
+            .take(self.k)
+            .map(|(_, episode)| episode.clone())
+            .collect())
+    }
+
+    /// The summary and the relevant exchanges rendered for a prompt, or `None` when nothing
+    /// was remembered yet.
+    pub async fn recall(&self, input: &str) -> Result<Option<String>, LongTermMemoryError> {
+        let episodes = self.relevant(input).await?;
+        let summary = self.summary().await;
+        if summary.is_empty() && episodes.is_empty() {
+            return Ok(None);
+        }
+        let mut out = format!("What you remember from earlier sessions:\n{}", summary);
+        if !episodes.is_empty() {
+            out.push_str("\n\nRelated earlier exchanges:");
+            for episode in episodes {
+                out.push_str(&format!(
+                    "\nUser: {}\nAssistant: {}",
+                    episode.input, episode.answer
+                ));
+            }
+        }
+        Ok(Some(out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{FakeLLM, LetterEmbedder};
+
+    /// Summarizes by appending the new input to the summary.
+    fn appender() -> FakeLLM {
+        FakeLLM::replying(|messages| {
+            let prompt = &messages[0].content;
+            let summary = prompt
+                .split("Summary:\n")
+                .nth(1)
+                .and_then(|rest| rest.split("\n\n").next())
+                .unwrap_or_default();
+            let input = prompt
+                .split("User: ")
+                .nth(1)
+                .and_then(|rest| rest.lines().next())
+                .unwrap_or_default();
+            format!("{} {}", summary, input)
+        })
+    }
+
+    #[tokio::test]
+    async fn test_recalls_summary_and_similar_exchanges() {
+        let memory = LongTermMemory::new(appender(), LetterEmbedder).with_k(1);
+        assert_eq!(memory.recall("aa").await.unwrap(), None);
+
+        memory.remember("aaa", "first").await.unwrap();
+        memory.remember("ccc", "second").await.unwrap();
+        assert_eq!(memory.summary().await, "aaa ccc");
+
+        let recalled = memory.recall("cc").await.unwrap().unwrap();
+        assert!(recalled.contains("aaa ccc"));
+        assert!(recalled.contains("User: ccc\nAssistant: second"));
+        assert!(!recalled.contains("first"));
+    }
+}
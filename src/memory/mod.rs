@@ -1,7 +1,17 @@
 mod dummy_memory;
+#[cfg(feature = "encryption")]
+mod encrypted_file_memory;
+mod long_term_memory;
+mod pruning;
 mod simple_memory;
+mod timestamped_memory;
 mod window_buffer;
 
 pub use dummy_memory::*;
+#[cfg(feature = "encryption")]
+pub use encrypted_file_memory::*;
+pub use long_term_memory::*;
+pub use pruning::*;
 pub use simple_memory::*;
+pub use timestamped_memory::*;
 pub use window_buffer::*;
@@ -0,0 +1,83 @@
+use crate::{
+    prompt::estimate_tokens,
+    schemas::{Message, MessageType},
+};
+
+/// The most recent `messages` that fit in `max_tokens`, counting their content and tool
+/// calls, preceded by a note saying how many were left out, so a long conversation can still
+/// be sent to a model with a small context window.
+///
+/// Tool results are never kept without the message holding their calls, which providers
+/// reject.
+pub fn prune_messages(messages: Vec<Message>, max_tokens: usize) -> Vec<Message> {
+    let mut tokens = 0;
+    let mut start = messages.len();
+    for (i, message) in messages.iter().enumerate().rev() {
+        tokens += estimate_tokens(&message.content);
+        if let Some(calls) = &message.tool_calls {
+            tokens += estimate_tokens(&calls.to_string());
+        }
+        if tokens > max_tokens {
+            break;
+        }
+        start = i;
+    }
+    while messages
+        .get(start)
+        .is_some_and(|m| m.message_type == MessageType::ToolMessage)
+    {
+        start += 1;
+    }
+    if start == 0 {
+        return messages;
+    }
+
+    log::debug!("Pruned {} messages from the history", start);
+    let mut pruned = vec![Message::new_system_message(format!(
+        "{} earlier messages of this conversation were omitted.",
+        start
+    ))];
+    pruned.extend(messages.into_iter().skip(start));
+    pruned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_messages() {
+        let messages = vec![
+            Message::new_human_message("a".repeat(40)),
+            Message::new_ai_message("").with_tool_calls(serde_json::json!([])),
+            Message::new_tool_message("b".repeat(40), "call-1"),
+            Message::new_human_message("c".repeat(40)),
+            Message::new_ai_message("d".repeat(40)),
+        ];
+
+        assert_eq!(prune_messages(messages.clone(), 100).len(), 5);
+
+        let pruned = prune_messages(messages, 25);
+        assert_eq!(pruned.len(), 3);
+        assert_eq!(pruned[0].message_type, MessageType::SystemMessage);
+        assert!(pruned[0].content.starts_with("3 earlier messages"));
+        assert_eq!(pruned[1].content, "c".repeat(40));
+    }
+
+    #[test]
+    fn test_counts_tool_calls() {
+        let messages = vec![
+            Message::new_human_message("a".repeat(40)),
+            Message::new_ai_message("").with_tool_calls(serde_json::json!([{
+                "id": "call-1",
+                "type": "function",
+                "function": {"name": "search", "arguments": "x".repeat(200)},
+            }])),
+            Message::new_tool_message("b", "call-1"),
+        ];
+
+        let pruned = prune_messages(messages, 40);
+        assert_eq!(pruned.len(), 1);
+        assert!(pruned[0].content.starts_with("3 earlier messages"));
+    }
+}
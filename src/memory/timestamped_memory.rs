@@ -0,0 +1,103 @@
+use std::{sync::Arc, time::SystemTime};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::{
+    retention::{Purgeable, RetentionError, RetentionPolicy},
+    schemas::{memory::BaseMemory, messages::Message},
+};
+
+/// In-memory history that records when each message was added, so it can be purged by age.
+///
+/// Share it as `Arc<Mutex<TimestampedMemory>>`: the same handle coerces to both the
+/// `Arc<Mutex<dyn BaseMemory>>` an executor expects and the `Arc<dyn Purgeable>` a
+/// [`crate::retention::RetentionPurger`] expects.
+#[derive(Default)]
+pub struct TimestampedMemory {
+    messages: Vec<(SystemTime, Message)>,
+}
+
+impl TimestampedMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drops messages older than the policy's `max_age`, then trims to `max_messages`.
+    pub fn apply(&mut self, policy: &RetentionPolicy, now: SystemTime) -> usize {
+        let before = self.messages.len();
+        if let Some(max_age) = policy.max_age {
+            self.messages.retain(|(added, _)| {
+                now.duration_since(*added)
+                    .map(|age| age <= max_age)
+                    .unwrap_or(true)
+            });
+        }
+        if let Some(max_messages) = policy.max_messages {
+            let excess = self.messages.len().saturating_sub(max_messages);
+            self.messages.drain(..excess);
+        }
+        before - self.messages.len()
+    }
+}
+
+impl From<TimestampedMemory> for Arc<Mutex<dyn BaseMemory>> {
+    fn from(memory: TimestampedMemory) -> Self {
+        Arc::new(Mutex::new(memory))
+    }
+}
+
+impl BaseMemory for TimestampedMemory {
+    fn messages(&self) -> Vec<Message> {
+        self.messages
+            .iter()
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+
+    fn add_message(&mut self, message: Message) {
+        self.messages.push((SystemTime::now(), message));
+    }
+
+    fn clear(&mut self) {
+        self.messages.clear();
+    }
+}
+
+#[async_trait]
+impl Purgeable for Mutex<TimestampedMemory> {
+    fn name(&self) -> String {
+        "timestamped_memory".to_string()
+    }
+
+    async fn purge(&self, policy: &RetentionPolicy) -> Result<usize, RetentionError> {
+        Ok(self.lock().await.apply(policy, SystemTime::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_apply_drops_expired_then_excess_messages() {
+        let mut memory = TimestampedMemory::new();
+        memory.add_user_message(&"old");
+        memory.add_user_message(&"recent");
+        memory.add_user_message(&"latest");
+        let now = SystemTime::now() + Duration::from_secs(60);
+        memory.messages[0].0 = now - Duration::from_secs(3600);
+
+        let policy = RetentionPolicy::new()
+            .with_max_age(Duration::from_secs(600))
+            .with_max_messages(1);
+        assert_eq!(memory.apply(&policy, now), 2);
+        assert_eq!(memory.messages()[0].content, "latest");
+
+        let shared = Arc::new(Mutex::new(memory));
+        let _executor_memory: Arc<Mutex<dyn BaseMemory>> = shared.clone();
+        let _purge_target: Arc<dyn Purgeable> = shared;
+    }
+}
@@ -0,0 +1,285 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::language_models::llm::LLM;
+
+use super::{MarkdownParser, OutputParser, OutputParserError};
+
+type Check = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+/// Enforces an output contract on final answers so downstream systems get the same shape from
+/// every agent.
+///
+/// The answer is validated against the contract: required markdown sections, a JSON schema
+/// (`type`, `required` and property `type`s are checked), a maximum length in characters and
+/// any custom checks. An answer that violates it is reformatted with one call to the
+/// formatting LLM, ideally a cheap one, and validated again. The target language is only
+/// enforced through that call; add a custom check with a language detector to validate it.
+///
+/// ```rust,ignore
+/// let formatter = AnswerFormatter::new()
+///     .with_sections(&["Summary", "Sources"])
+///     .with_max_length(1200)
+///     .with_llm(cheap_llm);
+/// let answer = formatter.parse(&answer).await?;
+/// ```
+#[derive(Clone, Default)]
+pub struct AnswerFormatter {
+    llm: Option<Arc<dyn LLM>>,
+    sections: Vec<String>,
+    json_schema: Option<Value>,
+    max_length: Option<usize>,
+    language: Option<String>,
+    checks: Vec<Check>,
+}
+
+impl AnswerFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// LLM used to reformat answers that break the contract. Without one they are rejected.
+    pub fn with_llm<L: LLM + 'static>(mut self, llm: L) -> Self {
+        self.llm = Some(Arc::new(llm));
+        self
+    }
+
+    /// Markdown headings the answer must contain, at any level.
+    pub fn with_sections<S: AsRef<str>>(mut self, sections: &[S]) -> Self {
+        self.sections = sections.iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
+    /// The answer must be JSON matching `schema`. A JSON code fence around it is removed.
+    pub fn with_json_schema(mut self, schema: Value) -> Self {
+        self.json_schema = Some(schema);
+        self
+    }
+
+    pub fn with_max_length(mut self, max_length: usize) -> Self {
+        self.max_length = Some(max_length);
+        self
+    }
+
+    pub fn with_language<S: Into<String>>(mut self, language: S) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    /// A custom rule, returning why the answer breaks it.
+    pub fn with_check<F: Fn(&str) -> Result<(), String> + Send + Sync + 'static>(
+        mut self,
+        check: F,
+    ) -> Self {
+        self.checks.push(Arc::new(check));
+        self
+    }
+
+    /// Every way `answer` breaks the contract.
+    pub async fn violations(&self, answer: &str) -> Vec<String> {
+        let mut violations = Vec::new();
+        for section in &self.sections {
+            let present = answer.lines().any(|line| {
+                let line = line.trim_start();
+                line.starts_with('#') && line.trim_start_matches('#').trim() == section
+            });
+            if !present {
+                violations.push(format!("Missing the markdown section \"{}\"", section));
+            }
+        }
+        if let Some(schema) = &self.json_schema {
+            match serde_json::from_str::<Value>(&self.json_text(answer).await) {
+                Ok(value) => validate_schema(&value, schema, "$", &mut violations),
+                Err(e) => violations.push(format!("Not valid JSON: {}", e)),
+            }
+        }
+        if let Some(max_length) = self.max_length {
+            let length = answer.chars().count();
+            if length > max_length {
+                violations.push(format!(
+                    "{} characters long, at most {} are allowed",
+                    length, max_length
+                ));
+            }
+        }
+        for check in &self.checks {
+            if let Err(violation) = check(answer) {
+                violations.push(violation);
+            }
+        }
+        violations
+    }
+
+    async fn json_text(&self, answer: &str) -> String {
+        MarkdownParser::new()
+            .with_trim(true)
+            .parse(answer)
+            .await
+            .unwrap_or_else(|_| answer.trim().to_string())
+    }
+
+    fn contract(&self) -> String {
+        let mut contract = Vec::new();
+        if !self.sections.is_empty() {
+            contract.push(format!(
+                "- Use exactly these markdown sections, as headings: {}",
+                self.sections.join(", ")
+            ));
+        }
+        if let Some(schema) = &self.json_schema {
+            contract.push(format!(
+                "- Reply with JSON only, matching this schema: {}",
+                schema
+            ));
+        }
+        if let Some(max_length) = self.max_length {
+            contract.push(format!("- At most {} characters", max_length));
+        }
+        if let Some(language) = &self.language {
+            contract.push(format!("- Written in {}", language));
+        }
+        contract.join("\n")
+    }
+
+    async fn finish(&self, answer: &str) -> String {
+        if self.json_schema.is_some() {
+            self.json_text(answer).await
+        } else {
+            answer.trim().to_string()
+        }
+    }
+}
+
+#[async_trait]
+impl OutputParser for AnswerFormatter {
+    async fn parse(&self, output: &str) -> Result<String, OutputParserError> {
+        let violations = self.violations(output).await;
+        // The language can only be checked by the LLM, so an answer with a target language
+        // always goes through it.
+        if violations.is_empty() && (self.language.is_none() || self.llm.is_none()) {
+            return Ok(self.finish(output).await);
+        }
+        let Some(llm) = &self.llm else {
+            return Err(OutputParserError::ParsingError(violations.join("; ")));
+        };
+
+        let mut prompt = format!(
+            "Reformat the answer below to follow these rules, keeping its content:\n{}\n\n",
+            self.contract()
+        );
+        if !violations.is_empty() {
+            prompt.push_str(&format!(
+                "The answer currently breaks them as follows:\n- {}\n\n",
+                violations.join("\n- ")
+            ));
+        }
+        prompt.push_str(&format!(
+            "Answer:\n{}\n\nReply with the reformatted answer only.",
+            output
+        ));
+        let reformatted = llm
+            .invoke(&prompt)
+            .await
+            .map_err(|e| OutputParserError::ParsingError(e.to_string()))?;
+
+        let violations = self.violations(&reformatted).await;
+        if violations.is_empty() {
+            Ok(self.finish(&reformatted).await)
+        } else {
+            Err(OutputParserError::ParsingError(violations.join("; ")))
+        }
+    }
+}
+
+fn validate_schema(value: &Value, schema: &Value, path: &str, violations: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type").and_then(Value::as_str) {
+        let matches = match expected {
+            "object" => value.is_object(),
+            "array" => value.is_array(),
+            "string" => value.is_string(),
+            "number" => value.is_number(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "boolean" => value.is_boolean(),
+            "null" => value.is_null(),
+            _ => true,
+        };
+        if !matches {
+            violations.push(format!("{} should be of type {}", path, expected));
+            return;
+        }
+    }
+    if let Some(required) = schema.get("required").and_then(Value::as_array) {
+        for key in required.iter().filter_map(Value::as_str) {
+            if value.get(key).is_none() {
+                violations.push(format!("{} is missing the field {}", path, key));
+            }
+        }
+    }
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (key, property) in properties {
+            if let Some(field) = value.get(key) {
+                validate_schema(field, property, &format!("{}.{}", path, key), violations);
+            }
+        }
+    }
+    if let (Some(items), Some(values)) = (schema.get("items"), value.as_array()) {
+        for (i, item) in values.iter().enumerate() {
+            validate_schema(item, items, &format!("{}[{}]", path, i), violations);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::test_utils::FakeLLM;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["answer", "confidence"],
+            "properties": {
+                "answer": {"type": "string"},
+                "confidence": {"type": "number"}
+            }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_reports_violations() {
+        let formatter = AnswerFormatter::new()
+            .with_sections(&["Summary"])
+            .with_json_schema(schema())
+            .with_max_length(10);
+
+        let violations = formatter.violations("```json\n{\"answer\": 42}\n```").await;
+
+        assert_eq!(violations.len(), 4, "{:?}", violations);
+        assert!(violations.contains(&"$.answer should be of type string".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_reformats_with_llm() {
+        let formatter = AnswerFormatter::new()
+            .with_json_schema(schema())
+            .with_llm(FakeLLM::fixed(
+                "```json\n{\"answer\": \"42\", \"confidence\": 0.9}\n```",
+            ));
+
+        let answer = formatter.parse("The answer is 42").await.unwrap();
+
+        assert_eq!(answer, "{\"answer\": \"42\", \"confidence\": 0.9}");
+    }
+
+    #[tokio::test]
+    async fn test_rejects_without_llm() {
+        let formatter = AnswerFormatter::new().with_sections(&["Summary"]);
+
+        assert!(formatter.parse("## Summary\nAll good").await.is_ok());
+        assert!(formatter.parse("All good").await.is_err());
+    }
+}
@@ -1,22 +1,32 @@
+use std::sync::OnceLock;
+
 use async_trait::async_trait;
 use regex::Regex;
 
 use super::{OutputParser, OutputParserError};
 
+const DEFAULT_EXPRESION: &str = r"```(?:\w+)?\s*([\s\S]+?)\s*```";
+
+fn default_regex() -> &'static Regex {
+    static DEFAULT_REGEX: OnceLock<Regex> = OnceLock::new();
+    DEFAULT_REGEX.get_or_init(|| Regex::new(DEFAULT_EXPRESION).unwrap())
+}
+
 pub struct MarkdownParser {
-    expresion: String,
+    /// Compiled once, an invalid custom expression is reported by every parse.
+    expresion: Result<Regex, regex::Error>,
     trim: bool,
 }
 impl MarkdownParser {
     pub fn new() -> Self {
         Self {
-            expresion: r"```(?:\w+)?\s*([\s\S]+?)\s*```".to_string(),
+            expresion: Ok(default_regex().clone()),
             trim: false,
         }
     }
 
     pub fn with_custom_expresion(mut self, expresion: &str) -> Self {
-        self.expresion = expresion.to_string();
+        self.expresion = Regex::new(expresion);
         self
     }
 
@@ -34,7 +44,7 @@ impl Default for MarkdownParser {
 #[async_trait]
 impl OutputParser for MarkdownParser {
     async fn parse(&self, output: &str) -> Result<String, OutputParserError> {
-        let re = Regex::new(r"```(?:\w+)?\s*([\s\S]+?)\s*```")?;
+        let re = self.expresion.as_ref().map_err(|e| e.clone())?;
         if let Some(cap) = re.captures(output) {
             let find = cap[1].to_string();
             if self.trim {
@@ -73,4 +83,18 @@ fn main() {
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), correct);
     }
+
+    #[tokio::test]
+    async fn test_markdown_parser_uses_custom_expresion() {
+        let parser = MarkdownParser::new().with_custom_expresion(r"<code>([\s\S]+?)</code>");
+        let result = parser.parse("Answer: <code>42</code>").await;
+        assert_eq!(result.unwrap(), "42");
+    }
+
+    #[tokio::test]
+    async fn test_markdown_parser_reports_invalid_expresion() {
+        let parser = MarkdownParser::new().with_custom_expresion(r"<code>(");
+        let result = parser.parse("Answer: <code>42</code>").await;
+        assert!(matches!(result, Err(OutputParserError::RegexError(_))));
+    }
 }
@@ -7,5 +7,8 @@ pub use markdown_parser::*;
 mod simple_parser;
 pub use simple_parser::*;
 
+mod answer_formatter;
+pub use answer_formatter::*;
+
 mod error;
 pub use error::*;
@@ -0,0 +1,253 @@
+use std::{ops::Deref, sync::Arc};
+
+use crate::{
+    schemas::{FunctionDefinition, Message},
+    tools::Tool,
+};
+
+use super::{
+    estimate_tokens, MessageFormatter, PromptArgs, PromptError, PromptFromatter, TokenCounter,
+};
+
+/// A prompt rendered without calling a model, with assertions to catch prompt regressions in
+/// `cargo test`.
+///
+/// The assertions panic with the rendered prompt in the message, like `assert!`, and return
+/// the prompt so they chain. Agent planning prompts are rendered with `agent::PromptProbe`.
+///
+/// ```rust,ignore
+/// RenderedPrompt::from_formatter(&prompt, prompt_args! {"input" => "What is 2 + 2?"})?
+///     .assert_section("Context")
+///     .assert_contains("What is 2 + 2?")
+///     .assert_max_tokens(500);
+/// ```
+pub struct RenderedPrompt {
+    messages: Vec<Message>,
+    functions: Vec<FunctionDefinition>,
+    counter: TokenCounter,
+}
+
+impl RenderedPrompt {
+    pub fn new(messages: Vec<Message>, functions: Vec<FunctionDefinition>) -> Self {
+        Self {
+            messages,
+            functions,
+            counter: Arc::new(estimate_tokens),
+        }
+    }
+
+    pub fn from_formatter(
+        formatter: &dyn MessageFormatter,
+        input_variables: PromptArgs,
+    ) -> Result<Self, PromptError> {
+        Ok(Self::new(
+            formatter.format_messages(input_variables)?,
+            Vec::new(),
+        ))
+    }
+
+    pub fn from_template(
+        template: &dyn PromptFromatter,
+        input_variables: PromptArgs,
+    ) -> Result<Self, PromptError> {
+        let text = template.format(input_variables)?;
+        Ok(Self::new(
+            vec![Message::new_human_message(text)],
+            Vec::new(),
+        ))
+    }
+
+    /// Counts tokens with the model's tokenizer instead of [`estimate_tokens`].
+    pub fn with_token_counter<F: Fn(&str) -> usize + Send + Sync + 'static>(
+        mut self,
+        counter: F,
+    ) -> Self {
+        self.counter = Arc::new(counter);
+        self
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    /// The tool definitions sent along with the messages, for tool calling agents.
+    pub fn functions(&self) -> &[FunctionDefinition] {
+        &self.functions
+    }
+
+    /// The content of every message, separated by blank lines.
+    pub fn text(&self) -> String {
+        self.messages
+            .iter()
+            .map(|message| message.content.as_str())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// Tokens of the messages and tool definitions.
+    pub fn tokens(&self) -> usize {
+        let functions = self
+            .functions
+            .iter()
+            .map(|function| {
+                format!(
+                    "{}\n{}\n{}\n",
+                    function.name, function.description, function.parameters
+                )
+            })
+            .collect::<String>();
+        (self.counter)(&self.text()) + (self.counter)(&functions)
+    }
+
+    /// Whether a line of the prompt is `heading`, ignoring case, Markdown `#` marks and a
+    /// trailing colon.
+    pub fn has_section(&self, heading: &str) -> bool {
+        let heading = heading.trim().to_lowercase();
+        self.text().lines().any(|line| {
+            line.trim()
+                .trim_start_matches('#')
+                .trim_end_matches(':')
+                .trim()
+                .to_lowercase()
+                == heading
+        })
+    }
+
+    /// Whether the model is told about `tool`, either by its definition or by its name and
+    /// description in the messages.
+    pub fn includes_tool<T>(&self, tool: &T) -> bool
+    where
+        T: Deref<Target = dyn Tool> + ?Sized,
+    {
+        let definition = FunctionDefinition::from_langchain_tool(tool);
+        let text = self.text();
+        self.functions.iter().any(|function| {
+            function.name == definition.name
+                && function.description == definition.description
+                && function.parameters == definition.parameters
+        }) || (text.contains(&tool.name()) && text.contains(&tool.description()))
+    }
+
+    pub fn assert_contains(&self, text: &str) -> &Self {
+        assert!(
+            self.text().contains(text),
+            "Prompt does not contain {:?}:\n{}",
+            text,
+            self.text()
+        );
+        self
+    }
+
+    pub fn assert_not_contains(&self, text: &str) -> &Self {
+        assert!(
+            !self.text().contains(text),
+            "Prompt contains {:?}:\n{}",
+            text,
+            self.text()
+        );
+        self
+    }
+
+    pub fn assert_section(&self, heading: &str) -> &Self {
+        assert!(
+            self.has_section(heading),
+            "Prompt has no {:?} section:\n{}",
+            heading,
+            self.text()
+        );
+        self
+    }
+
+    pub fn assert_max_tokens(&self, max_tokens: usize) -> &Self {
+        let tokens = self.tokens();
+        assert!(
+            tokens <= max_tokens,
+            "Prompt has {} tokens, more than {}:\n{}",
+            tokens,
+            max_tokens,
+            self.text()
+        );
+        self
+    }
+
+    pub fn assert_tool<T>(&self, tool: &T) -> &Self
+    where
+        T: Deref<Target = dyn Tool> + ?Sized,
+    {
+        assert!(
+            self.includes_tool(tool),
+            "Prompt does not describe the tool {}:\n{}",
+            tool.name(),
+            self.text()
+        );
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use async_trait::async_trait;
+    use serde_json::Value;
+
+    use crate::{prompt_args, template_fstring};
+
+    use super::*;
+
+    struct Weather;
+
+    #[async_trait]
+    impl Tool for Weather {
+        fn name(&self) -> String {
+            "weather".to_string()
+        }
+
+        fn description(&self) -> String {
+            "Current weather of a city".to_string()
+        }
+
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            Ok("Sunny".to_string())
+        }
+    }
+
+    #[test]
+    fn test_assertions_on_rendered_template() {
+        let template = template_fstring!(
+            "## Context:\n{context}\n\nQuestion: {input}",
+            "context",
+            "input"
+        );
+        let prompt = RenderedPrompt::from_template(
+            &template,
+            prompt_args! {"context" => "It rains in Lima.", "input" => "Weather in Lima?"},
+        )
+        .unwrap();
+        prompt
+            .assert_section("context")
+            .assert_contains("Weather in Lima?")
+            .assert_max_tokens(20);
+        assert!(!prompt.has_section("Question"));
+        assert!(prompt.tokens() > 10);
+
+        let weather: Arc<dyn Tool> = Arc::new(Weather);
+        assert!(!prompt.includes_tool(&weather));
+        let with_function = RenderedPrompt::new(
+            prompt.messages().to_vec(),
+            vec![FunctionDefinition::from_langchain_tool(&weather)],
+        );
+        with_function.assert_tool(&weather);
+        assert!(with_function.tokens() > prompt.tokens());
+        assert_eq!(with_function.functions()[0].name, "weather");
+    }
+
+    #[test]
+    #[should_panic(expected = "more than 5")]
+    fn test_assert_max_tokens_panics_over_limit() {
+        let template = template_fstring!("{input}", "input");
+        RenderedPrompt::from_template(&template, prompt_args! {"input" => "a".repeat(100)})
+            .unwrap()
+            .assert_max_tokens(5);
+    }
+}
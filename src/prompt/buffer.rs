@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+use super::{PromptArgs, PromptError, PromptTemplate};
+
+/// Reusable buffer for assembling prompts.
+///
+/// Keeping one `PromptBuffer` around (per executor, per worker, ...) and rendering into it keeps
+/// its allocation alive between invocations, so high-throughput batch jobs don't pay for a new
+/// string, and its growth, on every prompt.
+///
+/// # Usage
+/// ```rust,ignore
+/// let mut buffer = PromptBuffer::with_capacity(4096);
+/// for question in questions {
+///     let prompt = buffer
+///         .clear()
+///         .push_str("Answer briefly.\n")
+///         .push_template(&template, &prompt_args! {"question" => question})?
+///         .as_str();
+///     llm.invoke(prompt).await?;
+/// }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct PromptBuffer {
+    buf: String,
+}
+
+impl PromptBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buf: String::with_capacity(capacity),
+        }
+    }
+
+    /// Empties the buffer, keeping its allocation.
+    pub fn clear(&mut self) -> &mut Self {
+        self.buf.clear();
+        self
+    }
+
+    pub fn push_str(&mut self, s: &str) -> &mut Self {
+        self.buf.push_str(s);
+        self
+    }
+
+    pub fn push_display<D: std::fmt::Display>(&mut self, value: D) -> &mut Self {
+        let _ = write!(self.buf, "{}", value);
+        self
+    }
+
+    pub fn push_template(
+        &mut self,
+        template: &PromptTemplate,
+        input_variables: &PromptArgs,
+    ) -> Result<&mut Self, PromptError> {
+        template.format_into(input_variables, &mut self.buf)?;
+        Ok(self)
+    }
+
+    /// Clears the buffer and renders `template` into it.
+    pub fn render(
+        &mut self,
+        template: &PromptTemplate,
+        input_variables: &PromptArgs,
+    ) -> Result<&str, PromptError> {
+        self.clear();
+        template.format_into(input_variables, &mut self.buf)?;
+        Ok(&self.buf)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.buf
+    }
+
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    /// Takes the assembled prompt, leaving an empty buffer behind.
+    pub fn take(&mut self) -> String {
+        std::mem::take(&mut self.buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{prompt_args, template_fstring};
+
+    #[test]
+    fn test_buffer_reuses_allocation() {
+        let template = template_fstring!("Hello {name}!", "name");
+        let mut buffer = PromptBuffer::with_capacity(64);
+
+        assert_eq!(
+            buffer
+                .render(&template, &prompt_args! {"name" => "world"})
+                .unwrap(),
+            "Hello world!"
+        );
+        let capacity = buffer.capacity();
+
+        let prompt = buffer
+            .clear()
+            .push_str("> ")
+            .push_template(&template, &prompt_args! {"name" => "again"})
+            .unwrap()
+            .push_display(1)
+            .as_str();
+        assert_eq!(prompt, "> Hello again!1");
+        assert_eq!(buffer.capacity(), capacity);
+    }
+}
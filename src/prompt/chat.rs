@@ -26,7 +26,15 @@ impl HumanMessagePromptTemplate {
 }
 impl MessageFormatter for HumanMessagePromptTemplate {
     fn format_messages(&self, input_variables: PromptArgs) -> Result<Vec<Message>, PromptError> {
-        let message = Message::new_human_message(self.prompt.format(input_variables)?);
+        self.format_messages_ref(&input_variables)
+    }
+    fn format_messages_ref(
+        &self,
+        input_variables: &PromptArgs,
+    ) -> Result<Vec<Message>, PromptError> {
+        let mut content = String::new();
+        self.prompt.format_into(input_variables, &mut content)?;
+        let message = Message::new_human_message(content);
         log::debug!("message: {:?}", message);
         Ok(vec![message])
     }
@@ -79,7 +87,15 @@ impl FormatPrompter for SystemMessagePromptTemplate {
 
 impl MessageFormatter for SystemMessagePromptTemplate {
     fn format_messages(&self, input_variables: PromptArgs) -> Result<Vec<Message>, PromptError> {
-        let message = Message::new_system_message(self.prompt.format(input_variables)?);
+        self.format_messages_ref(&input_variables)
+    }
+    fn format_messages_ref(
+        &self,
+        input_variables: &PromptArgs,
+    ) -> Result<Vec<Message>, PromptError> {
+        let mut content = String::new();
+        self.prompt.format_into(input_variables, &mut content)?;
+        let message = Message::new_system_message(content);
         log::debug!("message: {:?}", message);
         Ok(vec![message])
     }
@@ -115,7 +131,15 @@ impl FormatPrompter for AIMessagePromptTemplate {
 
 impl MessageFormatter for AIMessagePromptTemplate {
     fn format_messages(&self, input_variables: PromptArgs) -> Result<Vec<Message>, PromptError> {
-        let message = Message::new_ai_message(self.prompt.format(input_variables)?);
+        self.format_messages_ref(&input_variables)
+    }
+    fn format_messages_ref(
+        &self,
+        input_variables: &PromptArgs,
+    ) -> Result<Vec<Message>, PromptError> {
+        let mut content = String::new();
+        self.prompt.format_into(input_variables, &mut content)?;
+        let message = Message::new_ai_message(content);
         log::debug!("message: {:?}", message);
         Ok(vec![message])
     }
@@ -207,17 +231,16 @@ impl MessageFormatterStruct {
         ));
     }
 
-    fn format(&self, input_variables: PromptArgs) -> Result<Vec<Message>, PromptError> {
+    fn format(&self, input_variables: &PromptArgs) -> Result<Vec<Message>, PromptError> {
         let mut result: Vec<Message> = Vec::new();
         for item in &self.items {
             match item {
                 MessageOrTemplate::Message(msg) => result.push(msg.clone()),
                 MessageOrTemplate::Template(tmpl) => {
-                    result.extend(tmpl.format_messages(input_variables.clone())?)
+                    result.extend(tmpl.format_messages_ref(input_variables)?)
                 }
                 MessageOrTemplate::MessagesPlaceholder(placeholder) => {
-                    let messages = input_variables[placeholder].clone();
-                    result.extend(Message::messages_from_value(&messages)?);
+                    result.extend(Message::messages_from_value(&input_variables[placeholder])?);
                 }
             }
         }
@@ -227,6 +250,12 @@ impl MessageFormatterStruct {
 
 impl MessageFormatter for MessageFormatterStruct {
     fn format_messages(&self, input_variables: PromptArgs) -> Result<Vec<Message>, PromptError> {
+        self.format(&input_variables)
+    }
+    fn format_messages_ref(
+        &self,
+        input_variables: &PromptArgs,
+    ) -> Result<Vec<Message>, PromptError> {
         self.format(input_variables)
     }
     fn input_variables(&self) -> Vec<String> {
@@ -248,7 +277,7 @@ impl MessageFormatter for MessageFormatterStruct {
 
 impl FormatPrompter for MessageFormatterStruct {
     fn format_prompt(&self, input_variables: PromptArgs) -> Result<PromptValue, PromptError> {
-        let messages = self.format(input_variables)?;
+        let messages = self.format(&input_variables)?;
         Ok(PromptValue::from_messages(messages))
     }
     fn get_input_variables(&self) -> Vec<String> {
@@ -9,6 +9,13 @@ pub enum PromptError {
     #[error("Serialization error: {0}")]
     SerializationError(#[from] SerdeJsonError),
 
+    #[error("Section {section} needs {needed} tokens but only {available} are left")]
+    TokenBudgetExceeded {
+        section: String,
+        needed: usize,
+        available: usize,
+    },
+
     #[error("Error: {0}")]
     OtherError(String),
 }
@@ -1,11 +1,17 @@
+mod assertions;
+mod buffer;
 mod chat;
 mod error;
+mod packer;
 mod prompt;
 
 use std::collections::HashMap;
 
+pub use assertions::*;
+pub use buffer::*;
 pub use chat::*;
 pub use error::*;
+pub use packer::*;
 pub use prompt::*;
 use serde_json::Value;
 
@@ -31,6 +37,15 @@ where
 pub trait MessageFormatter: Send + Sync {
     fn format_messages(&self, input_variables: PromptArgs) -> Result<Vec<Message>, PromptError>;
 
+    /// Same as `format_messages`, but borrows the input variables so formatters made of several
+    /// templates don't clone them (chat history and scratchpad included) once per template.
+    fn format_messages_ref(
+        &self,
+        input_variables: &PromptArgs,
+    ) -> Result<Vec<Message>, PromptError> {
+        self.format_messages(input_variables.clone())
+    }
+
     /// Returns a list of required input variable names for the template.
     fn input_variables(&self) -> Vec<String>;
 }
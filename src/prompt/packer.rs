@@ -0,0 +1,335 @@
+use std::{ops::Range, sync::Arc};
+
+use super::PromptError;
+
+/// Counts the tokens of a piece of text.
+pub type TokenCounter = Arc<dyn Fn(&str) -> usize + Send + Sync>;
+
+/// Rough count for when no tokenizer is at hand: one token per four bytes, rounded up.
+pub fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(4)
+}
+
+/// What to give up when a section does not fit in the budget left for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truncation {
+    /// The section is kept whole or packing fails, e.g. for the system prompt.
+    Never,
+    /// Keeps the first items and drops the rest, e.g. for retrieved documents ranked best
+    /// first. The last kept item may be cut at its end.
+    KeepFirst,
+    /// Keeps the last items and drops the oldest, e.g. for memory or the scratchpad. The first
+    /// kept item may be cut at its start.
+    KeepLast,
+    /// Keeps the section whole or leaves it out, e.g. for optional tool schemas.
+    DropSection,
+}
+
+/// One part of a prompt, made of items such as messages or documents.
+#[derive(Debug, Clone)]
+pub struct PromptSection {
+    pub name: String,
+    pub items: Vec<String>,
+    /// Sections with a higher priority are packed first.
+    pub priority: u32,
+    pub truncation: Truncation,
+    /// Caps the section below the overall budget.
+    pub max_tokens: Option<usize>,
+}
+
+impl PromptSection {
+    pub fn new<S: Into<String>>(name: S, items: Vec<String>) -> Self {
+        Self {
+            name: name.into(),
+            items,
+            priority: 0,
+            truncation: Truncation::KeepFirst,
+            max_tokens: None,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: u32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_truncation(mut self, truncation: Truncation) -> Self {
+        self.truncation = truncation;
+        self
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+}
+
+/// What was kept of a section.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedSection {
+    pub name: String,
+    /// Indices of the kept items in the section, to slice e.g. the original messages.
+    pub range: Range<usize>,
+    /// The kept items, the outermost one possibly truncated.
+    pub items: Vec<String>,
+    pub tokens: usize,
+}
+
+/// Sections in the order they were added to the packer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PackedPrompt {
+    pub sections: Vec<PackedSection>,
+    pub tokens: usize,
+}
+
+impl PackedPrompt {
+    pub fn section(&self, name: &str) -> Option<&PackedSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// Joins every kept item, skipping empty sections.
+    pub fn render(&self, separator: &str) -> String {
+        self.sections
+            .iter()
+            .flat_map(|section| section.items.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+}
+
+/// Packs prompt sections under an explicit token budget instead of sending everything and
+/// overflowing small context windows.
+///
+/// Sections are filled by descending priority, ties in the order they were added, each one
+/// taking what the sections before it left over. Item tokens are counted separately, so
+/// leave some headroom for separators and chat formatting.
+///
+/// ```rust,ignore
+/// let packed = ContextPacker::new(4096)
+///     .section(PromptSection::new("system", vec![system]).with_priority(3).with_truncation(Truncation::Never))
+///     .section(PromptSection::new("history", history).with_priority(1).with_truncation(Truncation::KeepLast))
+///     .section(PromptSection::new("docs", docs).with_priority(2).with_max_tokens(2048))
+///     .pack()?;
+/// let history = &messages[packed.section("history").unwrap().range.clone()];
+/// ```
+pub struct ContextPacker {
+    budget: usize,
+    counter: TokenCounter,
+    sections: Vec<PromptSection>,
+}
+
+impl ContextPacker {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            budget,
+            counter: Arc::new(estimate_tokens),
+            sections: Vec::new(),
+        }
+    }
+
+    /// Counts tokens with the model's tokenizer instead of [`estimate_tokens`].
+    pub fn with_token_counter<F: Fn(&str) -> usize + Send + Sync + 'static>(
+        mut self,
+        counter: F,
+    ) -> Self {
+        self.counter = Arc::new(counter);
+        self
+    }
+
+    pub fn section(mut self, section: PromptSection) -> Self {
+        self.sections.push(section);
+        self
+    }
+
+    pub fn pack(&self) -> Result<PackedPrompt, PromptError> {
+        let mut order = (0..self.sections.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| std::cmp::Reverse(self.sections[i].priority));
+
+        let mut packed = vec![None; self.sections.len()];
+        let mut remaining = self.budget;
+        for i in order {
+            let section = &self.sections[i];
+            let available = section
+                .max_tokens
+                .map_or(remaining, |max| max.min(remaining));
+            let kept = self.pack_section(section, available)?;
+            remaining = remaining.saturating_sub(kept.tokens);
+            packed[i] = Some(kept);
+        }
+
+        let sections = packed.into_iter().flatten().collect::<Vec<_>>();
+        Ok(PackedPrompt {
+            tokens: sections.iter().map(|section| section.tokens).sum(),
+            sections,
+        })
+    }
+
+    fn pack_section(
+        &self,
+        section: &PromptSection,
+        available: usize,
+    ) -> Result<PackedSection, PromptError> {
+        let counts = section
+            .items
+            .iter()
+            .map(|item| (self.counter)(item))
+            .collect::<Vec<_>>();
+        let total = counts.iter().sum::<usize>();
+        let whole = |range: Range<usize>| PackedSection {
+            name: section.name.clone(),
+            items: section.items[range.clone()].to_vec(),
+            tokens: counts[range.clone()].iter().sum(),
+            range,
+        };
+        if total <= available {
+            return Ok(whole(0..section.items.len()));
+        }
+
+        let len = section.items.len();
+        match section.truncation {
+            Truncation::Never => Err(PromptError::TokenBudgetExceeded {
+                section: section.name.clone(),
+                needed: total,
+                available,
+            }),
+            Truncation::DropSection => Ok(whole(0..0)),
+            Truncation::KeepFirst => {
+                let (mut end, mut used) = (0, 0);
+                while end < len && used + counts[end] <= available {
+                    used += counts[end];
+                    end += 1;
+                }
+                let mut kept = whole(0..end);
+                if end < len {
+                    let cut = self.truncate(&section.items[end], available - used, true);
+                    if !cut.is_empty() {
+                        kept.tokens += (self.counter)(&cut);
+                        kept.items.push(cut);
+                        kept.range.end += 1;
+                    }
+                }
+                Ok(kept)
+            }
+            Truncation::KeepLast => {
+                let (mut start, mut used) = (len, 0);
+                while start > 0 && used + counts[start - 1] <= available {
+                    start -= 1;
+                    used += counts[start];
+                }
+                let mut kept = whole(start..len);
+                if start > 0 {
+                    let cut = self.truncate(&section.items[start - 1], available - used, false);
+                    if !cut.is_empty() {
+                        kept.tokens += (self.counter)(&cut);
+                        kept.items.insert(0, cut);
+                        kept.range.start -= 1;
+                    }
+                }
+                Ok(kept)
+            }
+        }
+    }
+
+    /// The longest prefix (or suffix) of `text` within `budget` tokens, cut at a char boundary.
+    fn truncate(&self, text: &str, budget: usize, keep_start: bool) -> String {
+        let boundaries = text
+            .char_indices()
+            .map(|(i, _)| i)
+            .chain(std::iter::once(text.len()))
+            .collect::<Vec<_>>();
+        let part = |n: usize| {
+            if keep_start {
+                &text[..boundaries[n]]
+            } else {
+                &text[boundaries[boundaries.len() - 1 - n]..]
+            }
+        };
+
+        // Binary search for the most chars that still fit.
+        let (mut low, mut high) = (0, boundaries.len() - 1);
+        while low < high {
+            let mid = (low + high + 1) / 2;
+            if (self.counter)(part(mid)) <= budget {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        if keep_start {
+            part(low).trim_end().to_string()
+        } else {
+            part(low).trim_start().to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> usize {
+        text.split_whitespace().count()
+    }
+
+    fn items(items: &[&str]) -> Vec<String> {
+        items.iter().map(|item| item.to_string()).collect()
+    }
+
+    #[test]
+    fn test_packs_by_priority() {
+        let packed = ContextPacker::new(8)
+            .with_token_counter(words)
+            .section(
+                PromptSection::new("system", items(&["be brief"]))
+                    .with_priority(2)
+                    .with_truncation(Truncation::Never),
+            )
+            .section(
+                PromptSection::new("history", items(&["one two", "three four", "five six"]))
+                    .with_truncation(Truncation::KeepLast),
+            )
+            .section(
+                PromptSection::new("docs", items(&["a b c", "d e f"]))
+                    .with_priority(1)
+                    .with_max_tokens(4),
+            )
+            .pack()
+            .unwrap();
+
+        assert_eq!(packed.tokens, 8);
+        assert_eq!(packed.section("docs").unwrap().items, ["a b c", "d"]);
+        let history = packed.section("history").unwrap();
+        assert_eq!(history.range, 2..3);
+        assert_eq!(history.items, ["five six"]);
+        assert_eq!(packed.render(" | "), "be brief | five six | a b c | d");
+    }
+
+    #[test]
+    fn test_keep_last_cuts_oldest_item_from_start() {
+        let packed = ContextPacker::new(3)
+            .with_token_counter(words)
+            .section(
+                PromptSection::new("scratchpad", items(&["one two three", "four"]))
+                    .with_truncation(Truncation::KeepLast),
+            )
+            .pack()
+            .unwrap();
+
+        let scratchpad = packed.section("scratchpad").unwrap();
+        assert_eq!(scratchpad.range, 0..2);
+        assert_eq!(scratchpad.items, ["two three", "four"]);
+    }
+
+    #[test]
+    fn test_required_section_must_fit() {
+        let packer = ContextPacker::new(1).section(
+            PromptSection::new("tools", items(&["a long tool schema"]))
+                .with_truncation(Truncation::Never),
+        );
+
+        assert!(matches!(
+            packer.pack(),
+            Err(PromptError::TokenBudgetExceeded { .. })
+        ));
+    }
+}
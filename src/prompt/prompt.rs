@@ -1,3 +1,5 @@
+use std::fmt::Write;
+
 use crate::schemas::{messages::Message, prompt::PromptValue};
 
 use super::{FormatPrompter, PromptArgs, PromptError, PromptFromatter};
@@ -10,6 +12,8 @@ pub enum TemplateFormat {
 
 #[derive(Clone)]
 pub struct PromptTemplate {
+    /// Literal text rendered before the template, never scanned for placeholders.
+    prefix: String,
     template: String,
     variables: Vec<String>,
     format: TemplateFormat,
@@ -18,11 +22,23 @@ pub struct PromptTemplate {
 impl PromptTemplate {
     pub fn new(template: String, variables: Vec<String>, format: TemplateFormat) -> Self {
         Self {
+            prefix: String::new(),
             template,
             variables,
             format,
         }
     }
+
+    /// Returns a copy of the template with `prefix` prepended to it. The prefix is plain text:
+    /// braces in it are rendered as they are instead of being read as placeholders.
+    pub fn prepend(&self, prefix: &str) -> Self {
+        Self {
+            prefix: format!("{}{}", prefix, self.prefix),
+            template: self.template.clone(),
+            variables: self.variables.clone(),
+            format: self.format.clone(),
+        }
+    }
 }
 
 //PromptTemplate will be default transformed to an Human Input when used as FromatPrompter
@@ -38,7 +54,7 @@ impl FormatPrompter for PromptTemplate {
 
 impl PromptFromatter for PromptTemplate {
     fn template(&self) -> String {
-        self.template.clone()
+        format!("{}{}", self.prefix, self.template)
     }
 
     fn variables(&self) -> Vec<String> {
@@ -46,29 +62,62 @@ impl PromptFromatter for PromptTemplate {
     }
 
     fn format(&self, input_variables: PromptArgs) -> Result<String, PromptError> {
-        let mut prompt = self.template();
+        let mut prompt = String::with_capacity(self.prefix.len() + self.template.len());
+        self.format_into(&input_variables, &mut prompt)?;
+        log::debug!("Formatted prompt: {}", prompt);
+        Ok(prompt)
+    }
+}
 
+impl PromptTemplate {
+    /// Renders the template by appending it to `out`, without cloning the input variables or
+    /// allocating intermediate strings. Placeholders whose name is not in `input_variables` are
+    /// kept verbatim.
+    pub fn format_into(
+        &self,
+        input_variables: &PromptArgs,
+        out: &mut String,
+    ) -> Result<(), PromptError> {
         // check if all variables are in the input variables
-        for key in self.variables() {
+        for key in &self.variables {
             if !input_variables.contains_key(key.as_str()) {
-                return Err(PromptError::MissingVariable(key));
+                return Err(PromptError::MissingVariable(key.clone()));
             }
         }
 
-        for (key, value) in input_variables {
-            let key = match self.format {
-                TemplateFormat::FString => format!("{{{}}}", key),
-                TemplateFormat::Jinja2 => format!("{{{{{}}}}}", key),
-            };
-            let value_str = match &value {
-                serde_json::Value::String(s) => s.clone(),
-                _ => value.to_string(),
-            };
-            prompt = prompt.replace(&key, &value_str);
-        }
+        let (open, close) = match self.format {
+            TemplateFormat::FString => ("{", "}"),
+            TemplateFormat::Jinja2 => ("{{", "}}"),
+        };
 
-        log::debug!("Formatted prompt: {}", prompt);
-        Ok(prompt)
+        out.push_str(&self.prefix);
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find(open) {
+            out.push_str(&rest[..start]);
+            let after_open = &rest[start + open.len()..];
+            let value = after_open
+                .find(close)
+                .map(|end| (&after_open[..end], end))
+                .and_then(|(key, end)| input_variables.get(key).map(|value| (value, end)));
+            match value {
+                Some((value, end)) => {
+                    match value {
+                        serde_json::Value::String(s) => out.push_str(s),
+                        value => {
+                            // Display for Value writes the JSON encoding straight into `out`.
+                            let _ = write!(out, "{}", value);
+                        }
+                    }
+                    rest = &after_open[end + close.len()..];
+                }
+                None => {
+                    out.push_str(open);
+                    rest = after_open;
+                }
+            }
+        }
+        out.push_str(rest);
+        Ok(())
     }
 }
 
@@ -255,4 +304,37 @@ mod tests {
         let formatted_jinja2 = jinja2_template.format(input_variables_jinja2).unwrap();
         assert_eq!(formatted_jinja2, "Jinja2 Chat: Bob says Hi, Alice!");
     }
+
+    #[test]
+    fn should_render_single_pass() {
+        let template = template_fstring!("{a} {b} {missing} {a}", "a");
+        let input_variables = prompt_args! {
+            "a" => "{b}",
+            "b" => vec![1, 2],
+        };
+
+        let mut out = String::from("> ");
+        template.format_into(&input_variables, &mut out).unwrap();
+        // Substituted values are never re-scanned, and unknown placeholders are kept.
+        assert_eq!(out, "> {b} [1,2] {missing} {b}");
+    }
+
+    #[test]
+    fn should_keep_prepended_text_literal() {
+        let input_variables = prompt_args! {
+            "name" => "world",
+        };
+
+        let fstring = template_fstring!("Hello {name}!", "name").prepend("Say {name}.\n");
+        assert_eq!(
+            fstring.format(input_variables.clone()).unwrap(),
+            "Say {name}.\nHello world!"
+        );
+
+        let jinja2 = template_jinja2!("Hello {{name}}!", "name").prepend("Say {{name}}.\n");
+        assert_eq!(
+            jinja2.format(input_variables).unwrap(),
+            "Say {{name}}.\nHello world!"
+        );
+    }
 }
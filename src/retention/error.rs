@@ -0,0 +1,7 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RetentionError {
+    #[error("Purge failed for {target}: {reason}")]
+    PurgeFailed { target: String, reason: String },
+}
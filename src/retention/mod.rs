@@ -0,0 +1,8 @@
+mod error;
+pub use error::*;
+
+mod policy;
+pub use policy::*;
+
+mod purger;
+pub use purger::*;
@@ -0,0 +1,94 @@
+use std::{collections::HashMap, time::Duration};
+
+/// Limits on how much history a store may keep.
+///
+/// Every limit is optional; a store only enforces the limits that make sense for it (a memory
+/// has messages but no runs, a run log has runs but no messages).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RetentionPolicy {
+    pub max_age: Option<Duration>,
+    pub max_messages: Option<usize>,
+    pub max_runs: Option<usize>,
+}
+
+impl RetentionPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = Some(max_messages);
+        self
+    }
+
+    pub fn with_max_runs(mut self, max_runs: usize) -> Self {
+        self.max_runs = Some(max_runs);
+        self
+    }
+
+    /// Returns `self` with any unset limit taken from `fallback`.
+    pub fn or(self, fallback: &RetentionPolicy) -> Self {
+        Self {
+            max_age: self.max_age.or(fallback.max_age),
+            max_messages: self.max_messages.or(fallback.max_messages),
+            max_runs: self.max_runs.or(fallback.max_runs),
+        }
+    }
+}
+
+/// A default policy plus per-tenant overrides.
+///
+/// Overrides only need to set the limits they change; the rest fall back to the default.
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicies {
+    default: RetentionPolicy,
+    tenants: HashMap<String, RetentionPolicy>,
+}
+
+impl RetentionPolicies {
+    pub fn new(default: RetentionPolicy) -> Self {
+        Self {
+            default,
+            tenants: HashMap::new(),
+        }
+    }
+
+    pub fn with_tenant<S: Into<String>>(mut self, tenant: S, policy: RetentionPolicy) -> Self {
+        self.tenants.insert(tenant.into(), policy);
+        self
+    }
+
+    pub fn for_tenant(&self, tenant: Option<&str>) -> RetentionPolicy {
+        tenant
+            .and_then(|tenant| self.tenants.get(tenant))
+            .map(|policy| policy.clone().or(&self.default))
+            .unwrap_or_else(|| self.default.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tenant_override_falls_back_to_default() {
+        let policies = RetentionPolicies::new(
+            RetentionPolicy::new()
+                .with_max_age(Duration::from_secs(3600))
+                .with_max_messages(100),
+        )
+        .with_tenant("acme", RetentionPolicy::new().with_max_messages(10));
+
+        let acme = policies.for_tenant(Some("acme"));
+        assert_eq!(acme.max_messages, Some(10));
+        assert_eq!(acme.max_age, Some(Duration::from_secs(3600)));
+
+        assert_eq!(policies.for_tenant(Some("other")).max_messages, Some(100));
+        assert_eq!(policies.for_tenant(None).max_messages, Some(100));
+    }
+}
@@ -0,0 +1,174 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::schemas::memory::BaseMemory;
+
+use super::{RetentionError, RetentionPolicies, RetentionPolicy};
+
+/// A store that can drop data falling outside a [`RetentionPolicy`].
+#[async_trait]
+pub trait Purgeable: Send + Sync {
+    /// Name used in purge reports and errors.
+    fn name(&self) -> String;
+
+    /// Removes expired data and returns how many records were deleted.
+    async fn purge(&self, policy: &RetentionPolicy) -> Result<usize, RetentionError>;
+}
+
+/// Applies `max_messages` to any memory by keeping only its most recent messages.
+///
+/// Plain memories do not record when a message was added, so `max_age` is ignored; use
+/// [`crate::memory::TimestampedMemory`] when age-based purging is required.
+pub struct MemoryRetention {
+    name: String,
+    memory: Arc<Mutex<dyn BaseMemory>>,
+}
+
+impl MemoryRetention {
+    pub fn new<S: Into<String>>(name: S, memory: Arc<Mutex<dyn BaseMemory>>) -> Self {
+        Self {
+            name: name.into(),
+            memory,
+        }
+    }
+}
+
+#[async_trait]
+impl Purgeable for MemoryRetention {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn purge(&self, policy: &RetentionPolicy) -> Result<usize, RetentionError> {
+        let Some(max_messages) = policy.max_messages else {
+            return Ok(0);
+        };
+        let mut memory = self.memory.lock().await;
+        let messages = memory.messages();
+        if messages.len() <= max_messages {
+            return Ok(0);
+        }
+        let removed = messages.len() - max_messages;
+        memory.clear();
+        for message in messages.into_iter().skip(removed) {
+            memory.add_message(message);
+        }
+        Ok(removed)
+    }
+}
+
+/// Outcome of a single purge pass.
+#[derive(Debug, Default)]
+pub struct PurgeReport {
+    /// Records removed per target name.
+    pub removed: Vec<(String, usize)>,
+    /// Targets whose purge failed; the pass continues past them.
+    pub errors: Vec<RetentionError>,
+}
+
+impl PurgeReport {
+    pub fn total_removed(&self) -> usize {
+        self.removed.iter().map(|(_, removed)| removed).sum()
+    }
+}
+
+/// Enforces retention policies across registered stores.
+///
+/// Each target is registered with an optional tenant, which selects the per-tenant override
+/// from [`RetentionPolicies`].
+#[derive(Clone)]
+pub struct RetentionPurger {
+    policies: RetentionPolicies,
+    targets: Vec<(Option<String>, Arc<dyn Purgeable>)>,
+}
+
+impl RetentionPurger {
+    pub fn new(policies: RetentionPolicies) -> Self {
+        Self {
+            policies,
+            targets: Vec::new(),
+        }
+    }
+
+    pub fn with_target(mut self, target: Arc<dyn Purgeable>) -> Self {
+        self.targets.push((None, target));
+        self
+    }
+
+    pub fn with_tenant_target<S: Into<String>>(
+        mut self,
+        tenant: S,
+        target: Arc<dyn Purgeable>,
+    ) -> Self {
+        self.targets.push((Some(tenant.into()), target));
+        self
+    }
+
+    pub async fn purge_once(&self) -> PurgeReport {
+        let mut report = PurgeReport::default();
+        for (tenant, target) in &self.targets {
+            let policy = self.policies.for_tenant(tenant.as_deref());
+            match target.purge(&policy).await {
+                Ok(removed) => report.removed.push((target.name(), removed)),
+                Err(e) => {
+                    log::error!("Retention purge failed for {}: {}", target.name(), e);
+                    report.errors.push(e);
+                }
+            }
+        }
+        report
+    }
+
+    /// Runs [`Self::purge_once`] every `interval` on the tokio runtime until the handle is
+    /// aborted.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn spawn(self, interval: std::time::Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let report = self.purge_once().await;
+                log::debug!("Retention purge removed {} records", report.total_removed());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::SimpleMemory;
+
+    #[tokio::test]
+    async fn test_purge_applies_tenant_limits() {
+        let default_memory: Arc<Mutex<dyn BaseMemory>> = SimpleMemory::new().into();
+        let acme_memory: Arc<Mutex<dyn BaseMemory>> = SimpleMemory::new().into();
+        for i in 0..5 {
+            default_memory.lock().await.add_user_message(&i);
+            acme_memory.lock().await.add_user_message(&i);
+        }
+
+        let purger = RetentionPurger::new(
+            RetentionPolicies::new(RetentionPolicy::new().with_max_messages(4))
+                .with_tenant("acme", RetentionPolicy::new().with_max_messages(2)),
+        )
+        .with_target(Arc::new(MemoryRetention::new(
+            "default",
+            default_memory.clone(),
+        )))
+        .with_tenant_target(
+            "acme",
+            Arc::new(MemoryRetention::new("acme", acme_memory.clone())),
+        );
+
+        let report = purger.purge_once().await;
+        assert_eq!(report.total_removed(), 4);
+
+        let acme = acme_memory.lock().await.messages();
+        assert_eq!(acme.len(), 2);
+        assert_eq!(acme[0].content, "3");
+        assert_eq!(default_memory.lock().await.messages().len(), 4);
+    }
+}
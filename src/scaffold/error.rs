@@ -0,0 +1,19 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+    #[error("Invalid name {0:?}: use letters, digits, spaces, '-' or '_', starting with a letter")]
+    InvalidName(String),
+
+    #[error("Invalid name {0:?}: it is a Rust keyword and cannot name the module")]
+    KeywordName(String),
+
+    #[error("Unknown scaffold kind {0:?}, expected one of: tool, llm")]
+    UnknownKind(String),
+
+    #[error("{0} already exists")]
+    FileExists(String),
+
+    #[error("IO error: {0}")]
+    IOError(#[from] std::io::Error),
+}
@@ -0,0 +1,5 @@
+mod error;
+pub use error::*;
+
+mod scaffold;
+pub use scaffold::*;
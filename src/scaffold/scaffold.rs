@@ -0,0 +1,306 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use super::ScaffoldError;
+
+const TOOL_TEMPLATE: &str = include_str!("templates/tool.rs.tmpl");
+const TOOL_EXAMPLE_TEMPLATE: &str = include_str!("templates/tool_example.rs.tmpl");
+const LLM_TEMPLATE: &str = include_str!("templates/llm.rs.tmpl");
+const LLM_EXAMPLE_TEMPLATE: &str = include_str!("templates/llm_example.rs.tmpl");
+
+/// What to generate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScaffoldKind {
+    /// A [`crate::tools::Tool`] implementation with its config struct.
+    Tool,
+    /// A [`crate::language_models::llm::LLM`] provider with the usual `with_*` builders.
+    Llm,
+}
+
+impl FromStr for ScaffoldKind {
+    type Err = ScaffoldError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "tool" => Ok(Self::Tool),
+            "llm" | "provider" => Ok(Self::Llm),
+            _ => Err(ScaffoldError::UnknownKind(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for ScaffoldKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tool => write!(f, "tool"),
+            Self::Llm => write!(f, "llm"),
+        }
+    }
+}
+
+/// A file produced by [`Scaffold::generate`], with a path relative to the project root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+/// Generates the boilerplate for a new tool or LLM provider: the implementation, a config
+/// struct with builders, unit tests and a runnable example.
+///
+/// By default the code targets a project depending on `langchain-rust`. With
+/// [`Scaffold::in_tree`] it is laid out like the modules of this crate instead, for
+/// contributions upstream.
+#[derive(Clone, Debug)]
+pub struct Scaffold {
+    kind: ScaffoldKind,
+    snake: String,
+    pascal: String,
+    title: String,
+    in_tree: bool,
+}
+
+impl Scaffold {
+    /// `name` may be written in any case style, e.g. `weather lookup`, `weather-lookup` or
+    /// `WeatherLookup`.
+    pub fn new(kind: ScaffoldKind, name: &str) -> Result<Self, ScaffoldError> {
+        let words = split_words(name);
+        let valid = words
+            .first()
+            .and_then(|word| word.chars().next())
+            .is_some_and(|c| c.is_ascii_alphabetic());
+        if !valid
+            || name
+                .chars()
+                .any(|c| !c.is_ascii_alphanumeric() && !" -_".contains(c))
+        {
+            return Err(ScaffoldError::InvalidName(name.to_string()));
+        }
+        if words.len() == 1 && KEYWORDS.contains(&words[0].as_str()) {
+            return Err(ScaffoldError::KeywordName(name.to_string()));
+        }
+        Ok(Self {
+            kind,
+            snake: words.join("_"),
+            pascal: words.iter().map(|word| capitalize(word)).collect(),
+            title: words.join(" "),
+            in_tree: false,
+        })
+    }
+
+    pub fn in_tree(mut self, in_tree: bool) -> Self {
+        self.in_tree = in_tree;
+        self
+    }
+
+    pub fn generate(&self) -> Vec<GeneratedFile> {
+        let (template, example_template) = match self.kind {
+            ScaffoldKind::Tool => (TOOL_TEMPLATE, TOOL_EXAMPLE_TEMPLATE),
+            ScaffoldKind::Llm => (LLM_TEMPLATE, LLM_EXAMPLE_TEMPLATE),
+        };
+        let mut files = Vec::new();
+
+        let source_path = if self.in_tree {
+            let (parent, file, mod_rs) = match self.kind {
+                ScaffoldKind::Tool => (
+                    "tools",
+                    format!("{}.rs", self.snake),
+                    format!("mod {0};\npub use {0}::*;\n", self.snake),
+                ),
+                ScaffoldKind::Llm => (
+                    "llm",
+                    "client.rs".to_string(),
+                    "mod client;\npub use client::*;\n".to_string(),
+                ),
+            };
+            let dir = Path::new("src").join(parent).join(&self.snake);
+            files.push(GeneratedFile {
+                path: dir.join("mod.rs"),
+                contents: mod_rs,
+            });
+            dir.join(file)
+        } else {
+            Path::new("src").join(format!("{}.rs", self.snake))
+        };
+        files.push(GeneratedFile {
+            path: source_path,
+            contents: self.render(template),
+        });
+        files.push(GeneratedFile {
+            path: Path::new("examples").join(format!("{}.rs", self.snake)),
+            contents: self.render(example_template),
+        });
+        files
+    }
+
+    /// Manual steps left after the files are written.
+    pub fn next_steps(&self) -> Vec<String> {
+        if self.in_tree {
+            let parent = match self.kind {
+                ScaffoldKind::Tool => "src/tools/mod.rs",
+                ScaffoldKind::Llm => "src/llm/mod.rs",
+            };
+            return vec![
+                format!(
+                    "Declare the module in {}: `pub mod {1};` and `pub use {1}::*;`",
+                    parent, self.snake
+                ),
+                format!("Run `cargo run --example {}`", self.snake),
+            ];
+        }
+        let mut deps = "langchain-rust, async-trait, serde_json, tokio".to_string();
+        if self.kind == ScaffoldKind::Llm {
+            deps.push_str(", futures");
+        }
+        vec![
+            format!(
+                "Declare the module in your crate root: `pub mod {};`",
+                self.snake
+            ),
+            format!("Make sure Cargo.toml depends on {}", deps),
+            format!("Run `cargo run --example {}`", self.snake),
+        ]
+    }
+
+    /// Writes the generated files under `root`. Existing files are only replaced when
+    /// `overwrite` is set.
+    pub fn write_to<P: AsRef<Path>>(
+        &self,
+        root: P,
+        overwrite: bool,
+    ) -> Result<Vec<PathBuf>, ScaffoldError> {
+        let files = self
+            .generate()
+            .into_iter()
+            .map(|file| (root.as_ref().join(&file.path), file.contents))
+            .collect::<Vec<_>>();
+        if !overwrite {
+            if let Some((path, _)) = files.iter().find(|(path, _)| path.exists()) {
+                return Err(ScaffoldError::FileExists(path.display().to_string()));
+            }
+        }
+        for (path, contents) in &files {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, contents)?;
+        }
+        Ok(files.into_iter().map(|(path, _)| path).collect())
+    }
+
+    fn render(&self, template: &str) -> String {
+        let example_import = match (self.in_tree, self.kind) {
+            (true, ScaffoldKind::Tool) => format!("use langchain_rust::tools::{};", self.pascal),
+            (true, ScaffoldKind::Llm) => format!("use langchain_rust::llm::{};", self.pascal),
+            (false, _) => format!(
+                "#[allow(dead_code)]\n#[path = \"../src/{0}.rs\"]\nmod {0};\n\nuse {0}::{1};",
+                self.snake, self.pascal
+            ),
+        };
+        template
+            .replace("{{example_import}}", &example_import)
+            .replace(
+                "{{crate}}",
+                if self.in_tree {
+                    "crate"
+                } else {
+                    "langchain_rust"
+                },
+            )
+            .replace("{{snake}}", &self.snake)
+            .replace("{{pascal}}", &self.pascal)
+            .replace("{{title}}", &self.title)
+            .replace("{{env}}", &self.snake.to_ascii_uppercase())
+    }
+}
+
+/// Strict and reserved keywords of every edition, which a single-word name would turn into a
+/// module name.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Splits on separators and on lower-to-upper case changes, lowercasing every word.
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if " -_".contains(c) {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            previous_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && previous_lower {
+            words.push(std::mem::take(&mut current));
+        }
+        previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_are_normalized() {
+        for name in ["weather lookup", "weather-lookup", "WeatherLookup"] {
+            let scaffold = Scaffold::new(ScaffoldKind::Tool, name).unwrap();
+            assert_eq!(scaffold.snake, "weather_lookup");
+            assert_eq!(scaffold.pascal, "WeatherLookup");
+        }
+        assert!(Scaffold::new(ScaffoldKind::Tool, "1st tool").is_err());
+        assert!(Scaffold::new(ScaffoldKind::Tool, "bad/name").is_err());
+    }
+
+    #[test]
+    fn test_rejects_keywords() {
+        for name in ["type", "Match", "async", "self"] {
+            assert!(matches!(
+                Scaffold::new(ScaffoldKind::Tool, name),
+                Err(ScaffoldError::KeywordName(_))
+            ));
+        }
+        assert!(Scaffold::new(ScaffoldKind::Tool, "type lookup").is_ok());
+    }
+
+    #[test]
+    fn test_generates_every_placeholder() {
+        for kind in [ScaffoldKind::Tool, ScaffoldKind::Llm] {
+            for in_tree in [false, true] {
+                let files = Scaffold::new(kind, "acme search")
+                    .unwrap()
+                    .in_tree(in_tree)
+                    .generate();
+                for file in &files {
+                    assert!(!file.contents.contains("{{"), "{}", file.path.display());
+                }
+                let source = &files[files.len() - 2];
+                assert!(source.contents.contains("pub struct AcmeSearch"));
+                assert!(source.contents.contains("ACME_SEARCH_API_KEY"));
+            }
+        }
+    }
+}
@@ -4,14 +4,14 @@ pub use agent::*;
 pub mod memory;
 pub use memory::*;
 
-pub mod messages;
+pub use langchain_rust_schemas::messages;
 pub use messages::*;
 
 pub mod prompt;
 pub use prompt::*;
 
-pub mod document;
 pub use document::*;
+pub use langchain_rust_schemas::document;
 
 mod retrievers;
 pub use retrievers::*;
@@ -23,6 +23,5 @@ pub mod response_format_openai_like;
 pub use response_format_openai_like::*;
 
 pub mod convert;
-mod stream;
 
-pub use stream::*;
+pub use langchain_rust_schemas::StreamData;
@@ -1,3 +1,4 @@
+#[cfg(feature = "openai")]
 use crate::schemas::convert::OpenAIFromLangchain;
 
 #[derive(Clone, Debug)]
@@ -12,6 +13,7 @@ pub enum ResponseFormat {
     },
 }
 
+#[cfg(feature = "openai")]
 impl OpenAIFromLangchain<ResponseFormat> for async_openai::types::ResponseFormat {
     fn from_langchain(langchain: ResponseFormat) -> Self {
         match langchain {
@@ -1,5 +1,7 @@
+#[cfg(feature = "openai")]
 use crate::schemas::convert::{OpenAIFromLangchain, TryOpenAiFromLangchain};
 use crate::tools::Tool;
+#[cfg(feature = "openai")]
 use async_openai::types::{
     ChatCompletionNamedToolChoice, ChatCompletionTool, ChatCompletionToolArgs,
     ChatCompletionToolChoiceOption, ChatCompletionToolType, FunctionName, FunctionObjectArgs,
@@ -15,6 +17,7 @@ pub enum FunctionCallBehavior {
     Named(String),
 }
 
+#[cfg(feature = "openai")]
 impl OpenAIFromLangchain<FunctionCallBehavior> for ChatCompletionToolChoiceOption {
     fn from_langchain(langchain: FunctionCallBehavior) -> Self {
         match langchain {
@@ -61,6 +64,7 @@ impl FunctionDefinition {
     }
 }
 
+#[cfg(feature = "openai")]
 impl TryOpenAiFromLangchain<FunctionDefinition> for ChatCompletionTool {
     type Error = async_openai::error::OpenAIError;
     fn try_from_langchain(langchain: FunctionDefinition) -> Result<Self, Self::Error> {
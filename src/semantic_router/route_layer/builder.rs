@@ -4,11 +4,10 @@ use futures_util::future::try_join_all;
 
 use crate::{
     chain::{LLMChain, LLMChainBuilder},
-    embedding::{openai::OpenAiEmbedder, Embedder},
+    embedding::Embedder,
     language_models::llm::LLM,
-    llm::openai::OpenAI,
     prompt::HumanMessagePromptTemplate,
-    semantic_router::{Index, MemoryIndex, RouteLayerBuilderError, Router},
+    semantic_router::{Index, RouteLayerBuilderError, Router},
     template_jinja2,
 };
 
@@ -50,8 +49,14 @@ pub struct RouteLayerBuilder {
     top_k: usize,
     aggregation_method: AggregationMethod,
 }
+/// Defaults to the OpenAI embedder and LLM.
+#[cfg(feature = "openai")]
 impl Default for RouteLayerBuilder {
     fn default() -> Self {
+        use crate::{
+            embedding::openai::OpenAiEmbedder, llm::openai::OpenAI, semantic_router::MemoryIndex,
+        };
+
         Self::new()
             .embedder(OpenAiEmbedder::default())
             .llm(OpenAI::default())
@@ -0,0 +1,395 @@
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use futures::{stream, Stream, StreamExt};
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::{
+    chain::{Chain, ChainError},
+    language_models::TokenUsage,
+    prompt_args,
+    schemas::Message,
+};
+
+#[derive(Error, Debug)]
+pub enum ServeError {
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    #[error("Unknown or expired stream: {0}")]
+    UnknownStream(String),
+
+    #[error(transparent)]
+    ChainError(#[from] ChainError),
+}
+
+impl ServeError {
+    /// HTTP status to answer with.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            Self::InvalidRequest(_) => 400,
+            Self::UnknownStream(_) => 404,
+            Self::ChainError(_) => 500,
+        }
+    }
+
+    /// The error body OpenAI clients expect.
+    pub fn body(&self) -> Value {
+        let kind = match self {
+            Self::InvalidRequest(_) | Self::UnknownStream(_) => "invalid_request_error",
+            Self::ChainError(_) => "server_error",
+        };
+        json!({"error": {"message": self.to_string(), "type": kind}})
+    }
+}
+
+/// What to answer a chat completions request with.
+pub enum ChatCompletionsResponse {
+    /// A `chat.completion` object, sent as JSON.
+    Completion(Value),
+    /// Server-sent events, each already framed as `data: ...\n\n` and preceded by an `id:`
+    /// line when buffered, ending with `data: [DONE]`. Sent with the `text/event-stream`
+    /// content type.
+    Stream(Pin<Box<dyn Stream<Item = String> + Send>>),
+}
+
+/// Answers OpenAI `/v1/chat/completions` requests with an agent, or any other chain, so chat
+/// UIs such as LibreChat or OpenWebUI can talk to it as if it were a model.
+///
+/// The last message of a request is the chain's `input` and the earlier ones its
+/// `chat_history`. Requests with `"stream": true` are answered with server-sent events made
+/// from the chain's stream. The agent's tool calls stay hidden: clients only see the answer.
+///
+/// With an [`EventBuffer`](super::EventBuffer), streamed events carry SSE ids and a client
+/// reconnecting with `Last-Event-ID` gets the events it missed from [`Self::resume`].
+///
+/// The facade does not depend on a web framework; mount it on a route of any server:
+///
+/// ```rust,ignore
+/// let facade = Arc::new(ChatCompletions::new(executor).with_model("assistant"));
+/// let app = Router::new().route("/v1/chat/completions", post(move |body: String| async move {
+///     match facade.handle(&body).await {
+///         Ok(ChatCompletionsResponse::Completion(value)) => Json(value).into_response(),
+///         Ok(ChatCompletionsResponse::Stream(events)) => (
+///             [(CONTENT_TYPE, "text/event-stream")],
+///             Body::from_stream(events.map(Ok::<_, Infallible>)),
+///         ).into_response(),
+///         Err(e) => (StatusCode::from_u16(e.status_code()).unwrap(), Json(e.body())).into_response(),
+///     }
+/// }));
+/// ```
+pub struct ChatCompletions {
+    chain: Arc<dyn Chain>,
+    model: Option<String>,
+    #[cfg(not(target_arch = "wasm32"))]
+    events: Option<super::EventBuffer>,
+}
+
+impl ChatCompletions {
+    pub fn new<C: Chain + 'static>(chain: C) -> Self {
+        Self {
+            chain: Arc::new(chain),
+            model: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            events: None,
+        }
+    }
+
+    /// Model name reported in the responses, instead of the one requested.
+    pub fn with_model<S: Into<String>>(mut self, model: S) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Buffers streamed events so clients can resume them.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_event_buffer(mut self, events: super::EventBuffer) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Answers a reconnection with the events after its `Last-Event-ID`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn resume(&self, last_event_id: &str) -> Result<ChatCompletionsResponse, ServeError> {
+        self.events
+            .as_ref()
+            .and_then(|events| events.resume(last_event_id))
+            .map(ChatCompletionsResponse::Stream)
+            .ok_or_else(|| ServeError::UnknownStream(last_event_id.to_string()))
+    }
+
+    /// Handles the JSON body of a request.
+    pub async fn handle(&self, body: &str) -> Result<ChatCompletionsResponse, ServeError> {
+        let request: Value = serde_json::from_str(body)
+            .map_err(|e| ServeError::InvalidRequest(format!("Invalid JSON: {}", e)))?;
+        let (input, history) = parse_messages(&request["messages"])?;
+        let model = self
+            .model
+            .clone()
+            .or_else(|| request["model"].as_str().map(str::to_string))
+            .unwrap_or_else(|| "langchain-rust".to_string());
+        let args = prompt_args! {"input" => input, "chat_history" => history};
+        let completion = Completion {
+            id: completion_id(),
+            created: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+            model,
+        };
+
+        if !request["stream"].as_bool().unwrap_or(false) {
+            let result = self.chain.call(args).await?;
+            return Ok(ChatCompletionsResponse::Completion(
+                completion.response(&result.generation, result.tokens.as_ref()),
+            ));
+        }
+
+        let chunks = self.chain.stream(args).await?;
+        let first = stream::once({
+            let event = completion.event(json!({"role": "assistant", "content": ""}), None);
+            async move { event }
+        });
+        let content = {
+            let completion = completion.clone();
+            chunks.map(move |chunk| match chunk {
+                Ok(chunk) => completion.event(json!({"content": chunk.content}), None),
+                Err(e) => sse(&ServeError::from(e).body()),
+            })
+        };
+        let last = stream::iter([
+            completion.event(json!({}), Some("stop")),
+            "data: [DONE]\n\n".to_string(),
+        ]);
+        let events = first.chain(content).chain(last);
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(buffer) = &self.events {
+            buffer.publish(&completion.id, events);
+            if let Some(events) = buffer.subscribe(&completion.id, None) {
+                return Ok(ChatCompletionsResponse::Stream(events));
+            }
+            return Err(ServeError::UnknownStream(completion.id));
+        }
+        Ok(ChatCompletionsResponse::Stream(Box::pin(events)))
+    }
+}
+
+#[derive(Clone)]
+struct Completion {
+    id: String,
+    created: u64,
+    model: String,
+}
+
+impl Completion {
+    fn response(&self, content: &str, tokens: Option<&TokenUsage>) -> Value {
+        let mut response = json!({
+            "id": self.id,
+            "object": "chat.completion",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{
+                "index": 0,
+                "message": {"role": "assistant", "content": content},
+                "finish_reason": "stop"
+            }]
+        });
+        if let Some(tokens) = tokens {
+            response["usage"] = json!(tokens);
+        }
+        response
+    }
+
+    fn event(&self, delta: Value, finish_reason: Option<&str>) -> String {
+        sse(&json!({
+            "id": self.id,
+            "object": "chat.completion.chunk",
+            "created": self.created,
+            "model": self.model,
+            "choices": [{"index": 0, "delta": delta, "finish_reason": finish_reason}]
+        }))
+    }
+}
+
+fn sse(data: &Value) -> String {
+    format!("data: {}\n\n", data)
+}
+
+fn completion_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    format!(
+        "chatcmpl-{}-{}",
+        millis,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+/// Text of a message content, either a string or an array of parts of which the text parts
+/// are kept.
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) => parts
+            .iter()
+            .filter_map(|part| part["text"].as_str())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// Splits the request messages into the last user message and the history before it.
+fn parse_messages(messages: &Value) -> Result<(String, Vec<Message>), ServeError> {
+    let messages = messages
+        .as_array()
+        .filter(|messages| !messages.is_empty())
+        .ok_or_else(|| ServeError::InvalidRequest("messages must be a non-empty array".into()))?;
+    let (last, earlier) = messages.split_last().unwrap();
+    if last["role"] != "user" {
+        return Err(ServeError::InvalidRequest(
+            "the last message must come from the user".into(),
+        ));
+    }
+    let history = earlier
+        .iter()
+        .filter_map(|message| {
+            let content = content_text(&message["content"]);
+            match message["role"].as_str()? {
+                "system" | "developer" => Some(Message::new_system_message(content)),
+                "user" => Some(Message::new_human_message(content)),
+                "assistant" if !content.is_empty() => Some(Message::new_ai_message(content)),
+                // Tool calls of other models have no meaning to the agent.
+                _ => None,
+            }
+        })
+        .collect();
+    Ok((content_text(&last["content"]), history))
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::{language_models::GenerateResult, prompt::PromptArgs, schemas::StreamData};
+
+    /// Answers with the input and the length of the history.
+    struct Echo;
+
+    #[async_trait]
+    impl Chain for Echo {
+        async fn call(&self, input_variables: PromptArgs) -> Result<GenerateResult, ChainError> {
+            Ok(GenerateResult {
+                generation: format!(
+                    "{} after {}",
+                    input_variables["input"].as_str().unwrap(),
+                    input_variables["chat_history"].as_array().unwrap().len()
+                ),
+                ..Default::default()
+            })
+        }
+
+        async fn stream(
+            &self,
+            input_variables: PromptArgs,
+        ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, ChainError>> + Send>>, ChainError>
+        {
+            let words = input_variables["input"]
+                .as_str()
+                .unwrap()
+                .split(' ')
+                .map(|word| Ok(StreamData::new(json!(word), None, word)))
+                .collect::<Vec<_>>();
+            Ok(Box::pin(stream::iter(words)))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_answers_completions() {
+        let facade = ChatCompletions::new(Echo).with_model("assistant");
+        let body = json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "Be brief."},
+                {"role": "user", "content": "Hi"},
+                {"role": "assistant", "content": "Hello"},
+                {"role": "user", "content": [{"type": "text", "text": "How are you"}]}
+            ]
+        });
+        let ChatCompletionsResponse::Completion(response) =
+            facade.handle(&body.to_string()).await.unwrap()
+        else {
+            panic!("expected a completion");
+        };
+        assert_eq!(response["model"], "assistant");
+        assert_eq!(
+            response["choices"][0]["message"]["content"],
+            "How are you after 3"
+        );
+
+        let error = facade.handle(r#"{"messages": []}"#).await.err().unwrap();
+        assert_eq!(error.status_code(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_streams_server_sent_events() {
+        let facade = ChatCompletions::new(Echo);
+        let body = json!({
+            "model": "gpt-4o",
+            "stream": true,
+            "messages": [{"role": "user", "content": "How are you"}]
+        });
+        let ChatCompletionsResponse::Stream(events) =
+            facade.handle(&body.to_string()).await.unwrap()
+        else {
+            panic!("expected a stream");
+        };
+        let events = events.collect::<Vec<_>>().await;
+        assert_eq!(events.len(), 6);
+        assert!(events[1].contains(r#""delta":{"content":"How"}"#));
+        assert!(events[4].contains(r#""finish_reason":"stop""#));
+        assert_eq!(events[5], "data: [DONE]\n\n");
+    }
+
+    #[tokio::test]
+    async fn test_resumes_buffered_events() {
+        let facade = ChatCompletions::new(Echo).with_event_buffer(crate::serve::EventBuffer::new());
+        let body = json!({
+            "stream": true,
+            "messages": [{"role": "user", "content": "How are you"}]
+        });
+        let ChatCompletionsResponse::Stream(events) =
+            facade.handle(&body.to_string()).await.unwrap()
+        else {
+            panic!("expected a stream");
+        };
+        // The client goes away after the third event.
+        let seen = events.take(3).collect::<Vec<_>>().await;
+        let last_event_id = seen[2].lines().next().unwrap().trim_start_matches("id: ");
+        assert!(last_event_id.ends_with(":2"));
+
+        let ChatCompletionsResponse::Stream(missed) = facade.resume(last_event_id).unwrap() else {
+            panic!("expected a stream");
+        };
+        let missed = missed.collect::<Vec<_>>().await;
+        assert_eq!(missed.len(), 3);
+        assert!(missed[0].contains(r#""content":"you""#));
+        assert!(missed[2].ends_with("data: [DONE]\n\n"));
+
+        assert_eq!(
+            facade.resume("chatcmpl-0:1").err().unwrap().status_code(),
+            404
+        );
+    }
+}
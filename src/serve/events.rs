@@ -0,0 +1,143 @@
+use std::{
+    collections::HashMap,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{stream, Stream, StreamExt};
+use tokio::sync::watch;
+
+struct Buffered {
+    events: Vec<String>,
+    finished_at: Option<Instant>,
+    /// Bumped on every new event and when the stream ends.
+    updates: watch::Sender<()>,
+}
+
+/// Keeps the events of served streams for a while, so a client reconnecting with the
+/// `Last-Event-ID` header gets the events it missed instead of losing them.
+///
+/// Every event gets an SSE `id` made of its stream and sequence number, e.g.
+/// `chatcmpl-1-0:12`. Streams are driven by a background task, so a run goes on while its
+/// client is away, and are forgotten once they ended more than the TTL ago. Clones share the
+/// streams.
+#[derive(Clone)]
+pub struct EventBuffer {
+    ttl: Duration,
+    streams: Arc<Mutex<HashMap<String, Buffered>>>,
+}
+
+impl Default for EventBuffer {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::from_secs(300),
+            streams: Arc::default(),
+        }
+    }
+}
+
+impl EventBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// How long the events of an ended stream can still be resumed.
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    fn purge(&self, streams: &mut HashMap<String, Buffered>) {
+        streams.retain(|_, buffered| {
+            buffered
+                .finished_at
+                .is_none_or(|finished_at| finished_at.elapsed() < self.ttl)
+        });
+    }
+
+    /// Buffers the SSE events of `source` under `stream_id`, numbering them from 0.
+    pub(crate) fn publish<S>(&self, stream_id: &str, source: S)
+    where
+        S: Stream<Item = String> + Send + 'static,
+    {
+        {
+            let mut streams = self.streams.lock().unwrap();
+            self.purge(&mut streams);
+            streams.insert(
+                stream_id.to_string(),
+                Buffered {
+                    events: Vec::new(),
+                    finished_at: None,
+                    updates: watch::channel(()).0,
+                },
+            );
+        }
+        let streams = self.streams.clone();
+        let stream_id = stream_id.to_string();
+        tokio::spawn(async move {
+            let mut source = Box::pin(source);
+            while let Some(event) = source.next().await {
+                let mut streams = streams.lock().unwrap();
+                let Some(buffered) = streams.get_mut(&stream_id) else {
+                    return;
+                };
+                let id = format!("id: {}:{}\n", stream_id, buffered.events.len());
+                buffered.events.push(id + &event);
+                buffered.updates.send_replace(());
+            }
+            if let Some(buffered) = streams.lock().unwrap().get_mut(&stream_id) {
+                buffered.finished_at = Some(Instant::now());
+                buffered.updates.send_replace(());
+            }
+        });
+    }
+
+    /// The events of `stream_id` after the sequence number `after`, or all of them, followed
+    /// by the live ones until the stream ends. `None` if the stream is unknown or expired.
+    pub fn subscribe(
+        &self,
+        stream_id: &str,
+        after: Option<u64>,
+    ) -> Option<Pin<Box<dyn Stream<Item = String> + Send>>> {
+        let updates = {
+            let mut streams = self.streams.lock().unwrap();
+            self.purge(&mut streams);
+            streams.get(stream_id)?.updates.subscribe()
+        };
+        let next = after.map_or(0, |after| after as usize + 1);
+        let state = (self.streams.clone(), stream_id.to_string(), next, updates);
+        Some(Box::pin(stream::unfold(
+            state,
+            |(streams, stream_id, next, mut updates)| async move {
+                loop {
+                    updates.borrow_and_update();
+                    let event = {
+                        let buffered = streams.lock().unwrap();
+                        let buffered = buffered.get(&stream_id)?;
+                        match buffered.events.get(next) {
+                            Some(event) => Some(event.clone()),
+                            None if buffered.finished_at.is_some() => return None,
+                            None => None,
+                        }
+                    };
+                    match event {
+                        Some(event) => {
+                            return Some((event, (streams, stream_id, next + 1, updates)))
+                        }
+                        None => updates.changed().await.ok()?,
+                    }
+                }
+            },
+        )))
+    }
+
+    /// Resumes the stream a `Last-Event-ID` header points to.
+    pub fn resume(
+        &self,
+        last_event_id: &str,
+    ) -> Option<Pin<Box<dyn Stream<Item = String> + Send>>> {
+        let (stream_id, sequence) = last_event_id.trim().rsplit_once(':')?;
+        self.subscribe(stream_id, Some(sequence.parse().ok()?))
+    }
+}
@@ -0,0 +1,7 @@
+mod chat_completions;
+pub use chat_completions::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod events;
+#[cfg(not(target_arch = "wasm32"))]
+pub use events::*;
@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SessionError {
+    #[error("Serde json error: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("Unsupported session bundle version {found}, this build supports up to {supported}")]
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    #[error("Invalid session bundle: {0}")]
+    InvalidBundle(String),
+
+    #[error("LLM error: {0}")]
+    LLMError(#[from] crate::language_models::LLMError),
+
+    #[cfg(feature = "encryption")]
+    #[error("Encryption error: {0}")]
+    EncryptionError(#[from] crate::encryption::EncryptionError),
+}
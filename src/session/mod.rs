@@ -0,0 +1,11 @@
+mod error;
+pub use error::*;
+
+mod session;
+pub use session::*;
+
+mod title;
+pub use title::*;
+
+mod transcript;
+pub use transcript::*;
@@ -0,0 +1,271 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::{
+    memory::SimpleMemory,
+    schemas::{memory::BaseMemory, messages::Message},
+};
+
+use super::SessionError;
+
+/// Version written by [`Session::export`]. Bump it whenever [`SessionBundle`] changes shape,
+/// and keep [`Session::import`] able to read every older version.
+pub const SESSION_BUNDLE_VERSION: u32 = 1;
+
+/// A file or snippet attached to a conversation.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Attachment {
+    pub name: String,
+    pub media_type: String,
+    /// Text content; binary attachments should be base64 encoded by the caller.
+    pub content: String,
+}
+
+impl Attachment {
+    pub fn new<N: Into<String>, M: Into<String>, C: Into<String>>(
+        name: N,
+        media_type: M,
+        content: C,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            media_type: media_type.into(),
+            content: content.into(),
+        }
+    }
+}
+
+/// Portable, versioned snapshot of a [`Session`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionBundle {
+    pub version: u32,
+    pub id: String,
+    /// Seconds since the Unix epoch.
+    pub exported_at: u64,
+    pub messages: Vec<Message>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+    /// Opaque plan state of the agent that owned the session, if any.
+    #[serde(default)]
+    pub plan: Option<Value>,
+    /// Run metadata such as model, tenant or trace ids.
+    #[serde(default)]
+    pub metadata: HashMap<String, Value>,
+}
+
+impl SessionBundle {
+    /// Checks the invariants `serde` cannot express.
+    pub fn validate(&self) -> Result<(), SessionError> {
+        if self.version == 0 || self.version > SESSION_BUNDLE_VERSION {
+            return Err(SessionError::UnsupportedVersion {
+                found: self.version,
+                supported: SESSION_BUNDLE_VERSION,
+            });
+        }
+        if self.id.trim().is_empty() {
+            return Err(SessionError::InvalidBundle("session id is empty".into()));
+        }
+        let mut names = HashSet::new();
+        for attachment in &self.attachments {
+            if attachment.name.is_empty() {
+                return Err(SessionError::InvalidBundle(
+                    "attachment without a name".into(),
+                ));
+            }
+            if !names.insert(attachment.name.as_str()) {
+                return Err(SessionError::InvalidBundle(format!(
+                    "duplicate attachment {}",
+                    attachment.name
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A conversation that can be moved between deployments.
+///
+/// # Usage
+/// ```rust,ignore
+/// let session = Session::new("support-42").with_memory(memory.clone());
+/// let json = session.export_json().await?;
+///
+/// // elsewhere
+/// let session = Session::import_json(&json)?;
+/// let executor = AgentExecutor::from_agent(agent).with_memory(session.memory.clone());
+/// ```
+pub struct Session {
+    pub id: String,
+    pub memory: Arc<Mutex<dyn BaseMemory>>,
+    pub attachments: Vec<Attachment>,
+    pub plan: Option<Value>,
+    pub metadata: HashMap<String, Value>,
+}
+
+impl Session {
+    pub fn new<S: Into<String>>(id: S) -> Self {
+        Self {
+            id: id.into(),
+            memory: SimpleMemory::new().into(),
+            attachments: Vec::new(),
+            plan: None,
+            metadata: HashMap::new(),
+        }
+    }
+
+    pub fn with_memory(mut self, memory: Arc<Mutex<dyn BaseMemory>>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    pub fn with_attachment(mut self, attachment: Attachment) -> Self {
+        self.attachments.push(attachment);
+        self
+    }
+
+    pub fn with_plan(mut self, plan: Value) -> Self {
+        self.plan = Some(plan);
+        self
+    }
+
+    pub fn with_metadata<K: Into<String>>(mut self, key: K, value: Value) -> Self {
+        self.metadata.insert(key.into(), value);
+        self
+    }
+
+    pub async fn export(&self) -> SessionBundle {
+        let exported_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+        SessionBundle {
+            version: SESSION_BUNDLE_VERSION,
+            id: self.id.clone(),
+            exported_at,
+            messages: self.memory.lock().await.messages(),
+            attachments: self.attachments.clone(),
+            plan: self.plan.clone(),
+            metadata: self.metadata.clone(),
+        }
+    }
+
+    pub async fn export_json(&self) -> Result<String, SessionError> {
+        Ok(serde_json::to_string_pretty(&self.export().await)?)
+    }
+
+    /// Restores a session into a fresh [`SimpleMemory`].
+    pub fn import(bundle: SessionBundle) -> Result<Self, SessionError> {
+        bundle.validate()?;
+        let mut memory = SimpleMemory::new();
+        for message in bundle.messages {
+            memory.add_message(message);
+        }
+        Ok(Self {
+            id: bundle.id,
+            memory: memory.into(),
+            attachments: bundle.attachments,
+            plan: bundle.plan,
+            metadata: bundle.metadata,
+        })
+    }
+
+    /// Restores a session into an existing memory, replacing its contents.
+    pub async fn import_into(
+        bundle: SessionBundle,
+        memory: Arc<Mutex<dyn BaseMemory>>,
+    ) -> Result<Self, SessionError> {
+        bundle.validate()?;
+        {
+            let mut memory = memory.lock().await;
+            memory.clear();
+            for message in bundle.messages {
+                memory.add_message(message);
+            }
+        }
+        Ok(Self {
+            id: bundle.id,
+            memory,
+            attachments: bundle.attachments,
+            plan: bundle.plan,
+            metadata: bundle.metadata,
+        })
+    }
+
+    pub fn import_json(json: &str) -> Result<Self, SessionError> {
+        Self::import(serde_json::from_str(json)?)
+    }
+
+    /// Exports the bundle as an encrypted payload suitable for storing at rest.
+    #[cfg(feature = "encryption")]
+    pub async fn export_encrypted(
+        &self,
+        cipher: &crate::encryption::Cipher,
+    ) -> Result<Vec<u8>, SessionError> {
+        Ok(cipher.encrypt_json(&self.export().await)?)
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn import_encrypted(
+        payload: &[u8],
+        cipher: &crate::encryption::Cipher,
+    ) -> Result<Self, SessionError> {
+        Self::import(cipher.decrypt_json(payload)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_export_import_roundtrip() {
+        let memory: Arc<Mutex<dyn BaseMemory>> = SimpleMemory::new().into();
+        memory.lock().await.add_user_message(&"hello");
+        memory.lock().await.add_ai_message(&"hi there");
+
+        let session = Session::new("s-1")
+            .with_memory(memory)
+            .with_attachment(Attachment::new("notes.md", "text/markdown", "# Notes"))
+            .with_plan(json!({"step": 2}))
+            .with_metadata("model", json!("gpt-4o-mini"));
+        let json = session.export_json().await.unwrap();
+
+        let restored = Session::import_json(&json).unwrap();
+        assert_eq!(restored.id, "s-1");
+        assert_eq!(restored.memory.lock().await.messages().len(), 2);
+        assert_eq!(restored.attachments, session.attachments);
+        assert_eq!(restored.plan, Some(json!({"step": 2})));
+        assert_eq!(restored.metadata["model"], json!("gpt-4o-mini"));
+    }
+
+    #[tokio::test]
+    async fn test_import_rejects_invalid_bundles() {
+        let mut bundle = Session::new("s-1").export().await;
+        bundle.version = SESSION_BUNDLE_VERSION + 1;
+        assert!(matches!(
+            Session::import(bundle.clone()),
+            Err(SessionError::UnsupportedVersion { .. })
+        ));
+
+        bundle.version = SESSION_BUNDLE_VERSION;
+        bundle.attachments = vec![Attachment::new("a", "text/plain", ""); 2];
+        assert!(matches!(
+            Session::import(bundle),
+            Err(SessionError::InvalidBundle(_))
+        ));
+
+        let unknown_field = r#"{"version":1,"id":"s","exported_at":0,"messages":[],"extra":1}"#;
+        assert!(Session::import_json(unknown_field).is_err());
+    }
+}
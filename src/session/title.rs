@@ -0,0 +1,175 @@
+use serde_json::{json, Value};
+
+use crate::{
+    language_models::llm::LLM,
+    schemas::{Message, MessageType},
+};
+
+use super::{Session, SessionError};
+
+/// Metadata keys written by [`SessionTitler`].
+pub const TITLE_KEY: &str = "title";
+pub const TOPICS_KEY: &str = "topics";
+const TITLED_AT_TURN_KEY: &str = "titled_at_turn";
+
+const TITLE_PROMPT: &str = "Give the conversation below a short title of at most eight words \
+and up to {max_topics} topic tags of one or two words each.\n\n\
+{conversation}\n\n\
+Reply in exactly this format:\nTitle: <title>\nTopics: <tag>, <tag>";
+
+/// Keeps a session's `title` and `topics` metadata up to date, so apps listing many sessions
+/// can show them without summarizing conversations themselves.
+///
+/// The session is titled after its first turn, then retitled every `every_turns` user
+/// messages. Only the most recent messages are shown to the LLM, which should be a cheap one.
+///
+/// ```rust,ignore
+/// let titler = SessionTitler::new(cheap_llm);
+/// executor.invoke(prompt_args! {"input" => input}).await?;
+/// titler.update(&mut session).await?;
+/// let title = &session.metadata["title"];
+/// ```
+pub struct SessionTitler {
+    llm: Box<dyn LLM>,
+    every_turns: usize,
+    max_topics: usize,
+    max_messages: usize,
+}
+
+impl SessionTitler {
+    pub fn new<L: Into<Box<dyn LLM>>>(llm: L) -> Self {
+        Self {
+            llm: llm.into(),
+            every_turns: 3,
+            max_topics: 5,
+            max_messages: 20,
+        }
+    }
+
+    pub fn with_every_turns(mut self, every_turns: usize) -> Self {
+        self.every_turns = every_turns.max(1);
+        self
+    }
+
+    pub fn with_max_topics(mut self, max_topics: usize) -> Self {
+        self.max_topics = max_topics;
+        self
+    }
+
+    /// How many of the latest messages the title is based on.
+    pub fn with_max_messages(mut self, max_messages: usize) -> Self {
+        self.max_messages = max_messages;
+        self
+    }
+
+    /// Titles `session` if it is due, returning whether its metadata changed.
+    pub async fn update(&self, session: &mut Session) -> Result<bool, SessionError> {
+        let messages = session.memory.lock().await.messages();
+        let turns = messages
+            .iter()
+            .filter(|m| m.message_type == MessageType::HumanMessage)
+            .count();
+        let titled_at = session
+            .metadata
+            .get(TITLED_AT_TURN_KEY)
+            .and_then(Value::as_u64)
+            .map(|turn| turn as usize);
+        let due = match titled_at {
+            None => turns > 0,
+            Some(titled_at) => turns >= titled_at + self.every_turns,
+        };
+        if !due {
+            return Ok(false);
+        }
+
+        let (title, topics) = self.generate(&messages).await?;
+        session.metadata.insert(TITLE_KEY.into(), json!(title));
+        session.metadata.insert(TOPICS_KEY.into(), json!(topics));
+        session
+            .metadata
+            .insert(TITLED_AT_TURN_KEY.into(), json!(turns));
+        Ok(true)
+    }
+
+    /// A title and topic tags for `messages`.
+    pub async fn generate(
+        &self,
+        messages: &[Message],
+    ) -> Result<(String, Vec<String>), SessionError> {
+        let recent = &messages[messages.len().saturating_sub(self.max_messages)..];
+        let conversation = recent
+            .iter()
+            .filter(|m| m.message_type != MessageType::SystemMessage)
+            .map(|m| format!("{:?}: {}", m.message_type, m.content))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let reply = self
+            .llm
+            .invoke(
+                &TITLE_PROMPT
+                    .replace("{max_topics}", &self.max_topics.to_string())
+                    .replace("{conversation}", &conversation),
+            )
+            .await?;
+        Ok(parse_title(&reply, self.max_topics))
+    }
+}
+
+/// Reads the `Title:` and `Topics:` lines, taking the first line as title if there is no
+/// `Title:` line.
+fn parse_title(reply: &str, max_topics: usize) -> (String, Vec<String>) {
+    let field = |name: &str| {
+        reply.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    };
+    let title = field("title")
+        .or_else(|| reply.lines().map(str::trim).find(|line| !line.is_empty()))
+        .unwrap_or_default()
+        .trim_matches(|c| c == '"' || c == '*')
+        .to_string();
+    let topics = field("topics")
+        .unwrap_or_default()
+        .split(',')
+        .map(|topic| topic.trim().trim_start_matches('#').to_lowercase())
+        .filter(|topic| !topic.is_empty())
+        .take(max_topics)
+        .collect();
+    (title, topics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::FakeLLM;
+
+    #[tokio::test]
+    async fn test_titles_after_first_turn_then_every_few_turns() {
+        let mut session = Session::new("s-1");
+        let titler = SessionTitler::new(FakeLLM::fixed(
+            "Title: \"Trip to Lima\"\nTopics: Travel, #Peru, food",
+        ))
+        .with_every_turns(2)
+        .with_max_topics(2);
+        assert!(!titler.update(&mut session).await.unwrap());
+
+        session
+            .memory
+            .lock()
+            .await
+            .add_user_message(&"I want to visit Lima");
+        assert!(titler.update(&mut session).await.unwrap());
+        assert_eq!(session.metadata[TITLE_KEY], json!("Trip to Lima"));
+        assert_eq!(session.metadata[TOPICS_KEY], json!(["travel", "peru"]));
+
+        session
+            .memory
+            .lock()
+            .await
+            .add_user_message(&"What should I eat?");
+        assert!(!titler.update(&mut session).await.unwrap());
+        session.memory.lock().await.add_user_message(&"And drink?");
+        assert!(titler.update(&mut session).await.unwrap());
+    }
+}
@@ -0,0 +1,307 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::schemas::{Message, MessageType};
+
+/// The messages of one agent's run, e.g. read from its memory, to add to a [`Transcript`].
+#[derive(Debug, Clone)]
+pub struct TranscriptRun {
+    agent: String,
+    messages: Vec<Message>,
+    duration: Option<Duration>,
+}
+
+impl TranscriptRun {
+    pub fn new<S: Into<String>>(agent: S, messages: Vec<Message>) -> Self {
+        Self {
+            agent: agent.into(),
+            messages,
+            duration: None,
+        }
+    }
+
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+}
+
+struct ToolCall {
+    name: String,
+    arguments: String,
+    result: Option<String>,
+}
+
+enum Entry {
+    Message { speaker: String, content: String },
+    ToolCalls { agent: String, calls: Vec<ToolCall> },
+    Timing { agent: String, duration: Duration },
+}
+
+/// Renders agent runs as one conversation to share with people who do not read logs, in
+/// Markdown or HTML.
+///
+/// Runs are rendered in the order they are added, every answer labelled with the agent that
+/// gave it. Tool calls are collapsed into `<details>` blocks holding their arguments and
+/// results, and system messages are left out.
+#[derive(Default)]
+pub struct Transcript {
+    title: Option<String>,
+    runs: Vec<TranscriptRun>,
+}
+
+impl Transcript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_title<S: Into<String>>(mut self, title: S) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_run(mut self, run: TranscriptRun) -> Self {
+        self.runs.push(run);
+        self
+    }
+
+    fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        for run in &self.runs {
+            for message in &run.messages {
+                match message.message_type {
+                    MessageType::SystemMessage => {}
+                    MessageType::HumanMessage => entries.push(Entry::Message {
+                        speaker: "User".to_string(),
+                        content: message.content.clone(),
+                    }),
+                    MessageType::AIMessage => {
+                        if !message.content.trim().is_empty() {
+                            entries.push(Entry::Message {
+                                speaker: run.agent.clone(),
+                                content: message.content.clone(),
+                            });
+                        }
+                        if let Some(calls) = message.tool_calls.as_ref().and_then(Value::as_array) {
+                            entries.push(Entry::ToolCalls {
+                                agent: run.agent.clone(),
+                                calls: calls.iter().map(tool_call).collect(),
+                            });
+                        }
+                    }
+                    MessageType::ToolMessage => {
+                        attach_result(&mut entries, &run.agent, message, &run.messages)
+                    }
+                }
+            }
+            if let Some(duration) = run.duration {
+                entries.push(Entry::Timing {
+                    agent: run.agent.clone(),
+                    duration,
+                });
+            }
+        }
+        entries
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        if let Some(title) = &self.title {
+            out.push_str(&format!("# {}\n\n", title));
+        }
+        for entry in self.entries() {
+            match entry {
+                Entry::Message { speaker, content } => {
+                    out.push_str(&format!("**{}:** {}\n\n", speaker, content.trim()));
+                }
+                Entry::ToolCalls { agent, calls } => {
+                    for call in calls {
+                        out.push_str(&format!(
+                            "<details>\n<summary>{} used {}</summary>\n\n{}\n",
+                            agent,
+                            call.name,
+                            code_block(&call.arguments)
+                        ));
+                        if let Some(result) = call.result {
+                            out.push_str(&format!("\nResult:\n\n{}\n", code_block(result.trim())));
+                        }
+                        out.push_str("</details>\n\n");
+                    }
+                }
+                Entry::Timing { agent, duration } => {
+                    out.push_str(&format!("*{} took {}*\n\n", agent, seconds(duration)));
+                }
+            }
+        }
+        out.trim_end().to_string() + "\n"
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut out = String::from("<article class=\"transcript\">\n");
+        if let Some(title) = &self.title {
+            out.push_str(&format!("<h1>{}</h1>\n", escape(title)));
+        }
+        for entry in self.entries() {
+            match entry {
+                Entry::Message { speaker, content } => out.push_str(&format!(
+                    "<p class=\"message\"><strong>{}:</strong> {}</p>\n",
+                    escape(&speaker),
+                    escape(content.trim()).replace('\n', "<br>")
+                )),
+                Entry::ToolCalls { agent, calls } => {
+                    for call in calls {
+                        out.push_str(&format!(
+                            "<details class=\"tool-call\"><summary>{} used {}</summary><pre>{}</pre>",
+                            escape(&agent),
+                            escape(&call.name),
+                            escape(&call.arguments)
+                        ));
+                        if let Some(result) = call.result {
+                            out.push_str(&format!(
+                                "<p>Result:</p><pre>{}</pre>",
+                                escape(result.trim())
+                            ));
+                        }
+                        out.push_str("</details>\n");
+                    }
+                }
+                Entry::Timing { agent, duration } => out.push_str(&format!(
+                    "<p class=\"timing\"><em>{} took {}</em></p>\n",
+                    escape(&agent),
+                    seconds(duration)
+                )),
+            }
+        }
+        out.push_str("</article>\n");
+        out
+    }
+}
+
+/// A tool call in the OpenAI shape stored by the executor's memory.
+fn tool_call(call: &Value) -> ToolCall {
+    ToolCall {
+        name: call["function"]["name"]
+            .as_str()
+            .unwrap_or("a tool")
+            .to_string(),
+        arguments: call["function"]["arguments"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        result: None,
+    }
+}
+
+/// Attaches a tool message to the call it answers, matched by the call id, or adds it as a
+/// call of its own.
+fn attach_result(entries: &mut Vec<Entry>, agent: &str, message: &Message, messages: &[Message]) {
+    let name = message.id.as_deref().and_then(|id| {
+        messages
+            .iter()
+            .filter_map(|m| m.tool_calls.as_ref().and_then(Value::as_array))
+            .flatten()
+            .find(|call| call["id"] == id)
+            .map(tool_call)
+            .map(|call| call.name)
+    });
+    if let Some(Entry::ToolCalls { calls, .. }) = entries.last_mut() {
+        let pending = calls
+            .iter_mut()
+            .find(|call| call.result.is_none() && name.as_ref().is_none_or(|n| *n == call.name));
+        if let Some(call) = pending {
+            call.result = Some(message.content.clone());
+            return;
+        }
+    }
+    entries.push(Entry::ToolCalls {
+        agent: agent.to_string(),
+        calls: vec![ToolCall {
+            name: name.unwrap_or_else(|| "a tool".to_string()),
+            arguments: String::new(),
+            result: Some(message.content.clone()),
+        }],
+    });
+}
+
+/// A fenced code block with a fence longer than any backtick run in `content`, so the content
+/// cannot close it.
+fn code_block(content: &str) -> String {
+    let longest = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}\n{}\n{}", fence, content, fence)
+}
+
+fn seconds(duration: Duration) -> String {
+    format!("{:.1} s", duration.as_secs_f64())
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_renders_runs_as_one_conversation() {
+        let research = vec![
+            Message::new_human_message("Find the population of Lima"),
+            Message::new_ai_message("").with_tool_calls(json!([{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "search", "arguments": "{\"query\":\"Lima population\"}"}
+            }])),
+            Message::new_tool_message("About 10 million <2023>", "call_1"),
+            Message::new_ai_message("Lima has about 10 million inhabitants."),
+        ];
+        let review = vec![Message::new_ai_message("The figure matches the census.")];
+        let transcript = Transcript::new()
+            .with_title("Lima")
+            .with_run(
+                TranscriptRun::new("researcher", research)
+                    .with_duration(Duration::from_millis(2300)),
+            )
+            .with_run(TranscriptRun::new("reviewer", review));
+
+        let markdown = transcript.to_markdown();
+        assert!(markdown.starts_with("# Lima\n\n**User:** Find the population of Lima"));
+        assert!(markdown.contains(
+            "<summary>researcher used search</summary>\n\n```\n{\"query\":\"Lima population\"}\n```\n\nResult:\n\n```\nAbout 10 million <2023>\n```\n</details>"
+        ));
+        assert!(markdown.contains("*researcher took 2.3 s*"));
+        assert!(markdown.ends_with("**reviewer:** The figure matches the census.\n"));
+
+        let html = transcript.to_html();
+        assert!(html.contains("<pre>About 10 million &lt;2023&gt;</pre>"));
+        assert!(html.contains("<strong>reviewer:</strong>"));
+    }
+
+    #[test]
+    fn test_fence_outlasts_backticks_in_content() {
+        let messages = vec![
+            Message::new_ai_message("").with_tool_calls(json!([{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "run", "arguments": "{\"code\":\"```rust\\nmain()\\n```\"}"}
+            }])),
+            Message::new_tool_message("ok", "call_1"),
+        ];
+        let markdown = Transcript::new()
+            .with_run(TranscriptRun::new("coder", messages))
+            .to_markdown();
+        assert!(markdown.contains("\n````\n{\"code\":\"```rust"));
+        assert!(markdown.contains("```\"}\n````\n"));
+        assert!(markdown.contains("Result:\n\n```\nok\n```\n"));
+    }
+}
@@ -0,0 +1,122 @@
+//! Fakes shared by the unit tests.
+
+use std::{
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use futures::Stream;
+
+use crate::{
+    embedding::{Embedder, EmbedderError},
+    language_models::{llm::LLM, GenerateResult, LLMError},
+    schemas::{Message, StreamData},
+};
+
+type Reply = dyn Fn(&[Message]) -> Result<GenerateResult, LLMError> + Send + Sync;
+
+/// An LLM answering `generate` with a reply computed from the messages. Streaming is not
+/// scripted and fails with an error.
+#[derive(Clone)]
+pub(crate) struct FakeLLM {
+    reply: Arc<Reply>,
+    delay: Duration,
+    calls: Arc<AtomicUsize>,
+}
+
+impl FakeLLM {
+    /// Answers with whatever `reply` returns, errors included.
+    pub fn new<F>(reply: F) -> Self
+    where
+        F: Fn(&[Message]) -> Result<GenerateResult, LLMError> + Send + Sync + 'static,
+    {
+        Self {
+            reply: Arc::new(reply),
+            delay: Duration::ZERO,
+            calls: Arc::default(),
+        }
+    }
+
+    /// Answers with the text `reply` returns.
+    pub fn replying<F>(reply: F) -> Self
+    where
+        F: Fn(&[Message]) -> String + Send + Sync + 'static,
+    {
+        Self::new(move |messages| {
+            Ok(GenerateResult {
+                generation: reply(messages),
+                ..Default::default()
+            })
+        })
+    }
+
+    /// Always answers `text`.
+    pub fn fixed<S: Into<String>>(text: S) -> Self {
+        let text = text.into();
+        Self::replying(move |_| text.clone())
+    }
+
+    /// Answers with `results` in order, the last one repeating forever.
+    pub fn scripted(results: Vec<GenerateResult>) -> Self {
+        let next = AtomicUsize::new(0);
+        Self::new(move |_| {
+            let call = next.fetch_add(1, Ordering::SeqCst);
+            Ok(results[call.min(results.len() - 1)].clone())
+        })
+    }
+
+    /// Waits `delay` before every answer.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// How many times `generate` was called, shared by the clones.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::SeqCst)
+    }
+}
+
+#[async_trait]
+impl LLM for FakeLLM {
+    async fn generate(&self, messages: &[Message]) -> Result<GenerateResult, LLMError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+        (self.reply)(messages)
+    }
+
+    async fn stream(
+        &self,
+        _messages: &[Message],
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamData, LLMError>> + Send>>, LLMError> {
+        Err(LLMError::OtherError("FakeLLM does not stream".to_string()))
+    }
+}
+
+/// Embeds a text as its counts of the letters `a`, `b` and `c`.
+pub(crate) struct LetterEmbedder;
+
+#[async_trait]
+impl Embedder for LetterEmbedder {
+    async fn embed_documents(&self, documents: &[String]) -> Result<Vec<Vec<f64>>, EmbedderError> {
+        let mut embeddings = Vec::new();
+        for document in documents {
+            embeddings.push(self.embed_query(document).await?);
+        }
+        Ok(embeddings)
+    }
+
+    async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
+        Ok(['a', 'b', 'c']
+            .iter()
+            .map(|letter| text.matches(*letter).count() as f64 + 0.01)
+            .collect())
+    }
+}
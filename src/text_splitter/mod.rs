@@ -4,6 +4,7 @@ mod options;
 mod plain_text_splitter;
 mod text_splitter;
 mod token_splitter;
+mod tokenizer_splitter;
 
 pub use error::*;
 pub use markdown_splitter::*;
@@ -11,3 +12,4 @@ pub use options::*;
 pub use plain_text_splitter::*;
 pub use text_splitter::*;
 pub use token_splitter::*;
+pub use tokenizer_splitter::*;
@@ -0,0 +1,237 @@
+use std::{ops::Range, sync::Arc};
+
+use async_trait::async_trait;
+use tiktoken_rs::CoreBPE;
+
+use super::{SplitterOptions, TextSplitter, TextSplitterError};
+
+/// The tokenizer a [`TokenizerSplitter`] counts tokens with.
+#[derive(Clone)]
+pub enum SplitterTokenizer {
+    Tiktoken(Arc<CoreBPE>),
+    #[cfg(feature = "tokenizers")]
+    HuggingFace(Arc<tokenizers::Tokenizer>),
+}
+
+impl SplitterTokenizer {
+    /// The tiktoken encoding selected by `encoding_name` or `model_name`.
+    pub fn from_options(options: &SplitterOptions) -> Result<Self, TextSplitterError> {
+        Ok(Self::Tiktoken(Arc::new(options.get_bpe()?)))
+    }
+
+    /// Loads a HuggingFace `tokenizer.json`.
+    #[cfg(feature = "tokenizers")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, TextSplitterError> {
+        tokenizers::Tokenizer::from_file(path)
+            .map(|tokenizer| Self::HuggingFace(Arc::new(tokenizer)))
+            .map_err(|e| TextSplitterError::OtherError(e.to_string()))
+    }
+
+    pub fn count_tokens(&self, text: &str) -> Result<usize, TextSplitterError> {
+        Ok(self.token_spans(text)?.len())
+    }
+
+    /// The byte range each token covers in `text`, in order.
+    fn token_spans(&self, text: &str) -> Result<Vec<Range<usize>>, TextSplitterError> {
+        match self {
+            Self::Tiktoken(bpe) => {
+                let mut offset = 0;
+                Ok(bpe
+                    .encode_ordinary(text)
+                    .into_iter()
+                    .map(|token| {
+                        let end = (offset + bpe._decode_native(&[token]).len()).min(text.len());
+                        let span = offset..end;
+                        offset = end;
+                        span
+                    })
+                    .collect())
+            }
+            #[cfg(feature = "tokenizers")]
+            Self::HuggingFace(tokenizer) => {
+                let encoding = tokenizer
+                    .encode(text, false)
+                    .map_err(|e| TextSplitterError::OtherError(e.to_string()))?;
+                Ok(encoding
+                    .get_offsets()
+                    .iter()
+                    .map(|(start, end)| *start..*end)
+                    .collect())
+            }
+        }
+    }
+}
+
+impl From<CoreBPE> for SplitterTokenizer {
+    fn from(bpe: CoreBPE) -> Self {
+        Self::Tiktoken(Arc::new(bpe))
+    }
+}
+
+#[cfg(feature = "tokenizers")]
+impl From<tokenizers::Tokenizer> for SplitterTokenizer {
+    fn from(tokenizer: tokenizers::Tokenizer) -> Self {
+        Self::HuggingFace(Arc::new(tokenizer))
+    }
+}
+
+/// Splits text into windows of exactly `chunk_size` tokens (the last one may be shorter),
+/// consecutive windows sharing `chunk_overlap` tokens.
+///
+/// Unlike [`TokenSplitter`](super::TokenSplitter), which only uses tokens to size
+/// semantically split chunks, this counts tokens directly, so every chunk fits an embedding
+/// or prompt budget of `chunk_size` tokens. A chunk boundary that falls inside a multi-byte
+/// character is widened to the whole character.
+pub struct TokenizerSplitter {
+    splitter_options: SplitterOptions,
+    tokenizer: Option<SplitterTokenizer>,
+}
+
+impl Default for TokenizerSplitter {
+    fn default() -> Self {
+        TokenizerSplitter::new(SplitterOptions::default())
+    }
+}
+
+impl TokenizerSplitter {
+    pub fn new(options: SplitterOptions) -> TokenizerSplitter {
+        TokenizerSplitter {
+            splitter_options: options,
+            tokenizer: None,
+        }
+    }
+
+    /// Counts tokens with `tokenizer` instead of the tiktoken encoding named in the options.
+    pub fn with_tokenizer<T: Into<SplitterTokenizer>>(mut self, tokenizer: T) -> Self {
+        self.tokenizer = Some(tokenizer.into());
+        self
+    }
+}
+
+#[async_trait]
+impl TextSplitter for TokenizerSplitter {
+    async fn split_text(&self, text: &str) -> Result<Vec<String>, TextSplitterError> {
+        let options = &self.splitter_options;
+        if options.chunk_size == 0 || options.chunk_overlap >= options.chunk_size {
+            return Err(TextSplitterError::InvalidSplitterOptions);
+        }
+
+        let tokenizer = match &self.tokenizer {
+            Some(tokenizer) => tokenizer.clone(),
+            None => SplitterTokenizer::from_options(options)?,
+        };
+        let spans = tokenizer.token_spans(text)?;
+        let step = options.chunk_size - options.chunk_overlap;
+
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        while start < spans.len() {
+            let end = (start + options.chunk_size).min(spans.len());
+            if let Some(range) = covering_range(text, &spans[start..end]) {
+                let chunk = &text[range];
+                let chunk = if options.trim_chunks {
+                    chunk.trim()
+                } else {
+                    chunk
+                };
+                if !chunk.is_empty() {
+                    chunks.push(chunk.to_string());
+                }
+            }
+            if end == spans.len() {
+                break;
+            }
+            start += step;
+        }
+
+        Ok(chunks)
+    }
+}
+
+/// The smallest char-aligned range of `text` covering every non-empty span. Special tokens
+/// some tokenizers add carry empty spans and are ignored.
+fn covering_range(text: &str, spans: &[Range<usize>]) -> Option<Range<usize>> {
+    let mut spans = spans.iter().filter(|span| !span.is_empty());
+    let first = spans.next()?;
+    let (mut start, mut end) = (first.start, first.end);
+    for span in spans {
+        start = start.min(span.start);
+        end = end.max(span.end);
+    }
+
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+    Some(start..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = "The quick brown fox jumps over the lazy dog. \
+        Pack my box with five dozen liquor jugs. \
+        How vexingly quick daft zebras jump!";
+
+    #[tokio::test]
+    async fn test_chunks_fit_token_budget() {
+        let splitter = TokenizerSplitter::new(SplitterOptions::new().with_chunk_size(8));
+        let tokenizer = SplitterTokenizer::from_options(&SplitterOptions::new()).unwrap();
+
+        let chunks = splitter.split_text(TEXT).await.unwrap();
+
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(tokenizer.count_tokens(chunk).unwrap() <= 8);
+        }
+        assert_eq!(chunks.concat(), TEXT);
+    }
+
+    #[tokio::test]
+    async fn test_chunks_overlap_in_tokens() {
+        let options = SplitterOptions::new()
+            .with_chunk_size(8)
+            .with_chunk_overlap(3);
+        let splitter = TokenizerSplitter::new(options);
+
+        let chunks = splitter.split_text(TEXT).await.unwrap();
+
+        for pair in chunks.windows(2) {
+            let shared = (1..=pair[0].len())
+                .rev()
+                .find(|n| pair[1].starts_with(&pair[0][pair[0].len() - n..]));
+            assert!(
+                shared.is_some(),
+                "{:?} does not overlap {:?}",
+                pair[0],
+                pair[1]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_multibyte_characters_stay_whole() {
+        let splitter = TokenizerSplitter::new(SplitterOptions::new().with_chunk_size(1));
+
+        let chunks = splitter.split_text("héllo wörld 🦀").await.unwrap();
+
+        assert!(chunks.iter().all(|chunk| !chunk.is_empty()));
+        assert!(chunks.concat().contains('🦀'));
+    }
+
+    #[tokio::test]
+    async fn test_overlap_must_be_smaller_than_chunk_size() {
+        let options = SplitterOptions::new()
+            .with_chunk_size(4)
+            .with_chunk_overlap(4);
+        let splitter = TokenizerSplitter::new(options);
+
+        assert!(matches!(
+            splitter.split_text(TEXT).await,
+            Err(TextSplitterError::InvalidSplitterOptions)
+        ));
+    }
+}
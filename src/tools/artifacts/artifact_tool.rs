@@ -0,0 +1,96 @@
+use std::error::Error;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use crate::tools::Tool;
+
+use super::RunArtifacts;
+
+/// Lets the agent list the files tools produced earlier in the run and read them back.
+pub struct ArtifactTool {
+    max_chars: usize,
+}
+
+impl Default for ArtifactTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArtifactTool {
+    pub fn new() -> Self {
+        Self { max_chars: 10_000 }
+    }
+
+    /// Longer text artifacts are cut off when read.
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+}
+
+#[async_trait]
+impl Tool for ArtifactTool {
+    fn name(&self) -> String {
+        "artifacts".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Lists the files produced so far in this task, such as reports or images, or reads \
+         one of them by name."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["list", "read"],
+                },
+                "name": {
+                    "type": "string",
+                    "description": "The file to read",
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        serde_json::from_str(input).unwrap_or_else(|_| json!({ "action": input.trim() }))
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let run = RunArtifacts::current().ok_or("No artifacts are available outside a run")?;
+        match input["action"].as_str().unwrap_or("list") {
+            "list" => {
+                let artifacts = run.list().await?;
+                if artifacts.is_empty() {
+                    return Ok("No files have been produced yet.".to_string());
+                }
+                Ok(artifacts
+                    .iter()
+                    .map(|artifact| format!("{} ({} bytes)", artifact.name, artifact.size))
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
+            "read" => {
+                let name = input["name"].as_str().ok_or("Missing the file name")?;
+                let contents = run.read(name).await?;
+                match String::from_utf8(contents) {
+                    Ok(text) => Ok(text.chars().take(self.max_chars).collect()),
+                    Err(e) => Ok(format!(
+                        "{} is a binary file of {} bytes at {}",
+                        name,
+                        e.as_bytes().len(),
+                        run.dir().join(name).display()
+                    )),
+                }
+            }
+            action => Err(format!("Unknown action: {}", action).into()),
+        }
+    }
+}
@@ -0,0 +1,5 @@
+mod artifact_tool;
+pub use artifact_tool::*;
+
+mod store;
+pub use store::*;
@@ -0,0 +1,174 @@
+use std::{
+    future::Future,
+    io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+tokio::task_local! {
+    static CURRENT_RUN: RunArtifacts;
+}
+
+/// A file a tool produced during a run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Artifact {
+    pub name: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Keeps the artifacts of every run in a directory of its own under `root`, named after the
+/// run id.
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The artifacts of run `run_id`. Characters that are not safe in a file name are
+    /// replaced in the directory name.
+    pub fn run(&self, run_id: &str) -> RunArtifacts {
+        let dir_name = run_id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        RunArtifacts {
+            run_id: run_id.to_string(),
+            dir: self.root.join(dir_name),
+        }
+    }
+
+    pub async fn list(&self, run_id: &str) -> io::Result<Vec<Artifact>> {
+        self.run(run_id).list().await
+    }
+}
+
+/// The artifact directory of one run. Tools find the run they are called in with
+/// [`RunArtifacts::current`].
+#[derive(Debug, Clone)]
+pub struct RunArtifacts {
+    run_id: String,
+    dir: PathBuf,
+}
+
+impl RunArtifacts {
+    /// The artifacts of the run the calling task is part of, if the executor has an
+    /// [`ArtifactStore`].
+    pub fn current() -> Option<Self> {
+        CURRENT_RUN.try_with(Clone::clone).ok()
+    }
+
+    pub fn run_id(&self) -> &str {
+        &self.run_id
+    }
+
+    /// Created on the first write.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Runs `future` with these artifacts as [`RunArtifacts::current`].
+    pub async fn scope<F: Future>(self, future: F) -> F::Output {
+        CURRENT_RUN.scope(self, future).await
+    }
+
+    pub async fn write<C: AsRef<[u8]>>(&self, name: &str, contents: C) -> io::Result<Artifact> {
+        let path = self.path(name)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(&path, contents).await?;
+        let size = tokio::fs::metadata(&path).await?.len();
+        Ok(Artifact {
+            name: name.to_string(),
+            path,
+            size,
+        })
+    }
+
+    pub async fn read(&self, name: &str) -> io::Result<Vec<u8>> {
+        tokio::fs::read(self.path(name)?).await
+    }
+
+    /// The artifacts written so far, by name.
+    pub async fn list(&self) -> io::Result<Vec<Artifact>> {
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut artifacts = Vec::new();
+        while let Some(entry) = entries.next_entry().await? {
+            let metadata = entry.metadata().await?;
+            if metadata.is_file() {
+                artifacts.push(Artifact {
+                    name: entry.file_name().to_string_lossy().into_owned(),
+                    path: entry.path(),
+                    size: metadata.len(),
+                });
+            }
+        }
+        artifacts.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(artifacts)
+    }
+
+    /// Artifact names are plain file names, so tools cannot write outside the run directory.
+    fn path(&self, name: &str) -> io::Result<PathBuf> {
+        let is_file_name =
+            !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\']);
+        if !is_file_name {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid artifact name: {}", name),
+            ));
+        }
+        Ok(self.dir.join(name))
+    }
+}
+
+/// An id for runs started without a `run_id`, unique within the process.
+pub(crate) fn new_run_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default();
+    format!("run-{}-{}", millis, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_runs_are_isolated() {
+        let root = std::env::temp_dir().join(format!("artifacts-{}", std::process::id()));
+        let store = ArtifactStore::new(&root);
+
+        let run = store.run("run/1");
+        let artifact = run.write("report.md", "# Report").await.unwrap();
+        assert_eq!(artifact.size, 8);
+        assert!(run.write("../escape.md", "").await.is_err());
+
+        assert_eq!(store.list("run/1").await.unwrap(), [artifact]);
+        assert!(store.list("run-2").await.unwrap().is_empty());
+
+        assert!(RunArtifacts::current().is_none());
+        let current = run
+            .scope(async { RunArtifacts::current().map(|run| run.run_id().to_string()) })
+            .await;
+        assert_eq!(current.as_deref(), Some("run/1"));
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,319 @@
+use std::{
+    error::Error,
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::tools::Tool;
+
+/// Controls whether a [`CassetteTool`] talks to the wrapped tool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Always call the real tool and overwrite the cassette.
+    Record,
+    /// Never call the real tool; inputs without a recording are an error.
+    Replay,
+    /// Replay recorded inputs and record the ones that are missing.
+    Auto,
+}
+
+/// How a new tool input is compared against the recorded ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchRule {
+    Exact,
+    /// Compare inputs with all whitespace removed.
+    IgnoreWhitespace,
+    /// Compare inputs as JSON values (key order and formatting are ignored), falling back to
+    /// exact comparison when an input is not valid JSON.
+    Json,
+}
+
+impl MatchRule {
+    fn matches(&self, recorded: &str, input: &str) -> bool {
+        match self {
+            MatchRule::Exact => recorded == input,
+            MatchRule::IgnoreWhitespace => recorded
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .eq(input.chars().filter(|c| !c.is_whitespace())),
+            MatchRule::Json => match (
+                serde_json::from_str::<Value>(recorded),
+                serde_json::from_str::<Value>(input),
+            ) {
+                (Ok(recorded), Ok(input)) => recorded == input,
+                _ => recorded == input,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Interaction {
+    pub input: String,
+    pub output: String,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cassette {
+    tool: String,
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Default)]
+struct CassetteState {
+    cassette: Cassette,
+    /// Marks interactions already replayed so repeated identical inputs replay in order.
+    replayed: Vec<bool>,
+}
+
+/// Wraps a tool and records its input/output pairs to a JSON cassette file, replaying them on
+/// later runs so agent tests are fast and deterministic.
+///
+/// # Example
+/// ```rust,ignore
+/// let search = CassetteTool::new(Arc::new(DuckDuckGoSearchResults::default()), "tests/search.json")?
+///     .with_match_rule(MatchRule::Json);
+/// ```
+pub struct CassetteTool {
+    tool: Arc<dyn Tool>,
+    path: PathBuf,
+    mode: CassetteMode,
+    match_rule: MatchRule,
+    state: Mutex<CassetteState>,
+}
+
+impl CassetteTool {
+    /// Creates a cassette in [`CassetteMode::Auto`], loading `path` if it already exists. Fails
+    /// if the existing cassette was recorded for another tool.
+    pub fn new<P: AsRef<Path>>(tool: Arc<dyn Tool>, path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref().to_path_buf();
+        let cassette = if path.exists() {
+            let cassette: Cassette = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            if cassette.tool != tool.name() {
+                return Err(format!(
+                    "Cassette {} was recorded for {}, not {}",
+                    path.display(),
+                    cassette.tool,
+                    tool.name()
+                )
+                .into());
+            }
+            cassette
+        } else {
+            Cassette {
+                tool: tool.name(),
+                interactions: Vec::new(),
+            }
+        };
+        let replayed = vec![false; cassette.interactions.len()];
+
+        Ok(Self {
+            tool,
+            path,
+            mode: CassetteMode::Auto,
+            match_rule: MatchRule::Exact,
+            state: Mutex::new(CassetteState { cassette, replayed }),
+        })
+    }
+
+    pub fn with_mode(mut self, mode: CassetteMode) -> Self {
+        if mode == CassetteMode::Record {
+            // Recording starts from a blank cassette.
+            let state = self.state.get_mut();
+            state.cassette.interactions.clear();
+            state.replayed.clear();
+        }
+        self.mode = mode;
+        self
+    }
+
+    pub fn with_match_rule(mut self, match_rule: MatchRule) -> Self {
+        self.match_rule = match_rule;
+        self
+    }
+
+    pub async fn interactions(&self) -> Vec<Interaction> {
+        self.state.lock().await.cassette.interactions.clone()
+    }
+
+    fn find_recording(&self, state: &mut CassetteState, input: &str) -> Option<Interaction> {
+        let interactions = &state.cassette.interactions;
+        let matching = |(_, interaction): &(usize, &Interaction)| {
+            self.match_rule.matches(&interaction.input, input)
+        };
+        let (index, interaction) = interactions
+            .iter()
+            .enumerate()
+            .filter(matching)
+            .find(|(index, _)| !state.replayed[*index])
+            .or_else(|| interactions.iter().enumerate().find(matching))?;
+        let interaction = interaction.clone();
+        state.replayed[index] = true;
+        Some(interaction)
+    }
+
+    /// Replays the recording of `input` or, if there is none and the mode allows it, records
+    /// what `real` returns. The state is not locked while the real tool runs.
+    async fn play<F>(&self, input: &str, real: F) -> Result<String, Box<dyn Error>>
+    where
+        F: Future<Output = Result<String, Box<dyn Error>>>,
+    {
+        if self.mode != CassetteMode::Record {
+            let recording = self.find_recording(&mut *self.state.lock().await, input);
+            if let Some(interaction) = recording {
+                log::debug!("Replaying cassette interaction for {}", self.name());
+                return match interaction.is_error {
+                    true => Err(interaction.output.into()),
+                    false => Ok(interaction.output),
+                };
+            }
+            if self.mode == CassetteMode::Replay {
+                return Err(format!(
+                    "No recorded interaction in {} for input: {}",
+                    self.path.display(),
+                    input
+                )
+                .into());
+            }
+        }
+
+        // Errors are kept as their message, as on replay, since the boxed error is not Send.
+        let (output, is_error) = match real.await {
+            Ok(output) => (output, false),
+            Err(err) => (err.to_string(), true),
+        };
+        let mut state = self.state.lock().await;
+        state.cassette.interactions.push(Interaction {
+            input: input.to_string(),
+            output: output.clone(),
+            is_error,
+        });
+        state.replayed.push(true);
+        self.save(&state.cassette)?;
+        match is_error {
+            true => Err(output.into()),
+            false => Ok(output),
+        }
+    }
+
+    fn save(&self, cassette: &Cassette) -> Result<(), Box<dyn Error>> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string_pretty(cassette)?)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Tool for CassetteTool {
+    fn name(&self) -> String {
+        self.tool.name()
+    }
+
+    fn description(&self) -> String {
+        self.tool.description()
+    }
+
+    fn parameters(&self) -> Value {
+        self.tool.parameters()
+    }
+
+    async fn call(&self, input: &str) -> Result<String, Box<dyn Error>> {
+        self.play(input, self.tool.call(input)).await
+    }
+
+    /// Parsed inputs are recorded as their string, or their JSON encoding for other values.
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let recorded = match &input {
+            Value::String(s) => s.clone(),
+            value => value.to_string(),
+        };
+        self.play(&recorded, self.tool.run(input)).await
+    }
+
+    fn idempotency_key(&self, input: &Value) -> Option<String> {
+        self.tool.idempotency_key(input)
+    }
+
+    async fn shutdown(&self) -> Result<(), Box<dyn Error>> {
+        self.tool.shutdown().await
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        self.tool.parse_input(input).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    struct Counter {
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait]
+    impl Tool for Counter {
+        fn name(&self) -> String {
+            "Counter".to_string()
+        }
+        fn description(&self) -> String {
+            "Counts its invocations".to_string()
+        }
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            let calls = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(calls.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_then_replays() {
+        let path = std::env::temp_dir().join(format!("cassette-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter: Arc<dyn Tool> = Arc::new(Counter {
+            calls: calls.clone(),
+        });
+
+        let recorder = CassetteTool::new(counter.clone(), &path).unwrap();
+        assert_eq!(recorder.call(r#"{"a": 1, "b": 2}"#).await.unwrap(), "1");
+        assert_eq!(recorder.call("other").await.unwrap(), "2");
+
+        let player = CassetteTool::new(counter, &path)
+            .unwrap()
+            .with_mode(CassetteMode::Replay)
+            .with_match_rule(MatchRule::Json);
+        assert_eq!(player.call(r#"{"b":2,"a":1}"#).await.unwrap(), "1");
+        assert_eq!(player.call("other").await.unwrap(), "2");
+        assert!(player.call("unknown").await.is_err());
+        assert_eq!(player.run(Value::from("other")).await.unwrap(), "2");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejects_cassette_of_another_tool() {
+        let path = std::env::temp_dir().join(format!("cassette-other-{}.json", std::process::id()));
+        fs::write(&path, r#"{"tool": "Search", "interactions": []}"#).unwrap();
+
+        let counter: Arc<dyn Tool> = Arc::new(Counter {
+            calls: Arc::default(),
+        });
+        assert!(CassetteTool::new(counter, &path).is_err());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
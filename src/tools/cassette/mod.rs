@@ -0,0 +1,2 @@
+mod cassette;
+pub use cassette::*;
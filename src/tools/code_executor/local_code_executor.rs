@@ -0,0 +1,325 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    path::Path,
+    process::Stdio,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt},
+    process::Command,
+};
+
+use crate::tools::{RunArtifacts, Tool};
+
+/// How to run the code of one language: `program [args...] <script file>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpreter {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Extension of the script file, which some interpreters require.
+    pub extension: String,
+}
+
+impl Interpreter {
+    pub fn new<P: Into<String>, E: Into<String>>(program: P, extension: E) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            extension: extension.into(),
+        }
+    }
+
+    pub fn with_args<S: Into<String>>(mut self, args: Vec<S>) -> Self {
+        self.args = args.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+/// The outcome of running a piece of code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExecutionOutput {
+    /// `None` if the process was killed.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    /// Whether output beyond the size limit was dropped.
+    pub truncated: bool,
+}
+
+/// Runs code with the real interpreters installed on the machine, for trusted environments
+/// only: the code can do anything the user running the agent can.
+///
+/// Every call gets a new script file and a clean environment with only `PATH`. The code
+/// runs in the run's artifact directory when the executor has an
+/// [`ArtifactStore`](crate::tools::ArtifactStore), so the files it writes become artifacts,
+/// and in a temporary directory removed afterwards otherwise. Processes running longer than
+/// the timeout are killed, and each output stream is cut off at `max_output_bytes`.
+pub struct LocalCodeExecutor {
+    interpreters: HashMap<String, Interpreter>,
+    timeout: Duration,
+    max_output_bytes: usize,
+    max_memory_mb: Option<u64>,
+}
+
+impl Default for LocalCodeExecutor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LocalCodeExecutor {
+    /// Runs `python` with python3, `javascript` with node and `bash` with bash.
+    pub fn new() -> Self {
+        Self {
+            interpreters: HashMap::from([
+                ("python".to_string(), Interpreter::new("python3", "py")),
+                ("javascript".to_string(), Interpreter::new("node", "js")),
+                ("bash".to_string(), Interpreter::new("bash", "sh")),
+            ]),
+            timeout: Duration::from_secs(30),
+            max_output_bytes: 16 * 1024,
+            max_memory_mb: None,
+        }
+    }
+
+    /// Adds or replaces the interpreter of `language`.
+    pub fn with_interpreter<S: Into<String>>(
+        mut self,
+        language: S,
+        interpreter: Interpreter,
+    ) -> Self {
+        self.interpreters.insert(language.into(), interpreter);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_output_bytes(mut self, max_output_bytes: usize) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Caps the virtual memory of the process with `ulimit -v`, on Unix only. Runtimes that
+    /// reserve a lot of address space up front, like node, need a generous limit.
+    pub fn with_max_memory_mb(mut self, max_memory_mb: u64) -> Self {
+        self.max_memory_mb = Some(max_memory_mb);
+        self
+    }
+
+    pub async fn execute(
+        &self,
+        language: &str,
+        code: &str,
+    ) -> Result<ExecutionOutput, Box<dyn Error>> {
+        let interpreter = self.interpreters.get(language).ok_or_else(|| {
+            format!(
+                "Unsupported language {}, expected one of: {}",
+                language,
+                self.languages().join(", ")
+            )
+        })?;
+
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let script_dir = std::env::temp_dir().join(format!(
+            "langchain-code-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::create_dir_all(&script_dir).await?;
+        let result = self
+            .run_script(interpreter, code, &script_dir)
+            .await
+            .map_err(|e| e.to_string());
+        if let Err(e) = tokio::fs::remove_dir_all(&script_dir).await {
+            log::warn!("Could not remove {}: {}", script_dir.display(), e);
+        }
+        Ok(result?)
+    }
+
+    async fn run_script(
+        &self,
+        interpreter: &Interpreter,
+        code: &str,
+        script_dir: &Path,
+    ) -> Result<ExecutionOutput, Box<dyn Error>> {
+        let script = script_dir.join(format!("main.{}", interpreter.extension));
+        tokio::fs::write(&script, code).await?;
+        let work_dir = match RunArtifacts::current() {
+            Some(run) => {
+                tokio::fs::create_dir_all(run.dir()).await?;
+                run.dir().to_path_buf()
+            }
+            None => script_dir.to_path_buf(),
+        };
+
+        let mut command = match self.max_memory_mb.filter(|_| cfg!(unix)) {
+            Some(max_memory_mb) => {
+                let mut command = Command::new("sh");
+                command
+                    .arg("-c")
+                    .arg(format!(
+                        "ulimit -v {} && exec \"$0\" \"$@\"",
+                        max_memory_mb * 1024
+                    ))
+                    .arg(&interpreter.program);
+                command
+            }
+            None => Command::new(&interpreter.program),
+        };
+        command
+            .args(&interpreter.args)
+            .arg(&script)
+            .current_dir(&work_dir)
+            .env_clear()
+            .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+
+        let mut child = command.spawn()?;
+        let stdout = child.stdout.take().ok_or("No stdout")?;
+        let stderr = child.stderr.take().ok_or("No stderr")?;
+        let finished = tokio::time::timeout(self.timeout, async {
+            tokio::join!(
+                read_capped(stdout, self.max_output_bytes),
+                read_capped(stderr, self.max_output_bytes),
+                child.wait()
+            )
+        })
+        .await;
+
+        match finished {
+            Ok((stdout, stderr, status)) => {
+                let (stdout, stdout_truncated) = stdout?;
+                let (stderr, stderr_truncated) = stderr?;
+                Ok(ExecutionOutput {
+                    exit_code: status?.code(),
+                    stdout,
+                    stderr,
+                    timed_out: false,
+                    truncated: stdout_truncated || stderr_truncated,
+                })
+            }
+            Err(_) => {
+                child.kill().await?;
+                Ok(ExecutionOutput {
+                    exit_code: None,
+                    stdout: String::new(),
+                    stderr: format!("Killed after {:?}", self.timeout),
+                    timed_out: true,
+                    truncated: false,
+                })
+            }
+        }
+    }
+
+    fn languages(&self) -> Vec<&str> {
+        let mut languages = self
+            .interpreters
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        languages.sort();
+        languages
+    }
+}
+
+/// Reads `reader` to the end, keeping the first `max_bytes` bytes, so the process never
+/// blocks on a full pipe.
+async fn read_capped<R: AsyncRead + Unpin>(
+    mut reader: R,
+    max_bytes: usize,
+) -> std::io::Result<(String, bool)> {
+    let mut output = Vec::new();
+    let mut truncated = false;
+    let mut buffer = [0; 8192];
+    loop {
+        let read = reader.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        let kept = read.min(max_bytes - output.len());
+        output.extend_from_slice(&buffer[..kept]);
+        truncated |= kept < read;
+    }
+    Ok((String::from_utf8_lossy(&output).into_owned(), truncated))
+}
+
+#[async_trait]
+impl Tool for LocalCodeExecutor {
+    fn name(&self) -> String {
+        "code_executor".to_string()
+    }
+
+    fn description(&self) -> String {
+        format!(
+            "Runs a program and returns its exit code, stdout and stderr. Supported languages: \
+             {}. Print the results you need.",
+            self.languages().join(", ")
+        )
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "language": {
+                    "type": "string",
+                    "enum": self.languages(),
+                },
+                "code": {
+                    "type": "string",
+                    "description": "The source code of the program",
+                }
+            },
+            "required": ["language", "code"]
+        })
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        serde_json::from_str(input).unwrap_or_else(|_| json!({ "code": input }))
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let code = input["code"].as_str().ok_or("Missing the code")?;
+        let language = input["language"].as_str().unwrap_or("python");
+        let output = self.execute(language, code).await?;
+        Ok(serde_json::to_string(&output)?)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_runs_code_with_limits() {
+        let executor = LocalCodeExecutor::new()
+            .with_timeout(Duration::from_millis(500))
+            .with_max_output_bytes(4);
+
+        let output = executor
+            .execute("bash", "echo hello; echo oops >&2; exit 3")
+            .await
+            .unwrap();
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stdout, "hell");
+        assert_eq!(output.stderr, "oops");
+        assert!(output.truncated);
+
+        let output = executor.execute("bash", "sleep 5").await.unwrap();
+        assert!(output.timed_out);
+        assert!(executor.execute("cobol", "").await.is_err());
+    }
+}
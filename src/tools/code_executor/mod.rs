@@ -0,0 +1,2 @@
+mod local_code_executor;
+pub use local_code_executor::*;
@@ -0,0 +1,5 @@
+mod patch_tool;
+pub use patch_tool::*;
+
+mod patch;
+pub use patch::*;
@@ -0,0 +1,237 @@
+use serde::{Deserialize, Serialize};
+
+/// The changes a unified diff makes to one file. A missing path is `/dev/null`, so a file
+/// without an old path is created and one without a new path deleted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The `@@ ... @@` line.
+    pub header: String,
+    /// 1-based line of the old file the hunk starts at, 0 when it adds to an empty file.
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+impl Hunk {
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// A hunk that could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RejectedHunk {
+    /// 1-based position of the hunk in its file's patch.
+    pub hunk: usize,
+    pub header: String,
+    pub reason: String,
+}
+
+/// Parses a unified diff as produced by `diff -u` or `git diff`. Lines outside of file
+/// changes, such as `diff --git` and `index` lines or prose around the diff, are skipped.
+pub fn parse_patch(patch: &str) -> Result<Vec<FilePatch>, String> {
+    let mut files: Vec<FilePatch> = Vec::new();
+    let mut lines = patch.lines();
+    while let Some(line) = lines.next() {
+        if let Some(old_path) = line.strip_prefix("--- ") {
+            let new_path = lines
+                .next()
+                .and_then(|line| line.strip_prefix("+++ "))
+                .ok_or_else(|| format!("Expected a +++ line after: {}", line))?;
+            files.push(FilePatch {
+                old_path: patch_path(old_path, "a/"),
+                new_path: patch_path(new_path, "b/"),
+                hunks: Vec::new(),
+            });
+        } else if line.starts_with("@@") {
+            let file = files
+                .last_mut()
+                .ok_or_else(|| format!("Hunk before any file header: {}", line))?;
+            let (old_start, mut old_count, mut new_count) = parse_header(line)?;
+            let mut hunk = Hunk {
+                header: line.to_string(),
+                old_start,
+                lines: Vec::new(),
+            };
+            while old_count > 0 || new_count > 0 {
+                let line = lines
+                    .next()
+                    .ok_or_else(|| format!("The hunk {} is cut off", hunk.header))?;
+                let (kind, text) = match line.chars().next() {
+                    // Some editors strip the space of empty context lines.
+                    None => (' ', ""),
+                    Some(kind) => (kind, &line[kind.len_utf8()..]),
+                };
+                let text = text.to_string();
+                match kind {
+                    ' ' => {
+                        old_count = old_count.saturating_sub(1);
+                        new_count = new_count.saturating_sub(1);
+                        hunk.lines.push(HunkLine::Context(text));
+                    }
+                    '-' => {
+                        old_count = old_count.saturating_sub(1);
+                        hunk.lines.push(HunkLine::Remove(text));
+                    }
+                    '+' => {
+                        new_count = new_count.saturating_sub(1);
+                        hunk.lines.push(HunkLine::Add(text));
+                    }
+                    '\\' => {}
+                    _ => return Err(format!("Unexpected line in {}: {}", hunk.header, line)),
+                }
+            }
+            file.hunks.push(hunk);
+        }
+    }
+    Ok(files)
+}
+
+fn patch_path(path: &str, prefix: &str) -> Option<String> {
+    // Timestamps follow the path after a tab in `diff -u` output.
+    let path = path.split('\t').next().unwrap_or_default().trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(path.strip_prefix(prefix).unwrap_or(path).to_string())
+}
+
+/// The old start and the line counts of `@@ -start,count +start,count @@`.
+fn parse_header(header: &str) -> Result<(usize, usize, usize), String> {
+    let invalid = || format!("Invalid hunk header: {}", header);
+    let mut ranges = header
+        .trim_start_matches('@')
+        .split_whitespace()
+        .take(2)
+        .map(|range| {
+            let range = range.trim_start_matches(['-', '+']);
+            let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+            Ok::<_, String>((
+                start.parse::<usize>().map_err(|_| invalid())?,
+                count.parse::<usize>().map_err(|_| invalid())?,
+            ))
+        });
+    let (old_start, old_count) = ranges.next().ok_or_else(invalid)??;
+    let (_, new_count) = ranges.next().ok_or_else(invalid)??;
+    Ok((old_start, old_count, new_count))
+}
+
+/// Applies `hunks` to `content`, returning the new content and the hunks whose removed and
+/// context lines were not found. A hunk is looked for at its line number first, then at
+/// growing distances from it, but never before the end of the previous hunk.
+pub fn apply_hunks(content: &str, hunks: &[Hunk]) -> (String, Vec<RejectedHunk>) {
+    let trailing_newline = content.is_empty() || content.ends_with('\n');
+    let mut lines = content.lines().collect::<Vec<_>>();
+    let mut rejected = Vec::new();
+    // Offset of the new lines from the old ones, caused by the hunks applied so far.
+    let mut offset: isize = 0;
+    let mut cursor = 0;
+    for (i, hunk) in hunks.iter().enumerate() {
+        let old = hunk.old_lines();
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        match find(&lines, &old, expected, cursor) {
+            Some(at) => {
+                let new = hunk.new_lines();
+                lines.splice(at..at + old.len(), new.iter().copied());
+                offset += new.len() as isize - old.len() as isize;
+                cursor = at + new.len();
+            }
+            None => rejected.push(RejectedHunk {
+                hunk: i + 1,
+                header: hunk.header.clone(),
+                reason: "The lines it changes were not found in the file".to_string(),
+            }),
+        }
+    }
+    let mut content = lines.join("\n");
+    if trailing_newline && !content.is_empty() {
+        content.push('\n');
+    }
+    (content, rejected)
+}
+
+fn find(lines: &[&str], old: &[&str], expected: usize, cursor: usize) -> Option<usize> {
+    if old.len() > lines.len() {
+        return None;
+    }
+    let last = lines.len() - old.len();
+    let matches = |at: usize| at >= cursor && at <= last && lines[at..at + old.len()] == *old;
+    let expected = expected.min(last);
+    (0..=lines.len()).find_map(|distance| {
+        [expected.checked_sub(distance), Some(expected + distance)]
+            .into_iter()
+            .flatten()
+            .find(|&at| matches(at))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PATCH: &str = "diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@
+ fn main() {
+-    println!(\"hello\");
++    println!(\"hello, world\");
+ }
+@@ -10,2 +10,3 @@
+ fn other() {}
++fn added() {}
+
+";
+
+    #[test]
+    fn test_parse_and_apply() {
+        let files = parse_patch(PATCH).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].new_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(files[0].hunks.len(), 2);
+
+        // The second hunk is found a few lines before where the header puts it.
+        let content = "fn main() {\n    println!(\"hello\");\n}\n\nfn other() {}\n\n";
+        let (patched, rejected) = apply_hunks(content, &files[0].hunks);
+        assert!(rejected.is_empty());
+        assert_eq!(
+            patched,
+            "fn main() {\n    println!(\"hello, world\");\n}\n\nfn other() {}\nfn added() {}\n\n"
+        );
+
+        let (_, rejected) = apply_hunks("fn main() {}\n", &files[0].hunks);
+        assert_eq!(
+            rejected.iter().map(|r| r.hunk).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
+}
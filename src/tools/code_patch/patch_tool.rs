@@ -0,0 +1,369 @@
+use std::{
+    error::Error,
+    io,
+    path::{Component, Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::tools::Tool;
+
+use super::{apply_hunks, parse_patch, RejectedHunk};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Created,
+    Modified,
+    Deleted,
+}
+
+/// What a patch did, or would do in a dry run, to one file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilePatchResult {
+    pub path: String,
+    pub change: FileChange,
+    pub hunks_applied: usize,
+    pub hunks_rejected: Vec<RejectedHunk>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchResult {
+    /// Whether the files were written. A patch is only applied when all of its hunks apply.
+    pub applied: bool,
+    pub dry_run: bool,
+    pub files: Vec<FilePatchResult>,
+}
+
+/// The contents of the files a patch changed before it was applied, `None` for created files.
+type Backup = Vec<(PathBuf, Option<String>)>;
+
+/// Applies unified diffs written by the LLM to the files of a workspace directory.
+///
+/// A patch is applied all or nothing: if a hunk's lines are not found in its file, nothing
+/// is written and the result lists the rejected hunks, so the agent can regenerate them. If
+/// writing a file fails, the files already written are restored.
+/// Hunks are also found when the line numbers are a bit off. Dry runs report the outcome
+/// without writing, and applied patches can be rolled back, the latest first.
+///
+/// The tool takes `{"patch": "<diff>"}`, optionally with `"dry_run": true`, or
+/// `{"action": "rollback"}`, and answers with the [`PatchResult`] as JSON.
+pub struct CodePatchTool {
+    workspace: PathBuf,
+    dry_run: bool,
+    history: Mutex<Vec<Backup>>,
+}
+
+impl CodePatchTool {
+    pub fn new<P: Into<PathBuf>>(workspace: P) -> Self {
+        Self {
+            workspace: workspace.into(),
+            dry_run: false,
+            history: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Never writes, whatever the input asks for.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub async fn apply(&self, patch: &str, dry_run: bool) -> Result<PatchResult, Box<dyn Error>> {
+        let files = parse_patch(patch)?;
+        if files.is_empty() {
+            return Err("The patch does not change any file".into());
+        }
+
+        let mut results = Vec::new();
+        let mut writes = Vec::new();
+        for file in files {
+            let (path, change) = match (file.old_path, file.new_path) {
+                (None, Some(path)) => (path, FileChange::Created),
+                (Some(path), None) => (path, FileChange::Deleted),
+                (Some(old_path), Some(path)) if old_path == path => (path, FileChange::Modified),
+                (Some(old_path), Some(path)) => {
+                    return Err(
+                        format!("Renaming {} to {} is not supported", old_path, path).into(),
+                    )
+                }
+                (None, None) => return Err("A file of the patch has no path".into()),
+            };
+            let full_path = self.resolve(&path)?;
+            let original = match tokio::fs::read_to_string(&full_path).await {
+                Ok(content) => Some(content),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e.into()),
+            };
+
+            let (content, mut hunks_rejected) =
+                apply_hunks(original.as_deref().unwrap_or_default(), &file.hunks);
+            let file_error = match (change, &original) {
+                (FileChange::Created, Some(_)) => Some("The file already exists"),
+                (FileChange::Modified | FileChange::Deleted, None) => {
+                    Some("The file does not exist")
+                }
+                (FileChange::Deleted, Some(_)) if !content.is_empty() => {
+                    Some("The patch does not remove the whole file")
+                }
+                _ => None,
+            };
+            if let Some(reason) = file_error {
+                hunks_rejected = file
+                    .hunks
+                    .iter()
+                    .enumerate()
+                    .map(|(i, hunk)| RejectedHunk {
+                        hunk: i + 1,
+                        header: hunk.header.clone(),
+                        reason: reason.to_string(),
+                    })
+                    .collect();
+            }
+
+            results.push(FilePatchResult {
+                path,
+                change,
+                hunks_applied: file.hunks.len() - hunks_rejected.len(),
+                hunks_rejected,
+            });
+            let content = (change != FileChange::Deleted).then_some(content);
+            writes.push((full_path, original, content));
+        }
+
+        let clean = results.iter().all(|file| file.hunks_rejected.is_empty());
+        let applied = clean && !dry_run;
+        if applied {
+            let mut backup = Backup::new();
+            for (path, original, content) in writes {
+                backup.push((path.clone(), original));
+                if let Err(e) = write_file(&path, content).await {
+                    if let Err(restore_error) = restore(backup).await {
+                        log::error!("Could not restore the patched files: {}", restore_error);
+                    }
+                    return Err(e.into());
+                }
+            }
+            self.history.lock().await.push(backup);
+        }
+        Ok(PatchResult {
+            applied,
+            dry_run,
+            files: results,
+        })
+    }
+
+    /// Restores the files changed by the latest applied patch, returning their paths.
+    pub async fn rollback(&self) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+        let backup = self
+            .history
+            .lock()
+            .await
+            .pop()
+            .ok_or("No applied patch to roll back")?;
+        Ok(restore(backup).await?)
+    }
+
+    /// Patch paths must be relative and stay inside the workspace, symbolic links included.
+    fn resolve(&self, path: &str) -> Result<PathBuf, String> {
+        let outside = || format!("The path {} is outside the workspace", path);
+        let relative = Path::new(path);
+        if !relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(outside());
+        }
+        let full_path = self.workspace.join(relative);
+        let workspace = self
+            .workspace
+            .canonicalize()
+            .map_err(|e| format!("The workspace is not usable: {}", e))?;
+        // The deepest existing part of the path, links included, with the links followed.
+        let existing = full_path
+            .ancestors()
+            .find(|ancestor| ancestor.symlink_metadata().is_ok())
+            .and_then(|ancestor| ancestor.canonicalize().ok())
+            .ok_or_else(outside)?;
+        if existing.starts_with(&workspace) {
+            Ok(full_path)
+        } else {
+            Err(outside())
+        }
+    }
+}
+
+/// Writes `content` to `path`, or removes the file if there is no content.
+async fn write_file(path: &Path, content: Option<String>) -> io::Result<()> {
+    match content {
+        Some(content) => {
+            if let Some(parent) = path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            tokio::fs::write(path, content).await
+        }
+        None => tokio::fs::remove_file(path).await,
+    }
+}
+
+/// Puts back the files of `backup`, the last changed first, returning their paths. A file
+/// that cannot be restored does not stop the others; the first error is returned.
+async fn restore(backup: Backup) -> io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    let mut error = None;
+    for (path, original) in backup.into_iter().rev() {
+        let result = match original {
+            Some(content) => tokio::fs::write(&path, content).await,
+            // A created file may not have been written at all.
+            None => match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+                result => result,
+            },
+        };
+        match result {
+            Ok(()) => paths.push(path),
+            Err(e) => {
+                error.get_or_insert(e);
+            }
+        }
+    }
+    match error {
+        Some(e) => Err(e),
+        None => Ok(paths),
+    }
+}
+
+#[async_trait]
+impl Tool for CodePatchTool {
+    fn name(&self) -> String {
+        "code_patch".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Edits files by applying a unified diff with paths relative to the workspace. Set \
+         dry_run to check that a patch applies without writing it, or use the rollback \
+         action to undo the latest patch."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "patch": {
+                    "type": "string",
+                    "description": "A unified diff, as produced by git diff"
+                },
+                "dry_run": {
+                    "type": "boolean"
+                },
+                "action": {
+                    "type": "string",
+                    "enum": ["apply", "rollback"]
+                }
+            }
+        })
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        match serde_json::from_str::<Value>(input) {
+            Ok(input) if input.is_object() => input,
+            _ => json!({ "patch": input }),
+        }
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        if input["action"].as_str() == Some("rollback") {
+            let paths = self.rollback().await?;
+            return Ok(json!({ "rolled_back": paths }).to_string());
+        }
+        let patch = input["patch"].as_str().ok_or("Missing the patch")?;
+        let dry_run = self.dry_run || input["dry_run"].as_bool().unwrap_or(false);
+        let result = self.apply(patch, dry_run).await?;
+        Ok(serde_json::to_string(&result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_applies_checks_and_rolls_back() {
+        let workspace = std::env::temp_dir().join(format!("code-patch-{}", std::process::id()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(workspace.join("lib.rs"), "fn a() {}\nfn b() {}\n").unwrap();
+        let tool = CodePatchTool::new(&workspace);
+        let patch =
+            "--- a/lib.rs\n+++ b/lib.rs\n@@ -1,2 +1,2 @@\n fn a() {}\n-fn b() {}\n+fn c() {}\n\
+                     --- /dev/null\n+++ b/new.rs\n@@ -0,0 +1 @@\n+fn d() {}\n";
+
+        let preview = tool.apply(patch, true).await.unwrap();
+        assert!(!preview.applied);
+        assert_eq!(preview.files[1].change, FileChange::Created);
+        assert!(!workspace.join("new.rs").exists());
+
+        let result = tool.apply(patch, false).await.unwrap();
+        assert!(result.applied);
+        let read = |name: &str| std::fs::read_to_string(workspace.join(name)).unwrap();
+        assert_eq!(read("lib.rs"), "fn a() {}\nfn c() {}\n");
+        assert_eq!(read("new.rs"), "fn d() {}\n");
+
+        // The same patch no longer applies, and nothing is written.
+        let conflict = tool.apply(patch, false).await.unwrap();
+        assert!(!conflict.applied);
+        assert_eq!(conflict.files[0].hunks_rejected.len(), 1);
+        assert_eq!(
+            conflict.files[1].hunks_rejected[0].reason,
+            "The file already exists"
+        );
+
+        tool.rollback().await.unwrap();
+        assert_eq!(read("lib.rs"), "fn a() {}\nfn b() {}\n");
+        assert!(!workspace.join("new.rs").exists());
+        assert!(tool.apply("--- a/../x\n+++ b/../x\n", false).await.is_err());
+        let _ = std::fs::remove_dir_all(workspace);
+    }
+
+    #[tokio::test]
+    async fn test_failed_write_restores_written_files() {
+        let workspace =
+            std::env::temp_dir().join(format!("code-patch-restore-{}", std::process::id()));
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::write(workspace.join("lib.rs"), "fn a() {}\n").unwrap();
+        let tool = CodePatchTool::new(&workspace);
+        // `out` is written as a file, so `out/b.rs` cannot be created.
+        let patch = "--- a/lib.rs\n+++ b/lib.rs\n@@ -1 +1 @@\n-fn a() {}\n+fn b() {}\n\
+                     --- /dev/null\n+++ b/out\n@@ -0,0 +1 @@\n+x\n\
+                     --- /dev/null\n+++ b/out/b.rs\n@@ -0,0 +1 @@\n+fn b() {}\n";
+
+        assert!(tool.apply(patch, false).await.is_err());
+        assert_eq!(
+            std::fs::read_to_string(workspace.join("lib.rs")).unwrap(),
+            "fn a() {}\n"
+        );
+        assert!(!workspace.join("out").exists());
+        assert!(tool.rollback().await.is_err());
+        let _ = std::fs::remove_dir_all(workspace);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_rejects_paths_through_symlinks() {
+        let root = std::env::temp_dir().join(format!("code-patch-link-{}", std::process::id()));
+        let workspace = root.join("workspace");
+        let outside = root.join("outside");
+        std::fs::create_dir_all(&workspace).unwrap();
+        std::fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, workspace.join("link")).unwrap();
+        let tool = CodePatchTool::new(&workspace);
+
+        let patch = "--- /dev/null\n+++ b/link/x.rs\n@@ -0,0 +1 @@\n+fn x() {}\n";
+        assert!(tool.apply(patch, false).await.is_err());
+        assert!(!outside.join("x.rs").exists());
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
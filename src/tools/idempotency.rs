@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::Mutex;
+
+#[derive(Error, Debug)]
+pub enum IdempotencyError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Serde json error: {0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("Idempotency store error: {0}")]
+    StoreError(String),
+}
+
+/// What is known of an action once its key was issued.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum KeyState {
+    /// The action was started but its outcome was never recorded, e.g. because the process
+    /// stopped while the tool ran.
+    Pending,
+    Completed {
+        output: String,
+    },
+}
+
+/// Remembers the idempotency keys of the actions performed by side-effecting tools, see
+/// [`Tool::idempotency_key`](crate::tools::Tool::idempotency_key).
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Issues `key` if it was never issued, returning `None`, or returns what is known of its
+    /// action. Issuing must be atomic, so concurrent calls cannot both get `None`.
+    async fn issue(&self, key: &str) -> Result<Option<KeyState>, IdempotencyError>;
+
+    /// Records the output of the action of an issued key.
+    async fn complete(&self, key: &str, output: &str) -> Result<(), IdempotencyError>;
+
+    /// Forgets an issued key whose action failed, so it can be retried.
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError>;
+}
+
+/// Keeps the keys in memory, for the lifetime of the process. Clones share the keys.
+#[derive(Default, Clone)]
+pub struct InMemoryIdempotencyStore {
+    keys: Arc<Mutex<HashMap<String, KeyState>>>,
+}
+
+impl InMemoryIdempotencyStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for InMemoryIdempotencyStore {
+    async fn issue(&self, key: &str) -> Result<Option<KeyState>, IdempotencyError> {
+        let mut keys = self.keys.lock().await;
+        if let Some(state) = keys.get(key) {
+            return Ok(Some(state.clone()));
+        }
+        keys.insert(key.to_string(), KeyState::Pending);
+        Ok(None)
+    }
+
+    async fn complete(&self, key: &str, output: &str) -> Result<(), IdempotencyError> {
+        self.keys.lock().await.insert(
+            key.to_string(),
+            KeyState::Completed {
+                output: output.to_string(),
+            },
+        );
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError> {
+        self.keys.lock().await.remove(key);
+        Ok(())
+    }
+}
+
+/// Keeps the keys in a JSON file, rewritten on every change, so they survive restarts and
+/// checkpoint resumes.
+pub struct FileIdempotencyStore {
+    path: PathBuf,
+    keys: Mutex<HashMap<String, KeyState>>,
+}
+
+impl FileIdempotencyStore {
+    /// Opens the store at `path`, loading its keys if the file exists.
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, IdempotencyError> {
+        let path = path.as_ref().to_path_buf();
+        let keys = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self {
+            path,
+            keys: Mutex::new(keys),
+        })
+    }
+
+    /// Writes the keys to a temporary file first, so a crash never leaves a truncated store.
+    fn save(&self, keys: &HashMap<String, KeyState>) -> Result<(), IdempotencyError> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(keys)?)?;
+        fs::rename(tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for FileIdempotencyStore {
+    async fn issue(&self, key: &str) -> Result<Option<KeyState>, IdempotencyError> {
+        let mut keys = self.keys.lock().await;
+        if let Some(state) = keys.get(key) {
+            return Ok(Some(state.clone()));
+        }
+        keys.insert(key.to_string(), KeyState::Pending);
+        if let Err(e) = self.save(&keys) {
+            // A key that was never stored must not block the retry.
+            keys.remove(key);
+            return Err(e);
+        }
+        Ok(None)
+    }
+
+    async fn complete(&self, key: &str, output: &str) -> Result<(), IdempotencyError> {
+        let mut keys = self.keys.lock().await;
+        keys.insert(
+            key.to_string(),
+            KeyState::Completed {
+                output: output.to_string(),
+            },
+        );
+        self.save(&keys)
+    }
+
+    async fn release(&self, key: &str) -> Result<(), IdempotencyError> {
+        let mut keys = self.keys.lock().await;
+        if keys.remove(key).is_some() {
+            self.save(&keys)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_store_survives_restarts() {
+        let path = std::env::temp_dir().join("langchain_rust_idempotency_test.json");
+        let _ = fs::remove_file(&path);
+
+        let store = FileIdempotencyStore::new(&path).unwrap();
+        assert_eq!(store.issue("email:1").await.unwrap(), None);
+        assert_eq!(store.issue("ticket:2").await.unwrap(), None);
+        store.complete("email:1", "sent").await.unwrap();
+        store.issue("payment:3").await.unwrap();
+        store.release("payment:3").await.unwrap();
+
+        let store = FileIdempotencyStore::new(&path).unwrap();
+        assert_eq!(
+            store.issue("email:1").await.unwrap(),
+            Some(KeyState::Completed {
+                output: "sent".to_string()
+            })
+        );
+        assert_eq!(
+            store.issue("ticket:2").await.unwrap(),
+            Some(KeyState::Pending)
+        );
+        assert_eq!(store.issue("payment:3").await.unwrap(), None);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_failed_save_does_not_keep_key_pending() {
+        let path = std::env::temp_dir()
+            .join("langchain_rust_idempotency_missing_dir")
+            .join("keys.json");
+
+        let store = FileIdempotencyStore::new(&path).unwrap();
+        assert!(store.issue("email:1").await.is_err());
+        assert!(store.keys.lock().await.is_empty());
+        assert!(store.issue("email:1").await.is_err());
+    }
+}
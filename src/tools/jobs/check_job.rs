@@ -0,0 +1,73 @@
+use std::{error::Error, time::Duration};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+
+use crate::tools::Tool;
+
+use super::JobManager;
+
+/// Longest the agent may block waiting for a job.
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+/// Reports the status of background jobs started by other tools, optionally waiting a while
+/// for one to end.
+pub struct CheckJobTool {
+    jobs: JobManager,
+}
+
+impl CheckJobTool {
+    pub fn new(jobs: JobManager) -> Self {
+        Self { jobs }
+    }
+}
+
+#[async_trait]
+impl Tool for CheckJobTool {
+    fn name(&self) -> String {
+        "check_job".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Returns the status of a background job started by another tool, with its output once \
+         it has ended, or of all jobs if no id is given. Do other work while a job runs instead \
+         of checking it repeatedly."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "job_id": {
+                    "type": "string",
+                },
+                "wait_secs": {
+                    "type": "integer",
+                    "description": "How long to wait for the job to end, at most 60 seconds"
+                }
+            }
+        })
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        match serde_json::from_str::<Value>(input) {
+            Ok(input) if input.is_object() => input,
+            _ if input.trim().is_empty() => json!({}),
+            _ => json!({ "job_id": input.trim() }),
+        }
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let Some(id) = input["job_id"].as_str().filter(|id| !id.is_empty()) else {
+            return Ok(serde_json::to_string(&self.jobs.jobs())?);
+        };
+        let wait = Duration::from_secs(input["wait_secs"].as_u64().unwrap_or(0)).min(MAX_WAIT);
+        let job = self
+            .jobs
+            .wait(id, wait)
+            .await
+            .ok_or_else(|| format!("No job with id {}", id))?;
+        Ok(serde_json::to_string(&job)?)
+    }
+}
@@ -0,0 +1,385 @@
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::{sync::watch, task::AbortHandle};
+
+use super::CheckJobTool;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum JobStatus {
+    Running { progress: Option<String> },
+    Succeeded { output: String },
+    Failed { error: String },
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_running(&self) -> bool {
+        matches!(self, Self::Running { .. })
+    }
+}
+
+/// A job as reported to the agent and to listeners.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(flatten)]
+    pub status: JobStatus,
+    /// Time since the job started, up to now or to its end.
+    pub runtime: Duration,
+}
+
+/// Emitted when a job starts, reports progress or ends, and every heartbeat while it runs.
+pub type JobListener = Arc<dyn Fn(&JobInfo) + Send + Sync>;
+
+struct Job {
+    name: String,
+    started: Instant,
+    ended: Option<Instant>,
+    status: watch::Sender<JobStatus>,
+    abort: Option<AbortHandle>,
+}
+
+/// How many ended jobs a [`JobManager`] keeps by default.
+pub const DEFAULT_MAX_FINISHED_JOBS: usize = 100;
+
+struct Jobs {
+    jobs: Mutex<HashMap<String, Job>>,
+    next_id: AtomicU64,
+    listener: Mutex<Option<JobListener>>,
+    max_finished: AtomicUsize,
+}
+
+impl Default for Jobs {
+    fn default() -> Self {
+        Self {
+            jobs: Mutex::default(),
+            next_id: AtomicU64::default(),
+            listener: Mutex::default(),
+            max_finished: AtomicUsize::new(DEFAULT_MAX_FINISHED_JOBS),
+        }
+    }
+}
+
+impl Jobs {
+    fn info(&self, id: &str) -> Option<JobInfo> {
+        let jobs = self.jobs.lock().unwrap();
+        let job = jobs.get(id)?;
+        let status = job.status.borrow().clone();
+        Some(JobInfo {
+            id: id.to_string(),
+            name: job.name.clone(),
+            status,
+            runtime: job.ended.unwrap_or_else(Instant::now) - job.started,
+        })
+    }
+
+    fn set_status(&self, id: &str, status: JobStatus) {
+        {
+            let mut jobs = self.jobs.lock().unwrap();
+            let Some(job) = jobs.get_mut(id) else {
+                return;
+            };
+            // A cancelled job stays cancelled even if its task ends first.
+            if !job.status.borrow().is_running() {
+                return;
+            }
+            if !status.is_running() {
+                job.ended = Some(Instant::now());
+            }
+            job.status.send_replace(status);
+        }
+        self.notify(id);
+        self.evict_finished();
+    }
+
+    /// Forgets the jobs that ended first, keeping at most `max_finished` ended jobs.
+    fn evict_finished(&self) {
+        let max_finished = self.max_finished.load(Ordering::Relaxed);
+        let mut jobs = self.jobs.lock().unwrap();
+        let mut finished = jobs
+            .iter()
+            .filter_map(|(id, job)| job.ended.map(|ended| (ended, id.clone())))
+            .collect::<Vec<_>>();
+        if finished.len() <= max_finished {
+            return;
+        }
+        finished.sort();
+        for (_, id) in &finished[..finished.len() - max_finished] {
+            jobs.remove(id);
+        }
+    }
+
+    fn notify(&self, id: &str) {
+        let listener = self.listener.lock().unwrap().clone();
+        if let (Some(listener), Some(info)) = (listener, self.info(id)) {
+            listener(&info);
+        }
+    }
+}
+
+/// Lets a job report what it is doing, shown by `check_job` and sent to listeners.
+#[derive(Clone)]
+pub struct JobProgress {
+    id: String,
+    jobs: Arc<Jobs>,
+}
+
+impl JobProgress {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn report<S: Into<String>>(&self, progress: S) {
+        self.jobs.set_status(
+            &self.id,
+            JobStatus::Running {
+                progress: Some(progress.into()),
+            },
+        );
+    }
+}
+
+/// Runs long jobs, such as builds, deployments or large data jobs, in the background, so tools
+/// can answer with a job id right away and the agent can do other work while waiting.
+///
+/// The agent follows its jobs with the tool from [`JobManager::check_job_tool`], which must be
+/// given to the agent with its other tools. Clones share the jobs. Ended jobs are kept for
+/// `check_job` until more than [`DEFAULT_MAX_FINISHED_JOBS`] have ended, see
+/// [`JobManager::with_max_finished_jobs`].
+///
+/// ```rust,ignore
+/// let jobs = JobManager::new().with_heartbeat(Duration::from_secs(30));
+/// // In a tool holding a clone of `jobs`:
+/// let id = jobs.spawn("build", |progress| async move {
+///     progress.report("compiling");
+///     build().await.map_err(|e| e.to_string())
+/// });
+/// Ok(format!("Started the build as job {}", id))
+/// ```
+#[derive(Clone, Default)]
+pub struct JobManager {
+    jobs: Arc<Jobs>,
+    heartbeat: Option<Duration>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Called whenever a job changes, and on every heartbeat while it runs.
+    pub fn with_listener<F: Fn(&JobInfo) + Send + Sync + 'static>(self, listener: F) -> Self {
+        *self.jobs.listener.lock().unwrap() = Some(Arc::new(listener));
+        self
+    }
+
+    /// Reports running jobs to the listener every `heartbeat`.
+    pub fn with_heartbeat(mut self, heartbeat: Duration) -> Self {
+        self.heartbeat = Some(heartbeat);
+        self
+    }
+
+    /// Keeps at most `max` ended jobs, forgetting the ones that ended first.
+    pub fn with_max_finished_jobs(self, max: usize) -> Self {
+        self.jobs.max_finished.store(max, Ordering::Relaxed);
+        self
+    }
+
+    /// Starts `job` on the tokio runtime, returning its id.
+    pub fn spawn<S, F, Fut>(&self, name: S, job: F) -> String
+    where
+        S: Into<String>,
+        F: FnOnce(JobProgress) -> Fut,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        let id = format!(
+            "job-{}",
+            self.jobs.next_id.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        let (status, _) = watch::channel(JobStatus::Running { progress: None });
+        self.jobs.jobs.lock().unwrap().insert(
+            id.clone(),
+            Job {
+                name: name.into(),
+                started: Instant::now(),
+                ended: None,
+                status,
+                abort: None,
+            },
+        );
+        self.jobs.notify(&id);
+
+        let future = job(JobProgress {
+            id: id.clone(),
+            jobs: self.jobs.clone(),
+        });
+        let jobs = self.jobs.clone();
+        let heartbeat = self.heartbeat;
+        let task_id = id.clone();
+        let task = tokio::spawn(async move {
+            tokio::pin!(future);
+            let result = match heartbeat {
+                Some(heartbeat) => {
+                    let mut ticks = tokio::time::interval(heartbeat);
+                    ticks.tick().await;
+                    loop {
+                        tokio::select! {
+                            result = &mut future => break result,
+                            _ = ticks.tick() => jobs.notify(&task_id),
+                        }
+                    }
+                }
+                None => future.await,
+            };
+            let status = match result {
+                Ok(output) => JobStatus::Succeeded { output },
+                Err(error) => JobStatus::Failed { error },
+            };
+            jobs.set_status(&task_id, status);
+        });
+        if let Some(job) = self.jobs.jobs.lock().unwrap().get_mut(&id) {
+            job.abort = Some(task.abort_handle());
+        }
+        id
+    }
+
+    pub fn status(&self, id: &str) -> Option<JobInfo> {
+        self.jobs.info(id)
+    }
+
+    /// All jobs, oldest first.
+    pub fn jobs(&self) -> Vec<JobInfo> {
+        let mut ids = self
+            .jobs
+            .jobs
+            .lock()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        ids.sort_by_key(|id| id[4..].parse::<u64>().unwrap_or_default());
+        ids.iter().filter_map(|id| self.jobs.info(id)).collect()
+    }
+
+    /// Waits up to `timeout` for the job to end, returning its status either way.
+    pub async fn wait(&self, id: &str, timeout: Duration) -> Option<JobInfo> {
+        let mut status = self.jobs.jobs.lock().unwrap().get(id)?.status.subscribe();
+        let _ = tokio::time::timeout(timeout, status.wait_for(|status| !status.is_running())).await;
+        self.status(id)
+    }
+
+    /// Stops a running job, returning whether it was running.
+    pub fn cancel(&self, id: &str) -> bool {
+        let abort = match self.jobs.jobs.lock().unwrap().get(id) {
+            Some(job) if job.status.borrow().is_running() => job.abort.clone(),
+            _ => return false,
+        };
+        if let Some(abort) = abort {
+            abort.abort();
+        }
+        self.jobs.set_status(id, JobStatus::Cancelled);
+        true
+    }
+
+    /// Cancels every running job, returning how many there were.
+    pub fn cancel_all(&self) -> usize {
+        let ids = self
+            .jobs()
+            .into_iter()
+            .filter(|job| job.status.is_running())
+            .map(|job| job.id)
+            .collect::<Vec<_>>();
+        ids.iter().filter(|id| self.cancel(id)).count()
+    }
+
+    /// The `check_job` tool, for the agent to see how its jobs are doing.
+    pub fn check_job_tool(&self) -> CheckJobTool {
+        CheckJobTool::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_jobs_run_in_the_background() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let jobs = JobManager::new().with_listener(move |job: &JobInfo| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((job.name.clone(), job.status.clone()));
+        });
+
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let id = jobs.spawn("build", |progress| async move {
+            progress.report("compiling");
+            released.await.map_err(|e| e.to_string())?;
+            Ok("built".to_string())
+        });
+        let blocked = jobs.spawn("deploy", |_| std::future::pending());
+
+        let running = jobs.wait(&id, Duration::from_millis(20)).await.unwrap();
+        assert!(running.status.is_running());
+        release.send(()).unwrap();
+        let done = jobs.wait(&id, Duration::from_secs(1)).await.unwrap();
+        assert_eq!(
+            done.status,
+            JobStatus::Succeeded {
+                output: "built".to_string()
+            }
+        );
+
+        assert_eq!(jobs.cancel_all(), 1);
+        assert_eq!(jobs.status(&blocked).unwrap().status, JobStatus::Cancelled);
+        let build_events = events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(name, _)| name == "build")
+            .map(|(_, status)| status.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(build_events.len(), 3);
+        assert_eq!(
+            build_events[1],
+            JobStatus::Running {
+                progress: Some("compiling".to_string())
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oldest_finished_jobs_are_evicted() {
+        let jobs = JobManager::new().with_max_finished_jobs(2);
+        let running = jobs.spawn("watch", |_| std::future::pending());
+        let mut ids = Vec::new();
+        for name in ["first", "second", "third"] {
+            let id = jobs.spawn(name, |_| async { Ok("done".to_string()) });
+            jobs.wait(&id, Duration::from_secs(1)).await.unwrap();
+            ids.push(id);
+        }
+
+        assert!(jobs.status(&ids[0]).is_none());
+        let names = jobs
+            .jobs()
+            .into_iter()
+            .map(|job| job.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["watch", "second", "third"]);
+        assert!(jobs.cancel(&running));
+        assert!(jobs.status(&ids[1]).is_none());
+    }
+}
@@ -0,0 +1,5 @@
+mod check_job;
+pub use check_job::*;
+
+mod manager;
+pub use manager::*;
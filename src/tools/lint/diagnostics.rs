@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Longest rustfmt diff kept per location.
+const MAX_DIFF_CHARS: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Linter {
+    Clippy,
+    Rustfmt,
+    Eslint,
+}
+
+impl Linter {
+    /// The program and arguments checking the whole workspace, in a machine-readable format
+    /// when the linter has one.
+    pub fn command(&self) -> (String, Vec<String>) {
+        let (program, args): (&str, &[&str]) = match self {
+            Self::Clippy => (
+                "cargo",
+                &["clippy", "--message-format=json", "--all-targets"],
+            ),
+            Self::Rustfmt => ("cargo", &["fmt", "--check"]),
+            Self::Eslint => ("npx", &["eslint", "--format", "json", "."]),
+        };
+        (
+            program.to_string(),
+            args.iter().map(|arg| arg.to_string()).collect(),
+        )
+    }
+
+    pub fn parse(&self, output: &str) -> Vec<Diagnostic> {
+        match self {
+            Self::Clippy => parse_clippy(output),
+            Self::Rustfmt => parse_rustfmt(output),
+            Self::Eslint => parse_eslint(output),
+        }
+    }
+}
+
+/// One problem found by a linter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub linter: Linter,
+    pub file: String,
+    pub line: u64,
+    pub column: Option<u64>,
+    /// The lint or rule name, e.g. `clippy::needless_return` or `no-unused-vars`.
+    pub rule: Option<String>,
+    /// `error` or `warning`.
+    pub severity: String,
+    pub message: String,
+}
+
+/// The `compiler-message` lines of `--message-format=json`, at their primary span.
+fn parse_clippy(output: &str) -> Vec<Diagnostic> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|value| value["reason"] == "compiler-message")
+        .filter_map(|value| {
+            let message = &value["message"];
+            let span = message["spans"]
+                .as_array()?
+                .iter()
+                .find(|span| span["is_primary"] == true)?;
+            Some(Diagnostic {
+                linter: Linter::Clippy,
+                file: span["file_name"].as_str()?.to_string(),
+                line: span["line_start"].as_u64()?,
+                column: span["column_start"].as_u64(),
+                rule: message["code"]["code"].as_str().map(String::from),
+                severity: message["level"].as_str().unwrap_or("warning").to_string(),
+                message: message["message"].as_str()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// `Diff in <file> at line <n>:` or `Diff in <file>:<n>:` headers, each followed by the
+/// diff rustfmt would apply.
+fn parse_rustfmt(output: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+    for line in output.lines() {
+        let location = line
+            .strip_prefix("Diff in ")
+            .and_then(|location| location.strip_suffix(':'))
+            .and_then(|location| {
+                location
+                    .rsplit_once(" at line ")
+                    .or_else(|| location.rsplit_once(':'))
+            })
+            .and_then(|(file, line)| Some((file, line.parse::<u64>().ok()?)));
+        match location {
+            Some((file, line)) => diagnostics.push(Diagnostic {
+                linter: Linter::Rustfmt,
+                file: file.to_string(),
+                line,
+                column: None,
+                rule: None,
+                severity: "warning".to_string(),
+                message: "Not formatted as rustfmt would:".to_string(),
+            }),
+            None => {
+                if let Some(diagnostic) = diagnostics.last_mut() {
+                    if diagnostic.message.len() < MAX_DIFF_CHARS {
+                        diagnostic.message.push('\n');
+                        diagnostic.message.push_str(line);
+                    }
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+/// The `--format json` report: files with their messages.
+fn parse_eslint(output: &str) -> Vec<Diagnostic> {
+    let Some(files) = output
+        .find('[')
+        .and_then(|start| serde_json::from_str::<Vec<Value>>(&output[start..]).ok())
+    else {
+        return Vec::new();
+    };
+    files
+        .iter()
+        .flat_map(|file| {
+            let path = file["filePath"].as_str().unwrap_or_default().to_string();
+            file["messages"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |message| Diagnostic {
+                    linter: Linter::Eslint,
+                    file: path.clone(),
+                    line: message["line"].as_u64().unwrap_or_default(),
+                    column: message["column"].as_u64(),
+                    rule: message["ruleId"].as_str().map(String::from),
+                    severity: match message["severity"].as_u64() {
+                        Some(2) => "error",
+                        _ => "warning",
+                    }
+                    .to_string(),
+                    message: message["message"].as_str().unwrap_or_default().to_string(),
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_clippy() {
+        let output = r#"{"reason":"compiler-artifact","target":{}}
+{"reason":"compiler-message","message":{"level":"warning","message":"unneeded `return` statement","code":{"code":"clippy::needless_return"},"spans":[{"file_name":"src/lib.rs","line_start":3,"column_start":5,"is_primary":true}]}}
+{"reason":"compiler-message","message":{"level":"warning","message":"1 warning emitted","code":null,"spans":[]}}"#;
+        let diagnostics = Linter::Clippy.parse(output);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].file, "src/lib.rs");
+        assert_eq!(diagnostics[0].line, 3);
+        assert_eq!(
+            diagnostics[0].rule.as_deref(),
+            Some("clippy::needless_return")
+        );
+    }
+
+    #[test]
+    fn test_parse_rustfmt() {
+        let output = "Diff in /work/src/lib.rs at line 1:\n-fn a(){}\n+fn a() {}\n\
+                      Diff in /work/src/main.rs:7:\n-x\n+y\n";
+        let diagnostics = Linter::Rustfmt.parse(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].line, 1);
+        assert!(diagnostics[0].message.ends_with("+fn a() {}"));
+        assert_eq!(diagnostics[1].file, "/work/src/main.rs");
+        assert_eq!(diagnostics[1].line, 7);
+    }
+
+    #[test]
+    fn test_parse_eslint() {
+        let output = r#"[{"filePath":"/work/a.js","messages":[{"ruleId":"no-unused-vars","severity":2,"message":"'x' is unused","line":1,"column":5}]}]"#;
+        let diagnostics = Linter::Eslint.parse(output);
+        assert_eq!(diagnostics[0].rule.as_deref(), Some("no-unused-vars"));
+        assert_eq!(diagnostics[0].severity, "error");
+    }
+}
@@ -0,0 +1,159 @@
+use std::{error::Error, path::PathBuf, process::Stdio, time::Duration};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use crate::tools::Tool;
+
+use super::{Diagnostic, Linter};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintReport {
+    pub diagnostics: Vec<Diagnostic>,
+    /// Linters that could not run, with the reason.
+    pub errors: Vec<String>,
+}
+
+impl LintReport {
+    pub fn is_clean(&self) -> bool {
+        self.diagnostics.is_empty() && self.errors.is_empty()
+    }
+}
+
+/// Runs linters and formatters on a workspace and reports their findings as diagnostics with
+/// file, line, rule and message, so code-generation agents can fix them before presenting
+/// their changes.
+///
+/// Unless set, the linters are picked from the workspace: clippy and rustfmt for a
+/// `Cargo.toml`, eslint for a `package.json`.
+pub struct LintTool {
+    workspace: PathBuf,
+    linters: Vec<Linter>,
+    timeout: Duration,
+    max_diagnostics: usize,
+}
+
+impl LintTool {
+    pub fn new<P: Into<PathBuf>>(workspace: P) -> Self {
+        Self {
+            workspace: workspace.into(),
+            linters: Vec::new(),
+            timeout: Duration::from_secs(300),
+            max_diagnostics: 50,
+        }
+    }
+
+    pub fn with_linters(mut self, linters: &[Linter]) -> Self {
+        self.linters = linters.to_vec();
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Diagnostics beyond this number are left out of the tool's answer.
+    pub fn with_max_diagnostics(mut self, max_diagnostics: usize) -> Self {
+        self.max_diagnostics = max_diagnostics;
+        self
+    }
+
+    pub fn linters(&self) -> Vec<Linter> {
+        if !self.linters.is_empty() {
+            return self.linters.clone();
+        }
+        let mut linters = Vec::new();
+        if self.workspace.join("Cargo.toml").exists() {
+            linters.extend([Linter::Clippy, Linter::Rustfmt]);
+        }
+        if self.workspace.join("package.json").exists() {
+            linters.push(Linter::Eslint);
+        }
+        linters
+    }
+
+    pub async fn lint(&self) -> LintReport {
+        let mut report = LintReport::default();
+        for linter in self.linters() {
+            match self.run_linter(linter).await {
+                Ok(diagnostics) => report.diagnostics.extend(diagnostics),
+                Err(e) => report.errors.push(format!("{:?}: {}", linter, e)),
+            }
+        }
+        report
+    }
+
+    async fn run_linter(&self, linter: Linter) -> Result<Vec<Diagnostic>, String> {
+        let (program, args) = linter.command();
+        let child = Command::new(&program)
+            .args(args)
+            .current_dir(&self.workspace)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Could not start {}: {}", program, e))?;
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .map_err(|_| format!("Killed after {:?}", self.timeout))?
+            .map_err(|e| e.to_string())?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let diagnostics = linter.parse(&stdout);
+        // Linters exit with an error when they find problems, so only a failure without any
+        // diagnostic means the linter itself failed.
+        if !output.status.success() && diagnostics.is_empty() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().rev().find(|line| !line.trim().is_empty());
+            return Err(reason.unwrap_or("The linter failed").to_string());
+        }
+        Ok(diagnostics)
+    }
+}
+
+#[async_trait]
+impl Tool for LintTool {
+    fn name(&self) -> String {
+        "lint".to_string()
+    }
+
+    fn description(&self) -> String {
+        let linters = self
+            .linters()
+            .iter()
+            .map(|linter| format!("{:?}", linter).to_lowercase())
+            .collect::<Vec<_>>();
+        format!(
+            "Checks the project with {} and returns the problems found, each with its file, \
+             line, rule and message. Run it after editing code.",
+            linters.join(", ")
+        )
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+        let mut report = self.lint().await;
+        if report.is_clean() {
+            return Ok("No problems found.".to_string());
+        }
+        let total = report.diagnostics.len();
+        report.diagnostics.truncate(self.max_diagnostics);
+        let mut answer = serde_json::to_string(&report)?;
+        if total > self.max_diagnostics {
+            answer.push_str(&format!(
+                "\n{} more diagnostics were left out.",
+                total - self.max_diagnostics
+            ));
+        }
+        Ok(answer)
+    }
+}
@@ -0,0 +1,5 @@
+mod diagnostics;
+pub use diagnostics::*;
+
+mod lint_tool;
+pub use lint_tool::*;
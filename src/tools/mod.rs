@@ -1,23 +1,80 @@
 mod tool;
 pub use tool::*;
 
+mod tool_stats;
+pub use tool_stats::*;
+
+mod idempotency;
+pub use idempotency::*;
+
+mod saga;
+pub use saga::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod artifacts;
+#[cfg(not(target_arch = "wasm32"))]
+pub use artifacts::*;
+
+#[cfg(feature = "tools-web")]
 pub use wolfram::*;
+#[cfg(feature = "tools-web")]
 mod wolfram;
 
+#[cfg(feature = "tools-web")]
 mod scraper;
+#[cfg(feature = "tools-web")]
 pub use scraper::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod jobs;
+#[cfg(not(target_arch = "wasm32"))]
+pub use jobs::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod lint;
+#[cfg(not(target_arch = "wasm32"))]
+pub use lint::*;
+
 mod sql;
 pub use sql::*;
 
+#[cfg(feature = "tools-web")]
 mod duckduckgo;
+#[cfg(feature = "tools-web")]
 pub use duckduckgo::*;
 
+#[cfg(feature = "tools-web")]
 mod serpapi;
+#[cfg(feature = "tools-web")]
 pub use serpapi::*;
 
+#[cfg(feature = "tools-web")]
+mod web_research;
+#[cfg(feature = "tools-web")]
+pub use web_research::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod code_executor;
+#[cfg(not(target_arch = "wasm32"))]
+pub use code_executor::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod code_patch;
+#[cfg(not(target_arch = "wasm32"))]
+pub use code_patch::*;
+
+#[cfg(not(target_arch = "wasm32"))]
 mod command_executor;
+#[cfg(not(target_arch = "wasm32"))]
 pub use command_executor::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod test_runner;
+#[cfg(not(target_arch = "wasm32"))]
+pub use test_runner::*;
+
 mod text2speech;
 pub use text2speech::*;
+
+mod cassette;
+pub use cassette::*;
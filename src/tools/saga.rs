@@ -0,0 +1,240 @@
+use std::{collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::tools::Tool;
+
+/// Builds the input of a compensating call from the input and output of the call it undoes,
+/// or `None` when there is nothing to undo.
+pub type CompensationInput = Arc<dyn Fn(&str, &str) -> Option<String> + Send + Sync>;
+
+struct Compensation {
+    tool: Arc<dyn Tool>,
+    input: CompensationInput,
+}
+
+/// A tool call that took effect.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SagaStep {
+    pub tool: String,
+    pub input: String,
+    pub output: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompensationOutcome {
+    /// The step undone.
+    pub step: SagaStep,
+    pub tool: String,
+    pub input: String,
+    /// Why the compensating call failed, leaving the step in effect.
+    pub error: Option<String>,
+}
+
+/// What was undone after a saga failed, last step first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SagaReport {
+    pub compensations: Vec<CompensationOutcome>,
+    /// Steps of tools without a compensation, left in effect.
+    pub uncompensated: Vec<SagaStep>,
+}
+
+impl SagaReport {
+    /// Whether every step was undone.
+    pub fn is_rolled_back(&self) -> bool {
+        self.uncompensated.is_empty()
+            && self
+                .compensations
+                .iter()
+                .all(|compensation| compensation.error.is_none())
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("Tool {tool} failed: {error}")]
+pub struct SagaError {
+    pub tool: String,
+    pub error: String,
+    pub report: SagaReport,
+}
+
+/// Runs a sequence of side-effecting tool calls as a unit: when a call fails partway, the
+/// calls that took effect are undone in reverse order by their compensating tools, e.g. a
+/// created ticket is deleted.
+///
+/// ```rust,ignore
+/// let mut saga = Saga::new().with_compensation("create_ticket", delete_ticket, |_, output| {
+///     Some(json!({ "ticket_id": output }).to_string())
+/// });
+/// let ticket = saga.call(create_ticket.as_ref(), &ticket_input).await?;
+/// saga.call(send_email.as_ref(), &email_input).await?;
+/// ```
+#[derive(Default)]
+pub struct Saga {
+    compensations: HashMap<String, Compensation>,
+    steps: Vec<SagaStep>,
+}
+
+impl Saga {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Undoes the calls to the tool named `tool_name` by calling `compensating_tool` with the
+    /// input built by `input`.
+    pub fn with_compensation<S, F>(
+        mut self,
+        tool_name: S,
+        compensating_tool: Arc<dyn Tool>,
+        input: F,
+    ) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&str, &str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.compensations.insert(
+            tool_name.into(),
+            Compensation {
+                tool: compensating_tool,
+                input: Arc::new(input),
+            },
+        );
+        self
+    }
+
+    /// The calls that took effect so far, in order.
+    pub fn steps(&self) -> &[SagaStep] {
+        &self.steps
+    }
+
+    /// Calls `tool`, compensating every earlier step if it fails.
+    pub async fn call(&mut self, tool: &dyn Tool, input: &str) -> Result<String, SagaError> {
+        let result = tool.call(input).await.map_err(|e| e.to_string());
+        match result {
+            Ok(output) => {
+                self.steps.push(SagaStep {
+                    tool: tool.name(),
+                    input: input.to_string(),
+                    output: output.clone(),
+                });
+                Ok(output)
+            }
+            Err(error) => {
+                log::warn!(
+                    "Tool {} failed, compensating the saga: {}",
+                    tool.name(),
+                    error
+                );
+                Err(SagaError {
+                    tool: tool.name(),
+                    error,
+                    report: self.compensate().await,
+                })
+            }
+        }
+    }
+
+    /// Undoes every step, last first, e.g. after a failure outside of the saga. A failed
+    /// compensation does not stop the others.
+    pub async fn compensate(&mut self) -> SagaReport {
+        let mut report = SagaReport::default();
+        while let Some(step) = self.steps.pop() {
+            let Some(compensation) = self.compensations.get(&step.tool) else {
+                report.uncompensated.push(step);
+                continue;
+            };
+            let Some(input) = (compensation.input)(&step.input, &step.output) else {
+                continue;
+            };
+            let error = compensation
+                .tool
+                .call(&input)
+                .await
+                .err()
+                .map(|e| e.to_string());
+            if let Some(error) = &error {
+                log::error!("Could not compensate {}: {}", step.tool, error);
+            }
+            report.compensations.push(CompensationOutcome {
+                step,
+                tool: compensation.tool.name(),
+                input,
+                error,
+            });
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{error::Error, sync::Mutex};
+
+    use async_trait::async_trait;
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Records its inputs into a shared log, failing on `fail`.
+    struct Logged {
+        name: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Tool for Logged {
+        fn name(&self) -> String {
+            self.name.to_string()
+        }
+        fn description(&self) -> String {
+            "Logs its input".to_string()
+        }
+        async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+            let input = input.as_str().unwrap_or_default().to_string();
+            if input == "fail" {
+                return Err("refused".into());
+            }
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("{} {}", self.name, input));
+            Ok(format!("{}-1", input))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failure_compensates_in_reverse() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let tool = |name| Logged {
+            name,
+            log: log.clone(),
+        };
+        let undo = |name| -> Arc<dyn Tool> { Arc::new(tool(name)) };
+        let mut saga = Saga::new()
+            .with_compensation("create_ticket", undo("delete_ticket"), |_, output| {
+                Some(output.to_string())
+            })
+            .with_compensation("charge", undo("refund"), |input, _| Some(input.to_string()));
+
+        saga.call(&tool("create_ticket"), "bug").await.unwrap();
+        saga.call(&tool("charge"), "10").await.unwrap();
+        saga.call(&tool("notify"), "team").await.unwrap();
+        let error = saga.call(&tool("charge"), "fail").await.unwrap_err();
+
+        assert_eq!(error.tool, "charge");
+        assert_eq!(error.report.uncompensated.len(), 1);
+        assert!(!error.report.is_rolled_back());
+        assert!(saga.steps().is_empty());
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "create_ticket bug",
+                "charge 10",
+                "notify team",
+                "refund 10",
+                "delete_ticket bug-1"
+            ]
+        );
+    }
+}
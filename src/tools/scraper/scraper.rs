@@ -44,6 +44,11 @@ async fn scrape_url(url: &str) -> Result<String, Box<dyn Error>> {
     let res = reqwest::get(url).await?.text().await?;
 
     let document = Html::parse_document(&res);
+    Ok(page_text(&document))
+}
+
+/// The text of the page body, outside of scripts, with whitespace collapsed.
+pub(crate) fn page_text(document: &Html) -> String {
     let body_selector = Selector::parse("body").unwrap();
 
     let mut text = Vec::new();
@@ -55,7 +60,7 @@ async fn scrape_url(url: &str) -> Result<String, Box<dyn Error>> {
     let cleaned_text = joined_text.replace(['\n', '\t'], " ");
     let re = Regex::new(r"\s+").unwrap();
     let final_text = re.replace_all(&cleaned_text, " ");
-    Ok(final_text.to_string())
+    final_text.to_string()
 }
 
 fn collect_text_not_in_script(element: &ElementRef, text: &mut Vec<String>) {
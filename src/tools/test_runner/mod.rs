@@ -0,0 +1,5 @@
+mod report;
+pub use report::*;
+
+mod test_runner_tool;
+pub use test_runner_tool::*;
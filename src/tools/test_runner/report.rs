@@ -0,0 +1,282 @@
+use serde::{Deserialize, Serialize};
+
+/// Longest failure message kept, so a single huge assertion diff does not crowd out the rest.
+const MAX_MESSAGE_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TestFramework {
+    Cargo,
+    Pytest,
+    Npm,
+}
+
+impl TestFramework {
+    /// The program and arguments running the tests, restricted to the tests matching
+    /// `filter` if there is one.
+    pub fn command(&self, filter: Option<&str>) -> (String, Vec<String>) {
+        let (program, mut args) = match self {
+            Self::Cargo => ("cargo", vec!["test", "--color", "never"]),
+            Self::Pytest => ("pytest", vec!["-rA", "--tb=short", "--color=no"]),
+            Self::Npm => ("npm", vec!["test", "--"]),
+        };
+        match (self, filter) {
+            (Self::Pytest, Some(_)) => args.push("-k"),
+            (Self::Npm, Some(_)) => args.push("-t"),
+            _ => {}
+        }
+        let mut args = args.into_iter().map(String::from).collect::<Vec<_>>();
+        args.extend(filter.map(String::from));
+        (program.to_string(), args)
+    }
+
+    /// Reads the outcome of every test from the output of [`Self::command`].
+    pub fn parse(&self, output: &str) -> TestOutcomes {
+        match self {
+            Self::Cargo => parse_cargo(output),
+            Self::Pytest => parse_pytest(output),
+            Self::Npm => parse_npm(output),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestFailure {
+    pub name: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestOutcomes {
+    pub passed: Vec<String>,
+    pub failed: Vec<TestFailure>,
+    /// Problems outside of any test, such as compilation or collection errors.
+    pub errors: Vec<String>,
+    /// The runner's own summary line, e.g. `2 passed, 1 failed`.
+    pub summary: Option<String>,
+}
+
+impl TestOutcomes {
+    fn fail(&mut self, name: &str) {
+        if !self.failed.iter().any(|failure| failure.name == name) {
+            self.failed.push(TestFailure {
+                name: name.to_string(),
+                message: String::new(),
+            });
+        }
+    }
+
+    fn set_message(&mut self, name: &str, message: &[&str]) {
+        let message = message
+            .join("\n")
+            .trim()
+            .chars()
+            .take(MAX_MESSAGE_CHARS)
+            .collect();
+        if let Some(failure) = self.failed.iter_mut().find(|failure| failure.name == name) {
+            failure.message = message;
+        }
+    }
+}
+
+/// `test path::to::name ... ok` lines, `---- path::to::name stdout ----` failure sections and
+/// compiler errors.
+fn parse_cargo(output: &str) -> TestOutcomes {
+    let mut outcomes = TestOutcomes::default();
+    let mut section: Option<(&str, Vec<&str>)> = None;
+    for line in output.lines() {
+        if let Some(test) = line.strip_prefix("test ") {
+            if let Some(name) = test.strip_suffix(" ... ok") {
+                outcomes.passed.push(name.to_string());
+            } else if let Some(name) = test.strip_suffix(" ... FAILED") {
+                outcomes.fail(name);
+            }
+        }
+        let section_name = line
+            .strip_prefix("---- ")
+            .and_then(|line| line.strip_suffix(" stdout ----"));
+        if section_name.is_some() || line == "failures:" || line.starts_with("test result:") {
+            if let Some((name, message)) = section.take() {
+                outcomes.set_message(name, &message);
+            }
+        }
+        if let Some(name) = section_name {
+            section = Some((name, Vec::new()));
+        } else if let Some((_, message)) = section.as_mut() {
+            message.push(line);
+        }
+        if line.starts_with("error[") || line.starts_with("error:") {
+            outcomes.errors.push(line.to_string());
+        }
+        if line.starts_with("test result:") {
+            outcomes.summary = Some(line.to_string());
+        }
+    }
+    outcomes
+}
+
+/// The short test summary printed with `-rA`: `PASSED id`, `FAILED id - message` and
+/// `ERROR id - message` lines.
+fn parse_pytest(output: &str) -> TestOutcomes {
+    let mut outcomes = TestOutcomes::default();
+    for line in output.lines() {
+        let Some((status, rest)) = line.split_once(' ') else {
+            continue;
+        };
+        let (name, message) = rest.split_once(" - ").unwrap_or((rest, ""));
+        match status {
+            "PASSED" => outcomes.passed.push(name.to_string()),
+            "FAILED" => {
+                outcomes.fail(name);
+                outcomes.set_message(name, &[message]);
+            }
+            "ERROR" => outcomes.errors.push(rest.to_string()),
+            _ => {}
+        }
+    }
+    outcomes.summary = output
+        .lines()
+        .rev()
+        .map(|line| line.trim_matches(|c: char| c == '=' || c.is_whitespace()))
+        .find(|line| line.contains(" in ") && (line.contains("passed") || line.contains("failed")))
+        .map(String::from);
+    outcomes
+}
+
+/// Jest and mocha style output: `✓ name` and `✕ name` lines, with failure details in
+/// `● name` sections.
+fn parse_npm(output: &str) -> TestOutcomes {
+    let mut outcomes = TestOutcomes::default();
+    let mut section: Option<(String, Vec<&str>)> = None;
+    for line in output.lines() {
+        let trimmed = line.trim();
+        let mut chars = trimmed.chars();
+        let marker = chars.next();
+        let rest = chars.as_str().trim();
+        // Durations like `(5 ms)` follow the test name.
+        let name = rest
+            .rsplit_once(" (")
+            .filter(|(_, duration)| duration.ends_with("ms)") || duration.ends_with("s)"))
+            .map_or(rest, |(name, _)| name);
+        match marker {
+            Some('✓' | '✔' | '√') => outcomes.passed.push(name.to_string()),
+            Some('✕' | '✖' | '×') => outcomes.fail(name),
+            _ => {}
+        }
+
+        if marker == Some('●') || trimmed.starts_with("Tests:") {
+            if let Some((name, message)) = section.take() {
+                let failed = outcomes
+                    .failed
+                    .iter()
+                    .find(|failure| name.ends_with(&failure.name))
+                    .map(|failure| failure.name.clone());
+                match failed {
+                    Some(failed) => outcomes.set_message(&failed, &message),
+                    None => outcomes
+                        .errors
+                        .push(format!("{}\n{}", name, message.join("\n"))),
+                }
+            }
+        }
+        if marker == Some('●') {
+            section = Some((rest.to_string(), Vec::new()));
+        } else if let Some((_, message)) = section.as_mut() {
+            message.push(line);
+        }
+        if trimmed.starts_with("Tests:") {
+            outcomes.summary = Some(trimmed.to_string());
+        }
+    }
+    outcomes
+}
+
+/// A structured test run, as returned to the agent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestReport {
+    pub framework: TestFramework,
+    /// Whether the runner exited successfully.
+    pub success: bool,
+    pub timed_out: bool,
+    #[serde(flatten)]
+    pub outcomes: TestOutcomes,
+    /// The combined output, shortened in the middle when it is long.
+    pub log: String,
+}
+
+/// Keeps the start and, mostly, the end of `log`, where runners print failures and
+/// summaries, within `max_chars`.
+pub fn truncate_log(log: &str, max_chars: usize) -> String {
+    let total = log.chars().count();
+    if total <= max_chars {
+        return log.to_string();
+    }
+    let head = max_chars / 4;
+    let tail = max_chars - head;
+    let omitted = log.chars().skip(head).take(total - head - tail);
+    let omitted_lines = omitted.filter(|&c| c == '\n').count();
+    format!(
+        "{}\n... {} lines omitted ...\n{}",
+        log.chars().take(head).collect::<String>(),
+        omitted_lines,
+        log.chars().skip(total - tail).collect::<String>()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo() {
+        let output = "running 2 tests
+test tests::adds ... ok
+test tests::subtracts ... FAILED
+
+failures:
+
+---- tests::subtracts stdout ----
+thread 'tests::subtracts' panicked at src/lib.rs:9:9:
+assertion `left == right` failed
+
+failures:
+    tests::subtracts
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored";
+        let outcomes = TestFramework::Cargo.parse(output);
+        assert_eq!(outcomes.passed, ["tests::adds"]);
+        assert_eq!(outcomes.failed[0].name, "tests::subtracts");
+        assert!(outcomes.failed[0]
+            .message
+            .ends_with("`left == right` failed"));
+        assert!(outcomes.summary.unwrap().contains("1 failed"));
+    }
+
+    #[test]
+    fn test_parse_pytest() {
+        let output = "PASSED tests/test_math.py::test_add
+FAILED tests/test_math.py::test_sub - assert 1 == 2
+ERROR tests/test_io.py - ImportError: no module named io2
+=========== 1 failed, 1 passed, 1 error in 0.12s ===========";
+        let outcomes = TestFramework::Pytest.parse(output);
+        assert_eq!(outcomes.passed, ["tests/test_math.py::test_add"]);
+        assert_eq!(outcomes.failed[0].message, "assert 1 == 2");
+        assert_eq!(outcomes.errors.len(), 1);
+        assert_eq!(
+            outcomes.summary.as_deref(),
+            Some("1 failed, 1 passed, 1 error in 0.12s")
+        );
+    }
+
+    #[test]
+    fn test_truncate_log() {
+        let log = (0..100)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let truncated = truncate_log(&log, 40);
+        assert!(truncated.starts_with("0\n1\n"));
+        assert!(truncated.ends_with("98\n99"));
+        assert!(truncated.contains("lines omitted"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::{error::Error, path::PathBuf, process::Stdio, time::Duration};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use crate::tools::Tool;
+
+use super::{truncate_log, TestFramework, TestOutcomes, TestReport};
+
+/// Runs the test suite of a workspace and reports which tests passed and which failed with
+/// what message, so coding agents can iterate on the failures.
+///
+/// The framework is detected from the workspace unless set: `Cargo.toml` means cargo,
+/// `package.json` npm, and `pyproject.toml`, `pytest.ini`, `setup.py` or `setup.cfg` pytest.
+pub struct TestRunnerTool {
+    workspace: PathBuf,
+    framework: Option<TestFramework>,
+    timeout: Duration,
+    max_log_chars: usize,
+}
+
+impl TestRunnerTool {
+    pub fn new<P: Into<PathBuf>>(workspace: P) -> Self {
+        Self {
+            workspace: workspace.into(),
+            framework: None,
+            timeout: Duration::from_secs(600),
+            max_log_chars: 8000,
+        }
+    }
+
+    pub fn with_framework(mut self, framework: TestFramework) -> Self {
+        self.framework = Some(framework);
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Size of the log in the report. Failures and the summary are reported in full anyway.
+    pub fn with_max_log_chars(mut self, max_log_chars: usize) -> Self {
+        self.max_log_chars = max_log_chars;
+        self
+    }
+
+    pub fn framework(&self) -> Option<TestFramework> {
+        self.framework.or_else(|| {
+            let exists = |file: &str| self.workspace.join(file).exists();
+            if exists("Cargo.toml") {
+                Some(TestFramework::Cargo)
+            } else if exists("package.json") {
+                Some(TestFramework::Npm)
+            } else if ["pyproject.toml", "pytest.ini", "setup.py", "setup.cfg"]
+                .into_iter()
+                .any(exists)
+            {
+                Some(TestFramework::Pytest)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Runs the tests matching `filter`, or all of them.
+    pub async fn run_tests(&self, filter: Option<&str>) -> Result<TestReport, Box<dyn Error>> {
+        let framework = self.framework().ok_or_else(|| {
+            format!(
+                "No cargo, npm or pytest project found in {}",
+                self.workspace.display()
+            )
+        })?;
+        let (program, args) = framework.command(filter);
+        let child = Command::new(program)
+            .args(args)
+            .current_dir(&self.workspace)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let (success, timed_out, log) =
+            match tokio::time::timeout(self.timeout, child.wait_with_output()).await {
+                Ok(output) => {
+                    let output = output?;
+                    let log = format!(
+                        "{}{}",
+                        String::from_utf8_lossy(&output.stdout),
+                        String::from_utf8_lossy(&output.stderr)
+                    );
+                    (output.status.success(), false, log)
+                }
+                Err(_) => (
+                    false,
+                    true,
+                    format!("The tests were killed after {:?}", self.timeout),
+                ),
+            };
+        let outcomes: TestOutcomes = framework.parse(&log);
+        Ok(TestReport {
+            framework,
+            success,
+            timed_out,
+            outcomes,
+            log: truncate_log(&log, self.max_log_chars),
+        })
+    }
+}
+
+#[async_trait]
+impl Tool for TestRunnerTool {
+    fn name(&self) -> String {
+        "test_runner".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Runs the project's tests and returns the passed tests, the failed ones with their \
+         messages and the end of the log. Pass a filter to run only the matching tests."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "filter": {
+                    "type": "string",
+                    "description": "Only run the tests whose name matches"
+                }
+            }
+        })
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        match serde_json::from_str::<Value>(input) {
+            Ok(input) if input.is_object() => input,
+            _ if input.trim().is_empty() => json!({}),
+            _ => json!({ "filter": input.trim() }),
+        }
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let filter = input["filter"].as_str().filter(|filter| !filter.is_empty());
+        let report = self.run_tests(filter).await?;
+        Ok(serde_json::to_string(&report)?)
+    }
+}
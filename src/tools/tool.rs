@@ -60,6 +60,26 @@ pub trait Tool: Send + Sync {
     /// ```
     async fn run(&self, input: Value) -> Result<String, Box<dyn Error>>;
 
+    /// Identifies the external action the parsed `input` would perform, for tools with side
+    /// effects such as sending an email, creating a ticket or making a payment.
+    ///
+    /// An executor with an [`IdempotencyStore`](crate::tools::IdempotencyStore) performs the
+    /// action of a key once, answering later calls with the same key, e.g. retries or replays
+    /// after a checkpoint resume, with the first call's output. Derive the key from what makes
+    /// the action unique, such as the recipient and body of an email, never from the time of
+    /// the call.
+    fn idempotency_key(&self, _input: &Value) -> Option<String> {
+        None
+    }
+
+    /// Releases the resources the tool holds, such as browser sessions, connection pools or
+    /// interpreter kernels. The agent executor calls it when the agent is done, after which
+    /// the tool is not run again. Tools shared between executors may be shut down more than
+    /// once.
+    async fn shutdown(&self) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
     /// Parses the input string, which could be a JSON value or a raw string, depending on the LLM model.
     ///
     /// Implement this function to extract the parameters needed for your tool. If a simple
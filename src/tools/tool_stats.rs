@@ -0,0 +1,178 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use tokio::sync::Mutex;
+
+use crate::tools::Tool;
+
+#[derive(Debug, Clone, Copy)]
+struct ToolOutcome {
+    success: bool,
+    latency_ms: Option<u64>,
+}
+
+/// Success rate and latency of a tool over its most recent calls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ToolSummary {
+    pub calls: usize,
+    pub success_rate: f64,
+    /// Mean latency of the calls whose latency was measured.
+    pub avg_latency_ms: Option<f64>,
+}
+
+/// Rolling per-tool call statistics, optionally broken down by task type.
+///
+/// Cloning shares the underlying store, so one `ToolStats` can be filled by several executors
+/// and read by whatever selects tools.
+#[derive(Debug, Clone)]
+pub struct ToolStats {
+    window: usize,
+    outcomes: Arc<Mutex<HashMap<(String, Option<String>), VecDeque<ToolOutcome>>>>,
+}
+
+impl Default for ToolStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolStats {
+    pub fn new() -> Self {
+        Self {
+            window: 50,
+            outcomes: Arc::default(),
+        }
+    }
+
+    /// Number of most recent calls kept per tool and task type.
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Records one call. Calls with a task type also count towards the tool's overall stats.
+    pub async fn record(
+        &self,
+        tool: &str,
+        task_type: Option<&str>,
+        success: bool,
+        latency_ms: Option<u64>,
+    ) {
+        let outcome = ToolOutcome {
+            success,
+            latency_ms,
+        };
+        let mut outcomes = self.outcomes.lock().await;
+        let mut keys = vec![(tool.to_string(), None)];
+        if let Some(task_type) = task_type {
+            keys.push((tool.to_string(), Some(task_type.to_string())));
+        }
+        for key in keys {
+            let window = outcomes.entry(key).or_default();
+            if window.len() == self.window {
+                window.pop_front();
+            }
+            window.push_back(outcome);
+        }
+    }
+
+    /// Stats for `tool`, restricted to `task_type` when given. `None` if the tool has no
+    /// recorded calls.
+    pub async fn summary(&self, tool: &str, task_type: Option<&str>) -> Option<ToolSummary> {
+        let outcomes = self.outcomes.lock().await;
+        let window = outcomes.get(&(tool.to_string(), task_type.map(str::to_string)))?;
+        if window.is_empty() {
+            return None;
+        }
+
+        let successes = window.iter().filter(|outcome| outcome.success).count();
+        let latencies: Vec<u64> = window.iter().filter_map(|o| o.latency_ms).collect();
+        Some(ToolSummary {
+            calls: window.len(),
+            success_rate: successes as f64 / window.len() as f64,
+            avg_latency_ms: (!latencies.is_empty())
+                .then(|| latencies.iter().sum::<u64>() as f64 / latencies.len() as f64),
+        })
+    }
+
+    /// Orders `tools` by success rate for `task_type`, best first. Tools without recorded
+    /// calls count as fully successful so they still get tried, and ties keep their order.
+    pub async fn rank(
+        &self,
+        tools: &[Arc<dyn Tool>],
+        task_type: Option<&str>,
+    ) -> Vec<Arc<dyn Tool>> {
+        let mut ranked = Vec::with_capacity(tools.len());
+        for tool in tools {
+            let success_rate = self
+                .summary(&tool.name(), task_type)
+                .await
+                .map_or(1.0, |summary| summary.success_rate);
+            ranked.push((success_rate, tool.clone()));
+        }
+        ranked.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        ranked.into_iter().map(|(_, tool)| tool).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use async_trait::async_trait;
+    use serde_json::Value;
+
+    use super::*;
+
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl Tool for Named {
+        fn name(&self) -> String {
+            self.0.to_string()
+        }
+        fn description(&self) -> String {
+            String::new()
+        }
+        async fn run(&self, _input: Value) -> Result<String, Box<dyn Error>> {
+            Ok(String::new())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_window_keeps_recent_calls() {
+        let stats = ToolStats::new().with_window(2);
+        stats.record("search", None, false, Some(30)).await;
+        stats.record("search", None, true, Some(10)).await;
+        stats.record("search", None, true, None).await;
+
+        let summary = stats.summary("search", None).await.unwrap();
+        assert_eq!(summary.calls, 2);
+        assert_eq!(summary.success_rate, 1.0);
+        assert_eq!(summary.avg_latency_ms, Some(10.0));
+    }
+
+    #[tokio::test]
+    async fn test_rank_demotes_failing_tools_per_task_type() {
+        let stats = ToolStats::new();
+        stats.record("search", Some("math"), false, None).await;
+        stats.record("calculator", Some("math"), true, None).await;
+        let tools: Vec<Arc<dyn Tool>> = vec![
+            Arc::new(Named("search")),
+            Arc::new(Named("calculator")),
+            Arc::new(Named("wiki")),
+        ];
+
+        let names = |tools: Vec<Arc<dyn Tool>>| tools.iter().map(|t| t.name()).collect::<Vec<_>>();
+        assert_eq!(
+            names(stats.rank(&tools, Some("math")).await),
+            ["calculator", "wiki", "search"]
+        );
+        assert_eq!(
+            names(stats.rank(&tools, Some("travel")).await),
+            ["search", "calculator", "wiki"]
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use reqwest::Client;
+use scraper::{Html, Selector};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::{scraper::page_text, DuckDuckGoSearchResults, Tool};
+
+/// A page read during the research.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Citation {
+    pub url: String,
+    pub title: String,
+}
+
+/// The pages read so far, numbered from 1 in the order they were first read.
+#[derive(Clone, Default)]
+struct Citations(Arc<Mutex<Vec<Citation>>>);
+
+impl Citations {
+    /// Records the page, returning its number.
+    fn cite(&self, url: &str, title: &str) -> usize {
+        let mut citations = self.0.lock().unwrap();
+        if let Some(index) = citations.iter().position(|c| c.url == url) {
+            return index + 1;
+        }
+        citations.push(Citation {
+            url: url.to_string(),
+            title: title.to_string(),
+        });
+        citations.len()
+    }
+}
+
+/// Everything a research agent needs to browse the web: a search tool, a tool reading a page
+/// and a tool extracting the passages of a page relevant to a question.
+///
+/// The page tools number every page they read and start their output with its number, e.g.
+/// `[2] https://example.com`, so the agent can cite its sources. The pages read are listed by
+/// [`Self::citations`], to render the sources of the final answer.
+///
+/// ```rust,ignore
+/// let research = WebResearch::new();
+/// let agent = OpenAiToolAgentBuilder::new().tools(&research.tools()).build(llm)?;
+/// let answer = AgentExecutor::from_agent(agent).invoke(prompt_args! {"input" => question}).await?;
+/// println!("{}\n\n{}", answer, research.sources());
+/// ```
+pub struct WebResearch {
+    search: Arc<dyn Tool>,
+    client: Client,
+    max_page_chars: usize,
+    max_passages: usize,
+    citations: Citations,
+}
+
+impl Default for WebResearch {
+    fn default() -> Self {
+        Self {
+            search: Arc::new(DuckDuckGoSearchResults::new()),
+            client: Client::new(),
+            max_page_chars: 8000,
+            max_passages: 5,
+            citations: Citations::default(),
+        }
+    }
+}
+
+impl WebResearch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the default DuckDuckGo search, e.g. with [`SerpApi`](super::SerpApi).
+    pub fn with_search<T: Tool + 'static>(mut self, search: T) -> Self {
+        self.search = Arc::new(search);
+        self
+    }
+
+    /// Maximum length of the page text returned by `fetch_page`.
+    pub fn with_max_page_chars(mut self, max_page_chars: usize) -> Self {
+        self.max_page_chars = max_page_chars;
+        self
+    }
+
+    /// How many passages `extract_content` returns.
+    pub fn with_max_passages(mut self, max_passages: usize) -> Self {
+        self.max_passages = max_passages.max(1);
+        self
+    }
+
+    pub fn tools(&self) -> Vec<Arc<dyn Tool>> {
+        vec![
+            self.search.clone(),
+            Arc::new(FetchPage {
+                client: self.client.clone(),
+                max_chars: self.max_page_chars,
+                citations: self.citations.clone(),
+            }),
+            Arc::new(ExtractContent {
+                client: self.client.clone(),
+                max_passages: self.max_passages,
+                citations: self.citations.clone(),
+            }),
+        ]
+    }
+
+    pub fn citations(&self) -> Vec<Citation> {
+        self.citations.0.lock().unwrap().clone()
+    }
+
+    /// The pages read, one per line as `[n] title - url`.
+    pub fn sources(&self) -> String {
+        self.citations()
+            .iter()
+            .enumerate()
+            .map(|(i, citation)| format!("[{}] {} - {}", i + 1, citation.title, citation.url))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Fetches `url`, returning the page title and text.
+async fn fetch(client: &Client, url: &str) -> Result<(String, String), Box<dyn Error>> {
+    let html = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let document = Html::parse_document(&html);
+    let title = document
+        .select(&Selector::parse("title").unwrap())
+        .next()
+        .map(|title| title.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+    Ok((title, page_text(&document).trim().to_string()))
+}
+
+struct FetchPage {
+    client: Client,
+    max_chars: usize,
+    citations: Citations,
+}
+
+#[async_trait]
+impl Tool for FetchPage {
+    fn name(&self) -> String {
+        "fetch_page".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Reads a web page. Input should be its url. Output is the page text, starting with the \
+         number to cite the page with."
+            .to_string()
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let url = input.as_str().ok_or("Input should be a url")?.trim();
+        let (title, text) = fetch(&self.client, url).await?;
+        let number = self.citations.cite(url, &title);
+        let text = text.chars().take(self.max_chars).collect::<String>();
+        Ok(format!("[{}] {} - {}\n\n{}", number, title, url, text))
+    }
+}
+
+struct ExtractContent {
+    client: Client,
+    max_passages: usize,
+    citations: Citations,
+}
+
+#[async_trait]
+impl Tool for ExtractContent {
+    fn name(&self) -> String {
+        "extract_content".to_string()
+    }
+
+    fn description(&self) -> String {
+        "Extracts the passages of a web page relevant to a question, cheaper than reading the \
+         whole page. Output starts with the number to cite the page with."
+            .to_string()
+    }
+
+    fn parameters(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "Url of the page"
+                },
+                "question": {
+                    "type": "string",
+                    "description": "What the passages should be about"
+                }
+            },
+            "required": ["url", "question"]
+        })
+    }
+
+    async fn parse_input(&self, input: &str) -> Value {
+        serde_json::from_str(input).unwrap_or_else(|_| Value::String(input.to_string()))
+    }
+
+    async fn run(&self, input: Value) -> Result<String, Box<dyn Error>> {
+        let url = input["url"]
+            .as_str()
+            .ok_or("Input should have a url and a question")?
+            .trim();
+        let question = input["question"].as_str().unwrap_or_default();
+        let (title, text) = fetch(&self.client, url).await?;
+        let number = self.citations.cite(url, &title);
+        let passages = relevant_passages(&text, question, self.max_passages);
+        if passages.is_empty() {
+            return Ok(format!(
+                "[{}] {} - {}\n\nNothing on the page is about the question.",
+                number, title, url
+            ));
+        }
+        Ok(format!(
+            "[{}] {} - {}\n\n{}",
+            number,
+            title,
+            url,
+            passages.join("\n")
+        ))
+    }
+}
+
+fn words(text: &str) -> HashSet<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.len() > 2)
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The `max` sentences of `text` sharing the most words with `question`, in page order.
+fn relevant_passages(text: &str, question: &str, max: usize) -> Vec<String> {
+    let question = words(question);
+    let sentences = text
+        .split_inclusive(['.', '!', '?'])
+        .map(str::trim)
+        .filter(|sentence| !sentence.is_empty())
+        .collect::<Vec<_>>();
+    let mut scored = sentences
+        .iter()
+        .enumerate()
+        .map(|(i, sentence)| (words(sentence).intersection(&question).count(), i))
+        .filter(|(score, _)| *score > 0)
+        .collect::<Vec<_>>();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    let mut kept = scored
+        .into_iter()
+        .take(max)
+        .map(|(_, i)| i)
+        .collect::<Vec<_>>();
+    kept.sort();
+    kept.into_iter().map(|i| sentences[i].to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_page_tools_cite_pages() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rust")
+            .with_status(200)
+            .with_body(
+                "<html><head><title>Rust</title></head><body>Rust is a language. \
+                 It was first released in 2015. Its mascot is a crab.</body></html>",
+            )
+            .expect(2)
+            .create();
+        let url = format!("{}/rust", server.url());
+
+        let research = WebResearch::new().with_max_passages(1);
+        let tools = research.tools();
+        let page = tools[1].call(&url).await.unwrap();
+        assert!(page.starts_with(&format!("[1] Rust - {}", url)));
+        assert!(page.contains("Its mascot is a crab."));
+
+        let input = json!({"url": url, "question": "When was Rust released?"}).to_string();
+        let passages = tools[2].call(&input).await.unwrap();
+        assert!(passages.starts_with("[1] Rust"));
+        assert!(passages.ends_with("It was first released in 2015."));
+
+        assert_eq!(research.citations().len(), 1);
+        assert_eq!(research.sources(), format!("[1] Rust - {}", url));
+        mock.assert();
+    }
+}
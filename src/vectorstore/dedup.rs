@@ -0,0 +1,181 @@
+use std::{collections::HashSet, error::Error, sync::Arc};
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use crate::{
+    embedding::{Embedder, EmbedderError},
+    schemas::{Document, Retriever},
+    semantic_router::utils::cosine_similarity,
+};
+
+/// Merges near-identical documents, e.g. the same paragraph retrieved from three sources.
+///
+/// Documents are compared in order, and one whose embedding has a cosine similarity of at
+/// least `threshold` with an earlier kept document is folded into it. The kept document
+/// takes the higher score, and metadata values that differ are collected into an array, so
+/// every `source` stays citable. Works on any list of documents, memory entries included.
+pub async fn deduplicate_documents(
+    documents: Vec<Document>,
+    embedder: &dyn Embedder,
+    threshold: f64,
+) -> Result<Vec<Document>, EmbedderError> {
+    if documents.len() < 2 {
+        return Ok(documents);
+    }
+    let contents = documents
+        .iter()
+        .map(|document| document.page_content.clone())
+        .collect::<Vec<_>>();
+    let embeddings = embedder.embed_documents(&contents).await?;
+
+    // Every kept document with the metadata keys whose values were collected into an array.
+    let mut kept: Vec<(Document, &Vec<f64>, HashSet<String>)> = Vec::new();
+    for (document, embedding) in documents.into_iter().zip(&embeddings) {
+        match kept
+            .iter_mut()
+            .find(|(_, kept, _)| cosine_similarity(kept, embedding) >= threshold)
+        {
+            Some((original, _, merged)) => merge_into(original, merged, document),
+            None => kept.push((document, embedding, HashSet::new())),
+        }
+    }
+    Ok(kept.into_iter().map(|(document, _, _)| document).collect())
+}
+
+/// Folds `duplicate` into `original`. Keys in `merged` already hold the collected values, any
+/// other value is the original's own, arrays included.
+fn merge_into(original: &mut Document, merged: &mut HashSet<String>, duplicate: Document) {
+    original.score = original.score.max(duplicate.score);
+    for (key, value) in duplicate.metadata {
+        match original.metadata.get_mut(&key) {
+            None => {
+                original.metadata.insert(key, value);
+            }
+            Some(existing) if *existing == value => {}
+            Some(Value::Array(values)) if merged.contains(&key) => {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            Some(existing) => {
+                *existing = Value::Array(vec![existing.take(), value]);
+                merged.insert(key);
+            }
+        }
+    }
+}
+
+/// Wraps a retriever so its results are deduplicated with [`deduplicate_documents`] before
+/// they reach the prompt.
+pub struct DedupRetriever {
+    retriever: Box<dyn Retriever>,
+    embedder: Arc<dyn Embedder>,
+    threshold: f64,
+}
+
+impl DedupRetriever {
+    pub fn new<R: Into<Box<dyn Retriever>>, E: Embedder + 'static>(
+        retriever: R,
+        embedder: E,
+    ) -> Self {
+        Self {
+            retriever: retriever.into(),
+            embedder: Arc::new(embedder),
+            threshold: 0.95,
+        }
+    }
+
+    pub fn with_threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+}
+
+#[async_trait]
+impl Retriever for DedupRetriever {
+    async fn get_relevant_documents(&self, query: &str) -> Result<Vec<Document>, Box<dyn Error>> {
+        let documents = self.retriever.get_relevant_documents(query).await?;
+        Ok(deduplicate_documents(documents, self.embedder.as_ref(), self.threshold).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use super::*;
+
+    /// Embeds a text by its first word, so texts starting alike are duplicates.
+    struct FirstWordEmbedder;
+
+    #[async_trait]
+    impl Embedder for FirstWordEmbedder {
+        async fn embed_documents(
+            &self,
+            documents: &[String],
+        ) -> Result<Vec<Vec<f64>>, EmbedderError> {
+            let mut embeddings = Vec::new();
+            for document in documents {
+                embeddings.push(self.embed_query(document).await?);
+            }
+            Ok(embeddings)
+        }
+
+        async fn embed_query(&self, text: &str) -> Result<Vec<f64>, EmbedderError> {
+            let first = text.split_whitespace().next().unwrap_or_default();
+            Ok(vec![
+                (first == "rust") as u8 as f64,
+                (first == "python") as u8 as f64,
+            ])
+        }
+    }
+
+    fn document(content: &str, source: &str, score: f64) -> Document {
+        Document::new(content)
+            .with_metadata(HashMap::from([("source".to_string(), json!(source))]))
+            .with_score(score)
+    }
+
+    #[tokio::test]
+    async fn test_merges_duplicates_keeping_sources() {
+        let documents = vec![
+            document("rust is fast", "a.md", 0.7),
+            document("python is popular", "b.md", 0.6),
+            document("rust is fast.", "c.md", 0.9),
+            document("rust is fast", "a.md", 0.5),
+        ];
+
+        let deduplicated = deduplicate_documents(documents, &FirstWordEmbedder, 0.95)
+            .await
+            .unwrap();
+
+        assert_eq!(deduplicated.len(), 2);
+        assert_eq!(deduplicated[0].page_content, "rust is fast");
+        assert_eq!(deduplicated[0].score, 0.9);
+        assert_eq!(deduplicated[0].metadata["source"], json!(["a.md", "c.md"]));
+        assert_eq!(deduplicated[1].metadata["source"], json!("b.md"));
+    }
+
+    #[tokio::test]
+    async fn test_array_metadata_is_a_single_value() {
+        let tagged = |content: &str, tags: Value| {
+            Document::new(content).with_metadata(HashMap::from([("tags".to_string(), tags)]))
+        };
+        let documents = vec![
+            tagged("rust is fast", json!(["a", "b"])),
+            tagged("rust is fast", json!(["a", "b"])),
+            tagged("rust is fast.", json!(["c"])),
+            tagged("rust is fast!", json!(["a", "b"])),
+        ];
+
+        let deduplicated = deduplicate_documents(documents, &FirstWordEmbedder, 0.95)
+            .await
+            .unwrap();
+
+        assert_eq!(deduplicated.len(), 1);
+        assert_eq!(deduplicated[0].metadata["tags"], json!([["a", "b"], ["c"]]));
+    }
+}
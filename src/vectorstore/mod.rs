@@ -1,3 +1,4 @@
+mod dedup;
 mod options;
 
 #[cfg(feature = "postgres")]
@@ -20,5 +21,6 @@ pub mod qdrant;
 
 mod vectorstore;
 
+pub use dedup::*;
 pub use options::*;
 pub use vectorstore::*;
@@ -1,9 +1,13 @@
-use std::{collections::HashMap, env, error::Error, sync::Arc};
+use std::{collections::HashMap, error::Error, sync::Arc};
 
 use serde_json::{json, Value};
 use sqlx::{postgres::PgPoolOptions, Pool, Postgres, Row, Transaction};
 
-use crate::{embedding::embedder_trait::Embedder, vectorstore::VecStoreOptions};
+use crate::{
+    embedding::embedder_trait::Embedder,
+    env::{self, EnvError},
+    vectorstore::VecStoreOptions,
+};
 
 use super::{
     HNSWIndex, PgFilter, PgOptions, Store, PG_LOCKID_EXTENSION, PG_LOCK_ID_COLLECTION_TABLE,
@@ -49,6 +53,19 @@ impl StoreBuilder<PgFilter> {
         }
     }
 
+    // Returns a builder connecting to PGVECTOR_CONNECTION_STRING, with the collection name and
+    // vector dimensions of PGVECTOR_COLLECTION_NAME and PGVECTOR_VECTOR_DIMENSIONS when set
+    pub fn from_env() -> Result<Self, EnvError> {
+        let mut builder = Self::new().connection_url(&env::require("PGVECTOR_CONNECTION_STRING")?);
+        if let Some(collection_name) = env::var("PGVECTOR_COLLECTION_NAME") {
+            builder = builder.collection_name(&collection_name);
+        }
+        if let Some(vector_dimensions) = env::parse("PGVECTOR_VECTOR_DIMENSIONS")? {
+            builder = builder.vector_dimensions(vector_dimensions);
+        }
+        Ok(builder)
+    }
+
     pub fn pool(mut self, pool: Pool<Postgres>) -> Self {
         self.pool = Some(pool);
         self
@@ -148,7 +165,7 @@ impl StoreBuilder<PgFilter> {
                 let connection_url = match self.connection_url {
         Some(ref url) if !url.is_empty() => url.clone(),
         _ => env::var("PGVECTOR_CONNECTION_STRING")
-                            .map_err(|_| "PGVECTOR_CONNECTION_STRING environment variable not set, and no connection URL provided.")?};
+                            .ok_or("PGVECTOR_CONNECTION_STRING environment variable not set, and no connection URL provided.")?};
 
                 // Check if the resolved `connection_url` is empty
                 if connection_url.is_empty() {
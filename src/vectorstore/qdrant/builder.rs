@@ -1,4 +1,5 @@
 use crate::embedding::Embedder;
+use crate::env::{self, EnvError};
 use crate::vectorstore::qdrant::Store;
 use qdrant_client::qdrant::{CreateCollectionBuilder, Distance, Filter, VectorParamsBuilder};
 use qdrant_client::Qdrant;
@@ -35,6 +36,24 @@ impl StoreBuilder {
         }
     }
 
+    /// Create a StoreBuilder with a client for the server at `QDRANT_URL`, authenticated with
+    /// `QDRANT_API_KEY` if set, and the collection named by `QDRANT_COLLECTION_NAME` if set.
+    pub fn from_env() -> Result<Self, EnvError> {
+        let url = env::require("QDRANT_URL")?;
+        let client = Qdrant::from_url(&url)
+            .api_key(env::var("QDRANT_API_KEY"))
+            .build()
+            .map_err(|_| EnvError::Invalid {
+                name: "QDRANT_URL".to_string(),
+                value: url.clone(),
+            })?;
+        let builder = Self::new().client(client);
+        Ok(match env::var("QDRANT_COLLECTION_NAME") {
+            Some(collection_name) => builder.collection_name(&collection_name),
+            None => builder,
+        })
+    }
+
     /// An instance of [`qdrant_client::Qdrant`] for the Store. REQUIRED.
     pub fn client(mut self, client: Qdrant) -> Self {
         self.client = Some(client);
@@ -6,7 +6,10 @@ use sqlx::{
 };
 
 use super::Store;
-use crate::embedding::embedder_trait::Embedder;
+use crate::{
+    embedding::embedder_trait::Embedder,
+    env::{self, EnvError},
+};
 
 pub struct StoreBuilder {
     pool: Option<Pool<Sqlite>>,
@@ -27,6 +30,19 @@ impl StoreBuilder {
         }
     }
 
+    // Returns a builder opening SQLITE_VSS_CONNECTION_STRING, with the table and vector dimensions
+    // of SQLITE_VSS_TABLE and SQLITE_VSS_VECTOR_DIMENSIONS when set
+    pub fn from_env() -> Result<Self, EnvError> {
+        let mut builder = Self::new().connection_url(env::require("SQLITE_VSS_CONNECTION_STRING")?);
+        if let Some(table) = env::var("SQLITE_VSS_TABLE") {
+            builder = builder.table(&table);
+        }
+        if let Some(vector_dimensions) = env::parse("SQLITE_VSS_VECTOR_DIMENSIONS")? {
+            builder = builder.vector_dimensions(vector_dimensions);
+        }
+        Ok(builder)
+    }
+
     pub fn pool(mut self, pool: Pool<Sqlite>) -> Self {
         self.pool = Some(pool);
         self.connection_url = None;